@@ -0,0 +1,242 @@
+//! Scripted operations read from JSON Lines files.
+//!
+//! Each line is one operation tagged by `op`, named after the matching CLI
+//! subcommand:
+//!
+//! ```text
+//! {"op":"create","parent":"<page_id>","title":"Notes","content":"Hello"}
+//! {"op":"append","page":"<page_id>","content":"**Done**","markdown":true}
+//! {"op":"update","page":"<page_id>","properties":["Status:select=Done"]}
+//! ```
+//!
+//! The offline queue (`--offline` / `sync-queue`) stores pending changes in
+//! the same format.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::client::{NotionClient, PropertySpec};
+
+/// A single batch operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum Operation {
+    /// Create a page under a parent page
+    Create {
+        parent: String,
+        title: String,
+        content: Option<String>,
+        icon: Option<String>,
+    },
+    /// Create a row in a database
+    DbCreate {
+        database: String,
+        title: String,
+        #[serde(default)]
+        properties: Vec<String>,
+        content: Option<String>,
+        icon: Option<String>,
+    },
+    /// Append text (plain or inline Markdown) to a page
+    Append {
+        page: String,
+        content: String,
+        #[serde(default)]
+        markdown: bool,
+        after: Option<String>,
+    },
+    /// Append a code block
+    AppendCode {
+        page: String,
+        code: String,
+        #[serde(default = "default_language")]
+        language: String,
+        after: Option<String>,
+    },
+    /// Append a heading
+    AppendHeading {
+        page: String,
+        text: String,
+        #[serde(default = "default_heading_level")]
+        level: u8,
+        after: Option<String>,
+    },
+    /// Update a page's title, icon or properties
+    Update {
+        page: String,
+        title: Option<String>,
+        icon: Option<String>,
+        #[serde(default)]
+        properties: Vec<String>,
+    },
+    /// Archive a page
+    Delete { page: String },
+    /// Delete a block
+    DeleteBlock { block: String },
+    /// Comment on a page
+    Comment { page: String, text: String },
+}
+
+fn default_language() -> String {
+    "plain text".to_string()
+}
+
+fn default_heading_level() -> u8 {
+    2
+}
+
+impl Operation {
+    /// Parse one line of a batch file
+    pub fn parse(line: &str) -> Result<Self> {
+        serde_json::from_str(line).context("Invalid operation")
+    }
+
+    /// Short description for progress and summary output
+    pub fn describe(&self) -> String {
+        match self {
+            Operation::Create { title, .. } => format!("create \"{}\"", title),
+            Operation::DbCreate { title, .. } => format!("db-create \"{}\"", title),
+            Operation::Append { page, .. } => format!("append {}", page),
+            Operation::AppendCode { page, .. } => format!("append-code {}", page),
+            Operation::AppendHeading { page, .. } => format!("append-heading {}", page),
+            Operation::Update { page, .. } => format!("update {}", page),
+            Operation::Delete { page } => format!("delete {}", page),
+            Operation::DeleteBlock { block } => format!("delete-block {}", block),
+            Operation::Comment { page, .. } => format!("comment {}", page),
+        }
+    }
+
+    /// Run the operation, returning the API response (`null` for `delete-block`)
+    pub async fn execute(&self, client: &NotionClient) -> Result<serde_json::Value> {
+        match self {
+            Operation::Create {
+                parent,
+                title,
+                content,
+                icon,
+            } => {
+                client
+                    .create_page(parent, title, content.as_deref(), icon.as_deref())
+                    .await
+            }
+            Operation::DbCreate {
+                database,
+                title,
+                properties,
+                content,
+                icon,
+            } => {
+                let props = parse_properties(properties)?;
+                client
+                    .create_database_page(
+                        database,
+                        title,
+                        &props,
+                        content.as_deref(),
+                        icon.as_deref(),
+                    )
+                    .await
+            }
+            Operation::Append {
+                page,
+                content,
+                markdown: true,
+                after,
+            } => {
+                client
+                    .append_markdown(page, content, after.as_deref())
+                    .await
+            }
+            Operation::Append {
+                page,
+                content,
+                after,
+                ..
+            } => client.append_blocks(page, content, after.as_deref()).await,
+            Operation::AppendCode {
+                page,
+                code,
+                language,
+                after,
+            } => client
+                .append_code_block(page, code, language, None, after.as_deref())
+                .await
+                .map(|_| serde_json::Value::Null),
+            Operation::AppendHeading {
+                page,
+                text,
+                level,
+                after,
+            } => {
+                client
+                    .append_heading(page, text, *level, after.as_deref())
+                    .await
+            }
+            Operation::Update {
+                page,
+                title,
+                icon,
+                properties,
+            } => {
+                let props = parse_properties(properties)?;
+                client
+                    .update_page(page, title.as_deref(), icon.as_deref(), &props)
+                    .await
+            }
+            Operation::Delete { page } => client.delete_page(page).await,
+            Operation::DeleteBlock { block } => {
+                client.delete_block(block).await?;
+                Ok(serde_json::Value::Null)
+            }
+            Operation::Comment { page, text } => client.create_comment(page, text).await,
+        }
+    }
+}
+
+fn parse_properties(specs: &[String]) -> Result<Vec<PropertySpec>> {
+    specs.iter().map(|s| PropertySpec::parse(s)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_operations() {
+        let op = Operation::parse(r#"{"op":"append","page":"abc","content":"hi"}"#).unwrap();
+        assert!(matches!(
+            op,
+            Operation::Append {
+                markdown: false,
+                ..
+            }
+        ));
+
+        let op =
+            Operation::parse(r#"{"op":"append-heading","page":"abc","text":"Title"}"#).unwrap();
+        assert!(matches!(op, Operation::AppendHeading { level: 2, .. }));
+
+        let op =
+            Operation::parse(r#"{"op":"update","page":"abc","properties":["Status:select=Done"]}"#)
+                .unwrap();
+        assert_eq!(op.describe(), "update abc");
+
+        assert!(Operation::parse(r#"{"op":"explode","page":"abc"}"#).is_err());
+        assert!(Operation::parse(r#"{"op":"delete"}"#).is_err());
+        assert!(Operation::parse(r#"{"op":"delete","page":"a","typo":1}"#).is_err());
+    }
+
+    #[test]
+    fn test_operation_round_trip() {
+        let op = Operation::Append {
+            page: "abc".to_string(),
+            content: "**hi**".to_string(),
+            markdown: true,
+            after: None,
+        };
+        let line = serde_json::to_string(&op).unwrap();
+        assert!(line.starts_with(r#"{"op":"append","#));
+        let parsed = Operation::parse(&line).unwrap();
+        assert!(matches!(parsed, Operation::Append { markdown: true, .. }));
+    }
+}
@@ -0,0 +1,883 @@
+//! Builders for Notion block payloads.
+//!
+//! Each function returns a block object ready to be sent as one of the
+//! `children` of `PATCH /blocks/{id}/children` or `POST /pages`.
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::{json, Value};
+
+use crate::client::{MentionTarget, RichTextSegment};
+
+/// Maximum length of a single rich text object's content
+pub const MAX_TEXT_LENGTH: usize = 2000;
+
+/// Maximum number of rich text objects in one block
+pub const MAX_RICH_TEXT_ITEMS: usize = 100;
+
+/// Split text into chunks of at most `max_chars` characters, breaking after a
+/// newline where possible.
+///
+/// ```
+/// let chunks = notion_cli_tool::blocks::split_text("aaa\nbbb\ncc", 8);
+/// assert_eq!(chunks, vec!["aaa\nbbb\n", "cc"]);
+/// ```
+pub fn split_text(content: &str, max_chars: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = content;
+
+    while rest.chars().count() > max_chars {
+        let limit = rest
+            .char_indices()
+            .nth(max_chars)
+            .map(|(i, _)| i)
+            .unwrap_or(rest.len());
+        let cut = match rest[..limit].rfind('\n') {
+            Some(i) if i > 0 => i + 1,
+            _ => limit,
+        };
+        chunks.push(&rest[..cut]);
+        rest = &rest[cut..];
+    }
+    if !rest.is_empty() || chunks.is_empty() {
+        chunks.push(rest);
+    }
+
+    chunks
+}
+
+/// Build a rich text array of plain text. Content longer than
+/// [`MAX_TEXT_LENGTH`] is split across several text objects.
+///
+/// ```
+/// let rt = notion_cli_tool::blocks::text("Hello");
+/// assert_eq!(rt[0]["text"]["content"], "Hello");
+/// ```
+pub fn text(content: &str) -> Value {
+    let items: Vec<Value> = split_text(content, MAX_TEXT_LENGTH)
+        .into_iter()
+        .map(|chunk| {
+            json!({
+                "type": "text",
+                "text": { "content": chunk }
+            })
+        })
+        .collect();
+
+    Value::Array(items)
+}
+
+/// Build a rich text array from formatted segments. Segments with a
+/// mention become `mention` items.
+///
+/// ```
+/// use notion_cli_tool::{blocks, RichTextSegment};
+///
+/// let rt = blocks::rich_text(&[
+///     RichTextSegment::plain("See "),
+///     RichTextSegment::link("docs", "https://developers.notion.com"),
+///     RichTextSegment::mention_user("ada@example.com"),
+/// ]);
+/// assert_eq!(rt[1]["text"]["link"]["url"], "https://developers.notion.com");
+/// assert_eq!(rt[2]["mention"]["user"]["id"], "ada@example.com");
+/// ```
+pub fn rich_text(segments: &[RichTextSegment]) -> Value {
+    let items: Vec<Value> = segments
+        .iter()
+        .map(|seg| {
+            let mut annotations = json!({});
+            if seg.bold {
+                annotations["bold"] = json!(true);
+            }
+            if seg.italic {
+                annotations["italic"] = json!(true);
+            }
+            if seg.strikethrough {
+                annotations["strikethrough"] = json!(true);
+            }
+            if seg.code {
+                annotations["code"] = json!(true);
+            }
+
+            if let Some(ref mention) = seg.mention {
+                let mention = match mention {
+                    MentionTarget::User(id) => json!({ "type": "user", "user": { "id": id } }),
+                    MentionTarget::Page(id) => json!({ "type": "page", "page": { "id": id } }),
+                };
+                return json!({
+                    "type": "mention",
+                    "mention": mention,
+                    "annotations": annotations
+                });
+            }
+
+            let mut text_obj = json!({
+                "content": seg.text
+            });
+            if let Some(ref link) = seg.link {
+                text_obj["link"] = json!({ "url": link });
+            }
+
+            json!({
+                "type": "text",
+                "text": text_obj,
+                "annotations": annotations
+            })
+        })
+        .collect();
+
+    Value::Array(items)
+}
+
+/// Replace `{{name}}` placeholders in `text` with the matching values.
+/// Unknown placeholders are left as they are.
+///
+/// ```
+/// let vars = [("name".to_string(), "Alice".to_string())];
+/// let text = notion_cli_tool::blocks::fill_placeholders("Hi {{name}}, {{day}}", &vars);
+/// assert_eq!(text, "Hi Alice, {{day}}");
+/// ```
+pub fn fill_placeholders(text: &str, vars: &[(String, String)]) -> String {
+    vars.iter().fold(text.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{{{}}}}}", name), value)
+    })
+}
+
+/// Fill placeholders (see [`fill_placeholders`]) in every text rich text
+/// object inside `value`, such as a block tree or page properties.
+/// A placeholder split across differently formatted segments isn't matched.
+///
+/// ```
+/// let vars = [("date".to_string(), "2024-07-01".to_string())];
+/// let mut block = notion_cli_tool::blocks::heading(2, "Standup {{date}}");
+/// notion_cli_tool::blocks::substitute_vars(&mut block, &vars);
+/// assert_eq!(block["heading_2"]["rich_text"][0]["text"]["content"], "Standup 2024-07-01");
+/// ```
+pub fn substitute_vars(value: &mut Value, vars: &[(String, String)]) {
+    if vars.is_empty() {
+        return;
+    }
+    match value {
+        Value::Object(map) if map.get("type").and_then(Value::as_str) == Some("text") => {
+            if let Some(Value::String(content)) =
+                map.get_mut("text").and_then(|t| t.get_mut("content"))
+            {
+                *content = fill_placeholders(content, vars);
+            }
+        }
+        Value::Object(map) => map.values_mut().for_each(|v| substitute_vars(v, vars)),
+        Value::Array(items) => items.iter_mut().for_each(|v| substitute_vars(v, vars)),
+        _ => {}
+    }
+}
+
+/// Paragraph block with plain text.
+///
+/// ```
+/// let block = notion_cli_tool::blocks::paragraph("Hello");
+/// assert_eq!(block["type"], "paragraph");
+/// ```
+pub fn paragraph(content: &str) -> Value {
+    json!({
+        "object": "block",
+        "type": "paragraph",
+        "paragraph": {
+            "rich_text": text(content)
+        }
+    })
+}
+
+/// Paragraph blocks for arbitrarily long text, each holding at most
+/// [`MAX_TEXT_LENGTH`] characters.
+///
+/// ```
+/// let long = "x".repeat(4500);
+/// assert_eq!(notion_cli_tool::blocks::paragraphs(&long).len(), 3);
+/// ```
+pub fn paragraphs(content: &str) -> Vec<Value> {
+    split_text(content.trim_end(), MAX_TEXT_LENGTH)
+        .into_iter()
+        .map(|chunk| paragraph(chunk.trim_end_matches('\n')))
+        .collect()
+}
+
+/// Paragraph block with formatted rich text segments.
+pub fn rich_paragraph(segments: &[RichTextSegment]) -> Value {
+    rich_block("paragraph", segments)
+}
+
+/// Any text block (`heading_2`, `quote`, `bulleted_list_item`, ...) with
+/// formatted rich text segments.
+pub fn rich_block(block_type: &str, segments: &[RichTextSegment]) -> Value {
+    json!({
+        "object": "block",
+        "type": block_type,
+        (block_type): {
+            "rich_text": rich_text(segments)
+        }
+    })
+}
+
+/// Heading block. Levels above 3 are clamped to `heading_3`.
+///
+/// ```
+/// let block = notion_cli_tool::blocks::heading(1, "Title");
+/// assert_eq!(block["type"], "heading_1");
+/// ```
+pub fn heading(level: u8, content: &str) -> Value {
+    let block_type = match level {
+        1 => "heading_1",
+        2 => "heading_2",
+        _ => "heading_3",
+    };
+
+    json!({
+        "object": "block",
+        "type": block_type,
+        (block_type): {
+            "rich_text": text(content)
+        }
+    })
+}
+
+/// Code block with a language (e.g., "rust", "plain text").
+pub fn code(content: &str, language: &str) -> Value {
+    json!({
+        "object": "block",
+        "type": "code",
+        "code": {
+            "rich_text": text(content),
+            "language": language
+        }
+    })
+}
+
+/// Code blocks for content of any length. Each block holds at most
+/// [`MAX_RICH_TEXT_ITEMS`] text objects, so longer content continues in the
+/// next block. A caption numbers the parts (`main.rs (2/3)`) when there are
+/// several.
+///
+/// ```
+/// use notion_cli_tool::blocks::{code_blocks, MAX_RICH_TEXT_ITEMS, MAX_TEXT_LENGTH};
+///
+/// let line = format!("{}\n", "x".repeat(99));
+/// let content = line.repeat(MAX_TEXT_LENGTH * MAX_RICH_TEXT_ITEMS / 100 + 1);
+/// let blocks = code_blocks(&content, "rust", Some("main.rs"));
+/// assert_eq!(blocks.len(), 2);
+/// assert_eq!(blocks[0]["code"]["rich_text"].as_array().unwrap().len(), MAX_RICH_TEXT_ITEMS);
+/// assert_eq!(blocks[1]["code"]["caption"][0]["text"]["content"], "main.rs (2/2)");
+///
+/// let single = code_blocks("fn main() {}", "rust", Some("main.rs"));
+/// assert_eq!(single[0]["code"]["caption"][0]["text"]["content"], "main.rs");
+/// ```
+pub fn code_blocks(content: &str, language: &str, caption: Option<&str>) -> Vec<Value> {
+    let parts = split_text(content, MAX_TEXT_LENGTH * MAX_RICH_TEXT_ITEMS);
+    let count = parts.len();
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(i, part)| {
+            let mut block = code(part, language);
+            if let Some(caption) = caption {
+                let caption = if count > 1 {
+                    format!("{} ({}/{})", caption, i + 1, count)
+                } else {
+                    caption.to_string()
+                };
+                block["code"]["caption"] = text(&caption);
+            }
+            block
+        })
+        .collect()
+}
+
+/// The Notion code block language for a name or common alias (`rs`, `js`,
+/// `yml`, ...), case-insensitive.
+///
+/// ```
+/// use notion_cli_tool::blocks::code_language;
+///
+/// assert_eq!(code_language("rs"), Some("rust"));
+/// assert_eq!(code_language("Python"), Some("python"));
+/// assert_eq!(code_language("brainfuck"), None);
+/// ```
+pub fn code_language(name: &str) -> Option<&'static str> {
+    let name = name.trim().to_lowercase();
+    let language = match name.as_str() {
+        "js" | "jsx" | "mjs" | "cjs" => "javascript",
+        "ts" | "tsx" => "typescript",
+        "py" => "python",
+        "rs" => "rust",
+        "sh" | "zsh" | "console" => "shell",
+        "yml" => "yaml",
+        "h" => "c",
+        "cpp" | "cc" | "cxx" | "hpp" => "c++",
+        "cs" | "csharp" => "c#",
+        "rb" => "ruby",
+        "md" => "markdown",
+        "dockerfile" => "docker",
+        "kt" | "kts" => "kotlin",
+        "ex" | "exs" => "elixir",
+        "hs" => "haskell",
+        "pl" => "perl",
+        "ps1" => "powershell",
+        "htm" => "html",
+        "gql" => "graphql",
+        "mk" => "makefile",
+        "patch" => "diff",
+        "txt" | "text" | "plaintext" => "plain text",
+        other => other,
+    };
+    NOTION_LANGUAGES.iter().copied().find(|l| *l == language)
+}
+
+/// The code block language for a file from its extension (or name, for
+/// `Dockerfile` and `Makefile`), `plain text` when unknown.
+///
+/// ```
+/// use notion_cli_tool::blocks::language_for_path;
+/// use std::path::Path;
+///
+/// assert_eq!(language_for_path(Path::new("src/main.rs")), "rust");
+/// assert_eq!(language_for_path(Path::new("Dockerfile")), "docker");
+/// assert_eq!(language_for_path(Path::new("notes")), "plain text");
+/// ```
+pub fn language_for_path(path: &std::path::Path) -> &'static str {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    let by_name = match name.to_lowercase().as_str() {
+        "dockerfile" => Some("docker"),
+        "makefile" | "gnumakefile" => Some("makefile"),
+        _ => None,
+    };
+    by_name
+        .or_else(|| {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .and_then(code_language)
+        })
+        .unwrap_or("plain text")
+}
+
+/// Code block languages the API accepts that pages commonly use
+const NOTION_LANGUAGES: &[&str] = &[
+    "bash",
+    "c",
+    "c#",
+    "c++",
+    "css",
+    "dart",
+    "diff",
+    "docker",
+    "elixir",
+    "go",
+    "graphql",
+    "haskell",
+    "html",
+    "java",
+    "javascript",
+    "json",
+    "kotlin",
+    "lua",
+    "makefile",
+    "markdown",
+    "perl",
+    "php",
+    "plain text",
+    "powershell",
+    "python",
+    "r",
+    "ruby",
+    "rust",
+    "scala",
+    "scss",
+    "shell",
+    "sql",
+    "swift",
+    "toml",
+    "typescript",
+    "xml",
+    "yaml",
+];
+
+/// Bookmark block with an optional caption.
+pub fn bookmark(url: &str, caption: Option<&str>) -> Value {
+    let mut bookmark = json!({ "url": url });
+    if let Some(cap) = caption {
+        bookmark["caption"] = text(cap);
+    }
+
+    json!({
+        "object": "block",
+        "type": "bookmark",
+        "bookmark": bookmark
+    })
+}
+
+/// External image block with an optional caption.
+///
+/// ```
+/// let block = notion_cli_tool::blocks::image("https://example.com/a.png", Some("Diagram"));
+/// assert_eq!(block["image"]["external"]["url"], "https://example.com/a.png");
+/// ```
+pub fn image(url: &str, caption: Option<&str>) -> Value {
+    let mut image = json!({
+        "type": "external",
+        "external": { "url": url }
+    });
+    if let Some(cap) = caption {
+        image["caption"] = text(cap);
+    }
+
+    json!({
+        "object": "block",
+        "type": "image",
+        "image": image
+    })
+}
+
+/// External video block (e.g. a YouTube link) with an optional caption.
+///
+/// ```
+/// let block = notion_cli_tool::blocks::video("https://youtu.be/abc", None);
+/// assert_eq!(block["video"]["external"]["url"], "https://youtu.be/abc");
+/// ```
+pub fn video(url: &str, caption: Option<&str>) -> Value {
+    let mut video = json!({
+        "type": "external",
+        "external": { "url": url }
+    });
+    if let Some(cap) = caption {
+        video["caption"] = text(cap);
+    }
+
+    json!({
+        "object": "block",
+        "type": "video",
+        "video": video
+    })
+}
+
+/// Embed block (a web page, map, design file, ...) with an optional caption.
+///
+/// ```
+/// let block = notion_cli_tool::blocks::embed("https://example.com/map", Some("Venue"));
+/// assert_eq!(block["embed"]["url"], "https://example.com/map");
+/// ```
+pub fn embed(url: &str, caption: Option<&str>) -> Value {
+    let mut embed = json!({ "url": url });
+    if let Some(cap) = caption {
+        embed["caption"] = text(cap);
+    }
+
+    json!({
+        "object": "block",
+        "type": "embed",
+        "embed": embed
+    })
+}
+
+/// Media or file block referencing a completed file upload. `block_type` is
+/// one of `image`, `video`, `audio`, `pdf` or `file`.
+pub fn file_upload(block_type: &str, upload_id: &str, caption: Option<&str>) -> Value {
+    let mut media = json!({
+        "type": "file_upload",
+        "file_upload": { "id": upload_id }
+    });
+    if let Some(cap) = caption {
+        media["caption"] = text(cap);
+    }
+
+    json!({
+        "object": "block",
+        "type": block_type,
+        (block_type): media
+    })
+}
+
+/// Block type used to display a file of the given MIME type
+pub fn block_type_for_content_type(content_type: &str) -> &'static str {
+    match content_type {
+        t if t.starts_with("image/") => "image",
+        t if t.starts_with("video/") => "video",
+        t if t.starts_with("audio/") => "audio",
+        "application/pdf" => "pdf",
+        _ => "file",
+    }
+}
+
+/// Divider block.
+pub fn divider() -> Value {
+    json!({
+        "object": "block",
+        "type": "divider",
+        "divider": {}
+    })
+}
+
+/// Table of contents block (Notion lists the page's headings in it).
+pub fn table_of_contents() -> Value {
+    json!({
+        "object": "block",
+        "type": "table_of_contents",
+        "table_of_contents": {}
+    })
+}
+
+/// Breadcrumb block (Notion shows the page's ancestors in it).
+pub fn breadcrumb() -> Value {
+    json!({
+        "object": "block",
+        "type": "breadcrumb",
+        "breadcrumb": {}
+    })
+}
+
+/// Bulleted list item block.
+pub fn bulleted_list_item(content: &str) -> Value {
+    json!({
+        "object": "block",
+        "type": "bulleted_list_item",
+        "bulleted_list_item": {
+            "rich_text": text(content)
+        }
+    })
+}
+
+/// Numbered list item block with plain text.
+///
+/// ```
+/// let block = notion_cli_tool::blocks::numbered_list_item("Step one");
+/// assert_eq!(block["numbered_list_item"]["rich_text"][0]["text"]["content"], "Step one");
+/// ```
+pub fn numbered_list_item(content: &str) -> Value {
+    json!({
+        "object": "block",
+        "type": "numbered_list_item",
+        "numbered_list_item": {
+            "rich_text": text(content)
+        }
+    })
+}
+
+/// An item of a (possibly nested) list
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListItem {
+    pub text: String,
+    pub numbered: bool,
+    pub children: Vec<ListItem>,
+}
+
+impl ListItem {
+    /// The item's own block, without children
+    pub fn block(&self) -> Value {
+        if self.numbered {
+            numbered_list_item(&self.text)
+        } else {
+            bulleted_list_item(&self.text)
+        }
+    }
+}
+
+/// Parse an indented list, one item per line. A line indented deeper than
+/// the item above it becomes that item's child (a tab counts as 4 spaces).
+/// `- `, `* ` and `+ ` markers make a bulleted item and `1. ` a numbered one;
+/// unmarked lines are numbered when `numbered` is set. Blank lines are skipped.
+///
+/// ```
+/// let items = notion_cli_tool::blocks::parse_list("Plan\n  - research\n  - write\nShip", true);
+/// assert_eq!(items.len(), 2);
+/// assert!(items[0].numbered);
+/// assert_eq!(items[0].children[1].text, "write");
+/// assert!(!items[0].children[1].numbered);
+/// ```
+pub fn parse_list(text: &str, numbered: bool) -> Vec<ListItem> {
+    let lines: Vec<(usize, ListItem)> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let indent = line
+                .chars()
+                .take_while(|c| c.is_whitespace())
+                .map(|c| if c == '\t' { 4 } else { 1 })
+                .sum();
+            let line = line.trim();
+            let (text, numbered) = match ["- ", "* ", "+ "]
+                .iter()
+                .find_map(|marker| line.strip_prefix(marker))
+            {
+                Some(text) => (text, false),
+                None => match numbered_marker(line) {
+                    Some(text) => (text, true),
+                    None => (line, numbered),
+                },
+            };
+            let item = ListItem {
+                text: text.trim().to_string(),
+                numbered,
+                children: Vec::new(),
+            };
+            (indent, item)
+        })
+        .collect();
+
+    nest_items(&lines)
+}
+
+/// Text after a `1. ` marker
+fn numbered_marker(line: &str) -> Option<&str> {
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits == 0 {
+        return None;
+    }
+    line[digits..].strip_prefix(". ")
+}
+
+/// Attach each line to the nearest less indented line above it
+fn nest_items(lines: &[(usize, ListItem)]) -> Vec<ListItem> {
+    let mut items = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let (indent, item) = &lines[i];
+        let end = lines[i + 1..]
+            .iter()
+            .position(|(next, _)| next <= indent)
+            .map_or(lines.len(), |n| i + 1 + n);
+        let mut item = item.clone();
+        item.children = nest_items(&lines[i + 1..end]);
+        items.push(item);
+        i = end;
+    }
+    items
+}
+
+/// Quote block.
+pub fn quote(content: &str) -> Value {
+    json!({
+        "object": "block",
+        "type": "quote",
+        "quote": {
+            "rich_text": text(content)
+        }
+    })
+}
+
+/// Original synced block holding `children`, which pages can then show
+/// through [`synced_block_copy`]s.
+///
+/// ```
+/// use notion_cli_tool::blocks;
+///
+/// let block = blocks::synced_block(&[blocks::paragraph("Shared")]);
+/// assert!(block["synced_block"]["synced_from"].is_null());
+/// assert_eq!(block["synced_block"]["children"][0]["type"], "paragraph");
+/// ```
+pub fn synced_block(children: &[Value]) -> Value {
+    json!({
+        "object": "block",
+        "type": "synced_block",
+        "synced_block": {
+            "synced_from": null,
+            "children": children
+        }
+    })
+}
+
+/// Synced block showing the content of the original synced block `block_id`.
+///
+/// ```
+/// let block = notion_cli_tool::blocks::synced_block_copy("abc");
+/// assert_eq!(block["synced_block"]["synced_from"]["block_id"], "abc");
+/// ```
+pub fn synced_block_copy(block_id: &str) -> Value {
+    json!({
+        "object": "block",
+        "type": "synced_block",
+        "synced_block": {
+            "synced_from": {
+                "type": "block_id",
+                "block_id": block_id
+            }
+        }
+    })
+}
+
+/// Toggle block with optional nested child blocks.
+///
+/// ```
+/// use notion_cli_tool::blocks;
+///
+/// let block = blocks::toggle("Details", &[blocks::paragraph("Hidden")]);
+/// assert_eq!(block["toggle"]["children"][0]["type"], "paragraph");
+/// ```
+pub fn toggle(content: &str, children: &[Value]) -> Value {
+    let mut toggle = json!({
+        "rich_text": text(content)
+    });
+    if !children.is_empty() {
+        toggle["children"] = json!(children);
+    }
+
+    json!({
+        "object": "block",
+        "type": "toggle",
+        "toggle": toggle
+    })
+}
+
+/// Table block with its `table_row` children. The table width is the longest
+/// row; shorter rows are padded with empty cells.
+///
+/// ```
+/// use notion_cli_tool::blocks;
+///
+/// let rows = vec![vec!["a".to_string(), "b".to_string()], vec!["c".to_string()]];
+/// let block = blocks::table(&rows, true);
+/// assert_eq!(block["table"]["table_width"], 2);
+/// assert_eq!(block["table"]["children"][1]["table_row"]["cells"][1], serde_json::json!([]));
+/// ```
+pub fn table(rows: &[Vec<String>], has_column_header: bool) -> Value {
+    let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+
+    let children: Vec<Value> = rows
+        .iter()
+        .map(|row| {
+            let cells: Vec<Value> = (0..width)
+                .map(|i| match row.get(i).map(|c| c.as_str()) {
+                    Some(c) if !c.is_empty() => text(c),
+                    _ => json!([]),
+                })
+                .collect();
+            json!({
+                "object": "block",
+                "type": "table_row",
+                "table_row": { "cells": cells }
+            })
+        })
+        .collect();
+
+    json!({
+        "object": "block",
+        "type": "table",
+        "table": {
+            "table_width": width,
+            "has_column_header": has_column_header,
+            "has_row_header": false,
+            "children": children
+        }
+    })
+}
+
+/// Levels of `children` the API accepts under a block in one request
+const MAX_NESTING: usize = 2;
+
+/// Check user-written block objects before they are sent: `value` must be
+/// an array of blocks (or `{"children": [...]}`), each with a `type` and a
+/// matching object, and children nested at most two levels deep. Errors
+/// name the offending block by its position, e.g. `[2].children[0]`.
+///
+/// ```
+/// use notion_cli_tool::blocks::validate_blocks;
+/// use serde_json::json;
+///
+/// let blocks = json!([
+///     { "type": "divider", "divider": {} },
+///     { "type": "toggle", "toggle": { "rich_text": [], "children": [{ "type": "quote" }] } }
+/// ]);
+/// let err = validate_blocks(&blocks).unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "Block [1].children[0]: type is \"quote\" but there is no \"quote\" object"
+/// );
+/// ```
+pub fn validate_blocks(value: &Value) -> Result<Vec<Value>> {
+    let blocks = value
+        .as_array()
+        .or_else(|| value.get("children").and_then(Value::as_array))
+        .ok_or_else(|| {
+            anyhow!("Expected a JSON array of block objects (or {{\"children\": [...]}})")
+        })?;
+    if blocks.is_empty() {
+        bail!("The block array is empty");
+    }
+    check_blocks(blocks, "", 0)?;
+    Ok(blocks.clone())
+}
+
+fn check_blocks(blocks: &[Value], path: &str, depth: usize) -> Result<()> {
+    for (i, block) in blocks.iter().enumerate() {
+        let path = format!("{}[{}]", path, i);
+        let fail = |problem: String| anyhow!("Block {}: {}", path, problem);
+
+        let fields = block
+            .as_object()
+            .ok_or_else(|| fail(format!("expected an object, found {}", json_kind(block))))?;
+        let block_type = match fields.get("type") {
+            Some(Value::String(t)) if !t.is_empty() => t,
+            Some(other) => {
+                return Err(fail(format!(
+                    "type must be a string, found {}",
+                    json_kind(other)
+                )))
+            }
+            None => return Err(fail("missing \"type\"".to_string())),
+        };
+        let content = fields
+            .get(block_type)
+            .and_then(Value::as_object)
+            .ok_or_else(|| {
+                fail(format!(
+                    "type is \"{0}\" but there is no \"{0}\" object",
+                    block_type
+                ))
+            })?;
+        if fields.contains_key("children") {
+            return Err(fail(format!(
+                "\"children\" belongs inside the \"{}\" object",
+                block_type
+            )));
+        }
+        if let Some(rich_text) = content.get("rich_text") {
+            if !rich_text.is_array() {
+                return Err(fail(format!(
+                    "\"{}.rich_text\" must be an array of rich text objects",
+                    block_type
+                )));
+            }
+        }
+
+        match content.get("children") {
+            None => {}
+            Some(Value::Array(_)) if depth == MAX_NESTING => {
+                return Err(fail(format!(
+                    "children are nested more than {} levels deep; append the deeper blocks \
+                     separately",
+                    MAX_NESTING
+                )))
+            }
+            Some(Value::Array(children)) => {
+                check_blocks(children, &format!("{}.children", path), depth + 1)?
+            }
+            Some(other) => {
+                return Err(fail(format!(
+                    "children must be an array, found {}",
+                    json_kind(other)
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `a string`, `a number`, ... for error messages
+fn json_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
@@ -1,5 +1,8 @@
-use crate::utils::DEFAULT_TIMEOUT_SECS;
-use clap::{Parser, Subcommand};
+use clap::builder::PossibleValuesParser;
+use clap::{Args, Parser, Subcommand};
+use clap_complete::Shell;
+use notion_cli_tool::utils::CONFIG_KEYS;
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "notion-cli")]
@@ -13,9 +16,157 @@ pub struct Cli {
     #[arg(long, global = true, env = "NOTION_API_KEY")]
     pub api_key: Option<String>,
 
-    /// Request timeout in seconds
-    #[arg(long, default_value_t = DEFAULT_TIMEOUT_SECS, global = true)]
-    pub timeout: u64,
+    /// Request timeout in seconds [default: 30, or `timeout` from config]
+    #[arg(long, global = true)]
+    pub timeout: Option<u64>,
+
+    /// Retries for rate limits and transient errors [default: 3, or `max_retries` from config]
+    #[arg(long, global = true)]
+    pub max_retries: Option<u32>,
+
+    /// Maximum requests per second, 0 for no limit [default: 3, or `rate_limit` from config]
+    #[arg(long, global = true)]
+    pub rate_limit: Option<f64>,
+
+    /// Blocks whose children are fetched at once when reading nested content [default: 4, or `concurrency` from config]
+    #[arg(long, global = true, value_parser = clap::value_parser!(u16).range(1..))]
+    pub concurrency: Option<u16>,
+
+    /// Notion-Version to send, e.g. 2022-06-28 [default: 2025-09-03, or `api_version` from the profile]
+    #[arg(long, global = true, value_parser = api_version)]
+    pub api_version: Option<String>,
+
+    /// Don't use the page cache for this command (see `cache` in config)
+    #[arg(long, global = true)]
+    pub no_cache: bool,
+
+    /// Hide progress spinners and bars (also hidden when stdout isn't a terminal) and
+    /// the lines narrating what the command is doing
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// When to use colors (`auto`: only on a terminal, and not when NO_COLOR is set)
+    #[arg(long, global = true, default_value = "auto", value_parser = ["auto", "always", "never"])]
+    pub color: String,
+
+    /// Time zone for dates typed without an offset and for dates shown, as an IANA name like Asia/Seoul [default: the system's]
+    #[arg(long, global = true, value_parser = time_zone)]
+    pub tz: Option<chrono_tz::Tz>,
+
+    /// Show dates and times as ISO 8601 in UTC, ignoring `date_format` (for scripts)
+    #[arg(long, global = true)]
+    pub utc: bool,
+
+    /// Config profile to use (`[profiles.<name>]` in config.toml)
+    #[arg(long, global = true, env = "NOTION_PROFILE")]
+    pub profile: Option<String>,
+
+    /// After the command, print the API calls, retries, bytes and time per endpoint to stderr
+    #[arg(long, global = true)]
+    pub stats: bool,
+
+    /// How to print errors: a message with a hint, or one JSON object on stderr
+    /// with the exit code, Notion error code and request ID (for scripts)
+    #[arg(long, global = true, default_value = "text", value_parser = ["text", "json"])]
+    pub error_format: String,
+
+    /// Same as --error-format, given before the command (after it, commands
+    /// like export and query use --output for their output file)
+    #[arg(long, value_parser = ["text", "json"], conflicts_with = "error_format")]
+    pub output: Option<String>,
+}
+
+/// A page given by ID/URL, or by title with `--name`
+#[derive(Args)]
+pub struct PageTarget {
+    /// Page ID or URL (or title, with --name)
+    pub page_id: String,
+    /// Look the page up by title instead of ID
+    #[arg(long)]
+    pub name: bool,
+}
+
+/// A database given by ID/URL, or by title with `--name`
+#[derive(Args)]
+pub struct DatabaseTarget {
+    /// Database ID or URL (or title, with --name)
+    pub database_id: String,
+    /// Look the database up by title instead of ID
+    #[arg(long)]
+    pub name: bool,
+}
+
+/// Manual pagination: fetch one page of results and print the raw JSON
+/// response, whose `next_cursor` continues the listing
+#[derive(Args)]
+pub struct Pagination {
+    /// Start at this cursor (the `next_cursor` of a previous page)
+    #[arg(long)]
+    pub start_cursor: Option<String>,
+    /// Results in the page (1-100) [default: 100]
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=100))]
+    pub page_size: Option<u8>,
+}
+
+impl Pagination {
+    /// Whether a single page was asked for, and how many results it holds
+    pub fn manual(&self) -> Option<usize> {
+        if self.start_cursor.is_none() && self.page_size.is_none() {
+            return None;
+        }
+        Some(self.page_size.map_or(100, usize::from))
+    }
+}
+
+/// Which database rows `query` and `db-export` return, in what order
+#[derive(Args)]
+pub struct QueryFilters {
+    /// Filter by property (format: "Name[:type]<op>value" or "Name:type:operator[=value]")
+    /// Operators: = != > >= < <= ~ (contains) !~ (not contains); repeatable
+    /// The type is taken from the database schema unless given (title, rich_text, select,
+    /// multi_select, status, checkbox, number, date, ...)
+    #[arg(short, long)]
+    pub filter: Vec<String>,
+    /// How multiple filters combine
+    #[arg(long, default_value = "and", value_parser = ["and", "or"])]
+    pub filter_mode: String,
+    /// Sort key as "Name[:asc|desc]" (repeatable, first key sorts first);
+    /// created_time and last_edited_time sort by page timestamps
+    #[arg(short, long)]
+    pub sort: Vec<String>,
+    /// Direction for sort keys that don't give one
+    #[arg(long, default_value = "desc", value_parser = ["asc", "desc"])]
+    pub direction: String,
+}
+
+/// How `query` prints its results
+#[derive(Args)]
+pub struct QueryOutput {
+    /// Output format: an aligned table, a list with a few properties per page, CSV,
+    /// or one JSON page object per line as results arrive (ndjson)
+    #[arg(long, default_value = "table", value_parser = ["table", "list", "csv", "ndjson"])]
+    pub format: String,
+    /// Properties to include as table or CSV columns, comma-separated (default: all)
+    #[arg(long, value_delimiter = ',')]
+    pub columns: Vec<String>,
+    /// Show table cells in full instead of truncating them to fit the terminal
+    #[arg(long)]
+    pub full: bool,
+    /// Write CSV to a file instead of stdout
+    #[arg(short, long, requires = "format")]
+    pub output: Option<PathBuf>,
+    /// Show related pages by title instead of ID (one request per related page)
+    #[arg(long)]
+    pub resolve_relations: bool,
+    /// Print the number of results per value of this property instead of the results
+    #[arg(long, value_name = "PROPERTY", conflicts_with = "format")]
+    pub count_by: Option<String>,
+    /// Print the total and average of a numeric property (per group with --count-by); repeatable
+    #[arg(long, value_name = "PROPERTY", conflicts_with = "format")]
+    pub sum: Vec<String>,
+    /// Print only the IDs of the results, one per line
+    #[arg(long, conflicts_with_all = ["format", "count_by", "sum", "start_cursor", "page_size"])]
+    pub id_only: bool,
 }
 
 #[derive(Subcommand)]
@@ -27,94 +178,499 @@ pub enum Commands {
         /// Maximum results to fetch (handles pagination)
         #[arg(short, long, default_value_t = 100)]
         limit: usize,
+        /// Only return pages or only databases
+        #[arg(long, value_parser = ["pages", "databases"])]
+        only: Option<String>,
+        /// Sort by timestamp
+        #[arg(short, long, value_parser = ["last_edited_time"])]
+        sort: Option<String>,
+        /// Sort direction (asc or desc)
+        #[arg(long, default_value = "desc", requires = "sort")]
+        direction: String,
+        /// Output format: a list, or one JSON object per line as results arrive
+        #[arg(long, default_value = "list", value_parser = ["list", "ndjson"])]
+        format: String,
+        /// Print only the IDs of the results, one per line
+        #[arg(long, conflicts_with_all = ["format", "start_cursor", "page_size"])]
+        id_only: bool,
+        #[command(flatten)]
+        pagination: Pagination,
+    },
+    /// Search the content of pages with a regular expression
+    Grep {
+        /// Regular expression to match against each line of block text
+        pattern: String,
+        /// Only search this page (default: the most recently edited pages)
+        #[arg(short, long)]
+        page: Option<String>,
+        /// Match case-insensitively
+        #[arg(short, long)]
+        ignore_case: bool,
+        /// Maximum pages to search without --page
+        #[arg(short, long, default_value_t = 100)]
+        limit: usize,
+    },
+    /// List the child blocks of a page or block with their type, ID and text
+    Blocks {
+        #[command(flatten)]
+        target: PageTarget,
+        /// Also list nested blocks, indented under their parent
+        #[arg(short, long, conflicts_with_all = ["start_cursor", "page_size"])]
+        recursive: bool,
+        /// Only blocks of these types (e.g. code, to_do; comma-separated or repeatable)
+        #[arg(short = 't', long = "type", value_delimiter = ',', conflicts_with_all = ["start_cursor", "page_size"])]
+        types: Vec<String>,
+        /// Only blocks whose text contains this (ignoring case)
+        #[arg(long, conflicts_with_all = ["start_cursor", "page_size"])]
+        contains: Option<String>,
+        /// Print only the IDs of the blocks, one per line
+        #[arg(long, conflicts_with_all = ["start_cursor", "page_size"])]
+        id_only: bool,
+        #[command(flatten)]
+        pagination: Pagination,
     },
     /// Read a page content
     Read {
-        /// Page ID
-        page_id: String,
+        #[command(flatten)]
+        target: PageTarget,
+        /// Maximum nesting depth for child blocks (default: unlimited)
+        #[arg(short, long)]
+        depth: Option<usize>,
+        /// Look up the titles of mentioned pages and names of mentioned users
+        /// (one request each)
+        #[arg(long)]
+        resolve_mentions: bool,
+        /// Also read sub-pages, each as a section under its title
+        #[arg(short, long)]
+        follow: bool,
+        /// List the page's headings under each table of contents block
+        #[arg(long)]
+        resolve: bool,
+        /// Also copy the page's plain text to the clipboard
+        #[arg(long)]
+        copy: bool,
+    },
+    /// Open a page in the browser
+    Open {
+        /// Page ID or URL, or a title to search for
+        page: String,
+        /// Print the page URL instead of opening it
+        #[arg(long)]
+        print: bool,
+    },
+    /// Show page metadata: timestamps, authors, parents, icon, cover, URL
+    Info {
+        #[command(flatten)]
+        target: PageTarget,
     },
     /// Create a new page
     Create {
-        /// Parent page ID
+        /// Parent page ID (defaults to the profile's default_parent)
         #[arg(short, long)]
-        parent: String,
+        parent: Option<String>,
+        /// Create the page as a row in this database instead
+        #[arg(long, conflicts_with = "parent")]
+        parent_db: Option<String>,
+        /// Property value for the database row (format: "Name:type=value", repeatable; see db-create)
+        #[arg(short = 'P', long = "prop", requires = "parent_db")]
+        props: Vec<String>,
         /// Page title
         #[arg(short, long)]
         title: String,
-        /// Page content (optional)
+        /// Page content (optional, `-` reads stdin)
         #[arg(short, long)]
         content: Option<String>,
+        /// Read page content from a file
+        #[arg(short, long, conflicts_with = "content")]
+        file: Option<PathBuf>,
+        /// Page icon (emoji or image URL)
+        #[arg(short, long)]
+        icon: Option<String>,
+        /// Page icon from an image URL
+        #[arg(long, conflicts_with = "icon", value_parser = http_url)]
+        icon_url: Option<String>,
+        /// Queue the change locally instead of sending it (replay with `sync-queue`)
+        #[arg(long)]
+        offline: bool,
+        /// Print only the new page's ID
+        #[arg(long, conflicts_with = "offline")]
+        id_only: bool,
+    },
+    /// Create a row in a database
+    DbCreate {
+        #[command(flatten)]
+        target: DatabaseTarget,
+        /// Row title
+        #[arg(short, long)]
+        title: String,
+        /// Property value (format: "Name:type=value", repeatable)
+        /// Supported types: rich_text (default), select, status, multi_select, date,
+        /// number, checkbox, people (IDs, emails or names), url, email, phone_number
+        #[arg(short = 'P', long = "prop")]
+        props: Vec<String>,
+    },
+    /// Create one database row per CSV row (headers name the properties)
+    DbImport {
+        #[command(flatten)]
+        target: DatabaseTarget,
+        /// CSV file with a header row
+        file: PathBuf,
+    },
+    /// Export every row of a database as a Markdown table, JSON or CSV
+    DbExport {
+        #[command(flatten)]
+        target: DatabaseTarget,
+        /// Output format
+        #[arg(long, default_value = "md", value_parser = ["md", "json", "csv"])]
+        format: String,
+        /// Properties to include, comma-separated (default: all, title first)
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<String>,
+        #[command(flatten)]
+        filters: QueryFilters,
+        /// File to write instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Archive every database row matching filters, after listing them
+    DbArchive {
+        #[command(flatten)]
+        target: DatabaseTarget,
+        #[command(flatten)]
+        filters: QueryFilters,
+        /// Only rows not edited (or created, see --timestamp) within this long, e.g. 90d, 12w, 1y
+        #[arg(long, value_parser = age, required_unless_present = "filter")]
+        older_than: Option<chrono::Duration>,
+        /// Timestamp --older-than looks at
+        #[arg(
+            long,
+            default_value = "last_edited_time",
+            value_parser = ["last_edited_time", "created_time"],
+            requires = "older_than"
+        )]
+        timestamp: String,
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Find rows sharing a key property value, and archive or merge the extras
+    DbDedupe {
+        #[command(flatten)]
+        target: DatabaseTarget,
+        /// Property whose value identifies a row (compared ignoring case)
+        #[arg(short, long)]
+        key: String,
+        /// Archive every duplicate, keeping the oldest row of each group
+        #[arg(long, conflicts_with = "merge")]
+        archive: bool,
+        /// Fill the oldest row's empty properties from its duplicates, then archive them
+        #[arg(long)]
+        merge: bool,
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Create a new database under a page
+    DbNew {
+        /// Parent page ID (defaults to the profile's default_parent)
+        #[arg(short, long)]
+        parent: Option<String>,
+        /// Database title
+        #[arg(short, long)]
+        title: String,
+        /// JSON file with Notion property definitions
+        #[arg(long)]
+        schema: Option<PathBuf>,
+        /// Property definition (format: "Name:type[=options]", repeatable)
+        /// e.g. "Status:select=Todo,Doing,Done", "Points:number=dollar", "Due:date"
+        #[arg(short = 'P', long = "prop")]
+        props: Vec<String>,
+    },
+    /// Add, rename or remove database properties, or change select options
+    DbProp {
+        #[command(subcommand)]
+        action: DbPropCommand,
+    },
+    /// Show a database's properties and their types
+    DbSchema {
+        #[command(flatten)]
+        target: DatabaseTarget,
     },
     /// Append content to a page
     Append {
-        /// Page ID
-        page_id: String,
-        /// Content to append
-        content: String,
+        #[command(flatten)]
+        target: PageTarget,
+        /// Insert after this block ID instead of at the end of the page
+        #[arg(long)]
+        after: Option<String>,
+        /// Content to append (`-` reads stdin)
+        #[arg(required_unless_present_any = ["file", "clipboard", "mention_user", "mention_page"])]
+        content: Option<String>,
+        /// Read content from a file
+        #[arg(short, long, conflicts_with = "content")]
+        file: Option<PathBuf>,
+        /// Append the clipboard: its text, or an image as an uploaded file
+        #[arg(long, conflicts_with_all = ["content", "file"])]
+        clipboard: bool,
+        /// Parse inline Markdown (bold, italic, code, strikethrough, links,
+        /// and `@[user:email]` / `@[page:id]` mentions)
+        #[arg(short, long)]
+        markdown: bool,
+        /// Mention a user (email, name or ID) at the end of the paragraph; repeatable
+        #[arg(long = "mention-user", value_name = "USER")]
+        mention_user: Vec<String>,
+        /// Mention a page (ID or URL) at the end of the paragraph; repeatable
+        #[arg(long = "mention-page", value_name = "PAGE")]
+        mention_page: Vec<String>,
+        /// Queue the change locally instead of sending it (replay with `sync-queue`)
+        #[arg(long)]
+        offline: bool,
+    },
+    /// Append the readable content of a web page (a "web clipper")
+    Clip {
+        #[command(flatten)]
+        target: PageTarget,
+        /// URL of the web page, or `-` to read HTML from stdin
+        url: String,
+        /// Insert after this block ID instead of at the end of the page
+        #[arg(long)]
+        after: Option<String>,
+    },
+    /// Append the commits of a git repository to a release-notes page
+    GitLog {
+        /// Page ID, or database ID with --database
+        #[command(flatten)]
+        target: PageTarget,
+        /// Only commits after this tag, branch or hash
+        #[arg(long)]
+        since: Option<String>,
+        /// Repository to read (default: the current directory)
+        #[arg(long)]
+        repo: Option<PathBuf>,
+        /// Most recent commits to include
+        #[arg(short, long)]
+        limit: Option<usize>,
+        /// Block layout on the page
+        #[arg(short, long, default_value = "list", value_parser = ["list", "table"])]
+        format: String,
+        /// Heading to add above the commits
+        #[arg(long, conflicts_with = "database")]
+        heading: Option<String>,
+        /// Create one database row per commit instead of appending blocks
+        #[arg(long)]
+        database: bool,
     },
     /// Append a code block to a page
     AppendCode {
-        /// Page ID
-        page_id: String,
-        /// Code content
-        code: String,
-        /// Programming language (e.g., rust, python, javascript)
-        #[arg(short, long, default_value = "plain text")]
-        language: String,
+        #[command(flatten)]
+        target: PageTarget,
+        /// Insert after this block ID instead of at the end of the page
+        #[arg(long)]
+        after: Option<String>,
+        /// Code content (`-` reads stdin)
+        #[arg(required_unless_present = "file")]
+        code: Option<String>,
+        /// Read code from a file
+        #[arg(short, long, conflicts_with = "code")]
+        file: Option<PathBuf>,
+        /// Programming language (e.g., rust, python, javascript) [default: from
+        /// the --file extension, else plain text]
+        #[arg(short, long)]
+        language: Option<String>,
+        /// Caption the block; without a value, the --file name
+        #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "")]
+        caption: Option<String>,
+    },
+    /// Append raw block objects from a JSON file (for blocks the other commands don't build)
+    AppendJson {
+        #[command(flatten)]
+        target: PageTarget,
+        /// JSON file with an array of Notion block objects (`-` reads stdin)
+        file: PathBuf,
+        /// Insert after this block ID instead of at the end of the page
+        #[arg(long)]
+        after: Option<String>,
     },
     /// Append a bookmark to a page
     AppendBookmark {
-        /// Page ID
-        page_id: String,
+        #[command(flatten)]
+        target: PageTarget,
+        /// Insert after this block ID instead of at the end of the page
+        #[arg(long)]
+        after: Option<String>,
         /// Bookmark URL
         url: String,
         /// Optional caption
         #[arg(short, long)]
         caption: Option<String>,
     },
-    /// Update a page (title, icon)
+    /// Append an external image to a page
+    AppendImage {
+        #[command(flatten)]
+        target: PageTarget,
+        /// Insert after this block ID instead of at the end of the page
+        #[arg(long)]
+        after: Option<String>,
+        /// Image URL
+        url: String,
+        /// Optional caption
+        #[arg(short, long)]
+        caption: Option<String>,
+    },
+    /// Append an external video (e.g. YouTube) to a page
+    AppendVideo {
+        #[command(flatten)]
+        target: PageTarget,
+        /// Insert after this block ID instead of at the end of the page
+        #[arg(long)]
+        after: Option<String>,
+        /// Video URL
+        #[arg(value_parser = http_url)]
+        url: String,
+        /// Optional caption
+        #[arg(short, long)]
+        caption: Option<String>,
+    },
+    /// Append an embedded web page (maps, designs, forms, ...) to a page
+    AppendEmbed {
+        #[command(flatten)]
+        target: PageTarget,
+        /// Insert after this block ID instead of at the end of the page
+        #[arg(long)]
+        after: Option<String>,
+        /// URL to embed
+        #[arg(value_parser = http_url)]
+        url: String,
+        /// Optional caption
+        #[arg(short, long)]
+        caption: Option<String>,
+    },
+    /// Upload a local file and attach it to a page
+    Upload {
+        #[command(flatten)]
+        target: PageTarget,
+        /// Insert after this block ID instead of at the end of the page
+        #[arg(long)]
+        after: Option<String>,
+        /// File to upload (images, videos, audio and PDFs are embedded inline)
+        path: PathBuf,
+        /// Optional caption
+        #[arg(short, long)]
+        caption: Option<String>,
+    },
+    /// Update a page (title, icon, properties)
     Update {
-        /// Page ID
-        page_id: String,
+        #[command(flatten)]
+        target: PageTarget,
         /// New title
         #[arg(short, long)]
         title: Option<String>,
-        /// New icon (emoji)
+        /// New icon (emoji or image URL)
         #[arg(short, long)]
         icon: Option<String>,
+        /// New icon from an image URL
+        #[arg(long, conflicts_with = "icon", value_parser = http_url)]
+        icon_url: Option<String>,
+        /// Property value to set (format: "Name:type=value", repeatable; see db-create)
+        #[arg(short = 'P', long = "prop")]
+        props: Vec<String>,
+        /// Queue the change locally instead of sending it (replay with `sync-queue`)
+        #[arg(long)]
+        offline: bool,
+    },
+    /// Link a page to other pages through a relation property, or unlink it
+    Relate {
+        #[command(flatten)]
+        target: PageTarget,
+        /// Relation property to change
+        #[arg(long = "prop")]
+        property: String,
+        /// Page ID or URL to link (repeatable)
+        #[arg(long, required = true)]
+        to: Vec<String>,
+        /// Unlink the --to pages instead
+        #[arg(long)]
+        remove: bool,
     },
     /// Delete (archive) a page
     Delete {
-        /// Page ID
-        page_id: String,
+        #[command(flatten)]
+        target: PageTarget,
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
     },
     /// Query a database
     Query {
-        /// Database ID
-        database_id: String,
-        /// Filter by property (format: "PropertyName=value" or "PropertyName:type=value")
-        /// Supported types: title, rich_text (default), select, checkbox, number
-        #[arg(short, long)]
-        filter: Option<String>,
-        /// Sort by property
-        #[arg(short, long)]
-        sort: Option<String>,
-        /// Sort direction (asc or desc)
-        #[arg(long, default_value = "desc")]
-        direction: String,
+        #[command(flatten)]
+        target: DatabaseTarget,
+        #[command(flatten)]
+        filters: QueryFilters,
         /// Maximum results
         #[arg(short, long, default_value_t = 100)]
         limit: usize,
+        #[command(flatten)]
+        output: QueryOutput,
+        #[command(flatten)]
+        pagination: Pagination,
+    },
+    /// Delete all (or some) child blocks of a page
+    Clear {
+        #[command(flatten)]
+        target: PageTarget,
+        /// Only delete blocks of this type (e.g. paragraph, heading_2)
+        #[arg(short = 't', long = "type")]
+        block_type: Option<String>,
+        /// Only delete blocks after this block ID
+        #[arg(long)]
+        after: Option<String>,
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Change the text of a block in place
+    UpdateBlock {
+        /// Block ID
+        block_id: String,
+        /// New text (`-` reads stdin)
+        #[arg(required_unless_present_any = ["checked", "unchecked", "language"])]
+        text: Option<String>,
+        /// Check a to-do
+        #[arg(long, conflicts_with = "unchecked")]
+        checked: bool,
+        /// Uncheck a to-do
+        #[arg(long)]
+        unchecked: bool,
+        /// Change a code block's language
+        #[arg(short, long)]
+        language: Option<String>,
+    },
+    /// Move a block (and its children) to another page or position
+    MoveBlock {
+        /// Block ID
+        block_id: String,
+        /// Destination page (or block) ID
+        #[arg(long)]
+        to: String,
+        /// Insert after this block ID instead of at the end
+        #[arg(long)]
+        after: Option<String>,
     },
     /// Delete (archive) a block
     DeleteBlock {
         /// Block ID
         block_id: String,
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
     },
     /// Append a heading to a page
     AppendHeading {
-        /// Page ID
-        page_id: String,
+        #[command(flatten)]
+        target: PageTarget,
+        /// Insert after this block ID instead of at the end of the page
+        #[arg(long)]
+        after: Option<String>,
         /// Heading text
         text: String,
         /// Heading level (1, 2, or 3)
@@ -123,20 +679,120 @@ pub enum Commands {
     },
     /// Append a divider to a page
     AppendDivider {
-        /// Page ID
-        page_id: String,
+        #[command(flatten)]
+        target: PageTarget,
+        /// Insert after this block ID instead of at the end of the page
+        #[arg(long)]
+        after: Option<String>,
+    },
+    /// Append a table of contents to a page
+    AppendToc {
+        #[command(flatten)]
+        target: PageTarget,
+        /// Insert after this block ID instead of at the end of the page
+        #[arg(long)]
+        after: Option<String>,
+    },
+    /// Append a breadcrumb (the page's location) to a page
+    AppendBreadcrumb {
+        #[command(flatten)]
+        target: PageTarget,
+        /// Insert after this block ID instead of at the end of the page
+        #[arg(long)]
+        after: Option<String>,
+    },
+    /// Append a quote to a page
+    AppendQuote {
+        #[command(flatten)]
+        target: PageTarget,
+        /// Insert after this block ID instead of at the end of the page
+        #[arg(long)]
+        after: Option<String>,
+        /// Quote text
+        text: String,
+    },
+    /// Append a toggle to a page
+    AppendToggle {
+        #[command(flatten)]
+        target: PageTarget,
+        /// Insert after this block ID instead of at the end of the page
+        #[arg(long)]
+        after: Option<String>,
+        /// Toggle summary text
+        text: String,
+        /// Paragraph nested inside the toggle (repeatable)
+        #[arg(short, long)]
+        child: Vec<String>,
+    },
+    /// Append a synced block: a new original, or a copy of an existing one
+    AppendSynced {
+        #[command(flatten)]
+        target: PageTarget,
+        /// Insert after this block ID instead of at the end of the page
+        #[arg(long)]
+        after: Option<String>,
+        /// Block ID of the original synced block to show here
+        #[arg(
+            long,
+            required_unless_present = "new",
+            conflicts_with_all = ["new", "content", "file", "markdown"]
+        )]
+        original: Option<String>,
+        /// Create a new original synced block holding the given content
+        #[arg(long)]
+        new: bool,
+        /// Content of a new synced block (`-` reads stdin)
+        #[arg(requires = "new", required_unless_present_any = ["original", "file"])]
+        content: Option<String>,
+        /// Read the content of a new synced block from a file
+        #[arg(short, long, requires = "new", conflicts_with = "content")]
+        file: Option<PathBuf>,
+        /// Parse inline Markdown in the content
+        #[arg(short, long, requires = "new")]
+        markdown: bool,
+    },
+    /// Append a table to a page
+    AppendTable {
+        #[command(flatten)]
+        target: PageTarget,
+        /// Insert after this block ID instead of at the end of the page
+        #[arg(long)]
+        after: Option<String>,
+        /// CSV file to read rows from
+        #[arg(long, conflicts_with = "rows", required_unless_present = "rows")]
+        csv: Option<PathBuf>,
+        /// Inline rows (format: "a,b;c,d" — rows separated by ';', cells by ',')
+        #[arg(long)]
+        rows: Option<String>,
+        /// Treat the first row as a column header
+        #[arg(long, default_value_t = false)]
+        header: bool,
     },
     /// Append a bulleted list to a page
     AppendList {
-        /// Page ID
-        page_id: String,
-        /// List items (comma-separated)
-        items: String,
+        #[command(flatten)]
+        target: PageTarget,
+        /// Insert after this block ID instead of at the end of the page
+        #[arg(long)]
+        after: Option<String>,
+        /// List items: comma-separated, or one per line with indentation for
+        /// nesting (`-` reads stdin)
+        #[arg(required_unless_present = "file")]
+        items: Option<String>,
+        /// Read items from a file, one per line (indent to nest)
+        #[arg(short, long, conflicts_with = "items")]
+        file: Option<PathBuf>,
+        /// Create a numbered list instead of a bulleted one
+        #[arg(short, long)]
+        numbered: bool,
     },
     /// Append a paragraph with a link
     AppendLink {
-        /// Page ID
-        page_id: String,
+        #[command(flatten)]
+        target: PageTarget,
+        /// Insert after this block ID instead of at the end of the page
+        #[arg(long)]
+        after: Option<String>,
         /// Text before the link
         #[arg(long)]
         prefix: Option<String>,
@@ -152,26 +808,487 @@ pub enum Commands {
     },
     /// Get block IDs for a page (for bulk operations)
     GetBlockIds {
-        /// Page ID
-        page_id: String,
+        #[command(flatten)]
+        target: PageTarget,
+    },
+    /// Show a page's nested blocks with their types, text and IDs, or the tree
+    /// of sub-pages and databases under a page (--root) or the whole workspace
+    Tree {
+        /// Page ID or URL (or title, with --name) whose blocks to show
+        #[arg(conflicts_with = "root")]
+        page_id: Option<String>,
+        /// Look the page up by title instead of ID
+        #[arg(long, requires = "page_id")]
+        name: bool,
+        /// Show the sub-pages and databases under this page instead of blocks
+        /// (without a page ID or --root, those of the whole workspace)
+        #[arg(long)]
+        root: Option<String>,
+        /// Maximum nesting depth for child blocks, or for sub-pages below the
+        /// root (default: unlimited)
+        #[arg(short, long)]
+        depth: Option<usize>,
+        /// Output format for the page tree
+        #[arg(long, default_value = "text", value_parser = ["text", "json"], conflicts_with = "page_id")]
+        format: String,
     },
     /// Move a page to a new parent
     Move {
-        /// Source page ID
-        page_id: String,
+        #[command(flatten)]
+        target: PageTarget,
         /// New parent page ID
         #[arg(short, long)]
         parent: String,
         /// Delete original page after copying
         #[arg(long, default_value_t = false)]
         delete: bool,
+        /// Skip the confirmation prompt for --delete
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Export a page with all its nested content as Markdown or HTML
+    Export {
+        #[command(flatten)]
+        target: PageTarget,
+        /// Output format (`html` is a standalone document with inline CSS)
+        #[arg(short, long, default_value = "markdown", value_parser = ["markdown", "html"])]
+        format: String,
+        /// File to write instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Save images and files in a `<name>_files` folder next to the output and
+        /// link to those copies (Notion-hosted file URLs expire after an hour)
+        #[arg(long, requires = "output")]
+        download_assets: bool,
+    },
+    /// Edit a page as Markdown in $EDITOR and apply the changes
+    Edit {
+        #[command(flatten)]
+        target: PageTarget,
+    },
+    /// Poll a page and print what changed whenever it is edited
+    Watch {
+        #[command(flatten)]
+        target: PageTarget,
+        /// Seconds between checks
+        #[arg(short, long, default_value_t = 30, value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+        /// Shell command to run on each change, with the diff on stdin
+        #[arg(long)]
+        exec: Option<String>,
+    },
+    /// Run operations from a JSON Lines file, one per line
+    Batch {
+        /// Path to the .jsonl file (use - for stdin)
+        file: PathBuf,
+        /// Continue with the next line after a failed operation
+        #[arg(short, long)]
+        keep_going: bool,
+    },
+    /// Deep-copy a page with all of its content
+    Duplicate {
+        #[command(flatten)]
+        target: PageTarget,
+        /// Parent page ID for the copy (default: same parent as the original)
+        #[arg(short, long)]
+        parent: Option<String>,
+        /// Title for the copy (default: the original title)
+        #[arg(short, long)]
+        title: Option<String>,
+        /// Print only the copy's ID
+        #[arg(long)]
+        id_only: bool,
+    },
+    /// Publish a directory of Markdown files as pages under a page:
+    /// subdirectories become parent pages, `.md` files pages
+    Push {
+        /// Directory to publish
+        dir: PathBuf,
+        /// Page to publish under
+        #[arg(long)]
+        root: String,
+        /// Archive pages whose file or directory was removed since the last push
+        #[arg(long)]
+        archive_removed: bool,
+        /// Show what would change without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Save the pages under a page as Markdown files, one directory per page
+    /// with sub-pages, rewriting only the files of pages edited since
+    Pull {
+        #[command(flatten)]
+        target: PageTarget,
+        /// Directory to write to (created if missing)
+        dir: PathBuf,
+        /// Delete files pulled earlier whose page is no longer under the page
+        #[arg(long)]
+        prune: bool,
+    },
+    /// Create a page from a template page, filling in {{name}} placeholders
+    CreateFromTemplate {
+        /// Template page ID or URL
+        #[arg(short, long)]
+        template: String,
+        /// Parent page ID (defaults to `default_parent` from config)
+        #[arg(short, long)]
+        parent: Option<String>,
+        /// Title for the new page (default: the template title, with placeholders filled)
+        #[arg(long)]
+        title: Option<String>,
+        /// Placeholder value as name=value (repeatable)
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = template_var)]
+        vars: Vec<(String, String)>,
+    },
+    /// Add, list and reply to comments
+    Comment {
+        #[command(subcommand)]
+        action: CommentCommand,
+    },
+    /// List workspace users
+    Users {
+        #[command(subcommand)]
+        action: UsersCommand,
+    },
+    /// Add, list and complete tasks in a task database
+    Todo {
+        /// Task database ID (defaults to `todo_database` from config)
+        #[arg(short, long)]
+        database: Option<String>,
+        #[command(subcommand)]
+        action: TodoCommand,
+    },
+    /// Show the integration (bot) behind the current API key
+    Whoami,
+    /// Set up config interactively (API key, default parent, timeout)
+    Init(InitArgs),
+    /// Manage the stored API key
+    Auth {
+        #[command(subcommand)]
+        action: AuthCommand,
+    },
+    /// Show current config, or get/set individual keys
+    Config {
+        #[command(subcommand)]
+        action: Option<ConfigCommand>,
+    },
+    /// Send changes queued with --offline (or while Notion was unreachable)
+    SyncQueue {
+        /// Only list the queued changes
+        #[arg(short, long)]
+        list: bool,
+        /// Remove changes that fail instead of keeping them queued
+        #[arg(long)]
+        drop_failed: bool,
+    },
+    /// Answer JSON-RPC requests on a Unix socket with one long-lived client
+    Serve {
+        /// Socket path to listen on
+        #[arg(long)]
+        socket: PathBuf,
+    },
+    /// Browse, preview and edit pages in an interactive terminal UI
+    Tui {
+        /// Initial search (default: recently edited pages)
+        query: Option<String>,
+    },
+    /// Inspect the Notion API version in use
+    ApiVersion {
+        #[command(subcommand)]
+        action: ApiVersionCommand,
+    },
+    /// Manage the page content cache (enabled with `config set cache true`)
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommand,
+    },
+    /// Generate a shell completion script
+    Completions {
+        /// Shell to generate the script for
+        shell: Shell,
     },
-    /// Initialize config with API key
-    Init {
-        /// API key to save (if not provided, will prompt)
+    /// Print cached page and database IDs for shell completion
+    #[command(name = "__complete-pages", hide = true)]
+    CompletePages,
+}
+
+impl Commands {
+    /// Whether the command was asked to print only IDs (`--id-only`)
+    pub fn id_only(&self) -> bool {
+        match self {
+            Commands::Create { id_only, .. }
+            | Commands::Search { id_only, .. }
+            | Commands::Duplicate { id_only, .. }
+            | Commands::Blocks { id_only, .. } => *id_only,
+            Commands::Query { output, .. } => output.id_only,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Print a config value (api_key is masked)
+    Get {
+        /// Config key
+        #[arg(value_parser = PossibleValuesParser::new(CONFIG_KEYS))]
+        key: String,
+    },
+    /// Set a config value (on the --profile profile if given)
+    Set {
+        /// Config key
+        #[arg(value_parser = PossibleValuesParser::new(CONFIG_KEYS))]
+        key: String,
+        /// New value
+        value: String,
+    },
+    /// Remove a config value
+    Unset {
+        /// Config key
+        #[arg(value_parser = PossibleValuesParser::new(CONFIG_KEYS))]
+        key: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ApiVersionCommand {
+    /// Send a request with the configured version and report how it's handled
+    Check,
+}
+
+#[derive(Subcommand)]
+pub enum CacheCommand {
+    /// Delete all cached page content
+    Clear,
+}
+
+#[derive(Args)]
+pub struct InitArgs {
+    /// API key to save (if not provided, will prompt)
+    #[arg(long)]
+    pub api_key: Option<String>,
+    /// Store the key in the OS keyring instead of config.toml
+    #[arg(long)]
+    pub keyring: bool,
+    /// Save the key without checking it against the API
+    #[arg(long)]
+    pub no_verify: bool,
+    /// Log in through a public integration's OAuth consent page instead of pasting a key
+    #[arg(long, conflicts_with_all = ["api_key", "no_verify"])]
+    pub oauth: bool,
+    /// OAuth client ID of the public integration
+    #[arg(long, env = "NOTION_OAUTH_CLIENT_ID", requires = "oauth")]
+    pub client_id: Option<String>,
+    /// OAuth client secret of the public integration
+    #[arg(
+        long,
+        env = "NOTION_OAUTH_CLIENT_SECRET",
+        requires = "oauth",
+        hide_env_values = true
+    )]
+    pub client_secret: Option<String>,
+    /// Local port for the OAuth redirect (register http://localhost:<port>/callback) [default: 8765]
+    #[arg(long, requires = "oauth")]
+    pub port: Option<u16>,
+}
+
+#[derive(Subcommand)]
+pub enum AuthCommand {
+    /// Save an API key (same as `init`)
+    Login(InitArgs),
+    /// Remove the saved API key from config.toml and the OS keyring
+    Logout,
+    /// Check the API key in use against the API (fails if it is invalid or revoked)
+    Status,
+    /// Check the saved key of every profile
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum DbPropCommand {
+    /// Add a property
+    Add {
+        #[command(flatten)]
+        target: DatabaseTarget,
+        /// Property definition, as for `db-new --prop` (format: "Name:type[=options]")
+        /// e.g. "Priority:select", "Estimate:number=dollar", "Due:date"
+        spec: String,
+        /// Option of a select or multi_select property, as "Name[:color]" (repeatable)
+        #[arg(long = "option")]
+        options: Vec<String>,
+    },
+    /// Rename a property
+    Rename {
+        #[command(flatten)]
+        target: DatabaseTarget,
+        /// Current property name
+        property: String,
+        /// New property name
+        new_name: String,
+    },
+    /// Delete a property and its values in every row
+    Remove {
+        #[command(flatten)]
+        target: DatabaseTarget,
+        /// Property name
+        property: String,
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Add or remove the options of a select or multi_select property
+    Options {
+        #[command(flatten)]
+        target: DatabaseTarget,
+        /// Property name
+        property: String,
+        /// Option to add, as "Name[:color]" (repeatable)
+        #[arg(long, required_unless_present = "remove")]
+        add: Vec<String>,
+        /// Option to remove, by name (repeatable); rows lose the value
         #[arg(long)]
-        api_key: Option<String>,
+        remove: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CommentCommand {
+    /// Add a comment to a page
+    Add {
+        #[command(flatten)]
+        target: PageTarget,
+        /// Comment text
+        text: String,
+    },
+    /// List comments on a page or block, grouped by discussion
+    List {
+        #[command(flatten)]
+        target: PageTarget,
     },
-    /// Show current config
-    Config,
+    /// Reply to a discussion thread
+    Reply {
+        /// Discussion ID (shown by `comment list`)
+        discussion_id: String,
+        /// Reply text
+        text: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TodoCommand {
+    /// Add a task
+    Add {
+        /// Task title
+        title: String,
+        /// Due date: YYYY-MM-DD, today, tomorrow, a weekday, next week, "in 3 days" or +3d,
+        /// optionally with a time ("fri 14:00")
+        #[arg(long)]
+        due: Option<String>,
+        /// Other property values (format: "Name:type=value", repeatable; see db-create)
+        #[arg(short = 'P', long = "prop")]
+        props: Vec<String>,
+    },
+    /// List open tasks, soonest due first
+    List {
+        /// Only tasks with this status
+        #[arg(short, long, conflicts_with = "all")]
+        status: Option<String>,
+        /// Include finished tasks
+        #[arg(short, long)]
+        all: bool,
+        /// Maximum tasks
+        #[arg(short, long, default_value_t = 50)]
+        limit: usize,
+    },
+    /// Mark a task as done
+    Done {
+        /// Task page ID, or its number in the last `todo list`
+        task: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum UsersCommand {
+    /// List people and bots in the workspace
+    List {
+        /// Maximum users to fetch (handles pagination)
+        #[arg(short, long, default_value_t = 100)]
+        limit: usize,
+    },
+}
+
+/// Accept only http(s) URLs
+fn http_url(value: &str) -> Result<String, String> {
+    if value.starts_with("https://") || value.starts_with("http://") {
+        Ok(value.to_string())
+    } else {
+        Err("expected an http(s) URL".to_string())
+    }
+}
+
+/// Check an `--api-version` looks like a Notion-Version date
+fn api_version(value: &str) -> Result<String, String> {
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|_| value.to_string())
+        .map_err(|_| "expected a date like 2025-09-03".to_string())
+}
+
+/// Parse a `--tz` time zone name
+fn time_zone(value: &str) -> Result<chrono_tz::Tz, String> {
+    value
+        .parse()
+        .map_err(|_| "expected an IANA time zone name like Asia/Seoul or UTC".to_string())
+}
+
+/// Parse an age like `36h`, `90d`, `12w` or `1y` (365 days)
+fn age(value: &str) -> Result<chrono::Duration, String> {
+    let invalid = || "expected a number with h, d, w or y, e.g. 90d".to_string();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let count: i64 = value[..split].parse().map_err(|_| invalid())?;
+    match &value[split..] {
+        "h" => Ok(chrono::Duration::hours(count)),
+        "d" => Ok(chrono::Duration::days(count)),
+        "w" => Ok(chrono::Duration::weeks(count)),
+        "y" => Ok(chrono::Duration::days(count * 365)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parse a `--var name=value` template variable
+fn template_var(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err("expected NAME=VALUE".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli_definition() {
+        // Building the command tree in a debug build needs more than the
+        // 2 MiB stack test threads get
+        std::thread::Builder::new()
+            .stack_size(8 * 1024 * 1024)
+            .spawn(|| Cli::command().debug_assert())
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_age() {
+        assert_eq!(age("90d"), Ok(chrono::Duration::days(90)));
+        assert_eq!(age("2w"), Ok(chrono::Duration::days(14)));
+        assert_eq!(age("1y"), Ok(chrono::Duration::days(365)));
+        assert!(age("d").is_err());
+        assert!(age("3 months").is_err());
+    }
 }
@@ -1,18 +1,47 @@
 use anyhow::{bail, Context, Result};
 use colored::Colorize;
-use std::time::Duration;
-
+use futures_util::future::try_join_all;
+use indicatif::ProgressBar;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+use crate::blocks::{self, ListItem};
+use crate::dates;
+use crate::error::{ErrorKind, NotionError};
+use crate::markdown;
+use crate::models::{Page, PageNode};
+use crate::progress;
+use crate::replay::{Cassette, Mode};
+use crate::usage::{self, ApiStats, Call};
 use crate::utils::{
-    get_api_version, normalize_page_id, DEFAULT_RETRY_DELAY_SECS, MAX_RETRIES, NOTION_API_BASE,
+    get_api_version, guess_content_type, load_cached_page, normalize_page_id, save_cached_page,
+    CachedPage, DATA_SOURCES_API_VERSION, DEFAULT_CONCURRENCY, DEFAULT_RATE_LIMIT,
+    DEFAULT_RETRY_DELAY_SECS, MAX_RETRIES, NOTION_API_BASE, SINGLE_PART_UPLOAD_LIMIT,
+    UPLOAD_PART_SIZE,
 };
 
-#[derive(Debug, Clone, Default)]
+/// A run of text with optional link and annotations, used to build rich text arrays
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct RichTextSegment {
     pub text: String,
     pub link: Option<String>,
     pub bold: bool,
     pub italic: bool,
+    pub strikethrough: bool,
     pub code: bool,
+    /// Sent as a mention instead of text when set
+    pub mention: Option<MentionTarget>,
+}
+
+/// What a [`RichTextSegment`] mentions. Users may be given by email or name
+/// until [`NotionClient::resolve_mentions`] swaps in their IDs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MentionTarget {
+    User(String),
+    Page(String),
 }
 
 impl RichTextSegment {
@@ -31,7 +60,6 @@ impl RichTextSegment {
         }
     }
 
-    #[allow(dead_code)]
     pub fn code_inline(text: &str) -> Self {
         Self {
             text: text.to_string(),
@@ -40,7 +68,6 @@ impl RichTextSegment {
         }
     }
 
-    #[allow(dead_code)]
     pub fn bold(text: &str) -> Self {
         Self {
             text: text.to_string(),
@@ -48,644 +75,1381 @@ impl RichTextSegment {
             ..Default::default()
         }
     }
+
+    /// An @-mention of a user, by ID, email or name
+    pub fn mention_user(user: &str) -> Self {
+        Self {
+            text: user.to_string(),
+            mention: Some(MentionTarget::User(user.to_string())),
+            ..Default::default()
+        }
+    }
+
+    /// A mention linking a page, by ID or URL
+    pub fn mention_page(page: &str) -> Self {
+        Self {
+            text: page.to_string(),
+            mention: Some(MentionTarget::Page(page.to_string())),
+            ..Default::default()
+        }
+    }
 }
 
-pub struct NotionClient {
-    api_key: String,
-    api_version: String,
-    client: reqwest::blocking::Client,
+/// A property assignment parsed from `Name:type=value` (type defaults to `rich_text`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertySpec {
+    pub name: String,
+    pub prop_type: String,
+    pub value: String,
 }
 
-impl NotionClient {
-    pub fn new(api_key: String, timeout_secs: u64) -> Result<Self> {
-        let client = reqwest::blocking::Client::builder()
-            .timeout(Duration::from_secs(timeout_secs))
-            .build()
-            .context("Failed to create HTTP client")?;
+impl PropertySpec {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (prop_part, value) = spec
+            .split_once('=')
+            .with_context(|| format!("Invalid property '{}': expected Name:type=value", spec))?;
+
+        let (name, prop_type) = match prop_part.rsplit_once(':') {
+            Some((n, t)) => (n.trim(), t.trim()),
+            None => (prop_part.trim(), "rich_text"),
+        };
+
+        if name.is_empty() {
+            bail!("Invalid property '{}': property name is empty", spec);
+        }
 
         Ok(Self {
-            api_key,
-            api_version: get_api_version(),
-            client,
+            name: name.to_string(),
+            prop_type: prop_type.to_string(),
+            value: value.trim().to_string(),
         })
     }
 
-    /// Execute a request with retry logic for rate limiting (429)
-    fn execute_with_retry(
-        &self,
-        request_builder: impl Fn() -> reqwest::blocking::RequestBuilder,
-    ) -> Result<reqwest::blocking::Response> {
-        let mut retries = 0;
-
-        loop {
-            let response = request_builder()
-                .header("Authorization", format!("Bearer {}", self.api_key))
-                .header("Notion-Version", &self.api_version)
-                .send()
-                .context("Failed to send request")?;
+    /// Build the property value payload for `POST /pages` and `PATCH /pages/{id}`.
+    /// An empty value clears the property.
+    pub fn to_payload(&self) -> Result<serde_json::Value> {
+        let v = self.value.as_str();
+        let list = || -> Vec<&str> {
+            v.split(',')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .collect()
+        };
+        let or_null = |value: serde_json::Value| {
+            if v.is_empty() {
+                serde_json::Value::Null
+            } else {
+                value
+            }
+        };
 
-            if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
-                if retries >= MAX_RETRIES {
-                    bail!("Rate limit exceeded after {} retries", MAX_RETRIES);
-                }
+        let payload = match self.prop_type.as_str() {
+            "title" => serde_json::json!({ "title": blocks::text(v) }),
+            "rich_text" | "text" => serde_json::json!({ "rich_text": blocks::text(v) }),
+            "select" => serde_json::json!({ "select": or_null(serde_json::json!({ "name": v })) }),
+            "status" => serde_json::json!({ "status": or_null(serde_json::json!({ "name": v })) }),
+            "multi_select" => {
+                let options: Vec<_> = list()
+                    .into_iter()
+                    .map(|name| serde_json::json!({ "name": name }))
+                    .collect();
+                serde_json::json!({ "multi_select": options })
+            }
+            "date" => {
+                let start = if v.is_empty() {
+                    String::new()
+                } else {
+                    dates::parse_date(v)
+                        .with_context(|| format!("Invalid date for property '{}'", self.name))?
+                };
+                serde_json::json!({ "date": or_null(serde_json::json!({ "start": start })) })
+            }
+            "number" => {
+                let number = if v.is_empty() {
+                    serde_json::Value::Null
+                } else {
+                    let n: f64 = v.parse().with_context(|| {
+                        format!("Invalid number '{}' for property '{}'", v, self.name)
+                    })?;
+                    serde_json::json!(n)
+                };
+                serde_json::json!({ "number": number })
+            }
+            "checkbox" => {
+                let Some(checked) = parse_checkbox(v) else {
+                    bail!(
+                        "Invalid checkbox value '{}' for property '{}'",
+                        v,
+                        self.name
+                    );
+                };
+                serde_json::json!({ "checkbox": checked })
+            }
+            "people" => {
+                let users: Vec<_> = list()
+                    .into_iter()
+                    .map(|id| {
+                        normalize_page_id(id)
+                            .map(|id| serde_json::json!({ "object": "user", "id": id }))
+                    })
+                    .collect::<Result<_>>()?;
+                serde_json::json!({ "people": users })
+            }
+            "url" => serde_json::json!({ "url": or_null(serde_json::json!(v)) }),
+            "email" => serde_json::json!({ "email": or_null(serde_json::json!(v)) }),
+            "phone_number" => serde_json::json!({ "phone_number": or_null(serde_json::json!(v)) }),
+            other => bail!(
+                "Unsupported property type '{}' for '{}'. Supported: title, rich_text, select, \
+                status, multi_select, date, number, checkbox, people, url, email, phone_number",
+                other,
+                self.name
+            ),
+        };
 
-                let retry_after = response
-                    .headers()
-                    .get("Retry-After")
-                    .and_then(|v| v.to_str().ok())
-                    .and_then(|s| s.parse::<u64>().ok())
-                    .unwrap_or(DEFAULT_RETRY_DELAY_SECS);
+        Ok(payload)
+    }
+}
 
-                eprintln!(
-                    "{} Rate limited. Waiting {} seconds before retry ({}/{})...",
-                    "⚠".yellow(),
-                    retry_after,
-                    retries + 1,
-                    MAX_RETRIES
-                );
+/// Property types [`PropertySpec::to_payload`] can write
+pub const SETTABLE_PROPERTY_TYPES: [&str; 12] = [
+    "title",
+    "rich_text",
+    "select",
+    "status",
+    "multi_select",
+    "date",
+    "number",
+    "checkbox",
+    "people",
+    "url",
+    "email",
+    "phone_number",
+];
+
+/// Match CSV headers to database properties (exactly, then ignoring case).
+/// Returns the property name and type for each header, or `None` for headers
+/// that have no matching property or whose type can't be written.
+pub fn match_columns(
+    headers: &[String],
+    schema: &serde_json::Value,
+) -> Vec<Option<(String, String)>> {
+    let properties: Vec<(&String, &str)> = schema
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(name, prop)| prop["type"].as_str().map(|t| (name, t)))
+        .collect();
+
+    headers
+        .iter()
+        .map(|header| {
+            let header = header.trim();
+            properties
+                .iter()
+                .find(|(name, _)| name.as_str() == header)
+                .or_else(|| {
+                    properties
+                        .iter()
+                        .find(|(name, _)| name.eq_ignore_ascii_case(header))
+                })
+                .filter(|(_, prop_type)| SETTABLE_PROPERTY_TYPES.contains(prop_type))
+                .map(|(name, prop_type)| (name.to_string(), prop_type.to_string()))
+        })
+        .collect()
+}
 
-                std::thread::sleep(Duration::from_secs(retry_after));
-                retries += 1;
-                continue;
-            }
+/// Build a page `icon` object: an external image for http(s) URLs, otherwise
+/// an emoji
+pub fn build_icon(icon: &str) -> Result<serde_json::Value> {
+    if icon.starts_with("https://") || icon.starts_with("http://") {
+        return Ok(serde_json::json!({
+            "type": "external",
+            "external": { "url": icon }
+        }));
+    }
+    // Keycap emoji contain ASCII digits, `#` or `*`, but never letters
+    if icon.trim().is_empty()
+        || icon
+            .chars()
+            .any(|c| c.is_ascii_alphabetic() || c.is_whitespace())
+    {
+        bail!(
+            "Invalid icon '{}': expected an emoji or an http(s) image URL",
+            icon
+        );
+    }
+    Ok(serde_json::json!({
+        "type": "emoji",
+        "emoji": icon
+    }))
+}
 
-            return response
-                .error_for_status()
-                .context("Notion API returned an error");
-        }
+/// Build a `properties` object from property specs
+pub fn build_properties(specs: &[PropertySpec]) -> Result<serde_json::Value> {
+    let mut properties = serde_json::Map::new();
+    for spec in specs {
+        properties.insert(spec.name.clone(), spec.to_payload()?);
     }
+    Ok(serde_json::Value::Object(properties))
+}
 
-    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<serde_json::Value>> {
-        let url = format!("{}/search", NOTION_API_BASE);
-        let mut all_results = Vec::new();
-        let mut start_cursor: Option<String> = None;
+/// A database property definition parsed from `Name:type[=options]`
+///
+/// Options depend on the type: comma-separated choices (`High:red,Low`) for
+/// select/multi_select, a format for number, an expression for formula and a
+/// target database ID for relation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaSpec {
+    pub name: String,
+    pub prop_type: String,
+    pub options: Option<String>,
+}
 
-        loop {
-            let mut body = serde_json::json!({
-                "query": query,
-                "page_size": 100.min(limit - all_results.len())
-            });
+impl SchemaSpec {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (prop_part, options) = match spec.split_once('=') {
+            Some((p, o)) => (p, Some(o.trim().to_string())),
+            None => (spec, None),
+        };
 
-            if let Some(cursor) = &start_cursor {
-                body["start_cursor"] = serde_json::json!(cursor);
-            }
+        let (name, prop_type) = match prop_part.rsplit_once(':') {
+            Some((n, t)) => (n.trim(), t.trim()),
+            None => (prop_part.trim(), "rich_text"),
+        };
 
-            let body_clone = body.clone();
-            let url_clone = url.clone();
-            let response = self.execute_with_retry(|| {
-                self.client
-                    .post(&url_clone)
-                    .header("Content-Type", "application/json")
-                    .json(&body_clone)
-            })?;
+        if name.is_empty() {
+            bail!("Invalid property '{}': property name is empty", spec);
+        }
 
-            let result: serde_json::Value = response.json().context("Failed to parse response")?;
+        Ok(Self {
+            name: name.to_string(),
+            prop_type: prop_type.to_string(),
+            options: options.filter(|o| !o.is_empty()),
+        })
+    }
 
-            if let Some(results) = result.get("results").and_then(|r| r.as_array()) {
-                all_results.extend(results.clone());
+    /// Build the property definition for `POST /databases`
+    pub fn to_definition(&self) -> Result<serde_json::Value> {
+        let opts = self.options.as_deref();
+
+        let definition = match self.prop_type.as_str() {
+            "title" | "rich_text" | "date" | "checkbox" | "url" | "email" | "phone_number"
+            | "people" | "files" | "created_time" | "created_by" | "last_edited_time"
+            | "last_edited_by" => serde_json::json!({ (self.prop_type.as_str()): {} }),
+            "number" => serde_json::json!({
+                "number": { "format": opts.unwrap_or("number") }
+            }),
+            "select" | "multi_select" => {
+                let options: Vec<_> = opts
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|o| !o.is_empty())
+                    .map(|o| match o.split_once(':') {
+                        Some((name, color)) => serde_json::json!({ "name": name, "color": color }),
+                        None => serde_json::json!({ "name": o }),
+                    })
+                    .collect();
+                serde_json::json!({ (self.prop_type.as_str()): { "options": options } })
             }
-
-            let has_more = result
-                .get("has_more")
-                .and_then(|h| h.as_bool())
-                .unwrap_or(false);
-            if !has_more || all_results.len() >= limit {
-                break;
+            "formula" => {
+                let expression = opts.with_context(|| {
+                    format!("Formula property '{}' needs an expression", self.name)
+                })?;
+                serde_json::json!({ "formula": { "expression": expression } })
             }
-
-            start_cursor = result
-                .get("next_cursor")
-                .and_then(|c| c.as_str())
-                .map(String::from);
-            if start_cursor.is_none() {
-                break;
+            "relation" => {
+                let target = opts.with_context(|| {
+                    format!(
+                        "Relation property '{}' needs a target database ID",
+                        self.name
+                    )
+                })?;
+                serde_json::json!({
+                    "relation": {
+                        "database_id": normalize_page_id(target)?,
+                        "single_property": {}
+                    }
+                })
             }
-        }
+            other => bail!(
+                "Unsupported property type '{}' for '{}'. Supported: title, rich_text, number, \
+                select, multi_select, date, checkbox, url, email, phone_number, people, files, \
+                formula, relation, created_time, created_by, last_edited_time, last_edited_by",
+                other,
+                self.name
+            ),
+        };
 
-        Ok(all_results)
+        Ok(definition)
     }
+}
 
-    pub fn get_page(&self, page_id: &str) -> Result<serde_json::Value> {
-        let page_id = normalize_page_id(page_id)?;
-        let url = format!("{}/pages/{}", NOTION_API_BASE, page_id);
+/// Build a database `properties` schema from specs, merged over an optional base
+/// schema. A `Name` title property is added when none is defined.
+pub fn build_schema(
+    specs: &[SchemaSpec],
+    base: Option<serde_json::Value>,
+) -> Result<serde_json::Value> {
+    let mut properties = match base {
+        Some(serde_json::Value::Object(map)) => map,
+        Some(_) => bail!("Schema must be a JSON object of property definitions"),
+        None => serde_json::Map::new(),
+    };
+
+    for spec in specs {
+        properties.insert(spec.name.clone(), spec.to_definition()?);
+    }
 
-        let response = self.execute_with_retry(|| self.client.get(&url))?;
-        let result: serde_json::Value = response.json().context("Failed to parse response")?;
-        Ok(result)
+    let has_title = properties.values().any(|p| p.get("title").is_some());
+    if !has_title {
+        properties.insert("Name".to_string(), serde_json::json!({ "title": {} }));
     }
 
-    pub fn get_blocks(&self, page_id: &str) -> Result<Vec<serde_json::Value>> {
-        let page_id = normalize_page_id(page_id)?;
-        let base_url = format!("{}/blocks/{}/children", NOTION_API_BASE, page_id);
-        let mut all_blocks = Vec::new();
-        let mut start_cursor: Option<String> = None;
+    Ok(serde_json::Value::Object(properties))
+}
 
-        loop {
-            let request_url = if let Some(cursor) = &start_cursor {
-                format!("{}?start_cursor={}", base_url, cursor)
-            } else {
-                base_url.clone()
-            };
+/// The definition update for a select or multi-select property that adds
+/// the options in `add` (`Name[:color]`) and drops those named in `remove`.
+/// `definition` is the property's current definition from the schema; its
+/// other options are kept.
+///
+/// ```
+/// use notion_cli_tool::client::update_options;
+/// use serde_json::json;
+///
+/// let priority = json!({ "type": "select", "select": { "options": [
+///     { "id": "a", "name": "Low", "color": "gray" },
+///     { "id": "b", "name": "High", "color": "red" }
+/// ] } });
+/// let update = update_options(&priority, &["Urgent:purple".to_string()], &["low".to_string()]).unwrap();
+/// assert_eq!(update, json!({ "select": { "options": [
+///     { "id": "b", "name": "High" },
+///     { "name": "Urgent", "color": "purple" }
+/// ] } }));
+/// ```
+pub fn update_options(
+    definition: &serde_json::Value,
+    add: &[String],
+    remove: &[String],
+) -> Result<serde_json::Value> {
+    let prop_type = definition["type"].as_str().unwrap_or_default();
+    match prop_type {
+        "select" | "multi_select" => {}
+        "status" => {
+            bail!("Notion's API can't change the options of status properties; edit them in Notion")
+        }
+        other => bail!(
+            "Only select and multi_select properties have options, not {}",
+            other
+        ),
+    }
 
-            let response = self.execute_with_retry(|| self.client.get(&request_url))?;
-            let result: serde_json::Value = response.json().context("Failed to parse response")?;
+    let current: Vec<&serde_json::Value> = definition[prop_type]["options"]
+        .as_array()
+        .map(|o| o.iter().collect())
+        .unwrap_or_default();
+    let named = |option: &serde_json::Value, name: &str| {
+        option["name"]
+            .as_str()
+            .is_some_and(|n| n.eq_ignore_ascii_case(name.trim()))
+    };
+
+    for name in remove {
+        if !current.iter().any(|o| named(o, name)) {
+            bail!("No option named '{}'", name.trim());
+        }
+    }
+    let mut options: Vec<serde_json::Value> = current
+        .iter()
+        .filter(|o| !remove.iter().any(|name| named(o, name)))
+        .map(|o| serde_json::json!({ "id": o["id"], "name": o["name"] }))
+        .collect();
+
+    for option in add {
+        let (name, color) = match option.split_once(':') {
+            Some((name, color)) => (name.trim(), Some(color.trim())),
+            None => (option.trim(), None),
+        };
+        if name.is_empty() {
+            bail!("Invalid option '{}': the name is empty", option);
+        }
+        if options.iter().any(|o| named(o, name)) {
+            bail!("Option '{}' already exists", name);
+        }
+        let mut new = serde_json::json!({ "name": name });
+        if let Some(color) = color {
+            new["color"] = color.into();
+        }
+        options.push(new);
+    }
 
-            if let Some(results) = result.get("results").and_then(|r| r.as_array()) {
-                all_blocks.extend(results.clone());
-            }
+    Ok(serde_json::json!({ (prop_type): { "options": options } }))
+}
 
-            let has_more = result
-                .get("has_more")
-                .and_then(|h| h.as_bool())
-                .unwrap_or(false);
-            if !has_more {
-                break;
-            }
+/// Maximum number of child blocks in one create/append request
+const MAX_BLOCKS_PER_REQUEST: usize = 100;
+
+/// Block types whose text `update_block_text` can change
+const TEXT_BLOCK_TYPES: [&str; 12] = [
+    "paragraph",
+    "heading_1",
+    "heading_2",
+    "heading_3",
+    "bulleted_list_item",
+    "numbered_list_item",
+    "quote",
+    "toggle",
+    "to_do",
+    "callout",
+    "code",
+    "template",
+];
+
+/// Comparison operators accepted in filter expressions, longest first
+const FILTER_OPERATORS: [&str; 8] = ["!=", ">=", "<=", "!~", "=", ">", "<", "~"];
+
+/// A database filter condition parsed from `Name[:type[:operator]]<op>value`.
+///
+/// `<op>` is one of `=`, `!=`, `>`, `>=`, `<`, `<=`, `~` (contains) or `!~`
+/// (does not contain). A Notion operator name may be given explicitly as the
+/// third segment instead (e.g. `Due:date:on_or_after=2024-07-01` or
+/// `Tags:multi_select:is_empty`). Relative date operators (`past_week`,
+/// `next_month`, ...) take no value, and date values may be relative too
+/// (`today`, `fri 9:30`, `in 3 days`; see [`crate::dates::parse_date_at`]).
+///
+/// Formula properties are filtered on their result, given as
+/// `Name:formula.<number|string|checkbox|date>` or inferred from the value.
+///
+/// Without a type, [`FilterSpec::parse`] assumes `rich_text`, while
+/// [`FilterSpec::parse_for_schema`] takes the property's type from the
+/// database schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterSpec {
+    pub property: String,
+    pub prop_type: String,
+    /// Result type a formula filter applies to
+    pub formula_type: Option<String>,
+    pub operator: String,
+    pub value: Option<String>,
+}
 
-            start_cursor = result
-                .get("next_cursor")
-                .and_then(|c| c.as_str())
-                .map(String::from);
-            if start_cursor.is_none() {
-                break;
-            }
-        }
+impl FilterSpec {
+    pub fn parse(expr: &str) -> Result<Self> {
+        Self::parse_with(expr, None)
+    }
 
-        Ok(all_blocks)
+    /// Parse a condition on a property of the database whose `properties`
+    /// schema is given. The property must exist (matched exactly, then
+    /// ignoring case); its type is used unless one is given explicitly.
+    pub fn parse_for_schema(expr: &str, schema: &serde_json::Value) -> Result<Self> {
+        Self::parse_with(expr, Some(schema))
     }
 
-    pub fn create_page(
-        &self,
-        parent_id: &str,
-        title: &str,
-        content: Option<&str>,
-    ) -> Result<serde_json::Value> {
-        let parent_id = normalize_page_id(parent_id)?;
-        let url = format!("{}/pages", NOTION_API_BASE);
-
-        let mut children = vec![];
-        if let Some(text) = content {
-            children.push(serde_json::json!({
-                "object": "block",
-                "type": "paragraph",
-                "paragraph": {
-                    "rich_text": [{
-                        "type": "text",
-                        "text": { "content": text }
-                    }]
-                }
-            }));
+    fn parse_with(expr: &str, schema: Option<&serde_json::Value>) -> Result<Self> {
+        let op_match = FILTER_OPERATORS
+            .iter()
+            .filter_map(|op| expr.find(op).map(|pos| (pos, *op)))
+            .min_by_key(|(pos, op)| (*pos, std::cmp::Reverse(op.len())));
+
+        let (lhs, symbol, value) = match op_match {
+            Some((pos, op)) => (&expr[..pos], Some(op), Some(expr[pos + op.len()..].trim())),
+            None => (expr, None, None),
+        };
+
+        let mut parts = lhs.splitn(3, ':').map(str::trim);
+        let property = parts.next().unwrap_or_default();
+        let explicit_type = parts.next().filter(|t| !t.is_empty());
+        let named_op = parts.next().filter(|o| !o.is_empty());
+
+        if property.is_empty() {
+            bail!("Invalid filter '{}': property name is empty", expr);
         }
 
-        let body = serde_json::json!({
-            "parent": { "page_id": parent_id },
-            "properties": {
-                "title": {
-                    "title": [{
-                        "text": { "content": title }
-                    }]
-                }
-            },
-            "children": children
-        });
+        let (property, schema_type) = match schema {
+            Some(schema) => {
+                let (name, prop_type) = find_property(schema, property)?;
+                (name, Some(prop_type))
+            }
+            None => (property.to_string(), None),
+        };
+        let prop_type = explicit_type
+            .or(schema_type.as_deref())
+            .unwrap_or("rich_text");
+        let (prop_type, formula_type) = match prop_type.split_once('.') {
+            Some(("formula", result)) => ("formula", Some(result.to_string())),
+            _ if prop_type == "formula" => ("formula", Some(formula_result_type(value))),
+            _ => (prop_type, None),
+        };
+        // The type the condition itself is written for
+        let condition_type = formula_type.as_deref().unwrap_or(prop_type);
+
+        let operator = match (named_op, symbol) {
+            (Some(op), None | Some("=")) => op.to_string(),
+            (Some(_), Some(sym)) => bail!(
+                "Invalid filter '{}': use either a named operator or '{}', not both",
+                expr,
+                sym
+            ),
+            (None, Some(sym)) => Self::operator_for(condition_type, sym)
+                .with_context(|| {
+                    format!(
+                        "Operator '{}' is not supported for {} properties",
+                        sym, condition_type
+                    )
+                })?
+                .to_string(),
+            (None, None) => bail!(
+                "Invalid filter '{}': expected Name[:type]=value or Name:type:operator",
+                expr
+            ),
+        };
 
-        let response = self.execute_with_retry(|| {
-            self.client
-                .post(&url)
-                .header("Content-Type", "application/json")
-                .json(&body)
-        })?;
+        let value = match value {
+            Some(v) if DATE_FILTER_TYPES.contains(&condition_type) => {
+                Some(dates::parse_date(v).with_context(|| format!("Invalid filter '{}'", expr))?)
+            }
+            v => v.map(String::from),
+        };
 
-        let result: serde_json::Value = response.json().context("Failed to parse response")?;
-        Ok(result)
+        Ok(Self {
+            property,
+            prop_type: prop_type.to_string(),
+            formula_type,
+            operator,
+            value,
+        })
     }
 
-    pub fn append_blocks(&self, page_id: &str, content: &str) -> Result<serde_json::Value> {
-        let page_id = normalize_page_id(page_id)?;
-        let url = format!("{}/blocks/{}/children", NOTION_API_BASE, page_id);
+    /// Map a comparison symbol to the Notion operator for a property type
+    fn operator_for(prop_type: &str, symbol: &str) -> Option<&'static str> {
+        let op = match (prop_type, symbol) {
+            ("title" | "rich_text" | "url" | "email" | "phone_number" | "string", "=" | "~") => {
+                "contains"
+            }
+            ("title" | "rich_text" | "url" | "email" | "phone_number" | "string", "!~") => {
+                "does_not_contain"
+            }
+            ("multi_select" | "relation" | "people", "=" | "~") => "contains",
+            ("multi_select" | "relation" | "people", "!=" | "!~") => "does_not_contain",
+            ("date" | "created_time" | "last_edited_time", ">") => "after",
+            ("date" | "created_time" | "last_edited_time", ">=") => "on_or_after",
+            ("date" | "created_time" | "last_edited_time", "<") => "before",
+            ("date" | "created_time" | "last_edited_time", "<=") => "on_or_before",
+            ("number", ">") => "greater_than",
+            ("number", ">=") => "greater_than_or_equal_to",
+            ("number", "<") => "less_than",
+            ("number", "<=") => "less_than_or_equal_to",
+            (_, "=") => "equals",
+            (_, "!=") => "does_not_equal",
+            _ => return None,
+        };
+        Some(op)
+    }
 
-        let body = serde_json::json!({
-            "children": [{
-                "object": "block",
-                "type": "paragraph",
-                "paragraph": {
-                    "rich_text": [{
-                        "type": "text",
-                        "text": { "content": content }
-                    }]
+    /// Build the Notion filter object for this condition
+    pub fn to_filter(&self) -> Result<serde_json::Value> {
+        let condition_type = self.formula_type.as_deref().unwrap_or(&self.prop_type);
+        let condition = match self.operator.as_str() {
+            "is_empty" | "is_not_empty" => serde_json::json!(true),
+            op if RELATIVE_DATE_OPERATORS.contains(&op) => serde_json::json!({}),
+            _ => {
+                let raw = self.value.as_deref().with_context(|| {
+                    format!(
+                        "Filter on '{}' with operator '{}' needs a value",
+                        self.property, self.operator
+                    )
+                })?;
+                match condition_type {
+                    "number" => serde_json::json!(raw.parse::<f64>().with_context(|| {
+                        format!("Invalid number '{}' in filter on '{}'", raw, self.property)
+                    })?),
+                    "checkbox" => serde_json::json!(parse_checkbox(raw).with_context(|| {
+                        format!(
+                            "Invalid checkbox value '{}' in filter on '{}' (use true or false)",
+                            raw, self.property
+                        )
+                    })?),
+                    _ => serde_json::json!(raw),
                 }
-            }]
-        });
+            }
+        };
 
-        let response = self.execute_with_retry(|| {
-            self.client
-                .patch(&url)
-                .header("Content-Type", "application/json")
-                .json(&body)
-        })?;
+        let mut condition = serde_json::json!({ (self.operator.as_str()): condition });
+        if let Some(result) = &self.formula_type {
+            condition = serde_json::json!({ (result.as_str()): condition });
+        }
+        let mut filter = serde_json::json!({ "property": self.property });
+        filter[&self.prop_type] = condition;
+        Ok(filter)
+    }
+}
 
-        let result: serde_json::Value = response.json().context("Failed to parse response")?;
-        Ok(result)
+/// A checkbox value as typed: true/yes/1/on or false/no/0/off (or empty)
+fn parse_checkbox(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "1" | "on" => Some(true),
+        "false" | "no" | "0" | "off" | "" => Some(false),
+        _ => None,
     }
+}
 
-    pub fn update_page(
-        &self,
-        page_id: &str,
-        title: Option<&str>,
-        icon: Option<&str>,
-    ) -> Result<serde_json::Value> {
-        let page_id = normalize_page_id(page_id)?;
-        let url = format!("{}/pages/{}", NOTION_API_BASE, page_id);
+/// Property (and formula result) types filtered with date conditions
+const DATE_FILTER_TYPES: [&str; 3] = ["date", "created_time", "last_edited_time"];
+
+/// Date operators relative to today, which take no value
+const RELATIVE_DATE_OPERATORS: [&str; 7] = [
+    "past_week",
+    "past_month",
+    "past_year",
+    "this_week",
+    "next_week",
+    "next_month",
+    "next_year",
+];
+
+/// Guess a formula's result type from the value it's compared with
+fn formula_result_type(value: Option<&str>) -> String {
+    let result = match value {
+        Some(v) if v.parse::<f64>().is_ok() => "number",
+        Some(v) if v.eq_ignore_ascii_case("true") || v.eq_ignore_ascii_case("false") => "checkbox",
+        Some(v) if chrono::NaiveDate::parse_from_str(v, "%Y-%m-%d").is_ok() => "date",
+        _ => "string",
+    };
+    result.to_string()
+}
 
-        let mut body = serde_json::json!({});
+/// The user in `users` (from `GET /users`) whose email or name is `query`,
+/// ignoring case. Several users with the name is an error listing them.
+///
+/// ```
+/// use notion_cli_tool::client::match_user;
+/// use serde_json::json;
+///
+/// let users = [
+///     json!({ "id": "u1", "name": "Ada Lovelace", "type": "person", "person": { "email": "ada@example.com" } }),
+///     json!({ "id": "u2", "name": "Grace Hopper", "type": "person", "person": { "email": "grace@example.com" } }),
+/// ];
+/// assert_eq!(match_user(&users, "ADA@example.com").unwrap(), "u1");
+/// assert_eq!(match_user(&users, "grace hopper").unwrap(), "u2");
+/// assert!(match_user(&users, "Alan").is_err());
+/// ```
+pub fn match_user(users: &[serde_json::Value], query: &str) -> Result<String> {
+    let query = query.trim();
+    let email = |u: &serde_json::Value| {
+        u["person"]["email"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string()
+    };
+    if let Some(user) = users.iter().find(|u| email(u).eq_ignore_ascii_case(query)) {
+        return Ok(user["id"].as_str().unwrap_or_default().to_string());
+    }
 
-        if let Some(new_title) = title {
-            body["properties"] = serde_json::json!({
-                "title": {
-                    "title": [{
-                        "text": { "content": new_title }
-                    }]
-                }
-            });
+    let named: Vec<&serde_json::Value> = users
+        .iter()
+        .filter(|u| {
+            u["name"]
+                .as_str()
+                .is_some_and(|n| n.eq_ignore_ascii_case(query))
+        })
+        .collect();
+    match named.as_slice() {
+        [user] => Ok(user["id"].as_str().unwrap_or_default().to_string()),
+        [] => bail!(
+            "No user with the email or name '{}' (emails need the integration's \
+             \"Read user information including email addresses\" capability)",
+            query
+        ),
+        several => {
+            let listing: Vec<String> = several
+                .iter()
+                .map(|u| match email(u).as_str() {
+                    "" => u["id"].as_str().unwrap_or_default().to_string(),
+                    email => email.to_string(),
+                })
+                .collect();
+            bail!(
+                "Several users are named '{}': {}. Use an email or ID instead",
+                query,
+                listing.join(", ")
+            )
         }
+    }
+}
 
-        if let Some(emoji) = icon {
-            body["icon"] = serde_json::json!({
-                "type": "emoji",
-                "emoji": emoji
-            });
+/// Collect the `mention` objects of rich text items anywhere in `value`
+fn collect_mentions<'a>(
+    value: &'a mut serde_json::Value,
+    out: &mut Vec<&'a mut serde_json::Value>,
+) {
+    if value["type"] == "mention" {
+        if let Some(mention) = value.get_mut("mention") {
+            out.push(mention);
+        }
+        return;
+    }
+    match value {
+        serde_json::Value::Object(map) => {
+            for child in map.values_mut() {
+                collect_mentions(child, out);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for child in items {
+                collect_mentions(child, out);
+            }
         }
+        _ => {}
+    }
+}
 
-        let response = self.execute_with_retry(|| {
-            self.client
-                .patch(&url)
-                .header("Content-Type", "application/json")
-                .json(&body)
-        })?;
+/// Look up a property in a database schema (exactly, then ignoring case),
+/// returning its name and type
+pub fn find_property(schema: &serde_json::Value, name: &str) -> Result<(String, String)> {
+    let properties: Vec<(&String, &str)> = schema
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(name, prop)| prop["type"].as_str().map(|t| (name, t)))
+        .collect();
+
+    properties
+        .iter()
+        .find(|(n, _)| n.as_str() == name)
+        .or_else(|| {
+            properties
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        })
+        .map(|(n, t)| (n.to_string(), t.to_string()))
+        .with_context(|| {
+            let mut names: Vec<&str> = properties.iter().map(|(n, _)| n.as_str()).collect();
+            names.sort_by_key(|n| n.to_lowercase());
+            format!(
+                "Unknown property '{}'. Available: {}",
+                name,
+                names.join(", ")
+            )
+        })
+}
 
-        let result: serde_json::Value = response.json().context("Failed to parse response")?;
-        Ok(result)
-    }
+/// Pick the blocks `clear` should remove: those after the block `after` (when
+/// given) whose type matches `block_type` (when given).
+pub fn select_blocks<'a>(
+    blocks: &'a [serde_json::Value],
+    block_type: Option<&str>,
+    after: Option<&str>,
+) -> Result<Vec<&'a serde_json::Value>> {
+    let start = match after {
+        Some(after) => {
+            let after = normalize_page_id(after)?;
+            let position = blocks
+                .iter()
+                .position(|b| {
+                    b["id"]
+                        .as_str()
+                        .is_some_and(|id| id.replace('-', "") == after.replace('-', ""))
+                })
+                .with_context(|| format!("Block {} is not a direct child of the page", after))?;
+            position + 1
+        }
+        None => 0,
+    };
 
-    pub fn delete_page(&self, page_id: &str) -> Result<serde_json::Value> {
-        let page_id = normalize_page_id(page_id)?;
-        let url = format!("{}/pages/{}", NOTION_API_BASE, page_id);
+    Ok(blocks[start..]
+        .iter()
+        .filter(|b| block_type.is_none_or(|t| b["type"] == t))
+        .collect())
+}
 
-        let body = serde_json::json!({
-            "archived": true
-        });
+/// A database sort key parsed from `Name[:asc|desc]`. `created_time` and
+/// `last_edited_time` sort by the page timestamps rather than a property.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortSpec {
+    pub key: String,
+    pub ascending: bool,
+}
 
-        let response = self.execute_with_retry(|| {
-            self.client
-                .patch(&url)
-                .header("Content-Type", "application/json")
-                .json(&body)
-        })?;
+impl SortSpec {
+    /// Parse a sort key, using `default_ascending` when no direction is given
+    pub fn parse(expr: &str, default_ascending: bool) -> Result<Self> {
+        let (key, ascending) = match expr.rsplit_once(':') {
+            Some((key, dir)) => match dir.trim().to_lowercase().as_str() {
+                "asc" | "ascending" => (key, true),
+                "desc" | "descending" => (key, false),
+                _ => bail!("Invalid sort '{}': direction must be asc or desc", expr),
+            },
+            None => (expr, default_ascending),
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            bail!("Invalid sort '{}': property name is empty", expr);
+        }
 
-        let result: serde_json::Value = response.json().context("Failed to parse response")?;
-        Ok(result)
+        Ok(Self {
+            key: key.to_string(),
+            ascending,
+        })
     }
 
-    pub fn append_code_block(
-        &self,
-        page_id: &str,
-        code: &str,
-        language: &str,
-    ) -> Result<serde_json::Value> {
-        let page_id = normalize_page_id(page_id)?;
-        let url = format!("{}/blocks/{}/children", NOTION_API_BASE, page_id);
+    /// Build the Notion sort object
+    pub fn to_sort(&self) -> serde_json::Value {
+        let direction = if self.ascending {
+            "ascending"
+        } else {
+            "descending"
+        };
+        match self.key.as_str() {
+            "created_time" | "last_edited_time" => {
+                serde_json::json!({ "timestamp": self.key, "direction": direction })
+            }
+            _ => serde_json::json!({ "property": self.key, "direction": direction }),
+        }
+    }
+}
 
-        let body = serde_json::json!({
-            "children": [{
-                "object": "block",
-                "type": "code",
-                "code": {
-                    "rich_text": [{
-                        "type": "text",
-                        "text": { "content": code }
-                    }],
-                    "language": language
-                }
-            }]
-        });
+/// Combine filter conditions into a single filter (`and`/`or` compound when several)
+pub fn build_filter(specs: &[FilterSpec], match_any: bool) -> Result<Option<serde_json::Value>> {
+    let mut filters = specs
+        .iter()
+        .map(|s| s.to_filter())
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(match filters.len() {
+        0 => None,
+        1 => filters.pop(),
+        _ => {
+            let key = if match_any { "or" } else { "and" };
+            Some(serde_json::json!({ key: filters }))
+        }
+    })
+}
 
-        let response = self.execute_with_retry(|| {
-            self.client
-                .patch(&url)
-                .header("Content-Type", "application/json")
-                .json(&body)
-        })?;
+/// Async client for the Notion REST API.
+///
+/// All methods accept page/block IDs with or without dashes and retry rate
+/// limiting (HTTP 429) and transient server or network errors transparently.
+#[derive(Clone)]
+pub struct NotionClient {
+    api_key: String,
+    api_version: String,
+    client: reqwest::Client,
+    max_retries: u32,
+    /// Shared by clones, so concurrent requests draw from the same budget
+    rate_limiter: Arc<RateLimiter>,
+    /// Block fetches in flight at once during recursive reads
+    concurrency: usize,
+    /// Serve unchanged pages from the disk cache (see [`Self::get_page_content`])
+    page_cache: bool,
+    /// API root, `NOTION_API_BASE` unless pointed at a mock server
+    base_url: String,
+    /// Record or replay responses (see [`crate::replay`])
+    cassette: Option<Cassette>,
+    /// Data source resolved for each database ID (see [`Self::resolve_data_source`])
+    data_sources: Arc<Mutex<HashMap<String, String>>>,
+    /// Workspace users, listed once (see [`Self::find_user`])
+    users: Arc<Mutex<Option<Vec<serde_json::Value>>>>,
+    /// Count calls per endpoint (see [`Self::with_stats`])
+    stats: Option<ApiStats>,
+    /// Hide the progress lines of long operations (see [`Self::with_quiet`])
+    quiet: bool,
+}
 
-        let result: serde_json::Value = response.json().context("Failed to parse response")?;
-        Ok(result)
+/// Proxy and TLS settings for the HTTP client (`proxy` / `ca_bundle` in config)
+#[derive(Debug, Clone, Default)]
+pub struct NetworkOptions {
+    /// Proxy for all requests, used unless `HTTPS_PROXY` / `ALL_PROXY` is set
+    pub proxy: Option<String>,
+    /// PEM file of extra CA certificates to trust, e.g. a TLS-intercepting proxy's
+    pub ca_bundle: Option<PathBuf>,
+}
+
+impl NetworkOptions {
+    /// A reqwest builder with these settings. Proxy environment variables
+    /// (`HTTPS_PROXY`, `NO_PROXY`, ...) are honoured by reqwest itself.
+    pub fn client_builder(&self) -> Result<reqwest::ClientBuilder> {
+        let mut builder = reqwest::Client::builder();
+
+        let env_proxy = ["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"]
+            .iter()
+            .any(|name| std::env::var_os(name).is_some_and(|v| !v.is_empty()));
+        if let Some(url) = self.proxy.as_deref().filter(|_| !env_proxy) {
+            let proxy = reqwest::Proxy::all(url)
+                .with_context(|| format!("Invalid proxy URL '{}'", url))?
+                .no_proxy(reqwest::NoProxy::from_env());
+            builder = builder.proxy(proxy);
+        }
+
+        if let Some(path) = &self.ca_bundle {
+            let pem = std::fs::read(path)
+                .with_context(|| format!("Failed to read CA bundle {}", path.display()))?;
+            let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+                .with_context(|| format!("Invalid CA bundle {}", path.display()))?;
+            if certificates.is_empty() {
+                bail!("No certificates found in CA bundle {}", path.display());
+            }
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        Ok(builder)
     }
+}
 
-    pub fn append_bookmark(
-        &self,
-        page_id: &str,
-        url_str: &str,
-        caption: Option<&str>,
-    ) -> Result<serde_json::Value> {
-        let page_id = normalize_page_id(page_id)?;
-        let url = format!("{}/blocks/{}/children", NOTION_API_BASE, page_id);
+impl NotionClient {
+    /// Create a client authenticated with an integration token.
+    pub fn new(api_key: String, timeout_secs: u64) -> Result<Self> {
+        Self::new_with_network(api_key, timeout_secs, &NetworkOptions::default())
+    }
 
-        let bookmark_block = if let Some(cap) = caption {
-            serde_json::json!({
-                "object": "block",
-                "type": "bookmark",
-                "bookmark": {
-                    "url": url_str,
-                    "caption": [{
-                        "type": "text",
-                        "text": { "content": cap }
-                    }]
-                }
-            })
-        } else {
-            serde_json::json!({
-                "object": "block",
-                "type": "bookmark",
-                "bookmark": {
-                    "url": url_str
-                }
-            })
-        };
+    /// Like [`Self::new`], going through a proxy or trusting extra CA
+    /// certificates as `network` says.
+    pub fn new_with_network(
+        api_key: String,
+        timeout_secs: u64,
+        network: &NetworkOptions,
+    ) -> Result<Self> {
+        let client = network
+            .client_builder()?
+            .timeout(Duration::from_secs(timeout_secs))
+            .build()
+            .context("Failed to create HTTP client")?;
 
-        let body = serde_json::json!({
-            "children": [bookmark_block]
-        });
+        Ok(Self {
+            api_key,
+            api_version: get_api_version(),
+            client,
+            max_retries: MAX_RETRIES,
+            rate_limiter: Arc::new(RateLimiter::new(DEFAULT_RATE_LIMIT)),
+            concurrency: DEFAULT_CONCURRENCY,
+            page_cache: false,
+            base_url: NOTION_API_BASE.to_string(),
+            cassette: Cassette::from_env(),
+            data_sources: Arc::new(Mutex::new(HashMap::new())),
+            users: Arc::new(Mutex::new(None)),
+            stats: None,
+            quiet: false,
+        })
+    }
 
-        let response = self.execute_with_retry(|| {
-            self.client
-                .patch(&url)
-                .header("Content-Type", "application/json")
-                .json(&body)
-        })?;
+    /// Use a specific `Notion-Version` instead of `NOTION_API_VERSION` / the default.
+    pub fn with_api_version(mut self, api_version: impl Into<String>) -> Self {
+        self.api_version = api_version.into();
+        self
+    }
 
-        let result: serde_json::Value = response.json().context("Failed to parse response")?;
-        Ok(result)
+    /// The `Notion-Version` sent with every request
+    pub fn api_version(&self) -> &str {
+        &self.api_version
     }
 
-    pub fn delete_block(&self, block_id: &str) -> Result<()> {
-        let block_id = normalize_page_id(block_id)?;
-        let url = format!("{}/blocks/{}", NOTION_API_BASE, block_id);
+    /// Whether the configured API version models databases as data sources (2025-09-03+)
+    pub fn uses_data_sources(&self) -> bool {
+        self.api_version.as_str() >= DATA_SOURCES_API_VERSION
+    }
 
-        self.execute_with_retry(|| self.client.delete(&url))?;
-        Ok(())
+    /// Retry transient failures up to `max_retries` times (0 disables retries)
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
     }
 
-    pub fn append_heading(
-        &self,
-        page_id: &str,
-        text: &str,
-        level: u8,
-    ) -> Result<serde_json::Value> {
-        let page_id = normalize_page_id(page_id)?;
-        let url = format!("{}/blocks/{}/children", NOTION_API_BASE, page_id);
+    /// Send at most `requests_per_second` requests on average (0 disables the limit)
+    pub fn with_rate_limit(mut self, requests_per_second: f64) -> Self {
+        self.rate_limiter = Arc::new(RateLimiter::new(requests_per_second));
+        self
+    }
 
-        let block_type = match level {
-            1 => "heading_1",
-            2 => "heading_2",
-            _ => "heading_3",
-        };
+    /// Fetch up to `concurrency` blocks' children at once in recursive reads
+    /// (at least 1). Requests still go through the rate limiter.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
 
-        let body = serde_json::json!({
-            "children": [{
-                "object": "block",
-                "type": block_type,
-                (block_type): {
-                    "rich_text": [{
-                        "type": "text",
-                        "text": { "content": text }
-                    }]
-                }
-            }]
-        });
+    /// Keep page content in the disk cache (`~/.cache/notion-cli/pages`)
+    pub fn with_page_cache(mut self, enabled: bool) -> Self {
+        self.page_cache = enabled;
+        self
+    }
 
-        let response = self.execute_with_retry(|| {
-            self.client
-                .patch(&url)
-                .header("Content-Type", "application/json")
-                .json(&body)
-        })?;
+    /// Count every call, its retries, bytes and time in `stats` (`--stats`)
+    pub fn with_stats(mut self, stats: ApiStats) -> Self {
+        self.stats = Some(stats);
+        self
+    }
 
-        let result: serde_json::Value = response.json().context("Failed to parse response")?;
-        Ok(result)
+    /// Leave out the "→ Copying blocks..." progress lines on stderr (`--quiet`).
+    /// Warnings are still printed.
+    pub fn with_quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    /// Send requests to another API root (e.g. a mock server in tests)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Record responses to, or replay them from, a directory instead of the
+    /// `NOTION_CLI_RECORD` / `NOTION_CLI_REPLAY` environment variables
+    pub fn with_cassette(mut self, cassette: Option<Cassette>) -> Self {
+        self.cassette = cassette;
+        self
     }
 
-    pub fn append_rich_text(
+    /// Execute a request, retrying transient failures.
+    ///
+    /// Rate limits (429) and connection failures are always retried, since the
+    /// request never took effect. Server errors (500/502/503/504) and timeouts
+    /// are only retried for requests that are safe to repeat; anything else
+    /// could be applied twice.
+    async fn execute_with_retry(
         &self,
-        page_id: &str,
-        segments: &[RichTextSegment],
-    ) -> Result<serde_json::Value> {
-        let page_id = normalize_page_id(page_id)?;
-        let url = format!("{}/blocks/{}/children", NOTION_API_BASE, page_id);
+        request_builder: impl Fn() -> reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response> {
+        let Some(stats) = &self.stats else {
+            return self
+                .send_with_retry(&request_builder, &mut Call::default())
+                .await;
+        };
+        let started = Instant::now();
+        let mut call = Call::default();
+        let result = match self.send_with_retry(&request_builder, &mut call).await {
+            Ok(response) => read_counted(response, &mut call).await,
+            Err(e) => Err(e),
+        };
+        stats.record(&call, started.elapsed());
+        result
+    }
 
-        let rich_text: Vec<serde_json::Value> = segments
-            .iter()
-            .map(|seg| {
-                let mut text_obj = serde_json::json!({
-                    "content": seg.text
-                });
-                if let Some(ref link) = seg.link {
-                    text_obj["link"] = serde_json::json!({ "url": link });
+    /// [`Self::execute_with_retry`] without statistics, noting the endpoint,
+    /// retries and request bytes in `call`
+    async fn send_with_retry(
+        &self,
+        request_builder: &impl Fn() -> reqwest::RequestBuilder,
+        call: &mut Call,
+    ) -> Result<reqwest::Response> {
+        let mut retries = 0;
+
+        loop {
+            let request = request_builder()
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .header("Notion-Version", &self.api_version)
+                .build()
+                .context("Failed to build request")?;
+            let idempotent = is_idempotent(&request);
+            call.endpoint = usage::endpoint(request.method().as_str(), request.url().path());
+            call.retries = retries;
+            call.bytes += request
+                .body()
+                .and_then(|b| b.as_bytes())
+                .map_or(0, |b| b.len() as u64);
+
+            let fixture = self.cassette.as_ref().map(|cassette| {
+                let url = request.url().as_str();
+                let url = url.strip_prefix(self.base_url.as_str()).unwrap_or(url);
+                let body = request
+                    .body()
+                    .and_then(|b| b.as_bytes())
+                    .unwrap_or_default();
+                (
+                    cassette,
+                    cassette.path_for(request.method().as_str(), url, body),
+                )
+            });
+            if let Some((cassette, path)) = fixture.as_ref().filter(|(c, _)| c.mode == Mode::Replay)
+            {
+                let response = cassette.load(path)?;
+                if !response.status().is_success() {
+                    return Err(api_error(response).await);
                 }
+                return Ok(response);
+            }
 
-                let mut annotations = serde_json::json!({});
-                if seg.bold {
-                    annotations["bold"] = serde_json::json!(true);
+            self.rate_limiter.acquire().await;
+            let result = match (self.client.execute(request).await, &fixture) {
+                (Ok(response), Some((cassette, path))) => {
+                    Ok(cassette.record(path, response).await?)
                 }
-                if seg.italic {
-                    annotations["italic"] = serde_json::json!(true);
+                (result, _) => result,
+            };
+
+            let (reason, delay) = match result {
+                Ok(response) if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                    if retries >= self.max_retries {
+                        return Err(api_error(response)
+                            .await
+                            .context(format!("Rate limit exceeded after {} retries", retries)));
+                    }
+                    let retry_after = response
+                        .headers()
+                        .get("Retry-After")
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|s| s.parse::<u64>().ok())
+                        .unwrap_or(DEFAULT_RETRY_DELAY_SECS);
+                    ("Rate limited".to_string(), Duration::from_secs(retry_after))
                 }
-                if seg.code {
-                    annotations["code"] = serde_json::json!(true);
+                Ok(response) if is_transient_status(response.status()) => {
+                    let status = response.status();
+                    if !idempotent {
+                        return Err(api_error(response).await.context(format!(
+                            "Notion API returned {} (not retried, as the request may already have been applied)",
+                            status
+                        )));
+                    }
+                    if retries >= self.max_retries {
+                        return Err(api_error(response).await.context(format!(
+                            "Notion API is temporarily unavailable; gave up after {} retries",
+                            retries
+                        )));
+                    }
+                    (format!("Server error ({})", status), backoff_delay(retries))
                 }
-
-                serde_json::json!({
-                    "type": "text",
-                    "text": text_obj,
-                    "annotations": annotations
-                })
-            })
-            .collect();
-
-        let body = serde_json::json!({
-            "children": [{
-                "object": "block",
-                "type": "paragraph",
-                "paragraph": {
-                    "rich_text": rich_text
+                Ok(response) if !response.status().is_success() => {
+                    return Err(api_error(response).await);
                 }
-            }]
-        });
-
-        let response = self.execute_with_retry(|| {
-            self.client
-                .patch(&url)
-                .header("Content-Type", "application/json")
-                .json(&body)
-        })?;
+                Ok(response) => return Ok(response),
+                Err(e) if e.is_connect() || (idempotent && (e.is_timeout() || e.is_request())) => {
+                    if retries >= self.max_retries {
+                        return Err(e).with_context(|| {
+                            format!("Failed to send request; gave up after {} retries", retries)
+                        });
+                    }
+                    let reason = if e.is_timeout() {
+                        "Request timed out"
+                    } else {
+                        "Connection failed"
+                    };
+                    (reason.to_string(), backoff_delay(retries))
+                }
+                Err(e) => return Err(e).context("Failed to send request"),
+            };
 
-        let result: serde_json::Value = response.json().context("Failed to parse response")?;
-        Ok(result)
+            eprintln!(
+                "{} {}. Waiting {:.1} seconds before retry ({}/{})...",
+                "⚠".yellow(),
+                reason,
+                delay.as_secs_f64(),
+                retries + 1,
+                self.max_retries
+            );
+
+            tokio::time::sleep(delay).await;
+            retries += 1;
+        }
     }
 
-    pub fn append_divider(&self, page_id: &str) -> Result<serde_json::Value> {
-        let page_id = normalize_page_id(page_id)?;
-        let url = format!("{}/blocks/{}/children", NOTION_API_BASE, page_id);
-
-        let body = serde_json::json!({
-            "children": [{
-                "object": "block",
-                "type": "divider",
-                "divider": {}
-            }]
-        });
-
-        let response = self.execute_with_retry(|| {
-            self.client
-                .patch(&url)
-                .header("Content-Type", "application/json")
-                .json(&body)
-        })?;
+    /// Search pages and databases by title, following pagination up to `limit` results.
+    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<serde_json::Value>> {
+        self.search_filtered(query, None, None, "desc", limit).await
+    }
 
-        let result: serde_json::Value = response.json().context("Failed to parse response")?;
-        Ok(result)
+    /// Search restricted to one kind of `object` (`"page"` or `"database"`),
+    /// optionally sorted by a timestamp (`last_edited_time`, the only one the
+    /// API supports) in `direction` (asc or desc). An empty query matches
+    /// everything the integration can see.
+    pub async fn search_filtered(
+        &self,
+        query: &str,
+        object: Option<&str>,
+        sort: Option<&str>,
+        direction: &str,
+        limit: usize,
+    ) -> Result<Vec<serde_json::Value>> {
+        let mut all_results = Vec::new();
+        self.search_each(query, object, sort, direction, limit, |item| {
+            all_results.push(item);
+            Ok(())
+        })
+        .await?;
+        Ok(all_results)
     }
 
-    pub fn append_bulleted_list(
+    /// Like [`Self::search_filtered`], but hands each result to `f` as soon
+    /// as its page arrives instead of collecting them. Returns the number of
+    /// results seen.
+    pub async fn search_each(
         &self,
-        page_id: &str,
-        items: &[String],
-    ) -> Result<serde_json::Value> {
-        let page_id = normalize_page_id(page_id)?;
-        let url = format!("{}/blocks/{}/children", NOTION_API_BASE, page_id);
+        query: &str,
+        object: Option<&str>,
+        sort: Option<&str>,
+        direction: &str,
+        limit: usize,
+        mut f: impl FnMut(serde_json::Value) -> Result<()>,
+    ) -> Result<usize> {
+        let mut seen = 0;
+        let mut start_cursor: Option<String> = None;
+        let progress = progress::spinner("Searching");
 
-        let children: Vec<serde_json::Value> = items
-            .iter()
-            .map(|item| {
-                serde_json::json!({
-                    "object": "block",
-                    "type": "bulleted_list_item",
-                    "bulleted_list_item": {
-                        "rich_text": [{
-                            "type": "text",
-                            "text": { "content": item }
-                        }]
-                    }
-                })
-            })
-            .collect();
+        loop {
+            let page_size = 100.min(limit - seen);
+            let mut result = self
+                .search_page(
+                    query,
+                    object,
+                    sort,
+                    direction,
+                    start_cursor.as_deref(),
+                    page_size,
+                )
+                .await?;
+
+            if let Some(results) = result["results"].as_array_mut() {
+                for item in results.drain(..) {
+                    f(item)?;
+                    seen += 1;
+                }
+                progress.set_position(seen as u64);
+            }
 
-        let body = serde_json::json!({
-            "children": children
-        });
+            let has_more = result
+                .get("has_more")
+                .and_then(|h| h.as_bool())
+                .unwrap_or(false);
+            if !has_more || seen >= limit {
+                break;
+            }
 
-        let response = self.execute_with_retry(|| {
-            self.client
-                .patch(&url)
-                .header("Content-Type", "application/json")
-                .json(&body)
-        })?;
+            start_cursor = result
+                .get("next_cursor")
+                .and_then(|c| c.as_str())
+                .map(String::from);
+            if start_cursor.is_none() {
+                break;
+            }
+        }
 
-        let result: serde_json::Value = response.json().context("Failed to parse response")?;
-        Ok(result)
+        Ok(seen)
     }
 
-    pub fn query_database(
+    /// One page of search results, as the raw list response (`results`,
+    /// `has_more`, `next_cursor`), starting at `start_cursor` when given.
+    /// The other arguments are those of [`Self::search_filtered`].
+    pub async fn search_page(
         &self,
-        database_id: &str,
-        filter: Option<&str>,
+        query: &str,
+        object: Option<&str>,
         sort: Option<&str>,
         direction: &str,
-        limit: usize,
-    ) -> Result<Vec<serde_json::Value>> {
-        if limit == 0 {
-            return Ok(Vec::new());
-        }
+        start_cursor: Option<&str>,
+        page_size: usize,
+    ) -> Result<serde_json::Value> {
+        let url = format!("{}/search", self.base_url);
+        let mut body = serde_json::json!({
+            "query": query,
+            "page_size": page_size
+        });
 
-        let database_id = normalize_page_id(database_id)?;
-        let url = format!("{}/databases/{}/query", NOTION_API_BASE, database_id);
-        let mut all_results = Vec::new();
-        let mut start_cursor: Option<String> = None;
+        if let Some(cursor) = start_cursor {
+            body["start_cursor"] = serde_json::json!(cursor);
+        }
 
-        loop {
-            let remaining = limit.saturating_sub(all_results.len());
-            let page_size = remaining.clamp(1, 100);
+        if let Some(object) = object {
+            // Databases are searched as their data sources on newer API versions
+            let value = match object {
+                "database" if self.uses_data_sources() => "data_source",
+                other => other,
+            };
+            body["filter"] = serde_json::json!({ "property": "object", "value": value });
+        }
 
-            let mut body = serde_json::json!({
-                "page_size": page_size
+        if let Some(timestamp) = sort {
+            body["sort"] = serde_json::json!({
+                "timestamp": timestamp,
+                "direction": if direction == "asc" { "ascending" } else { "descending" }
             });
+        }
 
-            if let Some(cursor) = &start_cursor {
-                body["start_cursor"] = serde_json::json!(cursor);
-            }
+        let response = self
+            .execute_with_retry(|| {
+                self.client
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body)
+            })
+            .await?;
 
-            if let Some(filter_str) = filter {
-                if let Some((prop_part, value)) = filter_str.split_once('=') {
-                    let (prop, filter_type) = if let Some((p, t)) = prop_part.split_once(':') {
-                        (p.trim(), t.trim())
-                    } else {
-                        (prop_part.trim(), "rich_text")
-                    };
+        response.json().await.context("Failed to parse response")
+    }
 
-                    let filter_value = match filter_type {
-                        "title" => serde_json::json!({
-                            "property": prop,
-                            "title": { "contains": value.trim() }
-                        }),
-                        "select" => serde_json::json!({
-                            "property": prop,
-                            "select": { "equals": value.trim() }
-                        }),
-                        "checkbox" => serde_json::json!({
-                            "property": prop,
-                            "checkbox": { "equals": value.trim().to_lowercase() == "true" }
-                        }),
-                        "number" => {
-                            let num: f64 = value.trim().parse().unwrap_or(0.0);
-                            serde_json::json!({
-                                "property": prop,
-                                "number": { "equals": num }
-                            })
-                        }
-                        _ => serde_json::json!({
-                            "property": prop,
-                            "rich_text": { "contains": value.trim() }
-                        }),
-                    };
-                    body["filter"] = filter_value;
-                }
+    /// Search for pages (`object = "page"`) or databases (`"database"`) whose
+    /// title contains `title`, case-insensitively. Returns `(id, title)` pairs
+    /// with exact title matches first. Data source results are reported by
+    /// their parent database ID.
+    pub async fn find_by_title(&self, title: &str, object: &str) -> Result<Vec<(String, String)>> {
+        let needle = title.to_lowercase();
+        let mut matches: Vec<(String, String)> = Vec::new();
+
+        for item in self
+            .search_filtered(title, Some(object), None, "desc", 100)
+            .await?
+        {
+            let page: Page = serde_json::from_value(item.clone()).unwrap_or_default();
+            let id = match (object, page.object.as_str()) {
+                ("page", "page") | ("database", "database") => page.id.clone(),
+                ("database", "data_source") => match item["parent"]["database_id"].as_str() {
+                    Some(id) => id.to_string(),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            let Some(found) = page.title() else {
+                continue;
+            };
+            if found.to_lowercase().contains(&needle) && !matches.iter().any(|(i, _)| *i == id) {
+                matches.push((id, found));
             }
+        }
 
-            if let Some(sort_prop) = sort {
-                body["sorts"] = serde_json::json!([{
-                    "property": sort_prop,
-                    "direction": if direction == "asc" { "ascending" } else { "descending" }
-                }]);
-            }
+        matches.sort_by_key(|(_, t)| t.to_lowercase() != needle);
+        Ok(matches)
+    }
 
-            let body_clone = body.clone();
-            let url_clone = url.clone();
-            let response = self.execute_with_retry(|| {
-                self.client
-                    .post(&url_clone)
-                    .header("Content-Type", "application/json")
-                    .json(&body_clone)
-            })?;
+    /// Retrieve a page object (properties, parent, icon, url, ...).
+    pub async fn get_page(&self, page_id: &str) -> Result<serde_json::Value> {
+        let page_id = normalize_page_id(page_id)?;
+        let url = format!("{}/pages/{}", self.base_url, page_id);
+
+        let response = self.execute_with_retry(|| self.client.get(&url)).await?;
+        let result: serde_json::Value =
+            response.json().await.context("Failed to parse response")?;
+        Ok(result)
+    }
+
+    /// Retrieve a single block object.
+    pub async fn get_block(&self, block_id: &str) -> Result<serde_json::Value> {
+        let block_id = normalize_page_id(block_id)?;
+        let url = format!("{}/blocks/{}", self.base_url, block_id);
+
+        let response = self.execute_with_retry(|| self.client.get(&url)).await?;
+        let result: serde_json::Value =
+            response.json().await.context("Failed to parse response")?;
+        Ok(result)
+    }
+
+    /// Retrieve all direct child blocks of a page or block, following pagination.
+    pub async fn get_blocks(&self, page_id: &str) -> Result<Vec<serde_json::Value>> {
+        let mut all_blocks = Vec::new();
+        let mut start_cursor: Option<String> = None;
 
-            let result: serde_json::Value = response.json().context("Failed to parse response")?;
+        loop {
+            let result = self
+                .get_blocks_page(page_id, start_cursor.as_deref(), 100)
+                .await?;
 
             if let Some(results) = result.get("results").and_then(|r| r.as_array()) {
-                all_results.extend(results.clone());
+                all_blocks.extend(results.clone());
             }
 
             let has_more = result
                 .get("has_more")
                 .and_then(|h| h.as_bool())
                 .unwrap_or(false);
-            if !has_more || all_results.len() >= limit {
+            if !has_more {
                 break;
             }
 
@@ -698,153 +1462,2536 @@ impl NotionClient {
             }
         }
 
-        Ok(all_results)
+        Ok(all_blocks)
     }
 
-    /// Move a page to a new parent by copying content and deleting original
-    pub fn move_page(
+    /// One page of the direct child blocks of a page or block, as the raw list
+    /// response (`results`, `has_more`, `next_cursor`).
+    pub async fn get_blocks_page(
         &self,
         page_id: &str,
-        new_parent_id: &str,
-        delete_original: bool,
+        start_cursor: Option<&str>,
+        page_size: usize,
     ) -> Result<serde_json::Value> {
         let page_id = normalize_page_id(page_id)?;
-        let new_parent_id = normalize_page_id(new_parent_id)?;
-
-        // 1. Get original page info (title)
-        // Note: For database pages, title property name can vary (e.g., "Name", "Title")
-        // So we find the property with type="title" instead of assuming name="title"
-        eprintln!("{} Reading original page...", "→".blue());
-        let page = self.get_page(&page_id)?;
-        let title = page
-            .get("properties")
-            .and_then(|p| p.as_object())
-            .and_then(|props| {
-                // Find property where type == "title"
-                props
-                    .values()
-                    .find(|v| v.get("type").and_then(|t| t.as_str()) == Some("title"))
-            })
-            .and_then(|t| t.get("title"))
-            .and_then(|t| t.as_array())
-            .and_then(|arr| arr.first())
-            .and_then(|t| t.get("plain_text"))
-            .and_then(|t| t.as_str())
-            .unwrap_or("Untitled");
-
-        // 2. Get all blocks from original page
-        eprintln!("{} Fetching blocks...", "→".blue());
-        let blocks = self.get_blocks(&page_id)?;
-
-        // 3. Create new page under new parent
-        eprintln!("{} Creating new page under new parent...", "→".blue());
-        let new_page = self.create_page(&new_parent_id, title, None)?;
-        let new_page_id = new_page
-            .get("id")
-            .and_then(|id| id.as_str())
-            .context("Failed to get new page ID")?;
-
-        // 4. Copy blocks to new page
-        if !blocks.is_empty() {
-            eprintln!("{} Copying {} blocks...", "→".blue(), blocks.len());
-            self.copy_blocks_to_page(new_page_id, &blocks)?;
+        let mut url = format!(
+            "{}/blocks/{}/children?page_size={}",
+            self.base_url, page_id, page_size
+        );
+        if let Some(cursor) = start_cursor {
+            url.push_str(&format!("&start_cursor={}", cursor));
         }
 
-        // 5. Optionally delete original page
-        if delete_original {
-            eprintln!("{} Archiving original page...", "→".blue());
-            self.delete_page(&page_id)?;
-        }
-
-        Ok(new_page)
+        let response = self.execute_with_retry(|| self.client.get(&url)).await?;
+        response.json().await.context("Failed to parse response")
     }
 
-    /// Copy blocks to a page (handles nested blocks recursively)
-    fn copy_blocks_to_page(&self, page_id: &str, blocks: &[serde_json::Value]) -> Result<()> {
-        let url = format!("{}/blocks/{}/children", NOTION_API_BASE, page_id);
+    /// Fetch blocks recursively, following `has_children` up to `max_depth` levels.
+    /// Nested blocks are attached to their parent under a `children` key.
+    /// Sibling subtrees are fetched concurrently (see [`Self::with_concurrency`]).
+    pub async fn get_blocks_recursive(
+        &self,
+        page_id: &str,
+        max_depth: Option<usize>,
+    ) -> Result<Vec<serde_json::Value>> {
+        let progress = progress::spinner("Fetching blocks");
+        let permits = Semaphore::new(self.concurrency);
+        self.collect_block_tree(page_id, 0, max_depth, &progress, &permits)
+            .await
+    }
 
-        // Process blocks in batches of 100 (Notion API limit)
-        for chunk in blocks.chunks(100) {
-            let converted: Vec<(serde_json::Value, Option<String>)> = chunk
-                .iter()
-                .filter_map(|block| {
-                    let converted = self.convert_block_for_copy(block)?;
-                    // Track original block ID if it has children
-                    let original_id = if block.get("has_children") == Some(&serde_json::json!(true))
-                    {
-                        block.get("id").and_then(|id| id.as_str()).map(String::from)
-                    } else {
-                        None
-                    };
-                    Some((converted, original_id))
-                })
-                .collect();
+    async fn collect_block_tree(
+        &self,
+        block_id: &str,
+        depth: usize,
+        max_depth: Option<usize>,
+        progress: &ProgressBar,
+        permits: &Semaphore,
+    ) -> Result<Vec<serde_json::Value>> {
+        // Only the request holds a permit, so waiting on children can't starve them
+        let mut blocks = {
+            let _permit = permits.acquire().await.context("Block fetch cancelled")?;
+            self.get_blocks(block_id).await?
+        };
+        progress.inc(blocks.len() as u64);
+        let at_max_depth = max_depth.is_some_and(|max| depth >= max);
 
-            if converted.is_empty() {
+        let mut subtrees = Vec::new();
+        for (i, block) in blocks.iter().enumerate() {
+            let has_children = block
+                .get("has_children")
+                .and_then(|h| h.as_bool())
+                .unwrap_or(false);
+            if !has_children {
                 continue;
             }
 
-            let children: Vec<serde_json::Value> =
-                converted.iter().map(|(b, _)| b.clone()).collect();
-            let body = serde_json::json!({ "children": children });
-
-            let response = self.execute_with_retry(|| {
-                self.client
-                    .patch(&url)
-                    .header("Content-Type", "application/json")
-                    .json(&body)
-            })?;
-
-            // Get created block IDs to copy children recursively
-            let created: serde_json::Value = response.json().context("Failed to parse response")?;
-            if let Some(results) = created.get("results").and_then(|r| r.as_array()) {
-                for (i, (_, original_id)) in converted.iter().enumerate() {
-                    if let Some(orig_id) = original_id {
-                        if let Some(new_block) = results.get(i) {
-                            if let Some(new_id) = new_block.get("id").and_then(|id| id.as_str()) {
-                                // Recursively copy children
-                                let child_blocks = self.get_blocks(orig_id)?;
-                                if !child_blocks.is_empty() {
-                                    self.copy_blocks_to_page(new_id, &child_blocks)?;
-                                }
-                            }
-                        }
-                    }
-                }
+            // Child pages and databases are separate documents, not nested content
+            let block_type = block.get("type").and_then(|t| t.as_str()).unwrap_or("");
+            if block_type == "child_page" || block_type == "child_database" {
+                continue;
             }
-        }
 
+            // Table rows are part of the table itself, so they ignore the depth limit
+            if at_max_depth && block_type != "table" {
+                continue;
+            }
+
+            if let Some(id) = block.get("id").and_then(|i| i.as_str()) {
+                let id = id.to_string();
+                subtrees.push(async move {
+                    let children = Box::pin(self.collect_block_tree(
+                        &id,
+                        depth + 1,
+                        max_depth,
+                        progress,
+                        permits,
+                    ))
+                    .await?;
+                    Ok::<_, anyhow::Error>((i, children))
+                });
+            }
+        }
+
+        for (i, children) in try_join_all(subtrees).await? {
+            blocks[i]["children"] = serde_json::Value::Array(children);
+        }
+
+        Ok(blocks)
+    }
+
+    /// The sub-pages and databases of a page, found anywhere in its blocks
+    /// (toggles and columns included), each with its own sub-pages down to
+    /// `max_depth` levels below the page. Databases are leaves: their rows
+    /// aren't listed.
+    pub async fn get_page_tree(
+        &self,
+        page_id: &str,
+        max_depth: Option<usize>,
+    ) -> Result<Vec<PageNode>> {
+        if max_depth == Some(0) {
+            return Ok(Vec::new());
+        }
+        let blocks = self.get_blocks_recursive(page_id, None).await?;
+        let mut nodes = PageNode::from_blocks(&blocks);
+        for node in &mut nodes {
+            if node.object == "page" {
+                node.children =
+                    Box::pin(self.get_page_tree(&node.id, max_depth.map(|d| d - 1))).await?;
+            }
+        }
+        Ok(nodes)
+    }
+
+    /// A page object and its block tree (as [`Self::get_blocks_recursive`]).
+    ///
+    /// With the page cache enabled, the blocks are reused from the last fetch
+    /// while the page's `last_edited_time` is unchanged, costing a single
+    /// request, and a cached copy is returned when the API can't be reached.
+    pub async fn get_page_content(
+        &self,
+        page_id: &str,
+        max_depth: Option<usize>,
+    ) -> Result<(serde_json::Value, Vec<serde_json::Value>)> {
+        if !self.page_cache {
+            let page = self.get_page(page_id).await?;
+            let blocks = self.get_blocks_recursive(page_id, max_depth).await?;
+            return Ok((page, blocks));
+        }
+
+        let page_id = normalize_page_id(page_id)?;
+        let cached = load_cached_page(&page_id).filter(|c| c.depth == max_depth);
+
+        let page = match self.get_page(&page_id).await {
+            Ok(page) => page,
+            Err(e) if is_connection_error(&e) => match cached {
+                Some(cached) => {
+                    eprintln!(
+                        "{} Offline, showing the cached copy (last edited {})",
+                        "⚠".yellow(),
+                        cached.last_edited_time
+                    );
+                    return Ok((cached.page, cached.blocks));
+                }
+                None => return Err(e),
+            },
+            Err(e) => return Err(e),
+        };
+
+        let blocks = self.get_page_blocks(&page, max_depth).await?;
+        Ok((page, blocks))
+    }
+
+    /// The block tree of an already fetched page object (from `get_page`,
+    /// search or a query), served from the page cache when enabled and the
+    /// page's `last_edited_time` matches the cached copy.
+    pub async fn get_page_blocks(
+        &self,
+        page: &serde_json::Value,
+        max_depth: Option<usize>,
+    ) -> Result<Vec<serde_json::Value>> {
+        let page_id = page["id"].as_str().context("Page has no ID")?;
+        if !self.page_cache {
+            return self.get_blocks_recursive(page_id, max_depth).await;
+        }
+
+        let page_id = normalize_page_id(page_id)?;
+        let last_edited_time = page["last_edited_time"].as_str().unwrap_or_default();
+        if let Some(cached) = load_cached_page(&page_id)
+            .filter(|c| c.depth == max_depth && c.last_edited_time == last_edited_time)
+        {
+            return Ok(cached.blocks);
+        }
+
+        let blocks = self.get_blocks_recursive(&page_id, max_depth).await?;
+        let entry = CachedPage {
+            last_edited_time: last_edited_time.to_string(),
+            depth: max_depth,
+            page: page.clone(),
+            blocks,
+        };
+        // A cache that can't be written only costs speed
+        let _ = save_cached_page(&page_id, &entry);
+        Ok(entry.blocks)
+    }
+
+    /// List all comments on a page or block, following pagination
+    pub async fn list_comments(&self, block_id: &str) -> Result<Vec<serde_json::Value>> {
+        let block_id = normalize_page_id(block_id)?;
+        let base_url = format!("{}/comments?block_id={}", self.base_url, block_id);
+        let mut all_comments = Vec::new();
+        let mut start_cursor: Option<String> = None;
+
+        loop {
+            let request_url = if let Some(cursor) = &start_cursor {
+                format!("{}&start_cursor={}", base_url, cursor)
+            } else {
+                base_url.clone()
+            };
+
+            let response = self
+                .execute_with_retry(|| self.client.get(&request_url))
+                .await?;
+            let result: serde_json::Value =
+                response.json().await.context("Failed to parse response")?;
+
+            if let Some(results) = result.get("results").and_then(|r| r.as_array()) {
+                all_comments.extend(results.clone());
+            }
+
+            let has_more = result
+                .get("has_more")
+                .and_then(|h| h.as_bool())
+                .unwrap_or(false);
+            if !has_more {
+                break;
+            }
+
+            start_cursor = result
+                .get("next_cursor")
+                .and_then(|c| c.as_str())
+                .map(String::from);
+            if start_cursor.is_none() {
+                break;
+            }
+        }
+
+        Ok(all_comments)
+    }
+
+    /// Add a top-level comment to a page. Returns the created comment object.
+    pub async fn create_comment(&self, page_id: &str, text: &str) -> Result<serde_json::Value> {
+        let page_id = normalize_page_id(page_id)?;
+        let body = serde_json::json!({
+            "parent": { "page_id": page_id },
+            "rich_text": blocks::text(text)
+        });
+        self.post_comment(&body).await
+    }
+
+    /// Reply to an existing discussion thread. Returns the created comment object.
+    pub async fn reply_comment(
+        &self,
+        discussion_id: &str,
+        text: &str,
+    ) -> Result<serde_json::Value> {
+        let discussion_id = normalize_page_id(discussion_id)?;
+        let body = serde_json::json!({
+            "discussion_id": discussion_id,
+            "rich_text": blocks::text(text)
+        });
+        self.post_comment(&body).await
+    }
+
+    async fn post_comment(&self, body: &serde_json::Value) -> Result<serde_json::Value> {
+        let url = format!("{}/comments", self.base_url);
+
+        let response = self
+            .execute_with_retry(|| {
+                self.client
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .json(body)
+            })
+            .await?;
+
+        let result: serde_json::Value =
+            response.json().await.context("Failed to parse response")?;
+        Ok(result)
+    }
+
+    /// List workspace users (people and bots), following pagination up to `limit`
+    pub async fn list_users(&self, limit: usize) -> Result<Vec<serde_json::Value>> {
+        let base_url = format!("{}/users", self.base_url);
+        let mut all_users = Vec::new();
+        let mut start_cursor: Option<String> = None;
+
+        while all_users.len() < limit {
+            let page_size = (limit - all_users.len()).min(100);
+            let request_url = match &start_cursor {
+                Some(cursor) => format!(
+                    "{}?page_size={}&start_cursor={}",
+                    base_url, page_size, cursor
+                ),
+                None => format!("{}?page_size={}", base_url, page_size),
+            };
+
+            let response = self
+                .execute_with_retry(|| self.client.get(&request_url))
+                .await?;
+            let result: serde_json::Value =
+                response.json().await.context("Failed to parse response")?;
+
+            if let Some(results) = result.get("results").and_then(|r| r.as_array()) {
+                all_users.extend(results.clone());
+            }
+
+            let has_more = result
+                .get("has_more")
+                .and_then(|h| h.as_bool())
+                .unwrap_or(false);
+            if !has_more {
+                break;
+            }
+
+            start_cursor = result
+                .get("next_cursor")
+                .and_then(|c| c.as_str())
+                .map(String::from);
+            if start_cursor.is_none() {
+                break;
+            }
+        }
+
+        Ok(all_users)
+    }
+
+    /// The ID of the workspace member with this email or name (ignoring
+    /// case). Users are listed once per client and then looked up locally,
+    /// so bulk updates cost one listing. Emails need the integration's
+    /// "Read user information including email addresses" capability.
+    pub async fn find_user(&self, name_or_email: &str) -> Result<String> {
+        let cached = self.users.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let users = match cached {
+            Some(users) => users,
+            None => {
+                let users = self.list_users(usize::MAX).await?;
+                *self.users.lock().unwrap_or_else(|e| e.into_inner()) = Some(users.clone());
+                users
+            }
+        };
+        match_user(&users, name_or_email)
+    }
+
+    /// Property specs with the names and emails in `people` values replaced
+    /// by user IDs (see [`Self::find_user`]); other specs are unchanged
+    pub async fn resolve_people(&self, specs: &[PropertySpec]) -> Result<Vec<PropertySpec>> {
+        let mut resolved = specs.to_vec();
+        for spec in resolved.iter_mut().filter(|s| s.prop_type == "people") {
+            let mut ids = Vec::new();
+            for user in spec
+                .value
+                .split(',')
+                .map(str::trim)
+                .filter(|u| !u.is_empty())
+            {
+                let id = match normalize_page_id(user) {
+                    Ok(id) => id,
+                    Err(_) => self
+                        .find_user(user)
+                        .await
+                        .with_context(|| format!("Can't set people property '{}'", spec.name))?,
+                };
+                ids.push(id);
+            }
+            spec.value = ids.join(",");
+        }
+        Ok(resolved)
+    }
+
+    /// Replace the users and pages mentioned in the rich text of `blocks`
+    /// (and their children) with IDs: users given by email or name are
+    /// looked up with [`Self::find_user`], page URLs are reduced to IDs
+    pub async fn resolve_mentions(&self, blocks: &mut [serde_json::Value]) -> Result<()> {
+        let mut mentions = Vec::new();
+        for block in blocks.iter_mut() {
+            collect_mentions(block, &mut mentions);
+        }
+        for mention in mentions {
+            if let Some(user) = mention.pointer_mut("/user/id") {
+                let given = user.as_str().unwrap_or_default().to_string();
+                let id = match normalize_page_id(&given) {
+                    Ok(id) => id,
+                    Err(_) => self
+                        .find_user(&given)
+                        .await
+                        .with_context(|| format!("Can't mention '{}'", given))?,
+                };
+                *user = serde_json::json!(id);
+            } else if let Some(page) = mention.pointer_mut("/page/id") {
+                let given = page.as_str().unwrap_or_default().to_string();
+                let id = normalize_page_id(&given)
+                    .with_context(|| format!("Invalid page mention '{}'", given))?;
+                *page = serde_json::json!(id);
+            }
+        }
         Ok(())
     }
 
-    /// Convert a block for copying (remove IDs, timestamps, etc.)
-    fn convert_block_for_copy(&self, block: &serde_json::Value) -> Option<serde_json::Value> {
-        let block_type = block.get("type")?.as_str()?;
-        let content = block.get(block_type)?;
+    /// Retrieve the bot user behind the current integration token
+    pub async fn get_me(&self) -> Result<serde_json::Value> {
+        let url = format!("{}/users/me", self.base_url);
 
-        // Build new block with just type and content
-        let mut new_block = serde_json::json!({
-            "object": "block",
-            "type": block_type,
+        let response = self.execute_with_retry(|| self.client.get(&url)).await?;
+        let result: serde_json::Value =
+            response.json().await.context("Failed to parse response")?;
+        Ok(result)
+    }
+
+    /// Retrieve a user object (name, avatar, person/bot details)
+    pub async fn get_user(&self, user_id: &str) -> Result<serde_json::Value> {
+        let user_id = normalize_page_id(user_id)?;
+        let url = format!("{}/users/{}", self.base_url, user_id);
+
+        let response = self.execute_with_retry(|| self.client.get(&url)).await?;
+        let result: serde_json::Value =
+            response.json().await.context("Failed to parse response")?;
+        Ok(result)
+    }
+
+    /// Create a child page under `parent_id`, optionally with text content
+    /// (split into paragraphs as needed).
+    ///
+    /// Returns the created page object.
+    pub async fn create_page(
+        &self,
+        parent_id: &str,
+        title: &str,
+        content: Option<&str>,
+        icon: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        let parent_id = normalize_page_id(parent_id)?;
+        let properties = serde_json::json!({
+            "title": {
+                "title": blocks::text(title)
+            }
+        });
+
+        self.create_page_with(
+            serde_json::json!({ "page_id": parent_id }),
+            properties,
+            content,
+            icon,
+        )
+        .await
+    }
+
+    /// Create a row in a database with a title, property values and
+    /// optionally text content and an icon.
+    ///
+    /// The title is set through the `title` property ID, which works whatever
+    /// the title column is named. `database_id` may also be a data source ID
+    /// on 2025-09-03+. Returns the created page object.
+    pub async fn create_database_page(
+        &self,
+        database_id: &str,
+        title: &str,
+        props: &[PropertySpec],
+        content: Option<&str>,
+        icon: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        // Rows belong to a data source from 2025-09-03
+        let parent = if self.uses_data_sources() {
+            serde_json::json!({ "data_source_id": self.resolve_data_source(database_id).await? })
+        } else {
+            serde_json::json!({ "database_id": normalize_page_id(database_id)? })
+        };
+
+        let mut properties = build_properties(&self.resolve_people(props).await?)?;
+        properties["title"] = serde_json::json!({ "title": blocks::text(title) });
+
+        self.create_page_with(parent, properties, content, icon)
+            .await
+    }
+
+    async fn create_page_with(
+        &self,
+        parent: serde_json::Value,
+        properties: serde_json::Value,
+        content: Option<&str>,
+        icon: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        let url = format!("{}/pages", self.base_url);
+
+        let mut children = content.map(blocks::paragraphs).unwrap_or_default();
+        let rest = children.split_off(children.len().min(MAX_BLOCKS_PER_REQUEST));
+
+        let mut body = serde_json::json!({
+            "parent": parent,
+            "properties": properties
         });
+        if !children.is_empty() {
+            body["children"] = serde_json::json!(children);
+        }
+        if let Some(icon) = icon {
+            body["icon"] = build_icon(icon)?;
+        }
+
+        let response = self
+            .execute_with_retry(|| {
+                self.client
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body)
+            })
+            .await?;
+
+        let result: serde_json::Value =
+            response.json().await.context("Failed to parse response")?;
+
+        // Blocks beyond the per-request limit are appended afterwards
+        if !rest.is_empty() {
+            if let Some(id) = result.get("id").and_then(|i| i.as_str()) {
+                self.append_children(id, &rest).await?;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Retrieve a database object (title, parent, data sources, ...)
+    pub async fn get_database(&self, database_id: &str) -> Result<serde_json::Value> {
+        let database_id = normalize_page_id(database_id)?;
+        let url = format!("{}/databases/{}", self.base_url, database_id);
+
+        let response = self.execute_with_retry(|| self.client.get(&url)).await?;
+        let result: serde_json::Value =
+            response.json().await.context("Failed to parse response")?;
+        Ok(result)
+    }
+
+    /// Retrieve a data source object, which holds the property schema (2025-09-03+)
+    pub async fn get_data_source(&self, data_source_id: &str) -> Result<serde_json::Value> {
+        let data_source_id = normalize_page_id(data_source_id)?;
+        let url = format!("{}/data_sources/{}", self.base_url, data_source_id);
+
+        let response = self.execute_with_retry(|| self.client.get(&url)).await?;
+        let result: serde_json::Value =
+            response.json().await.context("Failed to parse response")?;
+        Ok(result)
+    }
+
+    /// Retrieve a database's property schema.
+    ///
+    /// Databases created under API version 2025-09-03 keep their properties on
+    /// data sources, so the first data source is consulted when the database
+    /// object itself has none. On those versions `database_id` may also be a
+    /// data source ID, whose own schema is returned. Returns `(database or
+    /// data source, properties)`.
+    pub async fn get_database_schema(
+        &self,
+        database_id: &str,
+    ) -> Result<(serde_json::Value, serde_json::Value)> {
+        let database = match self.get_database(database_id).await {
+            Err(e) if self.uses_data_sources() && is_not_found(&e) => {
+                let data_source = self.get_data_source(database_id).await?;
+                let props = data_source["properties"].clone();
+                return Ok((data_source, props));
+            }
+            result => result?,
+        };
+
+        if let Some(props) = database.get("properties") {
+            return Ok((database.clone(), props.clone()));
+        }
+
+        let data_source_id = database
+            .get("data_sources")
+            .and_then(|d| d.as_array())
+            .and_then(|d| d.first())
+            .and_then(|d| d.get("id"))
+            .and_then(|id| id.as_str())
+            .context("Database has no properties or data sources")?
+            .to_string();
 
-        // Copy the type-specific content
-        new_block[block_type] = content.clone();
+        let data_source = self.get_data_source(&data_source_id).await?;
+        let props = data_source
+            .get("properties")
+            .cloned()
+            .unwrap_or_else(|| serde_json::json!({}));
+        Ok((database, props))
+    }
+
+    /// The data source to query for a database or data source ID.
+    ///
+    /// A database ID resolves to its only data source; databases with several
+    /// need one of their data source IDs instead. An ID that isn't a database
+    /// is taken to be a data source. Results are remembered for the life of
+    /// the client.
+    pub async fn resolve_data_source(&self, id: &str) -> Result<String> {
+        let id = normalize_page_id(id)?;
+        if let Some(resolved) = self.data_sources.lock().unwrap().get(&id) {
+            return Ok(resolved.clone());
+        }
 
-        // Remove fields that shouldn't be copied
-        if let Some(obj) = new_block.get_mut(block_type) {
-            if let Some(map) = obj.as_object_mut() {
-                map.remove("id");
-                map.remove("created_time");
-                map.remove("last_edited_time");
-                map.remove("created_by");
-                map.remove("last_edited_by");
-                map.remove("has_children");
-                map.remove("archived");
-                map.remove("in_trash");
+        let resolved = match self.get_database(&id).await {
+            Ok(database) => only_data_source(&database)?,
+            Err(e) if is_not_found(&e) => id.clone(),
+            Err(e) => return Err(e),
+        };
+        self.data_sources
+            .lock()
+            .unwrap()
+            .insert(id, resolved.clone());
+        Ok(resolved)
+    }
+
+    /// Point relation properties in a schema at their target's data source,
+    /// which 2025-09-03+ expects instead of a database ID
+    async fn relations_to_data_sources(&self, properties: &mut serde_json::Value) -> Result<()> {
+        let Some(properties) = properties.as_object_mut() else {
+            return Ok(());
+        };
+        for definition in properties.values_mut() {
+            let Some(relation) = definition
+                .get_mut("relation")
+                .and_then(|r| r.as_object_mut())
+            else {
+                continue;
+            };
+            if relation.contains_key("data_source_id") {
+                continue;
             }
+            let Some(database_id) = relation
+                .remove("database_id")
+                .and_then(|id| id.as_str().map(String::from))
+            else {
+                continue;
+            };
+            let data_source_id = self.resolve_data_source(&database_id).await?;
+            relation.insert("data_source_id".to_string(), data_source_id.into());
+        }
+        Ok(())
+    }
+
+    /// Endpoint to query `id` on: `/databases/{id}/query` before 2025-09-03,
+    /// `/data_sources/{id}/query` (see [`Self::resolve_data_source`]) after
+    async fn query_url(&self, id: &str) -> Result<String> {
+        if self.uses_data_sources() {
+            let data_source_id = self.resolve_data_source(id).await?;
+            Ok(format!(
+                "{}/data_sources/{}/query",
+                self.base_url, data_source_id
+            ))
+        } else {
+            let database_id = normalize_page_id(id)?;
+            Ok(format!("{}/databases/{}/query", self.base_url, database_id))
+        }
+    }
+
+    /// Create a database under a parent page. Returns the created database object.
+    ///
+    /// From API version 2025-09-03 the schema belongs to the database's initial
+    /// data source, and relations point at data sources; earlier versions take
+    /// it as top-level `properties`.
+    pub async fn create_database(
+        &self,
+        parent_id: &str,
+        title: &str,
+        mut properties: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let parent_id = normalize_page_id(parent_id)?;
+        let url = format!("{}/databases", self.base_url);
+
+        let mut body = serde_json::json!({
+            "parent": { "type": "page_id", "page_id": parent_id },
+            "title": blocks::text(title)
+        });
+        if self.uses_data_sources() {
+            self.relations_to_data_sources(&mut properties).await?;
+            body["initial_data_source"] = serde_json::json!({ "properties": properties });
+        } else {
+            body["properties"] = properties;
         }
 
-        Some(new_block)
+        let response = self
+            .execute_with_retry(|| {
+                self.client
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body)
+            })
+            .await?;
+
+        let result: serde_json::Value =
+            response.json().await.context("Failed to parse response")?;
+        Ok(result)
+    }
+
+    /// Change a database's property schema: each entry of `properties` adds
+    /// a property, updates one (`{"name": ...}` renames it) or, when `null`,
+    /// deletes it. From API version 2025-09-03 the schema is the data
+    /// source's (see [`Self::resolve_data_source`]); earlier versions update
+    /// the database. Returns the updated database or data source.
+    pub async fn update_schema(
+        &self,
+        database_id: &str,
+        mut properties: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let url = if self.uses_data_sources() {
+            self.relations_to_data_sources(&mut properties).await?;
+            let data_source_id = self.resolve_data_source(database_id).await?;
+            format!("{}/data_sources/{}", self.base_url, data_source_id)
+        } else {
+            let database_id = normalize_page_id(database_id)?;
+            format!("{}/databases/{}", self.base_url, database_id)
+        };
+        let body = serde_json::json!({ "properties": properties });
+
+        let response = self
+            .execute_with_retry(|| {
+                self.client
+                    .patch(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body)
+            })
+            .await?;
+
+        response.json().await.context("Failed to parse response")
+    }
+
+    /// Append arbitrary block payloads (see [`crate::blocks`]) to a page or block.
+    ///
+    /// Returns the API response, whose `results` array holds the created blocks.
+    pub async fn append_children(
+        &self,
+        page_id: &str,
+        children: &[serde_json::Value],
+    ) -> Result<serde_json::Value> {
+        let page_id = normalize_page_id(page_id)?;
+        let url = format!("{}/blocks/{}/children", self.base_url, page_id);
+
+        // The API accepts at most 100 blocks per request; send larger lists in
+        // batches and merge the created blocks into one response.
+        let mut result = serde_json::json!({ "object": "list", "results": [] });
+        for batch in children.chunks(MAX_BLOCKS_PER_REQUEST) {
+            let body = serde_json::json!({
+                "children": batch
+            });
+
+            let response = self
+                .execute_with_retry(|| {
+                    self.client
+                        .patch(&url)
+                        .header("Content-Type", "application/json")
+                        .json(&body)
+                })
+                .await?;
+
+            let mut batch_result: serde_json::Value =
+                response.json().await.context("Failed to parse response")?;
+            if let (Some(all), Some(created)) = (
+                result["results"].as_array_mut(),
+                batch_result["results"].as_array_mut(),
+            ) {
+                all.append(created);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Append blocks at the end of a page, or after its child block `after`.
+    pub async fn append_children_after(
+        &self,
+        page_id: &str,
+        children: &[serde_json::Value],
+        after: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        match after {
+            Some(after) => self.insert_children(page_id, Some(after), children).await,
+            None => self.append_children(page_id, children).await,
+        }
+    }
+
+    /// Append Markdown paragraphs (see [`markdown::paragraphs`]), resolving
+    /// their mentions first
+    pub async fn append_markdown(
+        &self,
+        page_id: &str,
+        content: &str,
+        after: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        let mut children = markdown::paragraphs(content);
+        self.resolve_mentions(&mut children).await?;
+        self.append_children_after(page_id, &children, after).await
+    }
+
+    /// Replace the content of a page with `children`. Sub-pages and
+    /// databases on the page are kept, after the new content.
+    ///
+    /// The new blocks are added before the old ones are deleted, so a failed
+    /// request leaves the old content in place (with some of the new).
+    pub async fn replace_children(
+        &self,
+        page_id: &str,
+        children: &[serde_json::Value],
+    ) -> Result<()> {
+        let blocks = self.get_blocks(page_id).await?;
+        let kept = |b: &serde_json::Value| {
+            matches!(b["type"].as_str(), Some("child_page" | "child_database"))
+        };
+        let old: Vec<String> = blocks
+            .iter()
+            .filter(|b| !kept(b))
+            .filter_map(|b| b["id"].as_str().map(String::from))
+            .collect();
+
+        if !children.is_empty() {
+            match blocks.iter().position(kept) {
+                // Ahead of the first sub-page: after the block before it
+                Some(first_kept) => {
+                    let after = first_kept
+                        .checked_sub(1)
+                        .and_then(|i| blocks[i]["id"].as_str());
+                    self.insert_children(page_id, after, children).await?;
+                }
+                None => {
+                    self.append_children(page_id, children).await?;
+                }
+            }
+        }
+        self.delete_blocks(&old).await
+    }
+
+    /// Insert blocks after the child block `after`, or at the start of the
+    /// parent when `None`. Larger lists are sent in batches that chain on the
+    /// last block created.
+    pub async fn insert_children(
+        &self,
+        parent_id: &str,
+        after: Option<&str>,
+        children: &[serde_json::Value],
+    ) -> Result<serde_json::Value> {
+        let parent_id = normalize_page_id(parent_id)?;
+        let url = format!("{}/blocks/{}/children", self.base_url, parent_id);
+
+        let mut after = after.map(normalize_page_id).transpose()?;
+        let mut result = serde_json::json!({ "object": "list", "results": [] });
+        for batch in children.chunks(MAX_BLOCKS_PER_REQUEST) {
+            let mut body = serde_json::json!({
+                "children": batch
+            });
+            match &after {
+                Some(id) => body["after"] = serde_json::json!(id),
+                None => body["position"] = serde_json::json!({ "type": "start" }),
+            }
+
+            let response = self
+                .execute_with_retry(|| {
+                    self.client
+                        .patch(&url)
+                        .header("Content-Type", "application/json")
+                        .json(&body)
+                })
+                .await?;
+
+            let mut batch_result: serde_json::Value =
+                response.json().await.context("Failed to parse response")?;
+            if let Some(created) = batch_result["results"].as_array_mut() {
+                after = created
+                    .last()
+                    .and_then(|b| b["id"].as_str())
+                    .map(String::from)
+                    .or(after);
+                if let Some(all) = result["results"].as_array_mut() {
+                    all.append(created);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Replace a block's content with that of a block payload of the same type
+    /// (e.g. from [`blocks::paragraph`]). Children are left untouched.
+    pub async fn update_block(
+        &self,
+        block_id: &str,
+        block: &serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let block_id = normalize_page_id(block_id)?;
+        let url = format!("{}/blocks/{}", self.base_url, block_id);
+
+        let block_type = block["type"]
+            .as_str()
+            .context("Block payload has no type")?;
+        let body = serde_json::json!({ (block_type): block[block_type] });
+
+        let response = self
+            .execute_with_retry(|| {
+                self.client
+                    .patch(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body)
+            })
+            .await?;
+
+        let result: serde_json::Value =
+            response.json().await.context("Failed to parse response")?;
+        Ok(result)
+    }
+
+    /// Change the text of a text block, the checked state of a to-do or the
+    /// language of a code block, keeping its position and children.
+    pub async fn update_block_text(
+        &self,
+        block_id: &str,
+        text: Option<&str>,
+        checked: Option<bool>,
+        language: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        let block = self.get_block(block_id).await?;
+        let block_type = block["type"].as_str().unwrap_or_default();
+
+        if !TEXT_BLOCK_TYPES.contains(&block_type) {
+            bail!(
+                "Can't update the text of a {} block. Supported: {}",
+                block_type,
+                TEXT_BLOCK_TYPES.join(", ")
+            );
+        }
+        if checked.is_some() && block_type != "to_do" {
+            bail!(
+                "Only to_do blocks can be checked (this is a {} block)",
+                block_type
+            );
+        }
+        if language.is_some() && block_type != "code" {
+            bail!(
+                "Only code blocks have a language (this is a {} block)",
+                block_type
+            );
+        }
+
+        let mut content = serde_json::json!({});
+        if let Some(text) = text {
+            content["rich_text"] = blocks::text(text);
+        }
+        if let Some(checked) = checked {
+            content["checked"] = serde_json::json!(checked);
+        }
+        if let Some(language) = language {
+            content["language"] = serde_json::json!(language);
+        }
+
+        self.update_block(
+            block_id,
+            &serde_json::json!({ "type": block_type, (block_type): content }),
+        )
+        .await
+    }
+
+    /// Append text as paragraphs, splitting long content to fit the API limits.
+    pub async fn append_blocks(
+        &self,
+        page_id: &str,
+        content: &str,
+        after: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        self.append_children_after(page_id, &blocks::paragraphs(content), after)
+            .await
+    }
+
+    /// Update a page's title, icon (emoji or image URL) and/or property values.
+    /// Returns the updated page object.
+    pub async fn update_page(
+        &self,
+        page_id: &str,
+        title: Option<&str>,
+        icon: Option<&str>,
+        props: &[PropertySpec],
+    ) -> Result<serde_json::Value> {
+        let page_id = normalize_page_id(page_id)?;
+        let url = format!("{}/pages/{}", self.base_url, page_id);
+
+        let mut body = serde_json::json!({});
+
+        let mut properties = build_properties(&self.resolve_people(props).await?)?;
+        if let Some(new_title) = title {
+            properties["title"] = serde_json::json!({ "title": blocks::text(new_title) });
+        }
+        if properties.as_object().is_some_and(|p| !p.is_empty()) {
+            body["properties"] = properties;
+        }
+
+        if let Some(icon) = icon {
+            body["icon"] = build_icon(icon)?;
+        }
+
+        let response = self
+            .execute_with_retry(|| {
+                self.client
+                    .patch(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body)
+            })
+            .await?;
+
+        let result: serde_json::Value =
+            response.json().await.context("Failed to parse response")?;
+        Ok(result)
+    }
+
+    /// Set property values given as the API takes them (`{"Name": {"select":
+    /// {...}}}`), for values copied from other pages. Returns the updated page.
+    pub async fn set_properties(
+        &self,
+        page_id: &str,
+        properties: &serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let page_id = normalize_page_id(page_id)?;
+        let url = format!("{}/pages/{}", self.base_url, page_id);
+        let body = serde_json::json!({ "properties": properties });
+
+        let response = self
+            .execute_with_retry(|| {
+                self.client
+                    .patch(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body)
+            })
+            .await?;
+
+        response.json().await.context("Failed to parse response")
+    }
+
+    /// IDs of the pages a relation property of `page` (a page object) links
+    /// to. Page objects list at most 25, so longer relations are read from
+    /// the page property endpoint.
+    pub async fn get_relation(
+        &self,
+        page: &serde_json::Value,
+        property: &str,
+    ) -> Result<Vec<String>> {
+        let value = &page["properties"][property];
+        let listed = || {
+            value["relation"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|r| r["id"].as_str().map(String::from))
+                .collect()
+        };
+        if value["has_more"] != true {
+            return Ok(listed());
+        }
+
+        let page_id = normalize_page_id(page["id"].as_str().context("Page has no ID")?)?;
+        let property_id = value["id"].as_str().context("Property has no ID")?;
+        let mut ids = Vec::new();
+        let mut start_cursor: Option<String> = None;
+        loop {
+            let mut url = format!(
+                "{}/pages/{}/properties/{}?page_size=100",
+                self.base_url, page_id, property_id
+            );
+            if let Some(cursor) = &start_cursor {
+                url.push_str(&format!("&start_cursor={}", cursor));
+            }
+            let response = self.execute_with_retry(|| self.client.get(&url)).await?;
+            let result: serde_json::Value =
+                response.json().await.context("Failed to parse response")?;
+
+            ids.extend(
+                result["results"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|item| item["relation"]["id"].as_str().map(String::from)),
+            );
+
+            start_cursor = result["next_cursor"].as_str().map(String::from);
+            if result["has_more"] != true || start_cursor.is_none() {
+                break;
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Archive (trash) a page. Returns the archived page object.
+    pub async fn delete_page(&self, page_id: &str) -> Result<serde_json::Value> {
+        let page_id = normalize_page_id(page_id)?;
+        let url = format!("{}/pages/{}", self.base_url, page_id);
+
+        let body = serde_json::json!({
+            "archived": true
+        });
+
+        let response = self
+            .execute_with_retry(|| {
+                self.client
+                    .patch(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body)
+            })
+            .await?;
+
+        let result: serde_json::Value =
+            response.json().await.context("Failed to parse response")?;
+        Ok(result)
+    }
+
+    /// Append `code` as one code block, or several when it's longer than a
+    /// block holds (see [`blocks::code_blocks`]). Returns the block count.
+    pub async fn append_code_block(
+        &self,
+        page_id: &str,
+        code: &str,
+        language: &str,
+        caption: Option<&str>,
+        after: Option<&str>,
+    ) -> Result<usize> {
+        let blocks = blocks::code_blocks(code, language, caption);
+        self.append_children_after(page_id, &blocks, after).await?;
+        Ok(blocks.len())
+    }
+
+    pub async fn append_bookmark(
+        &self,
+        page_id: &str,
+        url_str: &str,
+        caption: Option<&str>,
+        after: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        self.append_children_after(page_id, &[blocks::bookmark(url_str, caption)], after)
+            .await
+    }
+
+    pub async fn append_image(
+        &self,
+        page_id: &str,
+        image_url: &str,
+        caption: Option<&str>,
+        after: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        self.append_children_after(page_id, &[blocks::image(image_url, caption)], after)
+            .await
+    }
+
+    pub async fn append_video(
+        &self,
+        page_id: &str,
+        video_url: &str,
+        caption: Option<&str>,
+        after: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        self.append_children_after(page_id, &[blocks::video(video_url, caption)], after)
+            .await
+    }
+
+    pub async fn append_embed(
+        &self,
+        page_id: &str,
+        url: &str,
+        caption: Option<&str>,
+        after: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        self.append_children_after(page_id, &[blocks::embed(url, caption)], after)
+            .await
+    }
+
+    /// Upload a local file through the File Upload API and return the upload ID.
+    ///
+    /// Files up to 20 MB are sent in one request; larger files use the
+    /// multi-part mode in 10 MB chunks followed by a `complete` call.
+    pub async fn upload_file(&self, path: &Path) -> Result<String> {
+        let bytes =
+            std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let filename = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("upload");
+
+        self.upload_bytes(&bytes, filename, guess_content_type(path))
+            .await
+    }
+
+    /// Upload in-memory content, such as a clipboard image, under `filename`;
+    /// see [`upload_file`](Self::upload_file)
+    pub async fn upload_bytes(
+        &self,
+        bytes: &[u8],
+        filename: &str,
+        content_type: &str,
+    ) -> Result<String> {
+        let parts: Vec<&[u8]> = if bytes.len() > SINGLE_PART_UPLOAD_LIMIT {
+            bytes.chunks(UPLOAD_PART_SIZE).collect()
+        } else {
+            vec![bytes]
+        };
+        let multi_part = parts.len() > 1;
+        // Checked up front, so no upload object is left behind for a bad type
+        let mut part_headers = reqwest::header::HeaderMap::new();
+        part_headers.insert(
+            reqwest::header::CONTENT_TYPE,
+            content_type
+                .parse()
+                .with_context(|| format!("Invalid content type '{}'", content_type))?,
+        );
+
+        // 1. Create the upload object
+        let url = format!("{}/file_uploads", self.base_url);
+        let mut body = serde_json::json!({
+            "filename": filename,
+            "content_type": content_type
+        });
+        if multi_part {
+            body["mode"] = serde_json::json!("multi_part");
+            body["number_of_parts"] = serde_json::json!(parts.len());
+        }
+
+        let response = self
+            .execute_with_retry(|| {
+                self.client
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body)
+            })
+            .await?;
+        let upload: serde_json::Value =
+            response.json().await.context("Failed to parse response")?;
+        let upload_id = upload
+            .get("id")
+            .and_then(|id| id.as_str())
+            .context("File upload response has no ID")?
+            .to_string();
+
+        // 2. Send the bytes (one request per part)
+        let send_url = format!("{}/file_uploads/{}/send", self.base_url, upload_id);
+        let progress = if multi_part {
+            progress::bar(parts.len(), "Uploading parts")
+        } else {
+            ProgressBar::hidden()
+        };
+        for (i, part) in parts.iter().enumerate() {
+            self.execute_with_retry(|| {
+                let file_part = reqwest::multipart::Part::bytes(part.to_vec())
+                    .file_name(filename.to_string())
+                    .headers(part_headers.clone());
+                let mut form = reqwest::multipart::Form::new().part("file", file_part);
+                if multi_part {
+                    form = form.text("part_number", (i + 1).to_string());
+                }
+                self.client.post(&send_url).multipart(form)
+            })
+            .await?;
+            progress.inc(1);
+        }
+
+        // 3. Multi-part uploads must be completed explicitly
+        if multi_part {
+            let complete_url = format!("{}/file_uploads/{}/complete", self.base_url, upload_id);
+            self.execute_with_retry(|| self.client.post(&complete_url))
+                .await?;
+        }
+
+        Ok(upload_id)
+    }
+
+    /// Upload a local file and attach it to a page as an image, video, audio,
+    /// pdf or file block depending on its type. Returns the append response.
+    pub async fn upload_and_attach(
+        &self,
+        page_id: &str,
+        path: &Path,
+        caption: Option<&str>,
+        after: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        let upload_id = self.upload_file(path).await?;
+        let block_type = blocks::block_type_for_content_type(guess_content_type(path));
+
+        self.append_children_after(
+            page_id,
+            &[blocks::file_upload(block_type, &upload_id, caption)],
+            after,
+        )
+        .await
+    }
+
+    /// Download a file by URL, such as a Notion-hosted image. The URL is
+    /// fetched as is, without the API key.
+    pub async fn download_file(&self, url: &str) -> Result<Vec<u8>> {
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .with_context(|| format!("Failed to download {}", url))?
+            .error_for_status()
+            .with_context(|| format!("Failed to download {}", url))?;
+        let bytes = response.bytes().await.context("Failed to read download")?;
+        Ok(bytes.to_vec())
+    }
+
+    pub async fn delete_block(&self, block_id: &str) -> Result<()> {
+        let block_id = normalize_page_id(block_id)?;
+        let url = format!("{}/blocks/{}", self.base_url, block_id);
+
+        self.execute_with_retry(|| self.client.delete(&url)).await?;
+        Ok(())
+    }
+
+    /// Delete (archive) blocks one by one, reporting progress on stderr.
+    pub async fn delete_blocks(&self, block_ids: &[String]) -> Result<()> {
+        let progress = progress::bar(block_ids.len(), "Deleting blocks");
+        for block_id in block_ids {
+            self.delete_block(block_id)
+                .await
+                .with_context(|| format!("Failed to delete block {}", block_id))?;
+            progress.inc(1);
+        }
+        Ok(())
+    }
+
+    pub async fn append_heading(
+        &self,
+        page_id: &str,
+        text: &str,
+        level: u8,
+        after: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        self.append_children_after(page_id, &[blocks::heading(level, text)], after)
+            .await
+    }
+
+    pub async fn append_rich_text(
+        &self,
+        page_id: &str,
+        segments: &[RichTextSegment],
+        after: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        self.append_children_after(page_id, &[blocks::rich_paragraph(segments)], after)
+            .await
+    }
+
+    pub async fn append_quote(
+        &self,
+        page_id: &str,
+        text: &str,
+        after: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        self.append_children_after(page_id, &[blocks::quote(text)], after)
+            .await
+    }
+
+    pub async fn append_toggle(
+        &self,
+        page_id: &str,
+        text: &str,
+        children: &[String],
+        after: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        let children: Vec<serde_json::Value> =
+            children.iter().map(|c| blocks::paragraph(c)).collect();
+
+        self.append_children_after(page_id, &[blocks::toggle(text, &children)], after)
+            .await
+    }
+
+    /// Append an original synced block containing `children`
+    pub async fn append_synced_block(
+        &self,
+        page_id: &str,
+        children: &[serde_json::Value],
+        after: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        self.append_children_after(page_id, &[blocks::synced_block(children)], after)
+            .await
+    }
+
+    /// Append a copy of the original synced block `original_id`, which shows
+    /// (and edits) the same content
+    pub async fn append_synced_copy(
+        &self,
+        page_id: &str,
+        original_id: &str,
+        after: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        let original_id = normalize_page_id(original_id)?;
+        self.append_children_after(page_id, &[blocks::synced_block_copy(&original_id)], after)
+            .await
+    }
+
+    /// Append a table built from rows of cell text. Short rows are padded with empty cells.
+    pub async fn append_table(
+        &self,
+        page_id: &str,
+        rows: &[Vec<String>],
+        has_column_header: bool,
+        after: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        if rows.is_empty() {
+            bail!("Table must have at least one row");
+        }
+
+        self.append_children_after(page_id, &[blocks::table(rows, has_column_header)], after)
+            .await
+    }
+
+    pub async fn append_divider(
+        &self,
+        page_id: &str,
+        after: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        self.append_children_after(page_id, &[blocks::divider()], after)
+            .await
+    }
+
+    pub async fn append_table_of_contents(
+        &self,
+        page_id: &str,
+        after: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        self.append_children_after(page_id, &[blocks::table_of_contents()], after)
+            .await
+    }
+
+    pub async fn append_breadcrumb(
+        &self,
+        page_id: &str,
+        after: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        self.append_children_after(page_id, &[blocks::breadcrumb()], after)
+            .await
+    }
+
+    pub async fn append_bulleted_list(
+        &self,
+        page_id: &str,
+        items: &[String],
+        after: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        let children: Vec<serde_json::Value> = items
+            .iter()
+            .map(|item| blocks::bulleted_list_item(item))
+            .collect();
+
+        self.append_children_after(page_id, &children, after).await
+    }
+
+    /// Append a (possibly nested) list, level by level. Returns the number
+    /// of items created.
+    pub async fn append_list(
+        &self,
+        parent_id: &str,
+        items: &[ListItem],
+        after: Option<&str>,
+    ) -> Result<usize> {
+        let payload: Vec<serde_json::Value> = items.iter().map(ListItem::block).collect();
+        let result = self
+            .append_children_after(parent_id, &payload, after)
+            .await?;
+        let mut created = payload.len();
+
+        let new_blocks = result["results"].as_array().cloned().unwrap_or_default();
+        for (item, new_block) in items.iter().zip(&new_blocks) {
+            if item.children.is_empty() {
+                continue;
+            }
+            let new_id = new_block["id"]
+                .as_str()
+                .context("Failed to get new list item ID")?;
+            created += Box::pin(self.append_list(new_id, &item.children, None)).await?;
+        }
+
+        Ok(created)
+    }
+
+    /// Query a database, or on API version 2025-09-03+ one of its data
+    /// sources, fetching up to `limit` rows. `database_id` may be a database
+    /// or data source ID.
+    pub async fn query_database(
+        &self,
+        database_id: &str,
+        filter: Option<&serde_json::Value>,
+        sorts: &[SortSpec],
+        limit: usize,
+    ) -> Result<Vec<serde_json::Value>> {
+        let mut all_results = Vec::new();
+        self.query_database_each(database_id, filter, sorts, limit, |item| {
+            all_results.push(item);
+            Ok(())
+        })
+        .await?;
+        Ok(all_results)
+    }
+
+    /// Like [`Self::query_database`], but hands each row to `f` as soon as
+    /// its page arrives instead of collecting them. Returns the number of
+    /// rows seen.
+    pub async fn query_database_each(
+        &self,
+        database_id: &str,
+        filter: Option<&serde_json::Value>,
+        sorts: &[SortSpec],
+        limit: usize,
+        mut f: impl FnMut(serde_json::Value) -> Result<()>,
+    ) -> Result<usize> {
+        if limit == 0 {
+            return Ok(0);
+        }
+
+        let url = self.query_url(database_id).await?;
+        let mut seen = 0;
+        let mut start_cursor: Option<String> = None;
+        let progress = progress::spinner("Querying rows");
+
+        loop {
+            let remaining = limit.saturating_sub(seen);
+            let page_size = remaining.clamp(1, 100);
+
+            let mut result = self
+                .query_page(&url, filter, sorts, start_cursor.as_deref(), page_size)
+                .await?;
+
+            if let Some(results) = result["results"].as_array_mut() {
+                for item in results.drain(..) {
+                    f(item)?;
+                    seen += 1;
+                }
+                progress.set_position(seen as u64);
+            }
+
+            let has_more = result
+                .get("has_more")
+                .and_then(|h| h.as_bool())
+                .unwrap_or(false);
+            if !has_more || seen >= limit {
+                break;
+            }
+
+            start_cursor = result
+                .get("next_cursor")
+                .and_then(|c| c.as_str())
+                .map(String::from);
+            if start_cursor.is_none() {
+                break;
+            }
+        }
+
+        Ok(seen)
+    }
+
+    /// One page of database (or data source) query results, as the raw list
+    /// response (`results`, `has_more`, `next_cursor`).
+    pub async fn query_database_page(
+        &self,
+        database_id: &str,
+        filter: Option<&serde_json::Value>,
+        sorts: &[SortSpec],
+        start_cursor: Option<&str>,
+        page_size: usize,
+    ) -> Result<serde_json::Value> {
+        let url = self.query_url(database_id).await?;
+        self.query_page(&url, filter, sorts, start_cursor, page_size)
+            .await
+    }
+
+    async fn query_page(
+        &self,
+        url: &str,
+        filter: Option<&serde_json::Value>,
+        sorts: &[SortSpec],
+        start_cursor: Option<&str>,
+        page_size: usize,
+    ) -> Result<serde_json::Value> {
+        let mut body = serde_json::json!({
+            "page_size": page_size
+        });
+
+        if let Some(cursor) = start_cursor {
+            body["start_cursor"] = serde_json::json!(cursor);
+        }
+
+        if let Some(f) = filter {
+            body["filter"] = f.clone();
+        }
+
+        if !sorts.is_empty() {
+            body["sorts"] = sorts.iter().map(SortSpec::to_sort).collect();
+        }
+
+        let response = self
+            .execute_with_retry(|| {
+                self.client
+                    .post(url)
+                    .header("Content-Type", "application/json")
+                    .json(&body)
+            })
+            .await?;
+
+        response.json().await.context("Failed to parse response")
+    }
+
+    /// Move a page to a new parent by copying content and deleting original
+    pub async fn move_page(
+        &self,
+        page_id: &str,
+        new_parent_id: &str,
+        delete_original: bool,
+    ) -> Result<serde_json::Value> {
+        let new_page = self
+            .duplicate_page(page_id, Some(new_parent_id), None)
+            .await?;
+
+        if delete_original {
+            self.progress("Archiving original page...");
+            self.delete_page(page_id).await?;
+        }
+
+        Ok(new_page)
+    }
+
+    /// Deep-copy a page with its icon, cover and full block tree.
+    ///
+    /// Without `new_parent_id` the copy is created next to the original; a
+    /// database row then keeps its writable property values. Under a new
+    /// parent page only the title carries over. Child pages and databases are
+    /// not copied. Returns the new page object.
+    pub async fn duplicate_page(
+        &self,
+        page_id: &str,
+        new_parent_id: Option<&str>,
+        new_title: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        self.copy_page(page_id, new_parent_id, new_title, &[]).await
+    }
+
+    /// Create a page from a template page: a deep copy (see
+    /// [`Self::duplicate_page`]) with `{{name}}` placeholders in the title,
+    /// text properties and all block rich text replaced by `vars`.
+    pub async fn create_from_template(
+        &self,
+        template_id: &str,
+        parent_id: &str,
+        title: Option<&str>,
+        vars: &[(String, String)],
+    ) -> Result<serde_json::Value> {
+        self.copy_page(template_id, Some(parent_id), title, vars)
+            .await
+    }
+
+    async fn copy_page(
+        &self,
+        page_id: &str,
+        new_parent_id: Option<&str>,
+        new_title: Option<&str>,
+        vars: &[(String, String)],
+    ) -> Result<serde_json::Value> {
+        let page_id = normalize_page_id(page_id)?;
+
+        self.progress("Reading original page...");
+        let page = self.get_page(&page_id).await?;
+        let title = match new_title {
+            Some(t) => t.to_string(),
+            None => blocks::fill_placeholders(
+                &serde_json::from_value::<Page>(page.clone())
+                    .ok()
+                    .and_then(|p| p.title())
+                    .unwrap_or_else(|| "Untitled".to_string()),
+                vars,
+            ),
+        };
+        let title_only = serde_json::json!({ "title": { "title": blocks::text(&title) } });
+
+        let parent_type = page["parent"]["type"].as_str().unwrap_or_default();
+        let (parent, properties) = match new_parent_id {
+            Some(id) => (
+                serde_json::json!({ "page_id": normalize_page_id(id)? }),
+                title_only,
+            ),
+            None if parent_type == "page_id" => (
+                serde_json::json!({ "page_id": page["parent"]["page_id"] }),
+                title_only,
+            ),
+            None if parent_type == "database_id" || parent_type == "data_source_id" => {
+                let mut properties = copy_properties(&page["properties"]);
+                blocks::substitute_vars(&mut properties, vars);
+                if new_title.is_some() {
+                    if let Some((name, _)) = page["properties"]
+                        .as_object()
+                        .into_iter()
+                        .flatten()
+                        .find(|(_, v)| v["type"] == "title")
+                    {
+                        properties[name] = serde_json::json!({ "title": blocks::text(&title) });
+                    }
+                }
+                (
+                    serde_json::json!({ (parent_type): page["parent"][parent_type] }),
+                    properties,
+                )
+            }
+            None => bail!("The page is not under a page or database; pass a parent page ID"),
+        };
+
+        self.progress("Fetching blocks...");
+        let mut tree = self.get_blocks_recursive(&page_id, None).await?;
+        for block in &mut tree {
+            blocks::substitute_vars(block, vars);
+        }
+
+        self.progress("Creating new page...");
+        let mut body = serde_json::json!({
+            "parent": parent,
+            "properties": properties
+        });
+        for key in ["icon", "cover"] {
+            if let Some(file) = copy_file_object(&page[key]) {
+                body[key] = file;
+            }
+        }
+
+        let url = format!("{}/pages", self.base_url);
+        let response = self
+            .execute_with_retry(|| {
+                self.client
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body)
+            })
+            .await?;
+        let new_page: serde_json::Value =
+            response.json().await.context("Failed to parse response")?;
+        let new_page_id = new_page
+            .get("id")
+            .and_then(|id| id.as_str())
+            .context("Failed to get new page ID")?;
+
+        if !tree.is_empty() {
+            self.progress("Copying blocks...");
+            let copied = self.copy_block_tree(new_page_id, &tree).await?;
+            self.progress(&format!("Copied {} blocks", copied));
+        }
+
+        Ok(new_page)
+    }
+
+    /// Move a block (with its children) under another page or block by
+    /// recreating it there and deleting the original. The API has no native
+    /// block move, so the moved block gets a new ID. Returns the new block.
+    pub async fn move_block(
+        &self,
+        block_id: &str,
+        new_parent_id: &str,
+        after: Option<&str>,
+    ) -> Result<serde_json::Value> {
+        let mut block = self.get_block(block_id).await?;
+        let block_type = block["type"].as_str().unwrap_or("unknown").to_string();
+        if block["has_children"].as_bool().unwrap_or(false) {
+            let children = self.get_blocks_recursive(block_id, None).await?;
+            block["children"] = serde_json::Value::Array(children);
+        }
+
+        // Deleting the original must not lose anything the copy left out
+        if let Some(lost) = uncopyable_content(&block) {
+            bail!(
+                "Can't move this {} block: it contains {}, which can't be recreated through the API",
+                block_type,
+                lost
+            );
+        }
+        let converted = convert_block_for_copy(&block).with_context(|| {
+            format!(
+                "A {} block can't be recreated through the API (use `move` for pages)",
+                block_type
+            )
+        })?;
+
+        let result = self
+            .append_children_after(new_parent_id, &[converted], after)
+            .await?;
+        let new_block = result["results"]
+            .get(0)
+            .cloned()
+            .context("Failed to create the moved block")?;
+
+        if !EMBEDDED_CHILDREN.contains(&block_type.as_str()) {
+            if let (Some(children), Some(new_id)) =
+                (block["children"].as_array(), new_block["id"].as_str())
+            {
+                self.copy_block_tree(new_id, children).await?;
+            }
+        }
+
+        self.delete_block(block_id).await?;
+        Ok(new_block)
+    }
+
+    /// A "→ ..." progress line on stderr, unless quiet
+    fn progress(&self, message: &str) {
+        if !self.quiet {
+            eprintln!("{} {}", "→".blue(), message);
+        }
+    }
+
+    /// Recreate a block tree (as returned by [`Self::get_blocks_recursive`])
+    /// under `parent_id`, level by level. Returns the number of blocks created.
+    async fn copy_block_tree(
+        &self,
+        parent_id: &str,
+        blocks: &[serde_json::Value],
+    ) -> Result<usize> {
+        let mut pairs: Vec<(serde_json::Value, &serde_json::Value)> = Vec::new();
+        for block in blocks {
+            match convert_block_for_copy(block) {
+                Some(converted) => pairs.push((converted, block)),
+                None => eprintln!(
+                    "{} Skipping {} block",
+                    "⚠".yellow(),
+                    block["type"].as_str().unwrap_or("unknown")
+                ),
+            }
+        }
+        if pairs.is_empty() {
+            return Ok(0);
+        }
+
+        let payloads: Vec<serde_json::Value> = pairs.iter().map(|(b, _)| b.clone()).collect();
+        let result = self.append_children(parent_id, &payloads).await?;
+        let mut copied = payloads.len();
+
+        // Children not already embedded in the payload are appended to the new block
+        let created = result["results"].as_array().cloned().unwrap_or_default();
+        for ((_, original), new_block) in pairs.iter().zip(&created) {
+            let block_type = original["type"].as_str().unwrap_or_default();
+            if EMBEDDED_CHILDREN.contains(&block_type) {
+                continue;
+            }
+            let (Some(children), Some(new_id)) =
+                (original["children"].as_array(), new_block["id"].as_str())
+            else {
+                continue;
+            };
+            if !children.is_empty() {
+                copied += Box::pin(self.copy_block_tree(new_id, children)).await?;
+            }
+        }
+
+        Ok(copied)
+    }
+}
+
+/// Block types that must be created together with their children
+const EMBEDDED_CHILDREN: [&str; 3] = ["table", "column_list", "column"];
+
+/// Property types whose values can't be set when creating a page
+pub(crate) const COMPUTED_PROPERTIES: [&str; 10] = [
+    "formula",
+    "rollup",
+    "created_time",
+    "created_by",
+    "last_edited_time",
+    "last_edited_by",
+    "unique_id",
+    "button",
+    "verification",
+    "files",
+];
+
+/// The ID of a database object's single data source
+fn only_data_source(database: &serde_json::Value) -> Result<String> {
+    let data_sources = database["data_sources"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    match data_sources.as_slice() {
+        [] => bail!("Database has no data sources"),
+        [only] => only["id"]
+            .as_str()
+            .map(String::from)
+            .context("Data source has no ID"),
+        many => {
+            let list: Vec<String> = many
+                .iter()
+                .map(|d| {
+                    format!(
+                        "  {}  {}",
+                        d["id"].as_str().unwrap_or_default(),
+                        d["name"].as_str().unwrap_or_default()
+                    )
+                })
+                .collect();
+            bail!(
+                "Database has {} data sources; use one of their IDs instead:\n{}",
+                many.len(),
+                list.join("\n")
+            )
+        }
+    }
+}
+
+/// Whether `e` is a 404 / `object_not_found` API error
+fn is_not_found(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<NotionError>()
+        .is_some_and(|api| api.kind == ErrorKind::ObjectNotFound)
+}
+
+/// Read `response` to add its size to `call`, returning an equivalent
+/// response (the body can only be read once)
+async fn read_counted(response: reqwest::Response, call: &mut Call) -> Result<reqwest::Response> {
+    let mut builder = http::Response::builder().status(response.status());
+    for (name, value) in response.headers() {
+        builder = builder.header(name, value);
+    }
+    let body = response.bytes().await.context("Failed to read response")?;
+    call.bytes += body.len() as u64;
+    Ok(builder.body(body).context("Invalid response")?.into())
+}
+
+/// Turn a failed response into a [`NotionError`]
+async fn api_error(response: reqwest::Response) -> anyhow::Error {
+    let status = response.status().as_u16();
+    let body = response.text().await.unwrap_or_default();
+    NotionError::from_response(status, &body).into()
+}
+
+/// Token bucket that spaces out requests to stay under Notion's average
+/// request rate, allowing short bursts up to one second's worth
+struct RateLimiter {
+    /// Tokens added per second; 0 or less means unlimited
+    rate: f64,
+    /// Available tokens and when they were last topped up. Tokens go negative
+    /// when callers are queued, each waiting for its own slot.
+    state: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    fn new(rate: f64) -> Self {
+        Self {
+            rate,
+            state: Mutex::new((rate.max(1.0), Instant::now())),
+        }
+    }
+
+    /// Take a token, returning how long to wait before using it
+    fn reserve(&self) -> Duration {
+        if self.rate <= 0.0 {
+            return Duration::ZERO;
+        }
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let (tokens, last) = &mut *state;
+        let now = Instant::now();
+        *tokens =
+            (*tokens + now.duration_since(*last).as_secs_f64() * self.rate).min(self.rate.max(1.0));
+        *last = now;
+        *tokens -= 1.0;
+        if *tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-*tokens / self.rate)
+        }
+    }
+
+    async fn acquire(&self) {
+        let wait = self.reserve();
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Whether a request can be sent again without changing the outcome. Reads
+/// sent as POST (search, queries) qualify; creating pages, comments or uploads
+/// and appending children don't.
+fn is_idempotent(request: &reqwest::Request) -> bool {
+    let path = request.url().path();
+    match *request.method() {
+        reqwest::Method::GET | reqwest::Method::DELETE => true,
+        reqwest::Method::PATCH => !path.ends_with("/children"),
+        reqwest::Method::POST => path.ends_with("/search") || path.ends_with("/query"),
+        _ => false,
+    }
+}
+
+/// Server errors worth retrying
+fn is_transient_status(status: reqwest::StatusCode) -> bool {
+    matches!(status.as_u16(), 500 | 502 | 503 | 504)
+}
+
+/// Whether a request failed because the API couldn't be reached at all
+fn is_connection_error(e: &anyhow::Error) -> bool {
+    is_unreachable(e)
+        || e.chain().any(|cause| {
+            cause
+                .downcast_ref::<reqwest::Error>()
+                .is_some_and(|r| r.is_timeout())
+        })
+}
+
+/// Whether a request failed to connect, so it was never sent (unlike a
+/// timeout, which may have been applied)
+pub fn is_unreachable(e: &anyhow::Error) -> bool {
+    e.chain().any(|cause| {
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|r| r.is_connect())
+    })
+}
+
+/// Exponential backoff from 0.5s, capped at 8s, plus up to 50% random jitter
+fn backoff_delay(retries: u32) -> Duration {
+    use std::hash::{BuildHasher, Hasher};
+
+    let base = Duration::from_millis(500) * 2u32.pow(retries.min(4));
+    let random = std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish();
+    base + base.mul_f64((random % 1000) as f64 / 2000.0)
+}
+
+/// Describe content in a block tree that [`convert_block_for_copy`] and
+/// `copy_block_tree` would leave out, if any
+fn uncopyable_content(block: &serde_json::Value) -> Option<String> {
+    let children = block["children"].as_array().into_iter().flatten();
+    for child in children {
+        let child_type = child["type"].as_str().unwrap_or("unknown");
+        if matches!(child_type, "child_page" | "child_database" | "unsupported") {
+            return Some(format!("a {} block", child_type));
+        }
+        // Columns are created in one request, which can't nest any deeper
+        if child_type == "column"
+            && child["children"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .any(|b| b["has_children"].as_bool().unwrap_or(false))
+        {
+            return Some("nested blocks inside a column".to_string());
+        }
+        if let Some(lost) = uncopyable_content(child) {
+            return Some(lost);
+        }
+    }
+    None
+}
+
+/// Turn a page's property values into a create payload, dropping computed ones
+fn copy_properties(properties: &serde_json::Value) -> serde_json::Value {
+    let mut copied = serde_json::Map::new();
+    for (name, prop) in properties.as_object().into_iter().flatten() {
+        let Some(prop_type) = prop["type"].as_str() else {
+            continue;
+        };
+        if COMPUTED_PROPERTIES.contains(&prop_type) {
+            continue;
+        }
+        copied.insert(
+            name.clone(),
+            serde_json::json!({ (prop_type): prop[prop_type] }),
+        );
+    }
+    serde_json::Value::Object(copied)
+}
+
+/// Copyable form of an icon, cover or media object. Notion-hosted files are
+/// re-linked by their (temporary) URL.
+fn copy_file_object(file: &serde_json::Value) -> Option<serde_json::Value> {
+    match file["type"].as_str()? {
+        "emoji" => Some(serde_json::json!({ "type": "emoji", "emoji": file["emoji"] })),
+        "external" => Some(
+            serde_json::json!({ "type": "external", "external": { "url": file["external"]["url"] } }),
+        ),
+        "file" => Some(
+            serde_json::json!({ "type": "external", "external": { "url": file["file"]["url"] } }),
+        ),
+        _ => None,
+    }
+}
+
+/// Convert a block for copying: keep only the type and its content, dropping
+/// IDs and timestamps. Tables and columns embed their (converted) children;
+/// blocks nested inside a column's content are not carried over.
+/// Returns `None` for blocks that can't be created through the API.
+fn convert_block_for_copy(block: &serde_json::Value) -> Option<serde_json::Value> {
+    let block_type = block.get("type")?.as_str()?;
+    if matches!(block_type, "child_page" | "child_database" | "unsupported") {
+        return None;
+    }
+    let mut content = block.get(block_type)?.clone();
+
+    // Remove fields that shouldn't be copied
+    if let Some(map) = content.as_object_mut() {
+        for key in [
+            "id",
+            "created_time",
+            "last_edited_time",
+            "created_by",
+            "last_edited_by",
+            "has_children",
+            "archived",
+            "in_trash",
+        ] {
+            map.remove(key);
+        }
+    }
+
+    if matches!(block_type, "image" | "video" | "pdf" | "audio" | "file") {
+        if let Some(source) = copy_file_object(&content) {
+            let caption = content.get("caption").cloned();
+            content = source;
+            if let Some(caption) = caption {
+                content["caption"] = caption;
+            }
+        }
+    }
+
+    if EMBEDDED_CHILDREN.contains(&block_type) {
+        let children: Vec<serde_json::Value> = block["children"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(convert_block_for_copy)
+            .collect();
+        content["children"] = serde_json::Value::Array(children);
+    }
+
+    Some(serde_json::json!({
+        "object": "block",
+        "type": block_type,
+        (block_type): content
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_property_spec_parse() {
+        let spec = PropertySpec::parse("Status:select=In Progress").unwrap();
+        assert_eq!(spec.name, "Status");
+        assert_eq!(spec.prop_type, "select");
+        assert_eq!(spec.value, "In Progress");
+    }
+
+    #[test]
+    fn test_property_spec_default_type() {
+        let spec = PropertySpec::parse("Notes=hello").unwrap();
+        assert_eq!(spec.prop_type, "rich_text");
+    }
+
+    #[test]
+    fn test_property_spec_invalid() {
+        assert!(PropertySpec::parse("no-equals-sign").is_err());
+        assert!(PropertySpec::parse(":select=x").is_err());
+    }
+
+    #[test]
+    fn test_filter_spec_defaults() {
+        let spec = FilterSpec::parse("Status=Done").unwrap();
+        assert_eq!(spec.prop_type, "rich_text");
+        assert_eq!(spec.operator, "contains");
+        assert_eq!(
+            spec.to_filter().unwrap(),
+            serde_json::json!({ "property": "Status", "rich_text": { "contains": "Done" } })
+        );
+    }
+
+    #[test]
+    fn test_filter_spec_operators() {
+        let due = FilterSpec::parse("Due:date>=2024-07-01").unwrap();
+        assert_eq!(due.operator, "on_or_after");
+
+        let points = FilterSpec::parse("Points:number<3").unwrap();
+        assert_eq!(points.to_filter().unwrap()["number"]["less_than"], 3.0);
+
+        let empty = FilterSpec::parse("Tags:multi_select:is_empty").unwrap();
+        assert_eq!(empty.to_filter().unwrap()["multi_select"]["is_empty"], true);
+
+        let named = FilterSpec::parse("Due:date:before=2024-01-01").unwrap();
+        assert_eq!(named.operator, "before");
+
+        assert!(FilterSpec::parse("Status:select>x").is_err());
+    }
+
+    #[test]
+    fn test_filter_spec_for_schema() {
+        let schema = serde_json::json!({
+            "Name": { "type": "title" },
+            "Status": { "type": "status" },
+            "Points": { "type": "number" }
+        });
+
+        let spec = FilterSpec::parse_for_schema("status=Done", &schema).unwrap();
+        assert_eq!(spec.property, "Status");
+        assert_eq!(
+            spec.to_filter().unwrap(),
+            serde_json::json!({ "property": "Status", "status": { "equals": "Done" } })
+        );
+
+        let points = FilterSpec::parse_for_schema("Points>=3", &schema).unwrap();
+        assert_eq!(points.operator, "greater_than_or_equal_to");
+
+        let typo = FilterSpec::parse_for_schema("Stauts=Done", &schema).unwrap_err();
+        assert_eq!(
+            typo.to_string(),
+            "Unknown property 'Stauts'. Available: Name, Points, Status"
+        );
+    }
+
+    #[test]
+    fn test_filter_spec_dates_and_formulas() {
+        let next = FilterSpec::parse("Due:date:next_week").unwrap();
+        assert_eq!(
+            next.to_filter().unwrap(),
+            serde_json::json!({ "property": "Due", "date": { "next_week": {} } })
+        );
+
+        let today = crate::dates::today().to_string();
+        let overdue = FilterSpec::parse("Due:date<today").unwrap();
+        assert_eq!(overdue.value.as_deref(), Some(today.as_str()));
+
+        let score = FilterSpec::parse("Score:formula>=3").unwrap();
+        assert_eq!(
+            score.to_filter().unwrap(),
+            serde_json::json!({
+                "property": "Score",
+                "formula": { "number": { "greater_than_or_equal_to": 3.0 } }
+            })
+        );
+
+        let label = FilterSpec::parse("Label:formula.string~urgent").unwrap();
+        assert_eq!(
+            label.to_filter().unwrap()["formula"]["string"]["contains"],
+            "urgent"
+        );
+
+        let files = FilterSpec::parse("Attachments:files:is_empty").unwrap();
+        assert_eq!(files.to_filter().unwrap()["files"]["is_empty"], true);
+    }
+
+    #[test]
+    fn test_sort_spec() {
+        let due = SortSpec::parse("Due:asc", false).unwrap();
+        assert_eq!(
+            due.to_sort(),
+            serde_json::json!({ "property": "Due", "direction": "ascending" })
+        );
+
+        let edited = SortSpec::parse("last_edited_time", false).unwrap();
+        assert_eq!(
+            edited.to_sort(),
+            serde_json::json!({ "timestamp": "last_edited_time", "direction": "descending" })
+        );
+
+        assert!(SortSpec::parse("Due:sideways", true).is_err());
+        assert!(SortSpec::parse(":desc", true).is_err());
+    }
+
+    #[test]
+    fn test_build_filter_compound() {
+        let specs = vec![
+            FilterSpec::parse("A:select=x").unwrap(),
+            FilterSpec::parse("B:checkbox=true").unwrap(),
+        ];
+        let filter = build_filter(&specs, true).unwrap().unwrap();
+        assert_eq!(filter["or"].as_array().unwrap().len(), 2);
+
+        let single = build_filter(&specs[..1], false).unwrap().unwrap();
+        assert_eq!(single["property"], "A");
+    }
+
+    #[test]
+    fn test_checkbox_filter_values() {
+        let filter = |expr: &str| FilterSpec::parse(expr).unwrap().to_filter();
+        assert_eq!(
+            filter("Done:checkbox=yes").unwrap(),
+            serde_json::json!({ "property": "Done", "checkbox": { "equals": true } })
+        );
+        assert_eq!(
+            filter("Done:checkbox=off").unwrap()["checkbox"]["equals"],
+            false
+        );
+        assert!(filter("Done:checkbox=maybe").is_err());
+    }
+
+    #[test]
+    fn test_schema_spec_definitions() {
+        let status = SchemaSpec::parse("Status:select=Todo,Done:green").unwrap();
+        assert_eq!(
+            status.to_definition().unwrap(),
+            serde_json::json!({
+                "select": { "options": [{ "name": "Todo" }, { "name": "Done", "color": "green" }] }
+            })
+        );
+
+        let due = SchemaSpec::parse("Due:date").unwrap();
+        assert_eq!(
+            due.to_definition().unwrap(),
+            serde_json::json!({ "date": {} })
+        );
+
+        assert!(SchemaSpec::parse("Total:formula")
+            .unwrap()
+            .to_definition()
+            .is_err());
+    }
+
+    #[test]
+    fn test_build_schema_adds_title() {
+        let specs = vec![SchemaSpec::parse("Done:checkbox").unwrap()];
+        let schema = build_schema(&specs, None).unwrap();
+        assert_eq!(schema["Name"], serde_json::json!({ "title": {} }));
+
+        let titled = vec![SchemaSpec::parse("Task:title").unwrap()];
+        let schema = build_schema(&titled, None).unwrap();
+        assert!(schema.get("Name").is_none());
+    }
+
+    #[test]
+    fn test_property_payloads() {
+        let tags = PropertySpec::parse("Tags:multi_select=a, b").unwrap();
+        assert_eq!(
+            tags.to_payload().unwrap(),
+            serde_json::json!({ "multi_select": [{ "name": "a" }, { "name": "b" }] })
+        );
+
+        let points = PropertySpec::parse("Points:number=3.5").unwrap();
+        assert_eq!(points.to_payload().unwrap()["number"], 3.5);
+
+        let bad = PropertySpec::parse("Points:number=lots").unwrap();
+        assert!(bad.to_payload().is_err());
+
+        let cleared = PropertySpec::parse("Due:date=").unwrap();
+        assert!(cleared.to_payload().unwrap()["date"].is_null());
+    }
+
+    #[test]
+    fn test_convert_block_for_copy() {
+        let block = serde_json::json!({
+            "object": "block",
+            "id": "abc",
+            "type": "table",
+            "has_children": true,
+            "table": { "table_width": 2, "has_column_header": true },
+            "children": [{
+                "id": "row",
+                "type": "table_row",
+                "table_row": { "cells": [[], []] }
+            }]
+        });
+        let copied = convert_block_for_copy(&block).unwrap();
+        assert!(copied.get("id").is_none());
+        assert_eq!(copied["table"]["table_width"], 2);
+        assert_eq!(copied["table"]["children"][0]["type"], "table_row");
+
+        let child_page =
+            serde_json::json!({ "type": "child_page", "child_page": { "title": "x" } });
+        assert!(convert_block_for_copy(&child_page).is_none());
+
+        let image = serde_json::json!({
+            "type": "image",
+            "image": { "type": "file", "file": { "url": "https://s3/x.png", "expiry_time": "t" }, "caption": [] }
+        });
+        let copied = convert_block_for_copy(&image).unwrap();
+        assert_eq!(copied["image"]["type"], "external");
+        assert_eq!(copied["image"]["external"]["url"], "https://s3/x.png");
+        assert_eq!(copied["image"]["caption"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_copy_properties_skips_computed() {
+        let properties = serde_json::json!({
+            "Name": { "id": "title", "type": "title", "title": [] },
+            "Done": { "id": "a", "type": "checkbox", "checkbox": true },
+            "Created": { "id": "b", "type": "created_time", "created_time": "2024-01-01" }
+        });
+        let copied = copy_properties(&properties);
+        assert_eq!(copied["Done"], serde_json::json!({ "checkbox": true }));
+        assert!(copied.get("Created").is_none());
+        assert!(copied.get("Name").is_some());
+    }
+
+    #[test]
+    fn test_select_blocks() {
+        let blocks = vec![
+            serde_json::json!({ "id": "11111111-1111-1111-1111-111111111111", "type": "heading_1" }),
+            serde_json::json!({ "id": "22222222-2222-2222-2222-222222222222", "type": "paragraph" }),
+            serde_json::json!({ "id": "33333333-3333-3333-3333-333333333333", "type": "divider" }),
+            serde_json::json!({ "id": "44444444-4444-4444-4444-444444444444", "type": "paragraph" }),
+        ];
+
+        assert_eq!(select_blocks(&blocks, None, None).unwrap().len(), 4);
+        assert_eq!(
+            select_blocks(&blocks, Some("paragraph"), None)
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let after = select_blocks(&blocks, None, Some("22222222222222222222222222222222")).unwrap();
+        assert_eq!(after.len(), 2);
+        assert_eq!(after[0]["type"], "divider");
+
+        let both = select_blocks(
+            &blocks,
+            Some("paragraph"),
+            Some("22222222-2222-2222-2222-222222222222"),
+        )
+        .unwrap();
+        assert_eq!(both.len(), 1);
+
+        assert!(select_blocks(&blocks, None, Some("55555555555555555555555555555555")).is_err());
+    }
+
+    #[test]
+    fn test_match_columns() {
+        let schema = serde_json::json!({
+            "Name": { "type": "title" },
+            "Status": { "type": "select" },
+            "Total": { "type": "formula" }
+        });
+        let headers: Vec<String> = ["Name", "status", "Total", "Missing"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect();
+
+        let columns = match_columns(&headers, &schema);
+        assert_eq!(columns[0], Some(("Name".to_string(), "title".to_string())));
+        assert_eq!(
+            columns[1],
+            Some(("Status".to_string(), "select".to_string()))
+        );
+        assert_eq!(columns[2], None);
+        assert_eq!(columns[3], None);
+    }
+
+    #[test]
+    fn test_uncopyable_content() {
+        let toggle = serde_json::json!({
+            "type": "toggle",
+            "children": [
+                { "type": "paragraph", "has_children": false },
+                { "type": "child_page", "has_children": false }
+            ]
+        });
+        assert_eq!(
+            uncopyable_content(&toggle).as_deref(),
+            Some("a child_page block")
+        );
+
+        let columns = serde_json::json!({
+            "type": "column_list",
+            "children": [{ "type": "column", "children": [{ "type": "toggle", "has_children": true }] }]
+        });
+        assert!(uncopyable_content(&columns).is_some());
+
+        let plain = serde_json::json!({ "type": "quote", "children": [{ "type": "paragraph" }] });
+        assert!(uncopyable_content(&plain).is_none());
+    }
+
+    #[test]
+    fn test_is_idempotent() {
+        let client = reqwest::Client::new();
+        let request = |method: reqwest::Method, path: &str| {
+            client
+                .request(method, format!("{}{}", NOTION_API_BASE, path))
+                .build()
+                .unwrap()
+        };
+
+        assert!(is_idempotent(&request(reqwest::Method::GET, "/pages/abc")));
+        assert!(is_idempotent(&request(
+            reqwest::Method::PATCH,
+            "/blocks/abc"
+        )));
+        assert!(is_idempotent(&request(
+            reqwest::Method::POST,
+            "/databases/abc/query"
+        )));
+        assert!(!is_idempotent(&request(
+            reqwest::Method::PATCH,
+            "/blocks/abc/children"
+        )));
+        assert!(!is_idempotent(&request(reqwest::Method::POST, "/pages")));
+        assert!(!is_idempotent(&request(reqwest::Method::POST, "/comments")));
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        for retries in 0..8 {
+            let base = Duration::from_millis(500) * 2u32.pow(retries.min(4));
+            let delay = backoff_delay(retries);
+            assert!(delay >= base && delay <= base.mul_f64(1.5));
+        }
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let limiter = RateLimiter::new(3.0);
+        // A burst of three goes straight through, then requests are spaced out
+        for _ in 0..3 {
+            assert!(limiter.reserve().is_zero());
+        }
+        let first = limiter.reserve();
+        let second = limiter.reserve();
+        assert!(first > Duration::from_millis(300) && first <= Duration::from_millis(334));
+        assert!(second > first + Duration::from_millis(300));
+
+        let unlimited = RateLimiter::new(0.0);
+        for _ in 0..10 {
+            assert!(unlimited.reserve().is_zero());
+        }
+    }
+
+    #[test]
+    fn test_build_icon() {
+        assert_eq!(build_icon("🚀").unwrap()["emoji"], "🚀");
+        assert_eq!(build_icon("1️⃣").unwrap()["type"], "emoji");
+        let icon = build_icon("https://example.com/icon.png").unwrap();
+        assert_eq!(icon["external"]["url"], "https://example.com/icon.png");
+        assert!(build_icon("rocket").is_err());
+        assert!(build_icon("").is_err());
+    }
+
+    #[test]
+    fn test_network_options_rejects_bad_settings() {
+        let options = |proxy: Option<&str>, ca_bundle: Option<&Path>| NetworkOptions {
+            proxy: proxy.map(String::from),
+            ca_bundle: ca_bundle.map(Path::to_path_buf),
+        };
+        assert!(options(None, None).client_builder().is_ok());
+
+        let missing = std::env::temp_dir().join("notion-cli-missing-ca.pem");
+        let err = options(None, Some(&missing)).client_builder().unwrap_err();
+        assert!(err.to_string().contains("Failed to read CA bundle"));
+
+        let not_pem =
+            std::env::temp_dir().join(format!("notion-cli-ca-{}.pem", std::process::id()));
+        std::fs::write(&not_pem, "not a certificate").unwrap();
+        assert!(options(None, Some(&not_pem)).client_builder().is_err());
+        std::fs::remove_file(&not_pem).unwrap();
     }
 }
@@ -0,0 +1,540 @@
+//! Web page clipping for `clip`.
+//!
+//! [`extract`] picks the readable part of an HTML document (an `<article>`
+//! or `<main>` element, else the element holding the most paragraph text)
+//! and converts it to Notion blocks: headings, paragraphs with inline
+//! formatting and links, lists, quotes, code, images, tables and dividers.
+//! Navigation, sidebars, scripts and forms are dropped.
+
+use reqwest::Url;
+use scraper::{ElementRef, Html, Node, Selector};
+use serde_json::Value;
+
+use crate::blocks::{self, MAX_RICH_TEXT_ITEMS, MAX_TEXT_LENGTH};
+use crate::client::RichTextSegment;
+
+/// Elements that are never part of the article
+const SKIPPED: &[&str] = &[
+    "script", "style", "noscript", "template", "nav", "header", "footer", "aside", "form",
+    "button", "input", "select", "textarea", "svg", "iframe", "dialog",
+];
+
+/// Elements that start a new block; anything else is inline
+const BLOCK_LEVEL: &[&str] = &[
+    "address",
+    "article",
+    "blockquote",
+    "dd",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "li",
+    "main",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "ul",
+];
+
+/// The readable content of a web page
+#[derive(Debug)]
+pub struct Article {
+    pub title: String,
+    pub blocks: Vec<Value>,
+}
+
+/// Extract the article from `html`. Relative links and image sources are
+/// resolved against `base`; without one they are dropped.
+///
+/// ```
+/// use notion_cli_tool::clip::extract;
+///
+/// let html = r#"<html><head><title>Post</title></head><body>
+///   <nav>Home | About</nav>
+///   <article><h2>Intro</h2><p>Hello <b>world</b></p></article>
+/// </body></html>"#;
+/// let article = extract(html, None);
+/// assert_eq!(article.title, "Post");
+/// assert_eq!(article.blocks[0]["type"], "heading_2");
+/// assert_eq!(article.blocks[1]["paragraph"]["rich_text"][1]["annotations"]["bold"], true);
+/// ```
+pub fn extract(html: &str, base: Option<&Url>) -> Article {
+    let document = Html::parse_document(html);
+    let title = page_title(&document);
+
+    let mut converter = Converter {
+        base,
+        title: &title,
+        blocks: Vec::new(),
+        inline: Vec::new(),
+        images: Vec::new(),
+    };
+    if let Some(root) = content_root(&document) {
+        converter.children(root);
+        converter.flush();
+    }
+    let blocks = converter.blocks;
+
+    Article { title, blocks }
+}
+
+fn selector(css: &str) -> Selector {
+    Selector::parse(css).expect("valid selector")
+}
+
+/// `og:title`, else `<title>`, else the first `<h1>`
+fn page_title(document: &Html) -> String {
+    let og = document
+        .select(&selector(r#"meta[property="og:title"]"#))
+        .find_map(|m| m.attr("content"))
+        .map(String::from);
+    let text = |css| {
+        document
+            .select(&selector(css))
+            .next()
+            .map(|e| collapse_whitespace(&e.text().collect::<String>()))
+    };
+    og.or_else(|| text("title"))
+        .or_else(|| text("h1"))
+        .map(|t| t.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// The element holding the article
+fn content_root(document: &Html) -> Option<ElementRef<'_>> {
+    let paragraph_text = |e: &ElementRef| -> usize {
+        e.select(&selector("p"))
+            .map(|p| p.text().map(str::len).sum::<usize>())
+            .sum()
+    };
+
+    let marked = document
+        .select(&selector(r#"article, main, [role="main"]"#))
+        .max_by_key(paragraph_text);
+    if let Some(root) = marked.filter(|e| paragraph_text(e) > 0) {
+        return Some(root);
+    }
+
+    // The container with the most text in its own paragraphs
+    let densest = document
+        .select(&selector("div, section, td"))
+        .map(|e| {
+            let own: usize = e
+                .child_elements()
+                .filter(|c| c.value().name() == "p")
+                .map(|p| p.text().map(str::len).sum::<usize>())
+                .sum();
+            (own, e)
+        })
+        .max_by_key(|(own, _)| *own)
+        .filter(|(own, _)| *own > 0)
+        .map(|(_, e)| e);
+    densest.or_else(|| document.select(&selector("body")).next())
+}
+
+/// Text formatting in effect while converting inline content
+#[derive(Clone, Default)]
+struct Style {
+    bold: bool,
+    italic: bool,
+    code: bool,
+    strikethrough: bool,
+    link: Option<String>,
+}
+
+struct Converter<'a> {
+    base: Option<&'a Url>,
+    title: &'a str,
+    blocks: Vec<Value>,
+    /// Inline content waiting to become a paragraph
+    inline: Vec<RichTextSegment>,
+    /// Images found inside inline content, added after its paragraph
+    images: Vec<Value>,
+}
+
+impl Converter<'_> {
+    fn children(&mut self, element: ElementRef) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => self.text(text, &Style::default()),
+                Node::Element(_) => {
+                    let Some(child) = ElementRef::wrap(child) else {
+                        continue;
+                    };
+                    if BLOCK_LEVEL.contains(&child.value().name()) {
+                        self.flush();
+                        self.block(child);
+                    } else {
+                        self.inline(child, &Style::default());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn block(&mut self, element: ElementRef) {
+        let name = element.value().name();
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let text = collapse_whitespace(&element.text().collect::<String>());
+                let text = text.trim();
+                // The page title usually repeats as the first heading
+                if !text.is_empty() && text != self.title {
+                    let level = match name {
+                        "h1" => 1,
+                        "h2" => 2,
+                        _ => 3,
+                    };
+                    self.blocks.push(blocks::heading(level, text));
+                }
+            }
+            "p" | "dt" | "dd" | "figcaption" | "address" => {
+                self.inline(element, &Style::default());
+                self.flush();
+            }
+            "pre" => {
+                let code: String = element.text().collect();
+                let code = code.trim_end_matches('\n');
+                if !code.trim().is_empty() {
+                    self.blocks.push(blocks::code(code, code_language(element)));
+                }
+            }
+            "blockquote" => {
+                // Nested paragraphs run together into one quote
+                self.inline(element, &Style::default());
+                for segments in rich_text_chunks(self.take_inline()) {
+                    self.blocks.push(blocks::rich_block("quote", &segments));
+                }
+                self.blocks.append(&mut self.images);
+            }
+            "ul" | "ol" => self.list(element, name == "ol"),
+            "hr" => self.blocks.push(blocks::divider()),
+            "figure" => {
+                let caption = element
+                    .select(&selector("figcaption"))
+                    .next()
+                    .map(|c| collapse_whitespace(&c.text().collect::<String>()))
+                    .filter(|c| !c.trim().is_empty());
+                for img in element.select(&selector("img")) {
+                    if let Some(block) = self.image(img, caption.as_deref()) {
+                        self.blocks.push(block);
+                    }
+                }
+            }
+            "table" => self.table(element),
+            _ if SKIPPED.contains(&name) => {}
+            _ => self.children(element),
+        }
+    }
+
+    /// One list item block per `<li>`; nested lists follow their item
+    fn list(&mut self, element: ElementRef, numbered: bool) {
+        let block_type = if numbered {
+            "numbered_list_item"
+        } else {
+            "bulleted_list_item"
+        };
+        for item in element
+            .child_elements()
+            .filter(|e| e.value().name() == "li")
+        {
+            let mut nested = Vec::new();
+            for child in item.children() {
+                match child.value() {
+                    Node::Text(text) => self.text(text, &Style::default()),
+                    Node::Element(e) if matches!(e.name(), "ul" | "ol") => nested.push(child),
+                    Node::Element(_) => {
+                        if let Some(child) = ElementRef::wrap(child) {
+                            self.inline(child, &Style::default());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            for segments in rich_text_chunks(self.take_inline()) {
+                self.blocks.push(blocks::rich_block(block_type, &segments));
+            }
+            self.blocks.append(&mut self.images);
+            for list in nested.into_iter().filter_map(ElementRef::wrap) {
+                self.list(list, list.value().name() == "ol");
+            }
+        }
+    }
+
+    fn table(&mut self, element: ElementRef) {
+        let rows: Vec<Vec<String>> = element
+            .select(&selector("tr"))
+            .map(|row| {
+                row.child_elements()
+                    .filter(|c| matches!(c.value().name(), "td" | "th"))
+                    .map(|c| {
+                        collapse_whitespace(&c.text().collect::<String>())
+                            .trim()
+                            .to_string()
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .filter(|row| !row.is_empty())
+            .collect();
+        let Some(width) = rows.iter().map(Vec::len).max() else {
+            return;
+        };
+        let rows: Vec<Vec<String>> = rows
+            .into_iter()
+            .map(|mut row| {
+                row.resize(width, String::new());
+                row
+            })
+            .collect();
+        let header = element.select(&selector("th")).next().is_some();
+        self.blocks.push(blocks::table(&rows, header));
+    }
+
+    fn inline(&mut self, element: ElementRef, style: &Style) {
+        let mut style = style.clone();
+        match element.value().name() {
+            "br" => {
+                self.push(RichTextSegment::plain("\n"), &style);
+                return;
+            }
+            "img" => {
+                if let Some(block) = self.image(element, None) {
+                    self.images.push(block);
+                }
+                return;
+            }
+            "strong" | "b" => style.bold = true,
+            "em" | "i" => style.italic = true,
+            "code" | "kbd" | "samp" => style.code = true,
+            "s" | "del" | "strike" => style.strikethrough = true,
+            "a" => {
+                style.link = element
+                    .attr("href")
+                    .and_then(|href| self.resolve(href))
+                    .filter(|u| matches!(u.scheme(), "http" | "https" | "mailto"))
+                    .map(String::from);
+            }
+            name if SKIPPED.contains(&name) => return,
+            _ => {}
+        }
+
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => self.text(text, &style),
+                Node::Element(_) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        self.inline(child, &style);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn text(&mut self, text: &str, style: &Style) {
+        let text = collapse_whitespace(text);
+        if !text.is_empty() {
+            self.push(RichTextSegment::plain(&text), style);
+        }
+    }
+
+    fn push(&mut self, mut segment: RichTextSegment, style: &Style) {
+        segment.bold = style.bold;
+        segment.italic = style.italic;
+        segment.code = style.code;
+        segment.strikethrough = style.strikethrough;
+        segment.link = style.link.clone();
+
+        // Merge with the previous segment when formatted the same
+        if let Some(last) = self.inline.last_mut() {
+            if (
+                last.bold,
+                last.italic,
+                last.code,
+                last.strikethrough,
+                &last.link,
+            ) == (
+                segment.bold,
+                segment.italic,
+                segment.code,
+                segment.strikethrough,
+                &segment.link,
+            ) {
+                let joined = last.text.ends_with(' ') && segment.text.starts_with(' ');
+                last.text.push_str(if joined {
+                    &segment.text[1..]
+                } else {
+                    &segment.text
+                });
+                return;
+            }
+        }
+        self.inline.push(segment);
+    }
+
+    /// The pending inline content, trimmed
+    fn take_inline(&mut self) -> Vec<RichTextSegment> {
+        let mut segments = std::mem::take(&mut self.inline);
+        if let Some(first) = segments.first_mut() {
+            first.text = first.text.trim_start().to_string();
+        }
+        if let Some(last) = segments.last_mut() {
+            last.text = last.text.trim_end().to_string();
+        }
+        segments.retain(|s| !s.text.is_empty());
+        segments
+    }
+
+    /// Turn pending inline content into paragraphs, followed by any images
+    /// it contained
+    fn flush(&mut self) {
+        for segments in rich_text_chunks(self.take_inline()) {
+            self.blocks.push(blocks::rich_paragraph(&segments));
+        }
+        self.blocks.append(&mut self.images);
+    }
+
+    fn image(&self, img: ElementRef, caption: Option<&str>) -> Option<Value> {
+        let src = img.attr("src").or_else(|| img.attr("data-src"))?;
+        let url = self.resolve(src)?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return None;
+        }
+        let alt = img.attr("alt").map(str::trim).filter(|a| !a.is_empty());
+        Some(blocks::image(url.as_str(), caption.or(alt)))
+    }
+
+    fn resolve(&self, href: &str) -> Option<Url> {
+        match self.base {
+            Some(base) => base.join(href).ok(),
+            None => Url::parse(href).ok(),
+        }
+    }
+}
+
+/// Split segments so that no text is longer than the API allows and no
+/// block holds too many items; each chunk becomes one block
+fn rich_text_chunks(segments: Vec<RichTextSegment>) -> Vec<Vec<RichTextSegment>> {
+    let mut split = Vec::new();
+    for segment in segments {
+        for part in blocks::split_text(&segment.text, MAX_TEXT_LENGTH) {
+            split.push(RichTextSegment {
+                text: part.to_string(),
+                ..segment.clone()
+            });
+        }
+    }
+    split
+        .chunks(MAX_RICH_TEXT_ITEMS)
+        .map(<[RichTextSegment]>::to_vec)
+        .collect()
+}
+
+/// Runs of whitespace (including newlines) as single spaces
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            space = true;
+        } else {
+            if space {
+                out.push(' ');
+                space = false;
+            }
+            out.push(c);
+        }
+    }
+    if space {
+        out.push(' ');
+    }
+    out
+}
+
+/// Notion language for a `<pre>` from its (or its `<code>`'s) `language-*`
+/// or `lang-*` class, `plain text` when unknown
+fn code_language(pre: ElementRef) -> &'static str {
+    let code = selector("code");
+    std::iter::once(pre)
+        .chain(pre.select(&code))
+        .flat_map(|e| e.value().classes().collect::<Vec<_>>())
+        .filter_map(|class| {
+            class
+                .strip_prefix("language-")
+                .or_else(|| class.strip_prefix("lang-"))
+        })
+        .find_map(blocks::code_language)
+        .unwrap_or("plain text")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_picks_article_content() {
+        let html = r#"<html><head><meta property="og:title" content="Shipping Rust"></head>
+            <body>
+              <header><a href="/">Blog</a></header>
+              <div class="post">
+                <h1>Shipping Rust</h1>
+                <p>Read the <a href="/docs">docs</a>,
+                   then <code>cargo build</code>.<img src="/a.png" alt="Diagram"></p>
+                <pre><code class="language-rs">fn main() {}</code></pre>
+                <ul><li>one<ul><li>nested</li></ul></li><li>two</li></ul>
+                <p>Closing words that make this the densest container.</p>
+              </div>
+              <aside><p>Subscribe!</p></aside>
+            </body></html>"#;
+        let base = Url::parse("https://blog.example/posts/1").unwrap();
+        let article = extract(html, Some(&base));
+
+        assert_eq!(article.title, "Shipping Rust");
+        let types: Vec<&str> = article
+            .blocks
+            .iter()
+            .map(|b| b["type"].as_str().unwrap())
+            .collect();
+        // The h1 repeating the title is dropped
+        assert_eq!(
+            types,
+            [
+                "paragraph",
+                "image",
+                "code",
+                "bulleted_list_item",
+                "bulleted_list_item",
+                "bulleted_list_item",
+                "paragraph"
+            ]
+        );
+
+        let text = &article.blocks[0]["paragraph"]["rich_text"];
+        assert_eq!(text[0]["text"]["content"], "Read the ");
+        assert_eq!(text[1]["text"]["link"]["url"], "https://blog.example/docs");
+        assert_eq!(text[3]["annotations"]["code"], true);
+        assert_eq!(text[4]["text"]["content"], ".");
+        assert_eq!(
+            article.blocks[1]["image"]["external"]["url"],
+            "https://blog.example/a.png"
+        );
+        assert_eq!(article.blocks[2]["code"]["language"], "rust");
+        assert_eq!(
+            article.blocks[4]["bulleted_list_item"]["rich_text"][0]["text"]["content"],
+            "nested"
+        );
+    }
+}
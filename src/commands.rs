@@ -1,64 +1,3323 @@
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use colored::Colorize;
+use regex::{Regex, RegexBuilder};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::cli::{DatabaseTarget, PageTarget, Pagination, QueryFilters, QueryOutput};
+
+use notion_cli_tool::batch::Operation;
+use notion_cli_tool::blocks::{self, ListItem};
+use notion_cli_tool::client::{
+    build_filter, build_schema, find_property, is_unreachable, match_columns, select_blocks,
+    update_options, FilterSpec, SchemaSpec, SortSpec,
+};
+use notion_cli_tool::clip;
+use notion_cli_tool::dedupe::{find_duplicates, merge_properties};
+use notion_cli_tool::diff::{diff, Change, Snapshot};
+use notion_cli_tool::edit::{self, EditOp};
+use notion_cli_tool::export::{self, asset_file_name, asset_urls};
+use notion_cli_tool::git::{self, Commit};
+use notion_cli_tool::markdown::{self, MarkdownBlock};
+use notion_cli_tool::models::{Block, BlockKind, Page, PageNode, PropertyValue, RichText};
+use notion_cli_tool::progress;
+use notion_cli_tool::render::{
+    blocks_to_text, expand_tables_of_contents, extract_property_value, extract_title,
+    format_block_tree, format_page_tree, format_properties, format_table, print_blocks,
+    print_schema,
+};
+#[cfg(unix)]
+use notion_cli_tool::server;
+use notion_cli_tool::stats::{format_number, group_stats};
+use notion_cli_tool::sync::{self, FrontMatter, PushAction, PushState, PushedPage};
+use notion_cli_tool::todo::{parse_index, TaskFields};
+use notion_cli_tool::utils::{
+    clear_page_cache, load_id_cache, load_queue, load_snapshot, load_todo_list, normalize_page_id,
+    push_queue, save_id_cache, save_queue, save_snapshot, save_todo_list, DEFAULT_API_VERSION,
+    KNOWN_API_VERSIONS,
+};
+use notion_cli_tool::{dates, NotionClient, PropertySpec, RichTextSegment};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Hide the lines that only narrate what a command is doing (`--quiet`)
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// `println!` for a line narrating what the command is doing ("Appending
+/// to: ..."), skipped with `--quiet`
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !QUIET.load(Ordering::Relaxed) {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Resolve a page argument: the ID/URL as given, or a `--name` title lookup
+pub async fn resolve_page(client: &NotionClient, target: &PageTarget) -> Result<String> {
+    if target.name {
+        resolve_by_name(client, &target.page_id, "page").await
+    } else {
+        Ok(target.page_id.clone())
+    }
+}
+
+/// Resolve a database argument: the ID/URL as given, or a `--name` title lookup
+pub async fn resolve_database(client: &NotionClient, target: &DatabaseTarget) -> Result<String> {
+    if target.name {
+        resolve_by_name(client, &target.database_id, "database").await
+    } else {
+        Ok(target.database_id.clone())
+    }
+}
+
+/// Look up an ID by title, using the local ID cache before searching the
+/// workspace. Ambiguous matches are offered as a numbered choice when stdin
+/// is a terminal.
+async fn resolve_by_name(client: &NotionClient, name: &str, object: &str) -> Result<String> {
+    let mut cache = load_id_cache();
+
+    let (id, title) = match cache.get(object, name) {
+        Some(entry) => (entry.id.clone(), entry.title.clone()),
+        None => {
+            let matches = client.find_by_title(name, object).await?;
+            let is_exact = |t: &str| t.to_lowercase() == name.to_lowercase();
+
+            match matches.as_slice() {
+                [] => bail!("No {} found matching \"{}\"", object, name),
+                [only] => only.clone(),
+                [first, second, ..] if is_exact(&first.1) && !is_exact(&second.1) => first.clone(),
+                _ => choose_match(name, object, &matches)?,
+            }
+        }
+    };
+
+    cache.insert(object, name, &title, &id);
+    if let Err(e) = save_id_cache(&cache) {
+        eprintln!("{} Could not update ID cache: {}", "⚠".yellow(), e);
+    }
+
+    Ok(id)
+}
+
+fn choose_match(
+    name: &str,
+    object: &str,
+    matches: &[(String, String)],
+) -> Result<(String, String)> {
+    let listing: Vec<String> = matches
+        .iter()
+        .enumerate()
+        .map(|(i, (id, title))| format!("  {}. {} ({})", i + 1, title, id))
+        .collect();
+
+    if !io::stdin().is_terminal() {
+        bail!(
+            "Multiple {}s match \"{}\"; use an ID instead:\n{}",
+            object,
+            name,
+            listing.join("\n")
+        );
+    }
+
+    println!("{} Multiple {}s match \"{}\":", "⚠".yellow(), object, name);
+    for line in &listing {
+        println!("{}", line);
+    }
+    print!("{} Choose [1-{}]: ", "→".blue(), matches.len());
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let choice: usize = input
+        .trim()
+        .parse()
+        .ok()
+        .filter(|n| (1..=matches.len()).contains(n))
+        .with_context(|| format!("Invalid choice '{}'", input.trim()))?;
+
+    Ok(matches[choice - 1].clone())
+}
+
+/// Ask a yes/no question before a destructive action; `yes` skips the prompt.
+/// Refuses to guess when stdin is not a terminal.
+fn confirm(question: &str, yes: bool) -> Result<bool> {
+    if yes {
+        return Ok(true);
+    }
+    if !io::stdin().is_terminal() {
+        bail!(
+            "{} Pass --yes to confirm when not running interactively",
+            question
+        );
+    }
+
+    print!("{} {} [y/N]: ", "⚠".yellow(), question);
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let answer = input.trim().to_lowercase();
+    if answer == "y" || answer == "yes" {
+        Ok(true)
+    } else {
+        println!("Cancelled");
+        Ok(false)
+    }
+}
+
+/// Delete the cached page content used by `read` and `grep`
+pub fn handle_cache_clear() -> Result<()> {
+    let count = clear_page_cache()?;
+    println!("{} Removed {} cached page(s)", "✓".green(), count);
+    Ok(())
+}
+
+/// Print an API response as pretty JSON (for manual pagination)
+pub fn print_json(value: &serde_json::Value) -> Result<()> {
+    let json = serde_json::to_string_pretty(value).context("Failed to serialize response")?;
+    println!("{}", json);
+    Ok(())
+}
+
+/// Write one object as a line of NDJSON to stdout
+fn write_ndjson(item: &serde_json::Value) -> Result<()> {
+    let line = serde_json::to_string(item).context("Failed to serialize result")?;
+    writeln!(io::stdout(), "{}", line).context("Failed to write output")?;
+    Ok(())
+}
+
+/// Write the ID of one object as a line to stdout (`--id-only`)
+fn write_id(item: &serde_json::Value) -> Result<()> {
+    writeln!(io::stdout(), "{}", item["id"].as_str().unwrap_or_default())
+        .context("Failed to write output")
+}
+
+/// One-line description of a block for confirmation prompts
+fn describe_block(block: &Block) -> String {
+    let text = block.text();
+    let line = text.lines().next().unwrap_or_default();
+    if line.is_empty() {
+        return block.block_type.clone();
+    }
+
+    format!("{} \"{}\"", block.block_type, truncate(line, 60))
+}
+
+/// At most `max` characters of `text`, with an ellipsis when cut
+fn truncate(text: &str, max: usize) -> String {
+    let mut snippet: String = text.chars().take(max).collect();
+    if snippet.len() < text.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_search(
+    client: &NotionClient,
+    query: &str,
+    object: Option<&str>,
+    sort: Option<&str>,
+    direction: &str,
+    limit: usize,
+    ndjson: bool,
+    id_only: bool,
+) -> Result<()> {
+    if ndjson || id_only {
+        client
+            .search_each(query, object, sort, direction, limit, |item| {
+                if id_only {
+                    write_id(&item)
+                } else {
+                    write_ndjson(&item)
+                }
+            })
+            .await?;
+        return Ok(());
+    }
+
+    status!("{} \"{}\"", "Searching:".blue(), query);
+
+    let results = client
+        .search_filtered(query, object, sort, direction, limit)
+        .await?;
+    status!("{} {} results found\n", "✓".green(), results.len());
+
+    for item in &results {
+        let object_type = item
+            .get("object")
+            .and_then(|o| o.as_str())
+            .unwrap_or("unknown");
+        let id = item.get("id").and_then(|i| i.as_str()).unwrap_or("no-id");
+        let title = extract_title(item);
+
+        println!("  {} [{}] {}", "•".cyan(), object_type, title);
+        println!("    ID: {}", id.dimmed());
+    }
+
+    Ok(())
+}
+
+/// Search block content with a regex, in one page or in the most recently
+/// edited pages the integration can see
+pub async fn handle_grep(
+    client: &NotionClient,
+    pattern: &str,
+    page: Option<&str>,
+    ignore_case: bool,
+    limit: usize,
+) -> Result<()> {
+    let regex = RegexBuilder::new(pattern)
+        .case_insensitive(ignore_case)
+        .build()
+        .with_context(|| format!("Invalid pattern '{}'", pattern))?;
+
+    let pages: Vec<serde_json::Value> = match page {
+        Some(id) => vec![client.get_page(id).await?],
+        None => {
+            client
+                .search_filtered("", Some("page"), Some("last_edited_time"), "desc", limit)
+                .await?
+        }
+    };
+    status!(
+        "{} /{}/ in {} page(s)",
+        "Searching:".blue(),
+        pattern,
+        pages.len()
+    );
+
+    let (mut matches, mut matched_pages) = (0, 0);
+    for page in &pages {
+        let page_id = page["id"].as_str().unwrap_or_default();
+        let title = extract_title(page);
+        let blocks: Vec<Block> = match client.get_page_blocks(page, None).await {
+            Ok(blocks) => blocks
+                .into_iter()
+                .map(serde_json::from_value)
+                .collect::<Result<_, _>>()?,
+            Err(e) => {
+                eprintln!("{} Skipping \"{}\": {:#}", "⚠".yellow(), title, e);
+                continue;
+            }
+        };
+
+        let mut found = Vec::new();
+        grep_blocks(&blocks, &regex, &mut found);
+        if found.is_empty() {
+            continue;
+        }
+
+        println!("\n{} {}", title.bold(), page_id.dimmed());
+        for (block_id, line) in &found {
+            println!("  {}  {}", block_id.dimmed(), highlight(line, &regex));
+        }
+        matches += found.len();
+        matched_pages += 1;
+    }
+
+    println!(
+        "\n{} {} matching line(s) in {} page(s)",
+        "✓".green(),
+        matches,
+        matched_pages
+    );
+    Ok(())
+}
+
+/// Collect `(block ID, line)` for every line of block text matching `regex`
+fn grep_blocks(blocks: &[Block], regex: &Regex, found: &mut Vec<(String, String)>) {
+    for block in blocks {
+        for line in block.text().lines() {
+            if regex.is_match(line) {
+                found.push((block.id.clone(), line.to_string()));
+            }
+        }
+        grep_blocks(&block.children, regex, found);
+    }
+}
+
+/// Emphasize every match of `regex` in `line`
+fn highlight(line: &str, regex: &Regex) -> String {
+    let mut out = String::new();
+    let mut last = 0;
+    for m in regex.find_iter(line) {
+        out.push_str(&line[last..m.start()]);
+        out.push_str(&m.as_str().red().bold().to_string());
+        last = m.end();
+    }
+    out.push_str(&line[last..]);
+    out
+}
+
+/// List the children of a page or block (nested ones too with `recursive`),
+/// one per line with its ID, keeping those of the given types whose text
+/// contains `contains`
+pub async fn handle_blocks(
+    client: &NotionClient,
+    page_id: &str,
+    recursive: bool,
+    types: &[String],
+    contains: Option<&str>,
+    id_only: bool,
+) -> Result<()> {
+    status!("{} {}", "Listing blocks:".blue(), page_id);
+
+    let blocks = if recursive {
+        client.get_blocks_recursive(page_id, None).await?
+    } else {
+        client.get_blocks(page_id).await?
+    };
+    let blocks: Vec<Block> = blocks
+        .into_iter()
+        .map(serde_json::from_value)
+        .collect::<Result<_, _>>()?;
+
+    let mut listed = Vec::new();
+    flatten_blocks(&blocks, 0, &mut listed);
+    let needle = contains.map(str::to_lowercase);
+    listed.retain(|(_, block)| {
+        (types.is_empty() || types.contains(&block.block_type))
+            && needle
+                .as_ref()
+                .is_none_or(|n| block.text().to_lowercase().contains(n))
+    });
+
+    if id_only {
+        for (_, block) in &listed {
+            println!("{}", block.id);
+        }
+        return Ok(());
+    }
+    status!("{} {} blocks found\n", "✓".green(), listed.len());
+
+    for (depth, block) in &listed {
+        // Nested blocks are listed already when recursive
+        let children = if block.has_children && !recursive {
+            " +"
+        } else {
+            ""
+        };
+        println!(
+            "  {}{} {}{}  {}",
+            "  ".repeat(*depth),
+            "•".cyan(),
+            describe_block(block),
+            children,
+            block.id.dimmed()
+        );
+    }
+
+    Ok(())
+}
+
+/// Blocks in reading order, each with its nesting depth
+fn flatten_blocks<'a>(blocks: &'a [Block], depth: usize, out: &mut Vec<(usize, &'a Block)>) {
+    for block in blocks {
+        out.push((depth, block));
+        flatten_blocks(&block.children, depth + 1, out);
+    }
+}
+
+pub async fn handle_read(
+    client: &NotionClient,
+    page_id: &str,
+    depth: Option<usize>,
+    resolve_mentions: bool,
+    follow: bool,
+    resolve_toc: bool,
+    copy: bool,
+) -> Result<()> {
+    status!("{} {}", "Reading page:".blue(), page_id);
+
+    let (page, blocks) = client.get_page_content(page_id, depth).await?;
+    let mut blocks: Vec<Block> = blocks
+        .into_iter()
+        .map(serde_json::from_value)
+        .collect::<Result<_, _>>()?;
+
+    if follow {
+        follow_child_pages(client, &mut blocks, depth).await?;
+    }
+    if resolve_mentions {
+        fill_mentions(client, &mut blocks).await;
+    }
+    if resolve_toc {
+        expand_tables_of_contents(&mut blocks);
+    }
+
+    let title = extract_title(&page);
+    println!("\n{} {}\n", "Title:".green(), title);
+
+    // Database rows: their property values, computed ones included
+    let properties = format_properties(&page["properties"]);
+    if !properties.is_empty() {
+        for line in &properties {
+            println!("  {}", line);
+        }
+        println!();
+    }
+
+    print_blocks(&blocks, 0);
+
+    if copy {
+        let text = format!("{}\n\n{}", title, blocks_to_text(&blocks));
+        copy_to_clipboard(&text)?;
+        println!(
+            "\n{} Copied {} characters to the clipboard",
+            "✓".green(),
+            text.chars().count()
+        );
+    }
+
+    Ok(())
+}
+
+/// Fetch the content of every sub-page in a block tree (and theirs, in turn)
+/// as the children of its child_page block
+async fn follow_child_pages(
+    client: &NotionClient,
+    blocks: &mut [Block],
+    depth: Option<usize>,
+) -> Result<()> {
+    for block in blocks {
+        if matches!(block.kind, BlockKind::ChildPage(_)) {
+            block.children = client
+                .get_blocks_recursive(&block.id, depth)
+                .await?
+                .into_iter()
+                .map(serde_json::from_value)
+                .collect::<Result<_, _>>()?;
+        }
+        Box::pin(follow_child_pages(client, &mut block.children, depth)).await?;
+    }
+    Ok(())
+}
+
+/// Call `f` on every mention in a block tree
+fn for_each_mention(blocks: &mut [Block], f: &mut impl FnMut(&mut RichText)) {
+    for block in blocks {
+        for item in block.rich_text_mut() {
+            if item.mention.is_some() {
+                f(item);
+            }
+        }
+        for_each_mention(&mut block.children, f);
+    }
+}
+
+/// Replace the text of page, database and user mentions with current titles
+/// and names. Objects the integration can't read keep their original text.
+async fn fill_mentions(client: &NotionClient, blocks: &mut [Block]) {
+    let mut objects = BTreeSet::new();
+    let mut users = BTreeSet::new();
+    for_each_mention(blocks, &mut |item| {
+        let Some(mention) = &item.mention else {
+            return;
+        };
+        match (mention.kind.as_str(), mention.object_id(), &mention.user) {
+            ("page" | "database", Some(id), _) => {
+                objects.insert((mention.kind.clone(), id.to_string()));
+            }
+            ("user", _, Some(user)) => {
+                users.insert(user.id.clone());
+            }
+            _ => {}
+        }
+    });
+
+    let mut titles = HashMap::new();
+    for (kind, id) in objects {
+        let object = match kind.as_str() {
+            "page" => client.get_page(&id).await,
+            _ => client.get_database(&id).await,
+        };
+        if let Ok(object) = object {
+            titles.insert(id, extract_title(&object));
+        }
+    }
+    let mut names = HashMap::new();
+    for id in users {
+        if let Ok(user) = client.get_user(&id).await {
+            if let Some(name) = user["name"].as_str() {
+                names.insert(id, name.to_string());
+            }
+        }
+    }
+
+    for_each_mention(blocks, &mut |item| {
+        let Some(mention) = &mut item.mention else {
+            return;
+        };
+        if let Some(title) = mention.object_id().and_then(|id| titles.get(id)) {
+            item.plain_text = title.clone();
+        }
+        if let Some(user) = &mut mention.user {
+            if let Some(name) = names.get(&user.id) {
+                user.name = name.clone();
+            }
+        }
+    });
+}
+
+/// Open a page (by ID, URL or title) in the default browser, or print its URL
+pub async fn handle_open(client: &NotionClient, page: &str, print: bool) -> Result<()> {
+    let page_id = match normalize_page_id(page) {
+        Ok(id) => id,
+        Err(_) => resolve_by_name(client, page, "page").await?,
+    };
+    let page = client.get_page(&page_id).await?;
+    let url = page["url"].as_str().context("Page has no URL")?;
+
+    if print {
+        println!("{}", url);
+        return Ok(());
+    }
+
+    status!("{} {}", "Opening:".blue(), url);
+    open_in_browser(url)
+}
+
+pub async fn handle_info(client: &NotionClient, page_id: &str) -> Result<()> {
+    let page = client.get_page(page_id).await?;
+    let field = |key: &str| page[key].as_str().unwrap_or("-").to_string();
+
+    let creator = page["created_by"]["id"].as_str().unwrap_or_default();
+    let editor = page["last_edited_by"]["id"].as_str().unwrap_or_default();
+    let created_by = user_name(client, creator).await;
+    let edited_by = if editor == creator {
+        created_by.clone()
+    } else {
+        user_name(client, editor).await
+    };
+
+    let mut parents = parent_chain(client, &page["parent"]).await;
+    parents.reverse();
+
+    println!("{} {}", "Title:".blue(), extract_title(&page));
+    println!("{} {}", "ID:".blue(), field("id"));
+    println!("{} {}", "URL:".blue(), field("url"));
+    println!("{} {}", "Parents:".blue(), parents.join(" › "));
+    println!(
+        "{} {} by {}",
+        "Created:".blue(),
+        dates::format_date(&field("created_time")),
+        created_by
+    );
+    println!(
+        "{} {} by {}",
+        "Last edited:".blue(),
+        dates::format_date(&field("last_edited_time")),
+        edited_by
+    );
+    println!("{} {}", "Icon:".blue(), describe_file(&page["icon"]));
+    println!("{} {}", "Cover:".blue(), describe_file(&page["cover"]));
+    println!(
+        "{} {}",
+        "Archived:".blue(),
+        page["archived"].as_bool().unwrap_or(false) || page["in_trash"].as_bool().unwrap_or(false)
+    );
+    println!(
+        "{} {}",
+        "Properties:".blue(),
+        page["properties"].as_object().map_or(0, |p| p.len())
+    );
+    for line in format_properties(&page["properties"]) {
+        println!("  {}", line);
+    }
+
+    Ok(())
+}
+
+/// A user's name, or their ID when it can't be read (that needs the
+/// integration's "user information" capability)
+async fn user_name(client: &NotionClient, user_id: &str) -> String {
+    match client.get_user(user_id).await {
+        Ok(user) => user["name"].as_str().unwrap_or(user_id).to_string(),
+        Err(_) => user_id.to_string(),
+    }
+}
+
+/// Titles of a page's ancestors, nearest first, ending at the workspace or
+/// the first ancestor the integration can't read
+async fn parent_chain(client: &NotionClient, parent: &serde_json::Value) -> Vec<String> {
+    let mut chain = Vec::new();
+    let mut parent = parent.clone();
+
+    // Guard against cycles in malformed responses
+    for _ in 0..32 {
+        let parent_type = parent["type"].as_str().unwrap_or_default().to_string();
+        let id = parent[&parent_type]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let object = match parent_type.as_str() {
+            "workspace" => {
+                chain.push("Workspace".to_string());
+                break;
+            }
+            "page_id" => client.get_page(&id).await,
+            "database_id" => client.get_database(&id).await,
+            "data_source_id" => client.get_data_source(&id).await,
+            // Pages inside blocks (columns, toggles) inherit the block's parent
+            "block_id" => match client.get_block(&id).await {
+                Ok(block) => {
+                    parent = block["parent"].clone();
+                    continue;
+                }
+                Err(e) => Err(e),
+            },
+            _ => break,
+        };
+
+        match object {
+            Ok(object) => {
+                // A database and its data source share a title
+                if parent_type != "data_source_id" {
+                    chain.push(extract_title(&object));
+                }
+                parent = object["parent"].clone();
+            }
+            Err(_) => {
+                chain.push(format!("{} (not shared with the integration)", id));
+                break;
+            }
+        }
+    }
+
+    chain
+}
+
+/// An icon or cover: the emoji itself, or the file's URL
+fn describe_file(value: &serde_json::Value) -> String {
+    match value["type"].as_str() {
+        Some("emoji") => value["emoji"].as_str().unwrap_or_default().to_string(),
+        Some("custom_emoji") => value["custom_emoji"]["name"]
+            .as_str()
+            .map(|n| format!(":{}:", n))
+            .unwrap_or_default(),
+        Some(kind) => value[kind]["url"].as_str().unwrap_or(kind).to_string(),
+        None => "-".to_string(),
+    }
+}
+
+/// Hand a URL to the platform's opener (`open`, `xdg-open` or `start`)
+pub fn open_in_browser(url: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut c = Command::new("cmd");
+        c.args(["/C", "start", ""]);
+        c
+    } else {
+        Command::new("xdg-open")
+    };
+    let status = command
+        .arg(url)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .context("Failed to start a browser (use --print to get the URL)")?;
+    if !status.success() {
+        bail!(
+            "Browser opener exited with {} (use --print to get the URL)",
+            status
+        );
+    }
+    Ok(())
+}
+
+/// Text from an argument, `-` (stdin) or a `--file` path
+fn read_content(arg: Option<&str>, file: Option<&Path>) -> Result<Option<String>> {
+    match (arg, file) {
+        (_, Some(path)) => fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))
+            .map(Some),
+        (Some("-"), None) => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .context("Failed to read stdin")?;
+            Ok(Some(input))
+        }
+        (Some(text), None) => Ok(Some(text.to_string())),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Where `create` puts the new page
+pub enum NewPageParent {
+    Page(String),
+    /// As a row of this database
+    Database(String),
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_create(
+    client: &NotionClient,
+    parent: &NewPageParent,
+    props: &[String],
+    title: &str,
+    content: Option<&str>,
+    file: Option<&Path>,
+    icon: Option<&str>,
+    offline: bool,
+    id_only: bool,
+) -> Result<()> {
+    let content = read_content(content, file)?;
+    // Catch malformed properties now rather than when a queued change is sent
+    for prop in props {
+        PropertySpec::parse(prop)?;
+    }
+    let op = match parent {
+        NewPageParent::Page(parent) => Operation::Create {
+            parent: parent.clone(),
+            title: title.to_string(),
+            content: content.clone(),
+            icon: icon.map(String::from),
+        },
+        NewPageParent::Database(database) => Operation::DbCreate {
+            database: database.clone(),
+            title: title.to_string(),
+            properties: props.to_vec(),
+            content: content.clone(),
+            icon: icon.map(String::from),
+        },
+    };
+    let send = create_page(client, parent, props, title, content, icon, id_only);
+    // A queued page has no ID to print, so `--id-only` fails rather than queues
+    if id_only {
+        return send.await;
+    }
+    send_or_queue(offline, op, send).await
+}
+
+async fn create_page(
+    client: &NotionClient,
+    parent: &NewPageParent,
+    props: &[String],
+    title: &str,
+    content: Option<String>,
+    icon: Option<&str>,
+    id_only: bool,
+) -> Result<()> {
+    status!("{} \"{}\"", "Creating page:".blue(), title);
+
+    let result = match parent {
+        NewPageParent::Page(parent) => {
+            client
+                .create_page(parent, title, content.as_deref(), icon)
+                .await?
+        }
+        NewPageParent::Database(database) => {
+            let specs = props
+                .iter()
+                .map(|p| PropertySpec::parse(p))
+                .collect::<Result<Vec<_>>>()?;
+            client
+                .create_database_page(database, title, &specs, content.as_deref(), icon)
+                .await?
+        }
+    };
+
+    let id = result
+        .get("id")
+        .and_then(|i| i.as_str())
+        .unwrap_or("unknown");
+    if id_only {
+        println!("{}", id);
+        return Ok(());
+    }
+    let url = result.get("url").and_then(|u| u.as_str());
+
+    println!("{} Page created!", "✓".green());
+    println!("  ID: {}", id);
+    if let Some(u) = url {
+        println!("  URL: {}", u);
+    }
+
+    Ok(())
+}
+
+pub async fn handle_db_create(
+    client: &NotionClient,
+    database_id: &str,
+    title: &str,
+    props: &[String],
+) -> Result<()> {
+    let specs = props
+        .iter()
+        .map(|p| PropertySpec::parse(p))
+        .collect::<Result<Vec<_>>>()?;
+
+    status!("{} \"{}\"", "Creating database row:".blue(), title);
+    for spec in &specs {
+        status!("  {} ({}): {}", spec.name, spec.prop_type, spec.value);
+    }
+
+    let result = client
+        .create_database_page(database_id, title, &specs, None, None)
+        .await?;
+
+    let id = result
+        .get("id")
+        .and_then(|i| i.as_str())
+        .unwrap_or("unknown");
+    let url = result.get("url").and_then(|u| u.as_str());
+
+    println!("{} Row created!", "✓".green());
+    println!("  ID: {}", id);
+    if let Some(u) = url {
+        println!("  URL: {}", u);
+    }
+
+    Ok(())
+}
+
+pub async fn handle_db_new(
+    client: &NotionClient,
+    parent: &str,
+    title: &str,
+    schema_file: Option<&Path>,
+    props: &[String],
+) -> Result<()> {
+    let base = match schema_file {
+        Some(path) => {
+            let content = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read schema file {}", path.display()))?;
+            Some(serde_json::from_str(&content).context("Schema file is not valid JSON")?)
+        }
+        None => None,
+    };
+    let specs = props
+        .iter()
+        .map(|p| SchemaSpec::parse(p))
+        .collect::<Result<Vec<_>>>()?;
+    let properties = build_schema(&specs, base)?;
+
+    status!("{} \"{}\"", "Creating database:".blue(), title);
+    if let Some(props) = properties.as_object() {
+        for (name, definition) in props {
+            let prop_type = definition
+                .as_object()
+                .and_then(|d| d.keys().find(|k| *k != "name" && *k != "description"))
+                .map(String::as_str)
+                .unwrap_or("unknown");
+            status!("  {} ({})", name, prop_type);
+        }
+    }
+
+    let result = client.create_database(parent, title, properties).await?;
+
+    let id = result
+        .get("id")
+        .and_then(|i| i.as_str())
+        .unwrap_or("unknown");
+    let url = result.get("url").and_then(|u| u.as_str());
+
+    println!("{} Database created!", "✓".green());
+    println!("  ID: {}", id);
+    if let Some(u) = url {
+        println!("  URL: {}", u);
+    }
+
+    Ok(())
+}
+
+pub async fn handle_db_prop_add(
+    client: &NotionClient,
+    database_id: &str,
+    spec: &str,
+    options: &[String],
+) -> Result<()> {
+    let mut spec = SchemaSpec::parse(spec)?;
+    if !options.is_empty() {
+        if !matches!(spec.prop_type.as_str(), "select" | "multi_select") {
+            bail!("--option only applies to select and multi_select properties");
+        }
+        let listed = spec.options.iter().map(String::as_str);
+        spec.options = Some(
+            listed
+                .chain(options.iter().map(String::as_str))
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+    }
+    if spec.prop_type == "title" {
+        bail!("A database has exactly one title property; rename it instead");
+    }
+    let definition = spec.to_definition()?;
+
+    let (_, schema) = client.get_database_schema(database_id).await?;
+    if let Ok((existing, _)) = find_property(&schema, &spec.name) {
+        bail!("Property '{}' already exists", existing);
+    }
+
+    client
+        .update_schema(
+            database_id,
+            serde_json::json!({ (spec.name.as_str()): definition }),
+        )
+        .await?;
+    println!(
+        "{} Added property '{}' ({})",
+        "✓".green(),
+        spec.name,
+        spec.prop_type
+    );
+
+    Ok(())
+}
+
+pub async fn handle_db_prop_rename(
+    client: &NotionClient,
+    database_id: &str,
+    property: &str,
+    new_name: &str,
+) -> Result<()> {
+    let new_name = new_name.trim();
+    if new_name.is_empty() {
+        bail!("The new property name is empty");
+    }
+    let (_, schema) = client.get_database_schema(database_id).await?;
+    let (name, _) = find_property(&schema, property)?;
+    if let Ok((existing, _)) = find_property(&schema, new_name) {
+        // Changing only the case of a name is a rename too
+        if existing != name {
+            bail!("Property '{}' already exists", existing);
+        }
+    }
+
+    client
+        .update_schema(
+            database_id,
+            serde_json::json!({ (name.as_str()): { "name": new_name } }),
+        )
+        .await?;
+    println!("{} Renamed '{}' to '{}'", "✓".green(), name, new_name);
+
+    Ok(())
+}
+
+pub async fn handle_db_prop_remove(
+    client: &NotionClient,
+    database_id: &str,
+    property: &str,
+    yes: bool,
+) -> Result<()> {
+    let (_, schema) = client.get_database_schema(database_id).await?;
+    let (name, prop_type) = find_property(&schema, property)?;
+    if prop_type == "title" {
+        bail!("The title property can't be removed");
+    }
+    if !confirm(
+        &format!("Remove property '{}' and its values in every row?", name),
+        yes,
+    )? {
+        return Ok(());
+    }
+
+    client
+        .update_schema(database_id, serde_json::json!({ (name.as_str()): null }))
+        .await?;
+    println!("{} Removed property '{}'", "✓".green(), name);
+
+    Ok(())
+}
+
+pub async fn handle_db_prop_options(
+    client: &NotionClient,
+    database_id: &str,
+    property: &str,
+    add: &[String],
+    remove: &[String],
+) -> Result<()> {
+    let (_, schema) = client.get_database_schema(database_id).await?;
+    let (name, prop_type) = find_property(&schema, property)?;
+    let update = update_options(&schema[&name], add, remove)?;
+
+    let updated = client
+        .update_schema(database_id, serde_json::json!({ (name.as_str()): update }))
+        .await?;
+    let options: Vec<&str> = updated["properties"][&name][&prop_type]["options"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|o| o["name"].as_str())
+        .collect();
+    println!(
+        "{} Updated the options of '{}': {}",
+        "✓".green(),
+        name,
+        options.join(", ")
+    );
+
+    Ok(())
+}
+
+pub async fn handle_db_schema(client: &NotionClient, database_id: &str) -> Result<()> {
+    status!("{} {}", "Reading database schema:".blue(), database_id);
+
+    let (database, properties) = client.get_database_schema(database_id).await?;
+
+    let title = extract_title(&database);
+    let count = properties.as_object().map(|p| p.len()).unwrap_or(0);
+    if database["object"] == "data_source" {
+        println!("\n{} {}", "Data source:".green(), title);
+        if let Some(parent) = database["parent"]["database_id"].as_str() {
+            println!("  Database: {}", parent);
+        }
+    } else {
+        println!("\n{} {}", "Database:".green(), title);
+    }
+
+    // Databases on 2025-09-03+ hold one or more data sources; the schema
+    // shown is the first one's
+    if let Some(data_sources) = database["data_sources"].as_array() {
+        println!("{} {} data source(s):", "✓".green(), data_sources.len());
+        for (i, source) in data_sources.iter().enumerate() {
+            let name = source["name"].as_str().unwrap_or_default();
+            let id = source["id"].as_str().unwrap_or_default();
+            let shown = if i == 0 { " (schema below)" } else { "" };
+            println!("  {}  {}{}", id, name, shown.dimmed());
+        }
+    }
+    println!("{} {} properties\n", "✓".green(), count);
+
+    print_schema(&properties);
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_append(
+    client: &NotionClient,
+    page_id: &str,
+    content: Option<&str>,
+    file: Option<&Path>,
+    markdown: bool,
+    mention_users: &[String],
+    mention_pages: &[String],
+    after: Option<&str>,
+    offline: bool,
+) -> Result<()> {
+    let mut content = read_content(content, file)?.unwrap_or_default();
+    let mut markdown = markdown;
+    // Mentions are written in the inline syntax so queued appends keep them
+    let mentions: Vec<String> = mention_users
+        .iter()
+        .map(|user| format!("@[user:{}]", user))
+        .chain(mention_pages.iter().map(|page| format!("@[page:{}]", page)))
+        .collect();
+    if !mentions.is_empty() {
+        if !markdown {
+            content = markdown::escape(&content);
+            markdown = true;
+        }
+        content = format!("{} {}", content.trim_end(), mentions.join(" "))
+            .trim_start()
+            .to_string();
+    }
+    if content.trim().is_empty() {
+        bail!("Nothing to append: content is empty");
+    }
+    let op = Operation::Append {
+        page: page_id.to_string(),
+        content: content.clone(),
+        markdown,
+        after: after.map(String::from),
+    };
+
+    let send = async {
+        status!("{} {}", "Appending to:".blue(), page_id);
+
+        let result = if markdown {
+            client.append_markdown(page_id, &content, after).await?
+        } else {
+            client.append_blocks(page_id, &content, after).await?
+        };
+        let count = result["results"].as_array().map_or(0, |r| r.len());
+        println!("{} Content appended! ({} blocks)", "✓".green(), count);
+        Ok(())
+    };
+    send_or_queue(offline, op, send).await
+}
+
+/// What the system clipboard holds
+pub enum ClipboardContent {
+    Text(String),
+    /// An image, encoded as PNG
+    Image(Vec<u8>),
+}
+
+/// Read the clipboard, preferring text when it offers both
+pub fn read_clipboard() -> Result<ClipboardContent> {
+    let mut clipboard = arboard::Clipboard::new().context("Failed to open the clipboard")?;
+    if let Ok(text) = clipboard.get_text() {
+        if !text.trim().is_empty() {
+            return Ok(ClipboardContent::Text(text));
+        }
+    }
+    let image = clipboard
+        .get_image()
+        .map_err(|_| anyhow::anyhow!("The clipboard holds no text or image"))?;
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, image.width as u32, image.height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder
+        .write_header()
+        .context("Failed to encode the clipboard image")?;
+    writer
+        .write_image_data(&image.bytes)
+        .context("Failed to encode the clipboard image")?;
+    writer
+        .finish()
+        .context("Failed to encode the clipboard image")?;
+    Ok(ClipboardContent::Image(png))
+}
+
+/// Put text on the clipboard. On Linux it stays available after exit only
+/// when a clipboard manager takes it over.
+fn copy_to_clipboard(text: &str) -> Result<()> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .context("Failed to copy to the clipboard")
+}
+
+pub async fn handle_append_clipboard_image(
+    client: &NotionClient,
+    page_id: &str,
+    png: Vec<u8>,
+    after: Option<&str>,
+) -> Result<()> {
+    status!("{} {}", "Uploading to:".blue(), page_id);
+    status!("  Clipboard image ({} bytes)", png.len());
+
+    let upload_id = client
+        .upload_bytes(&png, "clipboard.png", "image/png")
+        .await?;
+    client
+        .append_children_after(
+            page_id,
+            &[blocks::file_upload("image", &upload_id, None)],
+            after,
+        )
+        .await?;
+    println!("{} Clipboard image appended!", "✓".green());
+
+    Ok(())
+}
+
+pub async fn handle_append_code(
+    client: &NotionClient,
+    page_id: &str,
+    code: Option<&str>,
+    file: Option<&Path>,
+    language: Option<&str>,
+    caption: Option<&str>,
+    after: Option<&str>,
+) -> Result<()> {
+    let code = read_content(code, file)?.unwrap_or_default();
+    let language = match (language, file) {
+        (Some(name), _) => blocks::code_language(name).unwrap_or(name),
+        (None, Some(path)) => blocks::language_for_path(path),
+        (None, None) => "plain text",
+    };
+    let file_name = file
+        .and_then(|path| path.file_name())
+        .map(|name| name.to_string_lossy().into_owned());
+    let caption = match caption {
+        Some("") => Some(
+            file_name
+                .as_deref()
+                .context("--caption needs a value without --file")?,
+        ),
+        other => other,
+    };
+    status!(
+        "{} {} (language: {})",
+        "Appending code block to:".blue(),
+        page_id,
+        language
+    );
+
+    let count = client
+        .append_code_block(page_id, &code, language, caption, after)
+        .await?;
+    if count > 1 {
+        println!("{} Code appended as {} blocks!", "✓".green(), count);
+    } else {
+        println!("{} Code block appended!", "✓".green());
+    }
+
+    Ok(())
+}
+
+pub async fn handle_append_json(
+    client: &NotionClient,
+    page_id: &str,
+    file: &Path,
+    after: Option<&str>,
+) -> Result<()> {
+    let (json, source) = if file == Path::new("-") {
+        (read_content(Some("-"), None)?, "stdin".to_string())
+    } else {
+        (read_content(None, Some(file))?, file.display().to_string())
+    };
+    let value: serde_json::Value = serde_json::from_str(&json.unwrap_or_default())
+        .with_context(|| format!("Invalid JSON in {}", source))?;
+    let children = blocks::validate_blocks(&value)?;
+
+    status!(
+        "{} {} ({} blocks)",
+        "Appending to:".blue(),
+        page_id,
+        children.len()
+    );
+    let result = client
+        .append_children_after(page_id, &children, after)
+        .await?;
+    let created = result["results"].as_array().map_or(0, |r| r.len());
+    println!("{} Appended {} block(s)", "✓".green(), created);
+
+    Ok(())
+}
+
+pub async fn handle_append_bookmark(
+    client: &NotionClient,
+    page_id: &str,
+    url: &str,
+    caption: Option<&str>,
+    after: Option<&str>,
+) -> Result<()> {
+    status!("{} {}", "Appending bookmark to:".blue(), page_id);
+    status!("  URL: {}", url);
+    if let Some(cap) = caption {
+        status!("  Caption: {}", cap);
+    }
+
+    client.append_bookmark(page_id, url, caption, after).await?;
+    println!("{} Bookmark appended!", "✓".green());
+
+    Ok(())
+}
+
+pub async fn handle_clip(
+    client: &NotionClient,
+    page_id: &str,
+    url: &str,
+    after: Option<&str>,
+) -> Result<()> {
+    let (html, base) = if url == "-" {
+        (read_content(Some(url), None)?.unwrap_or_default(), None)
+    } else {
+        let base = reqwest::Url::parse(url).with_context(|| format!("Invalid URL '{}'", url))?;
+        let bytes = client.download_file(url).await?;
+        (String::from_utf8_lossy(&bytes).into_owned(), Some(base))
+    };
+
+    let article = clip::extract(&html, base.as_ref());
+    if article.blocks.is_empty() {
+        bail!("Found no readable content in {}", url);
+    }
+    status!(
+        "{} \"{}\" to {}",
+        "Clipping:".blue(),
+        article.title,
+        page_id
+    );
+
+    // Link back to the source above the content
+    let mut children = Vec::new();
+    if let Some(base) = &base {
+        children.push(blocks::bookmark(base.as_str(), None));
+    }
+    children.extend(article.blocks);
+
+    client
+        .append_children_after(page_id, &children, after)
+        .await?;
+    println!("{} {} blocks appended!", "✓".green(), children.len());
+
+    Ok(())
+}
+
+pub async fn handle_git_log(
+    client: &NotionClient,
+    page_id: &str,
+    commits: &[Commit],
+    format: &str,
+    heading: Option<&str>,
+) -> Result<()> {
+    if commits.is_empty() {
+        println!("{} No commits to add", "⚠".yellow());
+        return Ok(());
+    }
+    status!(
+        "{} {} commits to {}",
+        "Appending:".blue(),
+        commits.len(),
+        page_id
+    );
+
+    let mut children = Vec::new();
+    if let Some(heading) = heading {
+        children.push(blocks::heading(2, heading));
+    }
+    children.extend(match format {
+        "table" => git::table_blocks(commits),
+        _ => git::list_blocks(commits),
+    });
+
+    client.append_children(page_id, &children).await?;
+    println!("{} {} commits appended!", "✓".green(), commits.len());
+
+    Ok(())
+}
+
+/// One database row per commit, titled with its message. Hash, Author and
+/// Date are filled in when the database has writable properties by those names.
+pub async fn handle_git_log_rows(
+    client: &NotionClient,
+    database_id: &str,
+    commits: &[Commit],
+) -> Result<()> {
+    if commits.is_empty() {
+        println!("{} No commits to add", "⚠".yellow());
+        return Ok(());
+    }
+    status!(
+        "{} {} commits into {}",
+        "Creating rows:".blue(),
+        commits.len(),
+        database_id
+    );
+
+    let (_, schema) = client.get_database_schema(database_id).await?;
+    let headers = ["Hash", "Author", "Date"].map(String::from);
+    let columns = match_columns(&headers, &schema);
+
+    let mut failures = Vec::new();
+    let bar = progress::bar(commits.len(), "Creating rows");
+    // Oldest first, so the rows' creation order follows the history
+    for commit in commits.iter().rev() {
+        let values = [commit.short_hash(), &commit.author, &commit.date];
+        let specs: Vec<PropertySpec> = columns
+            .iter()
+            .zip(values)
+            .filter_map(|(column, value)| {
+                let (name, prop_type) = column.as_ref()?;
+                (prop_type != "title").then(|| PropertySpec {
+                    name: name.clone(),
+                    prop_type: prop_type.clone(),
+                    value: value.to_string(),
+                })
+            })
+            .collect();
+
+        if let Err(e) = client
+            .create_database_page(database_id, &commit.subject, &specs, None, None)
+            .await
+        {
+            failures.push((commit.short_hash(), format!("{:#}", e)));
+        }
+        bar.inc(1);
+    }
+    bar.finish_and_clear();
+
+    println!(
+        "{} {} rows created",
+        "✓".green(),
+        commits.len() - failures.len()
+    );
+    if failures.is_empty() {
+        return Ok(());
+    }
+
+    println!("{} {} rows failed:", "✗".red(), failures.len());
+    for (hash, error) in &failures {
+        println!("  {}: {}", hash, error);
+    }
+    bail!(
+        "{} of {} commits failed to import",
+        failures.len(),
+        commits.len()
+    )
+}
+
+pub async fn handle_append_image(
+    client: &NotionClient,
+    page_id: &str,
+    url: &str,
+    caption: Option<&str>,
+    after: Option<&str>,
+) -> Result<()> {
+    status!("{} {}", "Appending image to:".blue(), page_id);
+    status!("  URL: {}", url);
+    if let Some(cap) = caption {
+        status!("  Caption: {}", cap);
+    }
+
+    client.append_image(page_id, url, caption, after).await?;
+    println!("{} Image appended!", "✓".green());
+
+    Ok(())
+}
+
+pub async fn handle_append_video(
+    client: &NotionClient,
+    page_id: &str,
+    url: &str,
+    caption: Option<&str>,
+    after: Option<&str>,
+) -> Result<()> {
+    status!("{} {}", "Appending video to:".blue(), page_id);
+    status!("  URL: {}", url);
+    if let Some(cap) = caption {
+        status!("  Caption: {}", cap);
+    }
+
+    client.append_video(page_id, url, caption, after).await?;
+    println!("{} Video appended!", "✓".green());
+
+    Ok(())
+}
+
+pub async fn handle_append_embed(
+    client: &NotionClient,
+    page_id: &str,
+    url: &str,
+    caption: Option<&str>,
+    after: Option<&str>,
+) -> Result<()> {
+    status!("{} {}", "Appending embed to:".blue(), page_id);
+    status!("  URL: {}", url);
+    if let Some(cap) = caption {
+        status!("  Caption: {}", cap);
+    }
+
+    client.append_embed(page_id, url, caption, after).await?;
+    println!("{} Embed appended!", "✓".green());
+
+    Ok(())
+}
+
+pub async fn handle_upload(
+    client: &NotionClient,
+    page_id: &str,
+    path: &Path,
+    caption: Option<&str>,
+    after: Option<&str>,
+) -> Result<()> {
+    let size = fs::metadata(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?
+        .len();
+
+    status!("{} {}", "Uploading to:".blue(), page_id);
+    status!("  File: {} ({} bytes)", path.display(), size);
+
+    let result = client
+        .upload_and_attach(page_id, path, caption, after)
+        .await?;
+
+    let block_type = result
+        .get("results")
+        .and_then(|r| r.get(0))
+        .and_then(|b| b.get("type"))
+        .and_then(|t| t.as_str())
+        .unwrap_or("file");
+    println!(
+        "{} File uploaded and attached as {} block!",
+        "✓".green(),
+        block_type
+    );
+
+    Ok(())
+}
+
+pub async fn handle_update(
+    client: &NotionClient,
+    page_id: &str,
+    title: Option<&str>,
+    icon: Option<&str>,
+    props: &[String],
+    offline: bool,
+) -> Result<()> {
+    if title.is_none() && icon.is_none() && props.is_empty() {
+        bail!("At least one of --title, --icon, --icon-url or --prop must be specified");
+    }
+
+    let specs = props
+        .iter()
+        .map(|p| PropertySpec::parse(p))
+        .collect::<Result<Vec<_>>>()?;
+    let op = Operation::Update {
+        page: page_id.to_string(),
+        title: title.map(String::from),
+        icon: icon.map(String::from),
+        properties: props.to_vec(),
+    };
+    send_or_queue(
+        offline,
+        op,
+        update_page(client, page_id, title, icon, &specs),
+    )
+    .await
+}
+
+async fn update_page(
+    client: &NotionClient,
+    page_id: &str,
+    title: Option<&str>,
+    icon: Option<&str>,
+    specs: &[PropertySpec],
+) -> Result<()> {
+    status!("{} {}", "Updating page:".blue(), page_id);
+
+    let result = client.update_page(page_id, title, icon, specs).await?;
+
+    let new_title = extract_title(&result);
+    println!("{} Page updated!", "✓".green());
+    println!("  Title: {}", new_title);
+
+    for spec in specs {
+        let value = result
+            .get("properties")
+            .and_then(|p| p.get(&spec.name))
+            .and_then(extract_property_value)
+            .unwrap_or_else(|| "(empty)".to_string());
+        println!("  {}: {}", spec.name, value);
+    }
+
+    if let Some(icon_obj) = result.get("icon") {
+        if let Some(emoji) = icon_obj.get("emoji").and_then(|e| e.as_str()) {
+            println!("  Icon: {}", emoji);
+        }
+    }
+
+    Ok(())
+}
+
+/// Add pages to (or with `remove`, take them out of) a relation property,
+/// keeping the pages it already links to
+pub async fn handle_relate(
+    client: &NotionClient,
+    page_id: &str,
+    property: &str,
+    to: &[String],
+    remove: bool,
+) -> Result<()> {
+    let page = client.get_page(page_id).await?;
+    let relations: Vec<(&String, &serde_json::Value)> = page["properties"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .collect();
+    let (name, value) = relations
+        .iter()
+        .find(|(name, _)| name.as_str() == property)
+        .or_else(|| {
+            relations
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(property))
+        })
+        .with_context(|| format!("The page has no property '{}'", property))?;
+    if value["type"] != "relation" {
+        bail!(
+            "'{}' is a {} property, not a relation",
+            name,
+            value["type"].as_str().unwrap_or("unknown")
+        );
+    }
+
+    let mut linked = client.get_relation(&page, name).await?;
+    let before = linked.len();
+    for target in to {
+        let target = normalize_page_id(target)?;
+        let position = linked
+            .iter()
+            .position(|id| normalize_page_id(id).ok().as_deref() == Some(target.as_str()));
+        match (position, remove) {
+            (Some(i), true) => {
+                linked.remove(i);
+            }
+            (None, false) => linked.push(target),
+            _ => {}
+        }
+    }
+
+    let changed = linked.len().abs_diff(before);
+    if changed == 0 {
+        let state = if remove { "aren't" } else { "are already" };
+        println!(
+            "{} No change: the pages {} linked through '{}'",
+            "ℹ".yellow(),
+            state,
+            name
+        );
+        return Ok(());
+    }
+
+    let relation: Vec<serde_json::Value> = linked
+        .iter()
+        .map(|id| serde_json::json!({ "id": id }))
+        .collect();
+    client
+        .set_properties(
+            page_id,
+            &serde_json::json!({ (name.as_str()): { "relation": relation } }),
+        )
+        .await?;
+
+    let action = if remove { "Unlinked" } else { "Linked" };
+    println!(
+        "{} {} {} page(s) through '{}' ({} linked now)",
+        "✓".green(),
+        action,
+        changed,
+        name,
+        linked.len()
+    );
+    Ok(())
+}
+
+pub async fn handle_delete(client: &NotionClient, page_id: &str, yes: bool) -> Result<()> {
+    let title = extract_title(&client.get_page(page_id).await?);
+    if !confirm(&format!("Archive page \"{}\"?", title), yes)? {
+        return Ok(());
+    }
+
+    status!("{} {}", "Archiving page:".blue(), page_id);
+
+    let result = client.delete_page(page_id).await?;
+
+    let archived = result
+        .get("archived")
+        .and_then(|a| a.as_bool())
+        .unwrap_or(false);
+    if archived {
+        println!("{} Page archived (moved to trash)!", "✓".green());
+    } else {
+        println!("{} Page status unclear", "⚠".yellow());
+    }
+
+    Ok(())
+}
+
+pub async fn handle_db_import(client: &NotionClient, database_id: &str, file: &Path) -> Result<()> {
+    let mut reader = csv::Reader::from_path(file)
+        .with_context(|| format!("Failed to open CSV file {}", file.display()))?;
+    let headers: Vec<String> = reader
+        .headers()
+        .context("Failed to read CSV header row")?
+        .iter()
+        .map(String::from)
+        .collect();
+    let records = reader
+        .records()
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to parse CSV")?;
+
+    status!("{} {}", "Importing into database:".blue(), database_id);
+
+    let (_, schema) = client.get_database_schema(database_id).await?;
+    let columns = match_columns(&headers, &schema);
+    for (header, column) in headers.iter().zip(&columns) {
+        match column {
+            Some((name, prop_type)) => status!("  {} → {} ({})", header, name, prop_type),
+            None => println!(
+                "  {} Skipping column '{}' (no writable property with that name)",
+                "⚠".yellow(),
+                header
+            ),
+        }
+    }
+    let title_column = columns
+        .iter()
+        .position(|c| c.as_ref().is_some_and(|(_, t)| t == "title"));
+    if title_column.is_none() {
+        println!("  {} No title column; rows will be untitled", "⚠".yellow());
+    }
+
+    let mut failures = Vec::new();
+    let bar = progress::bar(records.len(), "Importing rows");
+    for (i, record) in records.iter().enumerate() {
+        // Row 1 is the header
+        let row = i + 2;
+        let title = title_column.and_then(|c| record.get(c)).unwrap_or_default();
+        let specs: Vec<PropertySpec> = columns
+            .iter()
+            .zip(record.iter())
+            .filter_map(|(column, value)| {
+                let (name, prop_type) = column.as_ref()?;
+                (prop_type != "title" && !value.trim().is_empty()).then(|| PropertySpec {
+                    name: name.clone(),
+                    prop_type: prop_type.clone(),
+                    value: value.trim().to_string(),
+                })
+            })
+            .collect();
+
+        if let Err(e) = client
+            .create_database_page(database_id, title, &specs, None, None)
+            .await
+        {
+            failures.push((row, format!("{:#}", e)));
+        }
+        bar.inc(1);
+    }
+    bar.finish_and_clear();
+
+    let created = records.len() - failures.len();
+    println!("{} {} rows created", "✓".green(), created);
+    if failures.is_empty() {
+        return Ok(());
+    }
+
+    println!("{} {} rows failed:", "✗".red(), failures.len());
+    for (row, error) in &failures {
+        println!("  line {}: {}", row, error);
+    }
+    bail!(
+        "{} of {} rows failed to import",
+        failures.len(),
+        records.len()
+    )
+}
+
+/// The API filter and sorts for `query` flags
+async fn query_filter(
+    client: &NotionClient,
+    database_id: &str,
+    filters: &QueryFilters,
+) -> Result<(Option<serde_json::Value>, Vec<SortSpec>)> {
+    let specs = if filters.filter.is_empty() {
+        Vec::new()
+    } else {
+        let (_, schema) = client.get_database_schema(database_id).await?;
+        filters
+            .filter
+            .iter()
+            .map(|f| FilterSpec::parse_for_schema(f, &schema))
+            .collect::<Result<Vec<_>>>()?
+    };
+    let filter = build_filter(&specs, filters.filter_mode == "or")?;
+    let sorts = filters
+        .sort
+        .iter()
+        .map(|s| SortSpec::parse(s, filters.direction == "asc"))
+        .collect::<Result<Vec<_>>>()?;
+    Ok((filter, sorts))
+}
+
+pub async fn handle_query(
+    client: &NotionClient,
+    database_id: &str,
+    filters: &QueryFilters,
+    limit: usize,
+    output: &QueryOutput,
+    pagination: &Pagination,
+) -> Result<()> {
+    let (filter, sorts) = query_filter(client, database_id, filters).await?;
+
+    if let Some(page_size) = pagination.manual() {
+        let page = client
+            .query_database_page(
+                database_id,
+                filter.as_ref(),
+                &sorts,
+                pagination.start_cursor.as_deref(),
+                page_size,
+            )
+            .await?;
+        return print_json(&page);
+    }
+
+    if output.format == "ndjson" || output.id_only {
+        client
+            .query_database_each(database_id, filter.as_ref(), &sorts, limit, |item| {
+                if output.id_only {
+                    write_id(&item)
+                } else {
+                    write_ndjson(&item)
+                }
+            })
+            .await?;
+        return Ok(());
+    }
+
+    if output.format == "csv" {
+        let mut results = client
+            .query_database(database_id, filter.as_ref(), &sorts, limit)
+            .await?;
+        if output.resolve_relations {
+            fill_relation_titles(client, &mut results).await;
+        }
+        return write_query_csv(&results, &output.columns, output.output.as_deref());
+    }
+
+    status!("{} {}", "Querying database:".blue(), database_id);
+
+    for f in &filters.filter {
+        status!("  Filter: {}", f);
+    }
+    if filters.filter.len() > 1 {
+        status!("  Filter mode: {}", filters.filter_mode);
+    }
+    for s in &sorts {
+        let direction = if s.ascending { "asc" } else { "desc" };
+        status!("  Sort: {} ({})", s.key, direction);
+    }
+
+    let mut results = client
+        .query_database(database_id, filter.as_ref(), &sorts, limit)
+        .await?;
+    if output.resolve_relations {
+        fill_relation_titles(client, &mut results).await;
+    }
+    status!("{} {} results found\n", "✓".green(), results.len());
+
+    if output.count_by.is_some() || !output.sum.is_empty() {
+        return print_query_stats(&results, output.count_by.as_deref(), &output.sum);
+    }
+    if output.format == "table" {
+        return print_query_table(&results, &output.columns, output.full);
+    }
+
+    for item in &results {
+        let page: Page = serde_json::from_value(item.clone())?;
+        let title = page.title().unwrap_or_else(|| "(Untitled)".to_string());
+
+        println!("  {} {}", "•".cyan(), title);
+        println!("    ID: {}", page.id.dimmed());
+
+        for (key, value) in page.properties.iter().take(3) {
+            if matches!(value, PropertyValue::Title(_)) {
+                continue;
+            }
+            if let Some(prop_value) = value.display() {
+                println!("    {}: {}", key.dimmed(), prop_value);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Add the title of every related page to the relation properties of query
+/// results (pages that can't be fetched keep showing their ID)
+async fn fill_relation_titles(client: &NotionClient, results: &mut [serde_json::Value]) {
+    let mut relations: Vec<&mut serde_json::Value> = results
+        .iter_mut()
+        .filter_map(|item| item["properties"].as_object_mut())
+        .flat_map(|props| props.values_mut())
+        .filter(|prop| prop["type"] == "relation")
+        .filter_map(|prop| prop["relation"].as_array_mut())
+        .flatten()
+        .collect();
+
+    let ids: BTreeSet<String> = relations
+        .iter()
+        .filter_map(|r| r["id"].as_str().map(String::from))
+        .collect();
+    let mut titles = HashMap::new();
+    for id in ids {
+        if let Ok(page) = client.get_page(&id).await {
+            titles.insert(id, extract_title(&page));
+        }
+    }
+
+    for related in relations.iter_mut() {
+        if let Some(title) = related["id"].as_str().and_then(|id| titles.get(id)) {
+            related["title"] = serde_json::Value::String(title.clone());
+        }
+    }
+}
+
+/// Print query results as a table with one row per page, fitted to the
+/// terminal width unless `full` is set
+fn print_query_table(results: &[serde_json::Value], columns: &[String], full: bool) -> Result<()> {
+    if results.is_empty() {
+        return Ok(());
+    }
+    let columns = query_columns(results, columns)?;
+    let rows: Vec<Vec<String>> = results
+        .iter()
+        .map(|item| query_row(item, &columns))
+        .collect();
+
+    let max_width = if full {
+        None
+    } else {
+        // Leave room for the two-space indent
+        console::Term::stdout()
+            .size_checked()
+            .map(|(_, width)| usize::from(width).saturating_sub(2))
+    };
+    print_table(&columns, &rows, max_width);
+    Ok(())
+}
+
+/// Print counts, totals and averages of query results, per group of
+/// `count_by` values when given
+fn print_query_stats(
+    results: &[serde_json::Value],
+    count_by: Option<&str>,
+    sum: &[String],
+) -> Result<()> {
+    if results.is_empty() {
+        return Ok(());
+    }
+    let names: Vec<String> = count_by
+        .map(String::from)
+        .into_iter()
+        .chain(sum.to_vec())
+        .collect();
+    query_columns(results, &names)?;
+
+    let groups = group_stats(results, count_by, sum)?;
+
+    let mut headers: Vec<String> = count_by.map(String::from).into_iter().collect();
+    headers.push("Count".to_string());
+    for name in sum {
+        headers.push(format!("Sum {}", name));
+        headers.push(format!("Avg {}", name));
+    }
+    let rows: Vec<Vec<String>> = groups
+        .iter()
+        .map(|group| {
+            let mut row: Vec<String> = count_by.map(|_| group.key.clone()).into_iter().collect();
+            row.push(group.count.to_string());
+            for (i, (total, _)) in group.sums.iter().enumerate() {
+                row.push(format_number(*total));
+                row.push(group.average(i).map(format_number).unwrap_or_default());
+            }
+            row
+        })
+        .collect();
+
+    print_table(&headers, &rows, None);
+    Ok(())
+}
+
+/// Print a [`format_table`] layout indented, with a bold header
+fn print_table(headers: &[String], rows: &[Vec<String>], max_width: Option<usize>) {
+    let mut lines = format_table(headers, rows, max_width).into_iter();
+    if let Some(header) = lines.next() {
+        println!("  {}", header.bold());
+    }
+    for (i, line) in lines.enumerate() {
+        if i == 0 {
+            println!("  {}", line.dimmed());
+        } else {
+            println!("  {}", line);
+        }
+    }
+}
+
+/// Columns to show for query results. Without `columns`, the title property
+/// comes first, followed by every other property in name order.
+fn query_columns(results: &[serde_json::Value], columns: &[String]) -> Result<Vec<String>> {
+    let available = default_csv_columns(results);
+    if columns.is_empty() {
+        return Ok(available);
+    }
+    // An empty result set has no properties to check against
+    if let Some(missing) = columns
+        .iter()
+        .find(|c| !results.is_empty() && !available.contains(c))
+    {
+        bail!(
+            "Unknown column '{}'. Available: {}",
+            missing,
+            available.join(", ")
+        );
+    }
+    Ok(columns.to_vec())
+}
+
+/// The displayed values of `columns` for one page (empty when unset)
+fn query_row(item: &serde_json::Value, columns: &[String]) -> Vec<String> {
+    columns
+        .iter()
+        .map(|column| {
+            item["properties"]
+                .get(column)
+                .and_then(extract_property_value)
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Write query results as CSV, one row per page (see [`query_columns`])
+fn write_query_csv(
+    results: &[serde_json::Value],
+    columns: &[String],
+    path: Option<&Path>,
+) -> Result<()> {
+    let columns = query_columns(results, columns)?;
+
+    let sink: Box<dyn Write> = match path {
+        Some(path) => Box::new(
+            fs::File::create(path)
+                .with_context(|| format!("Failed to create {}", path.display()))?,
+        ),
+        None => Box::new(io::stdout()),
+    };
+    let mut writer = csv::Writer::from_writer(sink);
+
+    writer.write_record(&columns)?;
+    for item in results {
+        writer.write_record(query_row(item, &columns))?;
+    }
+    writer.flush().context("Failed to write CSV")?;
+
+    if let Some(path) = path {
+        eprintln!(
+            "{} {} rows written to {}",
+            "✓".green(),
+            results.len(),
+            path.display()
+        );
+    }
+
+    Ok(())
+}
+
+pub async fn handle_db_export(
+    client: &NotionClient,
+    database_id: &str,
+    format: &str,
+    columns: &[String],
+    filters: &QueryFilters,
+    output: Option<&Path>,
+) -> Result<()> {
+    let (filter, sorts) = query_filter(client, database_id, filters).await?;
+    let results = client
+        .query_database(database_id, filter.as_ref(), &sorts, usize::MAX)
+        .await?;
+    if format == "csv" {
+        return write_query_csv(&results, columns, output);
+    }
+
+    let columns = query_columns(&results, columns)?;
+    let document = if format == "json" {
+        let rows = results
+            .iter()
+            .map(|item| {
+                let page: Page = serde_json::from_value(item.clone())?;
+                let properties: serde_json::Map<String, serde_json::Value> = columns
+                    .iter()
+                    .map(|c| {
+                        let value = page.properties.get(c).map(PropertyValue::to_json);
+                        (c.clone(), value.unwrap_or(serde_json::Value::Null))
+                    })
+                    .collect();
+                Ok(serde_json::json!({ "id": page.id, "url": page.url, "properties": properties }))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        serde_json::to_string_pretty(&rows)? + "\n"
+    } else {
+        let rows: Vec<Vec<String>> = results
+            .iter()
+            .map(|item| query_row(item, &columns))
+            .collect();
+        export::markdown_table(&columns, &rows) + "\n"
+    };
+
+    match output {
+        Some(path) => {
+            fs::write(path, document)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            eprintln!(
+                "{} {} rows written to {}",
+                "✓".green(),
+                results.len(),
+                path.display()
+            );
+        }
+        None => print!("{}", document),
+    }
+    Ok(())
+}
+
+/// Archive the rows of a database matching `filters` and, with `older_than`,
+/// whose `timestamp` is at least that old (checked locally), after listing
+/// them for confirmation
+pub async fn handle_db_archive(
+    client: &NotionClient,
+    database_id: &str,
+    filters: &QueryFilters,
+    older_than: Option<chrono::Duration>,
+    timestamp: &str,
+    yes: bool,
+) -> Result<()> {
+    let (filter, sorts) = query_filter(client, database_id, filters).await?;
+    let mut rows = client
+        .query_database(database_id, filter.as_ref(), &sorts, usize::MAX)
+        .await?;
+    if let Some(age) = older_than {
+        let cutoff = chrono::Utc::now() - age;
+        rows.retain(|row| {
+            row[timestamp]
+                .as_str()
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                .is_some_and(|t| t < cutoff)
+        });
+    }
+
+    if rows.is_empty() {
+        println!("{} No matching rows", "ℹ".yellow());
+        return Ok(());
+    }
+
+    status!("{} {} matching row(s):\n", "Archiving:".blue(), rows.len());
+    for row in &rows {
+        let time = row[timestamp].as_str().unwrap_or_default();
+        println!(
+            "  {} {}  {}  {}",
+            "•".cyan(),
+            extract_title(row),
+            time.get(..10).unwrap_or(time).dimmed(),
+            row["id"].as_str().unwrap_or_default().dimmed()
+        );
+    }
+    println!();
+    if !confirm(&format!("Archive {} row(s)?", rows.len()), yes)? {
+        return Ok(());
+    }
+
+    let mut failures = Vec::new();
+    let bar = progress::bar(rows.len(), "Archiving rows");
+    for row in &rows {
+        let id = row["id"].as_str().unwrap_or_default();
+        if let Err(e) = client.delete_page(id).await {
+            failures.push((extract_title(row), format!("{:#}", e)));
+        }
+        bar.inc(1);
+    }
+    bar.finish_and_clear();
+
+    println!(
+        "{} Archived {} row(s)",
+        "✓".green(),
+        rows.len() - failures.len()
+    );
+    if failures.is_empty() {
+        return Ok(());
+    }
+
+    println!("{} {} rows failed:", "✗".red(), failures.len());
+    for (title, error) in &failures {
+        println!("  {}: {}", title, error);
+    }
+    bail!(
+        "{} of {} rows failed to archive",
+        failures.len(),
+        rows.len()
+    )
+}
+
+/// Report rows of a database that share a `key` value. With `archive` or
+/// `merge`, the oldest row of each group is kept and the others archived,
+/// `merge` first filling the kept row's empty properties from them.
+pub async fn handle_db_dedupe(
+    client: &NotionClient,
+    database_id: &str,
+    key: &str,
+    archive: bool,
+    merge: bool,
+    yes: bool,
+) -> Result<()> {
+    status!(
+        "{} {} by '{}'",
+        "Finding duplicates:".blue(),
+        database_id,
+        key
+    );
+
+    let rows = client
+        .query_database(database_id, None, &[], usize::MAX)
+        .await?;
+    let groups = find_duplicates(&rows, key)?;
+    if groups.is_empty() {
+        println!("{} No duplicates among {} rows", "✓".green(), rows.len());
+        return Ok(());
+    }
+
+    let extra: usize = groups.iter().map(|g| g.rows.len() - 1).sum();
+    println!(
+        "{} {} duplicate row(s) in {} group(s) of {} rows\n",
+        "⚠".yellow(),
+        extra,
+        groups.len(),
+        rows.len()
+    );
+    for group in &groups {
+        println!("  {} ({} rows)", group.key.bold(), group.rows.len());
+        for (i, row) in group.rows.iter().enumerate() {
+            let created = row["created_time"].as_str().unwrap_or_default();
+            let marker = if i == 0 {
+                "keep".green()
+            } else {
+                "dup ".yellow()
+            };
+            println!(
+                "    {} {}  {}  {}",
+                marker,
+                extract_title(row),
+                created.get(..10).unwrap_or(created).dimmed(),
+                row["id"].as_str().unwrap_or_default().dimmed()
+            );
+        }
+    }
+
+    if !archive && !merge {
+        println!("\nRun with --merge or --archive to clean up");
+        return Ok(());
+    }
+    let question = if merge {
+        format!("Merge and archive {} duplicate row(s)?", extra)
+    } else {
+        format!("Archive {} duplicate row(s)?", extra)
+    };
+    if !confirm(&question, yes)? {
+        return Ok(());
+    }
+
+    let bar = progress::bar(extra, "Archiving duplicates");
+    let mut merged = 0;
+    for group in &groups {
+        let (keep, duplicates) = group.rows.split_first().expect("groups have two rows");
+        let keep_id = keep["id"].as_str().context("Row has no ID")?;
+        if merge {
+            let properties = merge_properties(keep, duplicates);
+            if properties.as_object().is_some_and(|p| !p.is_empty()) {
+                client
+                    .set_properties(keep_id, &properties)
+                    .await
+                    .with_context(|| format!("Failed to merge into row {}", keep_id))?;
+                merged += 1;
+            }
+        }
+        for row in duplicates {
+            let id = row["id"].as_str().context("Row has no ID")?;
+            client
+                .delete_page(id)
+                .await
+                .with_context(|| format!("Failed to archive row {}", id))?;
+            bar.inc(1);
+        }
+    }
+    bar.finish_and_clear();
+
+    if merge {
+        println!(
+            "{} Filled properties on {} row(s) and archived {} duplicate(s)",
+            "✓".green(),
+            merged,
+            extra
+        );
+    } else {
+        println!("{} Archived {} duplicate(s)", "✓".green(), extra);
+    }
+    Ok(())
+}
+
+/// Every property name seen in the results, title property first
+fn default_csv_columns(results: &[serde_json::Value]) -> Vec<String> {
+    let mut title = None;
+    let mut names = BTreeSet::new();
+    for item in results {
+        for (name, prop) in item["properties"].as_object().into_iter().flatten() {
+            if prop["type"] == "title" {
+                title.get_or_insert_with(|| name.clone());
+            } else {
+                names.insert(name.clone());
+            }
+        }
+    }
+    title.into_iter().chain(names).collect()
+}
+
+pub async fn handle_update_block(
+    client: &NotionClient,
+    block_id: &str,
+    text: Option<&str>,
+    checked: Option<bool>,
+    language: Option<&str>,
+) -> Result<()> {
+    let text = read_content(text, None)?.map(|t| t.trim_end_matches('\n').to_string());
+    status!("{} {}", "Updating block:".blue(), block_id);
+
+    let result = client
+        .update_block_text(block_id, text.as_deref(), checked, language)
+        .await?;
+    let block: Block = serde_json::from_value(result).context("Failed to parse block")?;
+    println!("{} Block updated: {}", "✓".green(), describe_block(&block));
+
+    Ok(())
+}
+
+pub async fn handle_move_block(
+    client: &NotionClient,
+    block_id: &str,
+    to: &str,
+    after: Option<&str>,
+) -> Result<()> {
+    status!("{} {} → {}", "Moving block:".blue(), block_id, to);
+
+    let result = client.move_block(block_id, to, after).await?;
+    let new_id = result
+        .get("id")
+        .and_then(|i| i.as_str())
+        .unwrap_or("unknown");
+
+    println!("{} Block moved!", "✓".green());
+    println!("  New ID: {}", new_id);
+
+    Ok(())
+}
+
+pub async fn handle_delete_block(client: &NotionClient, block_id: &str, yes: bool) -> Result<()> {
+    let block: Block = serde_json::from_value(client.get_block(block_id).await?)
+        .context("Failed to parse block")?;
+    if !confirm(&format!("Delete {} block?", describe_block(&block)), yes)? {
+        return Ok(());
+    }
+
+    status!("{} {}", "Deleting block:".blue(), block_id);
+
+    client.delete_block(block_id).await?;
+    println!("{} Block deleted!", "✓".green());
+
+    Ok(())
+}
+
+pub async fn handle_clear(
+    client: &NotionClient,
+    page_id: &str,
+    block_type: Option<&str>,
+    after: Option<&str>,
+    yes: bool,
+) -> Result<()> {
+    let title = extract_title(&client.get_page(page_id).await?);
+    let blocks = client.get_blocks(page_id).await?;
+    let block_ids: Vec<String> = select_blocks(&blocks, block_type, after)?
+        .iter()
+        .filter_map(|b| b["id"].as_str().map(String::from))
+        .collect();
+
+    if block_ids.is_empty() {
+        println!("{} No matching blocks on \"{}\"", "ℹ".yellow(), title);
+        return Ok(());
+    }
+
+    let question = format!(
+        "Delete {} of {} blocks from \"{}\"?",
+        block_ids.len(),
+        blocks.len(),
+        title
+    );
+    if !confirm(&question, yes)? {
+        return Ok(());
+    }
+
+    status!("{} {}", "Clearing page:".blue(), page_id);
+    client.delete_blocks(&block_ids).await?;
+    println!("{} Deleted {} blocks!", "✓".green(), block_ids.len());
+
+    Ok(())
+}
 
-use crate::client::{NotionClient, RichTextSegment};
-use crate::render::{extract_property_value, extract_title, print_block};
+pub async fn handle_append_heading(
+    client: &NotionClient,
+    page_id: &str,
+    text: &str,
+    level: u8,
+    after: Option<&str>,
+) -> Result<()> {
+    status!(
+        "{} {} (level {})",
+        "Appending heading to:".blue(),
+        page_id,
+        level
+    );
+
+    client.append_heading(page_id, text, level, after).await?;
+    println!("{} Heading appended!", "✓".green());
+
+    Ok(())
+}
+
+pub async fn handle_append_toc(
+    client: &NotionClient,
+    page_id: &str,
+    after: Option<&str>,
+) -> Result<()> {
+    status!("{} {}", "Appending table of contents to:".blue(), page_id);
+
+    client.append_table_of_contents(page_id, after).await?;
+    println!("{} Table of contents appended!", "✓".green());
+
+    Ok(())
+}
+
+pub async fn handle_append_breadcrumb(
+    client: &NotionClient,
+    page_id: &str,
+    after: Option<&str>,
+) -> Result<()> {
+    status!("{} {}", "Appending breadcrumb to:".blue(), page_id);
+
+    client.append_breadcrumb(page_id, after).await?;
+    println!("{} Breadcrumb appended!", "✓".green());
+
+    Ok(())
+}
+
+pub async fn handle_append_divider(
+    client: &NotionClient,
+    page_id: &str,
+    after: Option<&str>,
+) -> Result<()> {
+    status!("{} {}", "Appending divider to:".blue(), page_id);
+
+    client.append_divider(page_id, after).await?;
+    println!("{} Divider appended!", "✓".green());
+
+    Ok(())
+}
+
+pub async fn handle_append_quote(
+    client: &NotionClient,
+    page_id: &str,
+    text: &str,
+    after: Option<&str>,
+) -> Result<()> {
+    status!("{} {}", "Appending quote to:".blue(), page_id);
+
+    client.append_quote(page_id, text, after).await?;
+    println!("{} Quote appended!", "✓".green());
+
+    Ok(())
+}
+
+pub async fn handle_append_toggle(
+    client: &NotionClient,
+    page_id: &str,
+    text: &str,
+    children: &[String],
+    after: Option<&str>,
+) -> Result<()> {
+    status!("{} {}", "Appending toggle to:".blue(), page_id);
+
+    client.append_toggle(page_id, text, children, after).await?;
+    println!(
+        "{} Toggle appended ({} nested paragraphs)!",
+        "✓".green(),
+        children.len()
+    );
+
+    Ok(())
+}
+
+/// Append a copy of `original`, or (without it) a new original synced block
+pub async fn handle_append_synced(
+    client: &NotionClient,
+    page_id: &str,
+    original: Option<&str>,
+    content: Option<&str>,
+    file: Option<&Path>,
+    markdown: bool,
+    after: Option<&str>,
+) -> Result<()> {
+    let result = match original {
+        Some(original) => {
+            status!("{} {}", "Appending synced copy to:".blue(), page_id);
+            client.append_synced_copy(page_id, original, after).await?
+        }
+        None => {
+            let content = read_content(content, file)?.unwrap_or_default();
+            if content.trim().is_empty() {
+                bail!("Nothing to sync: content is empty");
+            }
+            let mut children = if markdown {
+                markdown::paragraphs(&content)
+            } else {
+                blocks::paragraphs(&content)
+            };
+            client.resolve_mentions(&mut children).await?;
+            status!("{} {}", "Appending synced block to:".blue(), page_id);
+            client
+                .append_synced_block(page_id, &children, after)
+                .await?
+        }
+    };
+
+    let block_id = result["results"][0]["id"].as_str().unwrap_or("unknown");
+    println!("{} Synced block appended!", "✓".green());
+    println!("  Block ID: {}", block_id);
+    if original.is_none() {
+        println!(
+            "  Show it on other pages with: notion-cli append-synced <page_id> --original {}",
+            block_id
+        );
+    }
+
+    Ok(())
+}
+
+pub async fn handle_append_table(
+    client: &NotionClient,
+    page_id: &str,
+    csv_file: Option<&Path>,
+    rows: Option<&str>,
+    header: bool,
+    after: Option<&str>,
+) -> Result<()> {
+    let rows: Vec<Vec<String>> = match (csv_file, rows) {
+        (Some(path), _) => {
+            let mut reader = csv::ReaderBuilder::new()
+                .has_headers(false)
+                .flexible(true)
+                .from_path(path)
+                .with_context(|| format!("Failed to open CSV file {}", path.display()))?;
+            reader
+                .records()
+                .map(|r| r.map(|rec| rec.iter().map(String::from).collect()))
+                .collect::<Result<_, _>>()
+                .context("Failed to parse CSV")?
+        }
+        (None, Some(inline)) => inline
+            .split(';')
+            .map(|row| row.split(',').map(|c| c.trim().to_string()).collect())
+            .collect(),
+        (None, None) => bail!("Either --csv or --rows must be specified"),
+    };
+
+    status!("{} {}", "Appending table to:".blue(), page_id);
+
+    client.append_table(page_id, &rows, header, after).await?;
+    println!("{} Table appended ({} rows)!", "✓".green(), rows.len());
+
+    Ok(())
+}
+
+pub async fn handle_append_list(
+    client: &NotionClient,
+    page_id: &str,
+    items: Option<&str>,
+    file: Option<&Path>,
+    numbered: bool,
+    after: Option<&str>,
+) -> Result<()> {
+    let content = read_content(items, file)?.unwrap_or_default();
+    // One item per line for files and multiline input, else comma-separated
+    let items: Vec<ListItem> = if file.is_some() || content.contains('\n') {
+        blocks::parse_list(&content, numbered)
+    } else {
+        content
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| ListItem {
+                text: s.to_string(),
+                numbered,
+                children: Vec::new(),
+            })
+            .collect()
+    };
+    if items.is_empty() {
+        bail!("No list items given");
+    }
+
+    status!("{} {}", "Appending list to:".blue(), page_id);
+    let created = client.append_list(page_id, &items, after).await?;
+    println!("{} List appended ({} items)!", "✓".green(), created);
+
+    Ok(())
+}
+
+pub async fn handle_append_link(
+    client: &NotionClient,
+    page_id: &str,
+    prefix: Option<&str>,
+    link_text: &str,
+    url: &str,
+    suffix: Option<&str>,
+    after: Option<&str>,
+) -> Result<()> {
+    status!("{} {}", "Appending link to:".blue(), page_id);
+
+    let mut segments = Vec::new();
+    if let Some(p) = prefix {
+        segments.push(RichTextSegment::plain(p));
+    }
+    segments.push(RichTextSegment::link(link_text, url));
+    if let Some(s) = suffix {
+        segments.push(RichTextSegment::plain(s));
+    }
+
+    client.append_rich_text(page_id, &segments, after).await?;
+    println!("{} Link appended!", "✓".green());
+
+    Ok(())
+}
+
+pub async fn handle_get_block_ids(client: &NotionClient, page_id: &str) -> Result<()> {
+    status!("{} {}", "Getting block IDs for:".blue(), page_id);
+
+    let blocks = client.get_blocks(page_id).await?;
+    println!("{} {} blocks found\n", "✓".green(), blocks.len());
+
+    for block in &blocks {
+        let id = block.get("id").and_then(|i| i.as_str()).unwrap_or("no-id");
+        let block_type = block
+            .get("type")
+            .and_then(|t| t.as_str())
+            .unwrap_or("unknown");
+        println!("{}  [{}]", id, block_type);
+    }
+
+    Ok(())
+}
+
+/// Print a page's block hierarchy, each block with its type, text and ID
+pub async fn handle_tree(client: &NotionClient, page_id: &str, depth: Option<usize>) -> Result<()> {
+    status!("{} {}", "Block tree:".blue(), page_id);
+
+    let blocks: Vec<Block> = client
+        .get_blocks_recursive(page_id, depth)
+        .await?
+        .into_iter()
+        .map(serde_json::from_value)
+        .collect::<Result<_, _>>()?;
+    let lines = format_block_tree(&blocks);
+    println!("{} {} blocks found\n", "✓".green(), lines.len());
+
+    for line in &lines {
+        println!("{}", line);
+    }
+
+    Ok(())
+}
+
+/// Print the sub-pages and databases under `root`, or under each top-level
+/// page of the workspace, as a tree or as JSON
+pub async fn handle_page_tree(
+    client: &NotionClient,
+    root: Option<&str>,
+    depth: Option<usize>,
+    format: &str,
+) -> Result<()> {
+    let json = format == "json";
+    let mut nodes = match root {
+        Some(root) => {
+            if !json {
+                status!("{} {}", "Page tree:".blue(), root);
+            }
+            let page = client.get_page(root).await?;
+            vec![PageNode {
+                object: "page".to_string(),
+                id: page["id"].as_str().unwrap_or(root).to_string(),
+                title: page_title(&page),
+                children: Vec::new(),
+            }]
+        }
+        None => {
+            if !json {
+                status!("{}", "Page tree of the workspace".blue());
+            }
+            workspace_roots(client).await?
+        }
+    };
+    for node in nodes.iter_mut().filter(|n| n.object == "page") {
+        node.children = client.get_page_tree(&node.id, depth).await?;
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&nodes)?);
+        return Ok(());
+    }
+    let (pages, databases) = nodes.iter().fold((0, 0), |(pages, databases), node| {
+        let (p, d) = node.descendants();
+        match node.object.as_str() {
+            "database" => (pages + p, databases + d + 1),
+            _ => (pages + p + 1, databases + d),
+        }
+    });
+    status!("{} {} pages, {} databases\n", "✓".green(), pages, databases);
+    for line in format_page_tree(&nodes) {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// The top of what the integration can see, sorted by title: pages and
+/// databases in the workspace itself, and shared pages whose parent page isn't
+/// shared. Database rows and pages inside other pages' blocks are left to the
+/// walk down from their page.
+async fn workspace_roots(client: &NotionClient) -> Result<Vec<PageNode>> {
+    let items = client
+        .search_filtered("", None, None, "desc", usize::MAX)
+        .await?;
+    let visible: HashSet<&str> = items.iter().filter_map(|i| i["id"].as_str()).collect();
+
+    let mut roots: Vec<PageNode> = items
+        .iter()
+        .filter_map(|item| {
+            // A data source stands for its database, whose parent is `database_parent`
+            let (object, id, parent) = match item["object"].as_str()? {
+                "page" => ("page", item["id"].as_str()?, &item["parent"]),
+                "database" => ("database", item["id"].as_str()?, &item["parent"]),
+                "data_source" => (
+                    "database",
+                    item["parent"]["database_id"].as_str()?,
+                    &item["database_parent"],
+                ),
+                _ => return None,
+            };
+            let top = match parent["type"].as_str()? {
+                "workspace" => true,
+                "page_id" => !visible.contains(parent["page_id"].as_str()?),
+                _ => false,
+            };
+            top.then(|| PageNode {
+                object: object.to_string(),
+                id: id.to_string(),
+                title: page_title(item),
+                children: Vec::new(),
+            })
+        })
+        .collect();
+    roots.sort_by(|a, b| (&a.title, &a.id).cmp(&(&b.title, &b.id)));
+    roots.dedup_by(|a, b| a.id == b.id);
+    Ok(roots)
+}
+
+/// A page or database title, empty when it has none
+fn page_title(item: &serde_json::Value) -> String {
+    serde_json::from_value::<Page>(item.clone())
+        .ok()
+        .and_then(|page| page.title())
+        .unwrap_or_default()
+}
+
+pub async fn handle_move(
+    client: &NotionClient,
+    page_id: &str,
+    new_parent: &str,
+    delete_original: bool,
+    yes: bool,
+) -> Result<()> {
+    if delete_original {
+        let title = extract_title(&client.get_page(page_id).await?);
+        let question = format!("Move page \"{}\" and archive the original?", title);
+        if !confirm(&question, yes)? {
+            return Ok(());
+        }
+    }
+
+    status!("{} {} → {}", "Moving page:".blue(), page_id, new_parent);
+
+    let result = client
+        .move_page(page_id, new_parent, delete_original)
+        .await?;
+
+    let new_id = result
+        .get("id")
+        .and_then(|i| i.as_str())
+        .unwrap_or("unknown");
+    let url = result.get("url").and_then(|u| u.as_str());
+
+    println!("{} Page moved successfully!", "✓".green());
+    println!("  New ID: {}", new_id);
+    if let Some(u) = url {
+        println!("  URL: {}", u);
+    }
+    if delete_original {
+        println!("  {} Original page archived", "→".blue());
+    } else {
+        println!(
+            "  {} Original page kept (use --delete to remove)",
+            "ℹ".yellow()
+        );
+    }
+
+    Ok(())
+}
+
+pub async fn handle_export(
+    client: &NotionClient,
+    page_id: &str,
+    format: &str,
+    output: Option<&Path>,
+    download_assets: bool,
+) -> Result<()> {
+    let (page, blocks) = client.get_page_content(page_id, None).await?;
+    let blocks: Vec<Block> = serde_json::from_value(serde_json::Value::Array(blocks))
+        .context("Failed to parse blocks")?;
+    let title = extract_title(&page);
+
+    // Asset URL -> path relative to the output file
+    let mut assets = HashMap::new();
+    if let Some(output) = output.filter(|_| download_assets) {
+        let stem = output
+            .file_stem()
+            .map_or("export".into(), |s| s.to_string_lossy());
+        let folder = format!("{}_files", stem);
+        let dir = output.with_file_name(&folder);
+        let urls = asset_urls(&blocks);
+        if !urls.is_empty() {
+            fs::create_dir_all(&dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+
+        let bar = progress::bar(urls.len(), "Downloading assets");
+        for (i, url) in urls.iter().enumerate() {
+            let name = asset_file_name(i + 1, url);
+            let saved = match client.download_file(url).await {
+                Ok(bytes) => fs::write(dir.join(&name), bytes)
+                    .with_context(|| format!("Failed to write {}", dir.join(&name).display())),
+                Err(e) => Err(e),
+            };
+            match saved {
+                Ok(()) => {
+                    assets.insert(url.clone(), format!("{}/{}", folder, name));
+                }
+                Err(e) => bar
+                    .suspend(|| eprintln!("{} {:#}; linking to the URL instead", "⚠".yellow(), e)),
+            }
+            bar.inc(1);
+        }
+        bar.finish_and_clear();
+    }
+
+    let document = match format {
+        "html" => export::to_html(&title, &blocks, &assets),
+        _ => export::to_markdown(&title, &blocks, &assets),
+    };
+
+    match output {
+        Some(path) => {
+            fs::write(path, &document)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            let downloaded = if download_assets {
+                format!(" with {} assets", assets.len())
+            } else {
+                String::new()
+            };
+            eprintln!(
+                "{} Exported \"{}\" to {}{}",
+                "✓".green(),
+                title,
+                path.display(),
+                downloaded
+            );
+        }
+        None => print!("{}", document),
+    }
+    Ok(())
+}
+
+pub async fn handle_edit(client: &NotionClient, page_id: &str) -> Result<()> {
+    let page_id = normalize_page_id(page_id)?;
+    let page = client.get_page(&page_id).await?;
+    let title = extract_title(&page);
+    let blocks: Vec<Block> =
+        serde_json::from_value(serde_json::Value::Array(client.get_blocks(&page_id).await?))
+            .context("Failed to parse blocks")?;
+
+    let (document, chunks) = edit::export(&title, &blocks);
+    let path = std::env::temp_dir().join(format!("notion-{}.md", page_id));
+    fs::write(&path, &document).with_context(|| format!("Failed to write {}", path.display()))?;
+
+    status!("{} \"{}\" ({})", "Editing:".blue(), title, path.display());
+    open_editor(&path)?;
+
+    let edited =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let ops = edit::plan(&chunks, &edited);
+    if ops.is_empty() {
+        fs::remove_file(&path).ok();
+        println!("{} No changes", "ℹ".yellow());
+        return Ok(());
+    }
+
+    // Don't overwrite edits made in Notion while the editor was open
+    let current = client.get_page(&page_id).await?;
+    if current["last_edited_time"] != page["last_edited_time"] {
+        bail!(
+            "The page was changed in Notion while you were editing; nothing was applied. Your version is in {}",
+            path.display()
+        );
+    }
+
+    let (mut updated, mut inserted, mut deleted) = (0, 0, 0);
+    for op in &ops {
+        match op {
+            EditOp::Update { .. } => updated += 1,
+            EditOp::Insert { blocks, .. } => inserted += blocks.len(),
+            EditOp::Delete { .. } => deleted += 1,
+        }
+    }
+    status!(
+        "{} Applying changes: {} updated, {} inserted, {} deleted",
+        "→".blue(),
+        updated,
+        inserted,
+        deleted
+    );
+    edit::apply(client, &page_id, &ops).await.with_context(|| {
+        format!(
+            "Failed to apply all changes (your version is in {})",
+            path.display()
+        )
+    })?;
+
+    fs::remove_file(&path).ok();
+    println!("{} Page updated!", "✓".green());
+
+    Ok(())
+}
+
+/// Open `path` in `$VISUAL` / `$EDITOR` (falling back to vi, or notepad on
+/// Windows) and wait for it to exit
+fn open_editor(path: &Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| {
+            if cfg!(windows) {
+                "notepad".to_string()
+            } else {
+                "vi".to_string()
+            }
+        });
+
+    // The editor may come with arguments, e.g. "code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().context("$EDITOR is empty")?;
+    let status = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to start editor '{}'", editor))?;
+    if !status.success() {
+        bail!(
+            "Editor exited with {}; nothing was applied (your version is in {})",
+            status,
+            path.display()
+        );
+    }
 
-pub fn handle_search(client: &NotionClient, query: &str, limit: usize) -> Result<()> {
-    println!("{} \"{}\"", "Searching:".blue(), query);
+    Ok(())
+}
+
+pub async fn handle_watch(
+    client: &NotionClient,
+    page_id: &str,
+    interval: u64,
+    exec: Option<&str>,
+) -> Result<()> {
+    let page_id = normalize_page_id(page_id)?;
+    let title = extract_title(&client.get_page(&page_id).await?);
+    status!(
+        "{} \"{}\" every {}s (Ctrl-C to stop)",
+        "Watching:".blue(),
+        title,
+        interval
+    );
+
+    let mut previous = load_snapshot(&page_id);
+    let mut first = true;
+    loop {
+        match take_snapshot(client, &page_id, previous.as_ref()).await {
+            Ok(Some(snapshot)) => {
+                if let Some(old) = &previous {
+                    report_changes(&title, &diff(old, &snapshot), exec);
+                } else {
+                    println!(
+                        "{} Snapshot saved ({} blocks)",
+                        "→".blue(),
+                        snapshot.blocks.len()
+                    );
+                }
+                if let Err(e) = save_snapshot(&page_id, &snapshot) {
+                    eprintln!("{} Could not save snapshot: {:#}", "⚠".yellow(), e);
+                }
+                previous = Some(snapshot);
+            }
+            Ok(None) => {}
+            // A bad page ID should fail fast; later hiccups shouldn't end the watch
+            Err(e) if first => return Err(e),
+            Err(e) => eprintln!("{} Check failed: {:#}", "⚠".yellow(), e),
+        }
+        first = false;
+
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+    }
+}
+
+/// Fetch a fresh snapshot, or `None` if the page wasn't edited since `previous`
+async fn take_snapshot(
+    client: &NotionClient,
+    page_id: &str,
+    previous: Option<&Snapshot>,
+) -> Result<Option<Snapshot>> {
+    let page = client.get_page(page_id).await?;
+    let edited = page["last_edited_time"].as_str().unwrap_or_default();
+    if previous.is_some_and(|p| p.last_edited_time == edited) {
+        return Ok(None);
+    }
+
+    let blocks: Vec<Block> = serde_json::from_value(serde_json::Value::Array(
+        client.get_blocks_recursive(page_id, None).await?,
+    ))
+    .context("Failed to parse blocks")?;
+    Ok(Some(Snapshot::new(edited, &blocks)))
+}
+
+/// Print a colored diff and run the `--exec` hook with the plain diff on stdin
+fn report_changes(title: &str, changes: &[Change], exec: Option<&str>) {
+    if changes.is_empty() {
+        return;
+    }
+
+    println!(
+        "\n{} \"{}\" changed ({} blocks)",
+        "✓".green(),
+        title,
+        changes.len()
+    );
+    for change in changes {
+        let line = change.to_string();
+        match change {
+            Change::Added(_) => println!("  {}", line.green()),
+            Change::Removed(_) => println!("  {}", line.red()),
+            Change::Changed { .. } => println!("  {}", line.yellow()),
+        }
+    }
+
+    if let Some(command) = exec {
+        let input: String = changes.iter().map(|c| format!("{}\n", c)).collect();
+        if let Err(e) = run_hook(command, &input) {
+            eprintln!("{} --exec failed: {:#}", "⚠".yellow(), e);
+        }
+    }
+}
 
-    let results = client.search(query, limit)?;
-    println!("{} {} results found\n", "✓".green(), results.len());
+/// Run a shell command with `input` on its stdin
+fn run_hook(command: &str, input: &str) -> Result<()> {
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let mut child = Command::new(shell)
+        .args([flag, command])
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run '{}'", command))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        bail!("'{}' exited with {}", command, status);
+    }
 
-    for item in &results {
-        let object_type = item
-            .get("object")
-            .and_then(|o| o.as_str())
-            .unwrap_or("unknown");
-        let id = item.get("id").and_then(|i| i.as_str()).unwrap_or("no-id");
-        let title = extract_title(item);
+    Ok(())
+}
 
-        println!("  {} [{}] {}", "•".cyan(), object_type, title);
-        println!("    ID: {}", id.dimmed());
+/// Run `send`, or queue `op` for `sync-queue` instead when `offline` is set
+/// or the API can't be reached
+async fn send_or_queue(
+    offline: bool,
+    op: Operation,
+    send: impl std::future::Future<Output = Result<()>>,
+) -> Result<()> {
+    if !offline {
+        match send.await {
+            Err(e) if is_unreachable(&e) => {
+                eprintln!("{} Notion is unreachable: {:#}", "⚠".yellow(), e);
+            }
+            result => return result,
+        }
     }
 
+    let pending = push_queue(&op)?;
+    println!(
+        "{} Queued {} ({} pending); run `notion-cli sync-queue` when back online",
+        "⏸".yellow(),
+        op.describe(),
+        pending
+    );
     Ok(())
 }
 
-pub fn handle_read(client: &NotionClient, page_id: &str) -> Result<()> {
-    println!("{} {}", "Reading page:".blue(), page_id);
+/// Replay queued offline changes in order. Stops at the first one that can't
+/// reach the API; failed operations stay queued unless `drop_failed` is set.
+pub async fn handle_sync_queue(client: &NotionClient, list: bool, drop_failed: bool) -> Result<()> {
+    let lines = load_queue();
+    if lines.is_empty() {
+        println!("{} Queue is empty", "✓".green());
+        return Ok(());
+    }
 
-    let page = client.get_page(page_id)?;
-    let blocks = client.get_blocks(page_id)?;
+    if list {
+        println!("{} {} pending", "Queue:".blue(), lines.len());
+        for line in &lines {
+            let label = Operation::parse(line).map_or_else(|_| line.clone(), |op| op.describe());
+            println!("  {} {}", "•".cyan(), label);
+        }
+        return Ok(());
+    }
 
-    let title = extract_title(&page);
-    println!("\n{} {}\n", "Title:".green(), title);
+    status!("{} {} queued operations", "Syncing:".blue(), lines.len());
+    let mut remaining = Vec::new();
+    let (mut sent, mut failed) = (0, 0);
+    for (i, line) in lines.iter().enumerate() {
+        let op = match Operation::parse(line) {
+            Ok(op) => op,
+            Err(e) => {
+                failed += 1;
+                println!("{} {:#}: {}", "✗".red(), e, line);
+                if !drop_failed {
+                    remaining.push(line.clone());
+                }
+                continue;
+            }
+        };
+        match op.execute(client).await {
+            Ok(_) => {
+                sent += 1;
+                println!("{} {}", "✓".green(), op.describe());
+            }
+            Err(e) if is_unreachable(&e) => {
+                remaining.extend(lines[i..].iter().cloned());
+                save_queue(&remaining)?;
+                bail!(
+                    "Notion is still unreachable; {} operations left in the queue",
+                    remaining.len()
+                );
+            }
+            Err(e) => {
+                failed += 1;
+                println!("{} {}: {:#}", "✗".red(), op.describe(), e);
+                if !drop_failed {
+                    remaining.push(line.clone());
+                }
+            }
+        }
+    }
+    save_queue(&remaining)?;
+
+    if failed > 0 {
+        let kept = if drop_failed {
+            "dropped"
+        } else {
+            "kept in the queue (use --drop-failed to discard)"
+        };
+        bail!("{} sent, {} failed and {}", sent, failed, kept);
+    }
+    println!("{} All {} queued operations sent", "✓".green(), sent);
+    Ok(())
+}
 
-    for block in &blocks {
-        print_block(block);
+pub async fn handle_batch(client: &NotionClient, file: &Path, keep_going: bool) -> Result<()> {
+    let script = if file == Path::new("-") {
+        let mut buf = String::new();
+        io::stdin()
+            .read_to_string(&mut buf)
+            .context("Failed to read batch from stdin")?;
+        buf
+    } else {
+        fs::read_to_string(file).with_context(|| format!("Failed to read {}", file.display()))?
+    };
+
+    let lines: Vec<(usize, &str)> = script
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .collect();
+    status!("{} {} operations", "Running batch:".blue(), lines.len());
+
+    let mut failed = 0;
+    for (done, (line_no, line)) in lines.iter().enumerate() {
+        let outcome = match Operation::parse(line) {
+            Ok(op) => op
+                .execute(client)
+                .await
+                .map(|result| (op.describe(), result)),
+            Err(e) => Err(e),
+        };
+
+        match outcome {
+            Ok((label, result)) => match result.get("id").and_then(|i| i.as_str()) {
+                Some(id) if result["object"] != "list" => {
+                    println!("{} line {}: {} → {}", "✓".green(), line_no, label, id)
+                }
+                _ => println!("{} line {}: {}", "✓".green(), line_no, label),
+            },
+            Err(e) => {
+                failed += 1;
+                println!("{} line {}: {:#}", "✗".red(), line_no, e);
+                if !keep_going {
+                    let skipped = lines.len() - done - 1;
+                    bail!(
+                        "Stopped at line {} ({} operations not run; use --keep-going to continue past errors)",
+                        line_no,
+                        skipped
+                    );
+                }
+            }
+        }
+    }
+
+    if failed > 0 {
+        bail!("{} of {} operations failed", failed, lines.len());
     }
+    println!("{} All {} operations succeeded", "✓".green(), lines.len());
 
     Ok(())
 }
 
-pub fn handle_create(
+pub async fn handle_duplicate(
     client: &NotionClient,
-    parent: &str,
-    title: &str,
-    content: Option<&str>,
+    page_id: &str,
+    new_parent: Option<&str>,
+    title: Option<&str>,
+    id_only: bool,
 ) -> Result<()> {
-    println!("{} \"{}\"", "Creating page:".blue(), title);
+    status!("{} {}", "Duplicating page:".blue(), page_id);
 
-    let result = client.create_page(parent, title, content)?;
+    let result = client.duplicate_page(page_id, new_parent, title).await?;
 
-    let id = result
+    let new_id = result
         .get("id")
         .and_then(|i| i.as_str())
         .unwrap_or("unknown");
+    if id_only {
+        println!("{}", new_id);
+        return Ok(());
+    }
     let url = result.get("url").and_then(|u| u.as_str());
 
-    println!("{} Page created!", "✓".green());
-    println!("  ID: {}", id);
+    println!("{} Page duplicated successfully!", "✓".green());
+    println!("  New ID: {}", new_id);
     if let Some(u) = url {
         println!("  URL: {}", u);
     }
@@ -66,258 +3325,687 @@ pub fn handle_create(
     Ok(())
 }
 
-pub fn handle_append(client: &NotionClient, page_id: &str, content: &str) -> Result<()> {
-    println!("{} {}", "Appending to:".blue(), page_id);
+/// Publish a Markdown directory under `root` (see [`sync`]), saving what was
+/// pushed to the state file after every change so an interrupted push picks
+/// up where it stopped
+pub async fn handle_push(
+    client: &NotionClient,
+    dir: &Path,
+    root: &str,
+    archive_removed: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let root = normalize_page_id(root)?;
+    let state_path = dir.join(sync::STATE_FILE);
+    let mut state = PushState::load(&state_path)?.unwrap_or_else(|| PushState::new(&root));
+    if state.root != root {
+        bail!(
+            "{} was pushed to page {}; delete {} to push it to another page",
+            dir.display(),
+            state.root,
+            state_path.display()
+        );
+    }
+    let pages = sync::scan(dir)?;
+    let actions = sync::plan(&state, &pages);
+    status!(
+        "{} {} → {}{}",
+        "Pushing:".blue(),
+        dir.display(),
+        root,
+        if dry_run { " (dry run)" } else { "" }
+    );
 
-    client.append_blocks(page_id, content)?;
-    println!("{} Content appended!", "✓".green());
+    let (mut created, mut updated, mut unchanged, mut archived, mut kept) = (0, 0, 0, 0, 0);
+    for action in actions {
+        match action {
+            PushAction::Create(page) => {
+                println!("  {} {}", "+".green(), page.path);
+                created += 1;
+                if dry_run {
+                    continue;
+                }
+                let parent = state
+                    .parent_id(&page.path)
+                    .with_context(|| format!("No page for the directory of {}", page.path))?
+                    .to_string();
+                let result = client.create_page(&parent, &page.title, None, None).await?;
+                let id = result["id"]
+                    .as_str()
+                    .context("Created page has no ID")?
+                    .to_string();
+                // Recorded without a hash until it has its content, so a
+                // failed append is retried as an update, not a second page
+                state.pages.insert(
+                    page.path.clone(),
+                    PushedPage {
+                        id: id.clone(),
+                        hash: None,
+                    },
+                );
+                state.save(&state_path)?;
+                if let Some(markdown) = &page.markdown {
+                    client
+                        .append_children(&id, &markdown_blocks(client, markdown).await?)
+                        .await
+                        .with_context(|| format!("Failed to push {}", page.path))?;
+                    state.pages.insert(
+                        page.path.clone(),
+                        PushedPage {
+                            id,
+                            hash: page.hash.clone(),
+                        },
+                    );
+                    state.save(&state_path)?;
+                }
+            }
+            PushAction::Update(page, id) => {
+                println!("  {} {}", "~".yellow(), page.path);
+                updated += 1;
+                if dry_run {
+                    continue;
+                }
+                client
+                    .update_page(&id, Some(&page.title), None, &[])
+                    .await?;
+                let markdown = page.markdown.as_deref().unwrap_or_default();
+                client
+                    .replace_children(&id, &markdown_blocks(client, markdown).await?)
+                    .await
+                    .with_context(|| format!("Failed to update {}", page.path))?;
+                state.pages.insert(
+                    page.path.clone(),
+                    PushedPage {
+                        id,
+                        hash: page.hash.clone(),
+                    },
+                );
+                state.save(&state_path)?;
+            }
+            PushAction::Unchanged(_) => unchanged += 1,
+            PushAction::Removed { path, id } if archive_removed => {
+                println!("  {} {}", "-".red(), path);
+                archived += 1;
+                if dry_run {
+                    continue;
+                }
+                client.delete_page(&id).await?;
+                state.pages.remove(&path);
+                state.save(&state_path)?;
+            }
+            PushAction::Removed { path, .. } => {
+                println!(
+                    "  {} {} (removed here, still in Notion)",
+                    "?".dimmed(),
+                    path
+                );
+                kept += 1;
+            }
+        }
+    }
 
+    println!(
+        "{} {} created, {} updated, {} unchanged, {} archived",
+        "✓".green(),
+        created,
+        updated,
+        unchanged,
+        archived
+    );
+    if kept > 0 {
+        println!(
+            "{} {} removed pages kept; --archive-removed archives them",
+            "ℹ".yellow(),
+            kept
+        );
+    }
     Ok(())
 }
 
-pub fn handle_append_code(
-    client: &NotionClient,
-    page_id: &str,
-    code: &str,
-    language: &str,
-) -> Result<()> {
-    println!(
-        "{} {} (language: {})",
-        "Appending code block to:".blue(),
-        page_id,
-        language
-    );
+/// Notion blocks for a Markdown document, with mentions resolved
+async fn markdown_blocks(client: &NotionClient, markdown: &str) -> Result<Vec<serde_json::Value>> {
+    let mut blocks: Vec<serde_json::Value> = markdown::parse_blocks(markdown)
+        .into_iter()
+        .filter_map(|block| match block {
+            MarkdownBlock::Block { block, .. } => Some(block),
+            MarkdownBlock::Comment(_) => None,
+        })
+        .collect();
+    client.resolve_mentions(&mut blocks).await?;
+    Ok(blocks)
+}
+
+/// What a pull wrote and kept
+struct Pull<'a> {
+    base: &'a Path,
+    written: usize,
+    unchanged: usize,
+    /// Every file a page under the root maps to
+    files: HashSet<PathBuf>,
+}
 
-    client.append_code_block(page_id, code, language)?;
-    println!("{} Code block appended!", "✓".green());
+/// Save the pages under `root` into `dir` as Markdown with front matter (see
+/// [`sync`]), mirroring the page hierarchy
+pub async fn handle_pull(client: &NotionClient, root: &str, dir: &Path, prune: bool) -> Result<()> {
+    status!("{} {} → {}", "Pulling:".blue(), root, dir.display());
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let (_, blocks) = client.get_page_content(root, None).await?;
+    let mut pull = Pull {
+        base: dir,
+        written: 0,
+        unchanged: 0,
+        files: HashSet::new(),
+    };
+    pull_children(client, &blocks, dir, &mut pull).await?;
+
+    let mut removed = 0;
+    for (path, _) in sync::pulled_files(dir)? {
+        if pull.files.contains(&path) {
+            continue;
+        }
+        let shown = path.strip_prefix(dir).unwrap_or(&path).display();
+        if prune {
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to delete {}", path.display()))?;
+            println!("  {} {}", "-".red(), shown);
+            removed += 1;
+        } else {
+            println!("  {} {} (no longer under the page)", "?".dimmed(), shown);
+        }
+    }
 
+    println!(
+        "{} {} written, {} unchanged, {} deleted",
+        "✓".green(),
+        pull.written,
+        pull.unchanged,
+        removed
+    );
     Ok(())
 }
 
-pub fn handle_append_bookmark(
+/// Write the sub-pages found in `blocks` into `dir`, and theirs into a
+/// directory named after each
+async fn pull_children(
     client: &NotionClient,
-    page_id: &str,
-    url: &str,
-    caption: Option<&str>,
+    blocks: &[serde_json::Value],
+    dir: &Path,
+    pull: &mut Pull<'_>,
 ) -> Result<()> {
-    println!("{} {}", "Appending bookmark to:".blue(), page_id);
-    println!("  URL: {}", url);
-    if let Some(cap) = caption {
-        println!("  Caption: {}", cap);
-    }
-
-    client.append_bookmark(page_id, url, caption)?;
-    println!("{} Bookmark appended!", "✓".green());
+    let mut stems = HashSet::new();
+    for node in PageNode::from_blocks(blocks) {
+        if node.object != "page" {
+            continue;
+        }
+        // Sibling pages with the same title get numbered files
+        let base = sync::file_stem(&node.title);
+        let stem = (1..)
+            .map(|n| match n {
+                1 => base.clone(),
+                n => format!("{} ({})", base, n),
+            })
+            .find(|stem| stems.insert(stem.to_lowercase()))
+            .unwrap_or(base);
+        let path = dir.join(format!("{}.md", stem));
+
+        let (page, blocks) = client.get_page_content(&node.id, None).await?;
+        let front = FrontMatter {
+            id: page["id"].as_str().unwrap_or(&node.id).to_string(),
+            last_edited_time: page["last_edited_time"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+        };
+        let pulled = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| FrontMatter::parse(&content).map(|(front, _)| front));
+        let shown = path.strip_prefix(pull.base).unwrap_or(&path).display();
+
+        if pulled.as_ref() == Some(&front) {
+            pull.unchanged += 1;
+        } else {
+            let parsed: Vec<Block> =
+                serde_json::from_value(serde_json::Value::Array(blocks.clone()))
+                    .context("Failed to parse blocks")?;
+            let document = format!(
+                "{}{}",
+                front.to_markdown(),
+                export::to_markdown(&extract_title(&page), &parsed, &HashMap::new())
+            );
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+            fs::write(&path, document)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            let mark = if pulled.is_some() {
+                "~".yellow()
+            } else {
+                "+".green()
+            };
+            println!("  {} {}", mark, shown);
+            pull.written += 1;
+        }
+        pull.files.insert(path);
 
+        Box::pin(pull_children(client, &blocks, &dir.join(&stem), pull)).await?;
+    }
     Ok(())
 }
 
-pub fn handle_update(
+pub async fn handle_create_from_template(
     client: &NotionClient,
-    page_id: &str,
+    template_id: &str,
+    parent_id: &str,
     title: Option<&str>,
-    icon: Option<&str>,
+    vars: &[(String, String)],
 ) -> Result<()> {
-    if title.is_none() && icon.is_none() {
-        bail!("At least one of --title or --icon must be specified");
-    }
-
-    println!("{} {}", "Updating page:".blue(), page_id);
+    status!("{} {}", "Creating from template:".blue(), template_id);
 
-    let result = client.update_page(page_id, title, icon)?;
+    let result = client
+        .create_from_template(template_id, parent_id, title, vars)
+        .await?;
 
-    let new_title = extract_title(&result);
-    println!("{} Page updated!", "✓".green());
-    println!("  Title: {}", new_title);
+    let new_id = result
+        .get("id")
+        .and_then(|i| i.as_str())
+        .unwrap_or("unknown");
+    let url = result.get("url").and_then(|u| u.as_str());
 
-    if let Some(icon_obj) = result.get("icon") {
-        if let Some(emoji) = icon_obj.get("emoji").and_then(|e| e.as_str()) {
-            println!("  Icon: {}", emoji);
-        }
+    println!("{} Page created from template!", "✓".green());
+    println!("  New ID: {}", new_id);
+    if let Some(u) = url {
+        println!("  URL: {}", u);
     }
 
     Ok(())
 }
 
-pub fn handle_delete(client: &NotionClient, page_id: &str) -> Result<()> {
-    println!("{} {}", "Archiving page:".blue(), page_id);
+pub async fn handle_comment_add(client: &NotionClient, page_id: &str, text: &str) -> Result<()> {
+    status!("{} {}", "Adding comment to:".blue(), page_id);
 
-    let result = client.delete_page(page_id)?;
+    let result = client.create_comment(page_id, text).await?;
 
-    let archived = result
-        .get("archived")
-        .and_then(|a| a.as_bool())
-        .unwrap_or(false);
-    if archived {
-        println!("{} Page archived (moved to trash)!", "✓".green());
-    } else {
-        println!("{} Page status unclear", "⚠".yellow());
-    }
+    let discussion = result
+        .get("discussion_id")
+        .and_then(|d| d.as_str())
+        .unwrap_or("unknown");
+    println!("{} Comment added!", "✓".green());
+    println!("  Discussion: {}", discussion);
 
     Ok(())
 }
 
-pub fn handle_query(
+pub async fn handle_comment_reply(
     client: &NotionClient,
-    database_id: &str,
-    filter: Option<&str>,
-    sort: Option<&str>,
-    direction: &str,
-    limit: usize,
+    discussion_id: &str,
+    text: &str,
 ) -> Result<()> {
-    println!("{} {}", "Querying database:".blue(), database_id);
+    status!("{} {}", "Replying to discussion:".blue(), discussion_id);
 
-    if let Some(f) = filter {
-        println!("  Filter: {}", f);
-    }
-    if let Some(s) = sort {
-        println!("  Sort: {} ({})", s, direction);
-    }
+    client.reply_comment(discussion_id, text).await?;
+    println!("{} Reply added!", "✓".green());
 
-    let results = client.query_database(database_id, filter, sort, direction, limit)?;
-    println!("{} {} results found\n", "✓".green(), results.len());
+    Ok(())
+}
 
-    for item in &results {
-        let id = item.get("id").and_then(|i| i.as_str()).unwrap_or("no-id");
-        let title = extract_title(item);
+pub async fn handle_comment_list(client: &NotionClient, page_id: &str) -> Result<()> {
+    status!("{} {}", "Listing comments on:".blue(), page_id);
 
-        println!("  {} {}", "•".cyan(), title);
-        println!("    ID: {}", id.dimmed());
+    let comments = client.list_comments(page_id).await?;
+    println!("{} {} comments found", "✓".green(), comments.len());
 
-        if let Some(props) = item.get("properties").and_then(|p| p.as_object()) {
-            for (key, value) in props.iter().take(3) {
-                if key == "title" || key == "Name" {
-                    continue;
-                }
-                if let Some(prop_value) = extract_property_value(value) {
-                    println!("    {}: {}", key.dimmed(), prop_value);
-                }
-            }
+    // Resolve each author once; integrations without user access fall back to the ID
+    let mut authors: HashMap<String, String> = HashMap::new();
+    let mut current_discussion = String::new();
+
+    for comment in &comments {
+        let discussion = comment
+            .get("discussion_id")
+            .and_then(|d| d.as_str())
+            .unwrap_or("unknown");
+        if discussion != current_discussion {
+            println!("\n  {} {}", "Discussion:".cyan(), discussion.dimmed());
+            current_discussion = discussion.to_string();
         }
-    }
 
-    Ok(())
-}
+        let author_id = comment
+            .get("created_by")
+            .and_then(|u| u.get("id"))
+            .and_then(|i| i.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+        if !authors.contains_key(&author_id) {
+            let name = match client.get_user(&author_id).await {
+                Ok(user) => user
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .unwrap_or(&author_id)
+                    .to_string(),
+                Err(_) => author_id.clone(),
+            };
+            authors.insert(author_id.clone(), name);
+        }
 
-pub fn handle_delete_block(client: &NotionClient, block_id: &str) -> Result<()> {
-    println!("{} {}", "Deleting block:".blue(), block_id);
+        let created = dates::format_date(
+            comment
+                .get("created_time")
+                .and_then(|t| t.as_str())
+                .unwrap_or(""),
+        );
+        let text = comment
+            .get("rich_text")
+            .and_then(|r| r.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|rt| rt.get("plain_text").and_then(|t| t.as_str()))
+                    .collect::<String>()
+            })
+            .unwrap_or_default();
 
-    client.delete_block(block_id)?;
-    println!("{} Block deleted!", "✓".green());
+        println!(
+            "    {} {} {}",
+            "•".cyan(),
+            authors[&author_id].bold(),
+            created.dimmed()
+        );
+        println!("      {}", text);
+    }
 
     Ok(())
 }
 
-pub fn handle_append_heading(
+/// Read a task database's schema and find its due date and completion properties
+async fn task_fields(client: &NotionClient, database_id: &str) -> Result<TaskFields> {
+    let (_, properties) = client.get_database_schema(database_id).await?;
+    Ok(TaskFields::detect(&properties))
+}
+
+pub async fn handle_todo_add(
     client: &NotionClient,
-    page_id: &str,
-    text: &str,
-    level: u8,
+    database_id: &str,
+    title: &str,
+    due: Option<&str>,
+    props: &[String],
 ) -> Result<()> {
-    println!(
-        "{} {} (level {})",
-        "Appending heading to:".blue(),
-        page_id,
-        level
-    );
-
-    client.append_heading(page_id, text, level)?;
-    println!("{} Heading appended!", "✓".green());
-
-    Ok(())
-}
+    let mut specs = props
+        .iter()
+        .map(|p| PropertySpec::parse(p))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut due_date = None;
+    if let Some(due) = due {
+        let fields = task_fields(client, database_id).await?;
+        let name = fields
+            .due
+            .context("The task database has no date property for --due")?;
+        let date = dates::parse_date(due)?;
+        specs.push(PropertySpec {
+            name,
+            prop_type: "date".to_string(),
+            value: date.clone(),
+        });
+        due_date = Some(date);
+    }
 
-pub fn handle_append_divider(client: &NotionClient, page_id: &str) -> Result<()> {
-    println!("{} {}", "Appending divider to:".blue(), page_id);
+    let result = client
+        .create_database_page(database_id, title, &specs, None, None)
+        .await?;
 
-    client.append_divider(page_id)?;
-    println!("{} Divider appended!", "✓".green());
+    match due_date {
+        Some(date) => println!("{} Added \"{}\" (due {})", "✓".green(), title, date),
+        None => println!("{} Added \"{}\"", "✓".green(), title),
+    }
+    println!("  ID: {}", result["id"].as_str().unwrap_or("unknown"));
 
     Ok(())
 }
 
-pub fn handle_append_list(client: &NotionClient, page_id: &str, items: &str) -> Result<()> {
-    println!("{} {}", "Appending list to:".blue(), page_id);
+pub async fn handle_todo_list(
+    client: &NotionClient,
+    database_id: &str,
+    status: Option<&str>,
+    all: bool,
+    limit: usize,
+) -> Result<()> {
+    let fields = task_fields(client, database_id).await?;
+    let filter = match (&fields.done, status) {
+        (Some(done), Some(status)) => Some(
+            done.status_filter(status)
+                .context("--status needs a status or select property, but tasks are checkboxes")?,
+        ),
+        (None, Some(_)) => bail!("The task database has no status property"),
+        (Some(done), None) if !all => Some(done.open_filter()),
+        _ => None,
+    };
+
+    let sorts: Vec<SortSpec> = fields
+        .due
+        .iter()
+        .map(|due| SortSpec {
+            key: due.clone(),
+            ascending: true,
+        })
+        .collect();
+    let tasks = client
+        .query_database(database_id, filter.as_ref(), &sorts, limit)
+        .await?;
+
+    let ids: Vec<String> = tasks
+        .iter()
+        .filter_map(|t| t["id"].as_str().map(String::from))
+        .collect();
+    save_todo_list(&ids)?;
+
+    if tasks.is_empty() {
+        println!("{} No tasks", "ℹ".yellow());
+        return Ok(());
+    }
 
-    let items: Vec<String> = items.split(',').map(|s| s.trim().to_string()).collect();
-    client.append_bulleted_list(page_id, &items)?;
-    println!("{} List appended ({} items)!", "✓".green(), items.len());
+    let today = dates::today().to_string();
+    let property = |task: &serde_json::Value, name: Option<&str>| {
+        name.and_then(|n| task["properties"].get(n))
+            .and_then(extract_property_value)
+            .unwrap_or_default()
+    };
+    let rows: Vec<(String, String, String, bool)> = tasks
+        .iter()
+        .map(|task| {
+            let due_start = fields
+                .due
+                .as_deref()
+                .and_then(|n| task["properties"][n]["date"]["start"].as_str())
+                .unwrap_or_default();
+            // ISO dates compare correctly as strings
+            let overdue = !due_start.is_empty() && due_start[..due_start.len().min(10)] < *today;
+            (
+                truncate(&extract_title(task), 50),
+                property(task, fields.done.as_ref().map(|d| d.name())),
+                property(task, fields.due.as_deref()),
+                overdue,
+            )
+        })
+        .collect();
+
+    let title_width = rows.iter().map(|r| r.0.chars().count()).max().unwrap_or(0);
+    let status_width = rows.iter().map(|r| r.1.chars().count()).max().unwrap_or(0);
+    let number_width = rows.len().to_string().len();
+    for (i, (title, status, due, overdue)) in rows.iter().enumerate() {
+        let due = if *overdue {
+            due.red().to_string()
+        } else {
+            due.dimmed().to_string()
+        };
+        println!(
+            "{:>nw$}  {:tw$}  {:sw$}  {}",
+            i + 1,
+            title,
+            status,
+            due,
+            nw = number_width,
+            tw = title_width,
+            sw = status_width
+        );
+    }
 
     Ok(())
 }
 
-pub fn handle_append_link(
-    client: &NotionClient,
-    page_id: &str,
-    prefix: Option<&str>,
-    link_text: &str,
-    url: &str,
-    suffix: Option<&str>,
-) -> Result<()> {
-    println!("{} {}", "Appending link to:".blue(), page_id);
+pub async fn handle_todo_done(client: &NotionClient, database_id: &str, task: &str) -> Result<()> {
+    let fields = task_fields(client, database_id).await?;
+    let done = fields
+        .done
+        .context("The task database has no status or checkbox property to mark tasks done")?;
 
-    let mut segments = Vec::new();
-    if let Some(p) = prefix {
-        segments.push(RichTextSegment::plain(p));
-    }
-    segments.push(RichTextSegment::link(link_text, url));
-    if let Some(s) = suffix {
-        segments.push(RichTextSegment::plain(s));
-    }
+    let listed = load_todo_list();
+    let task_id = match parse_index(task, listed.len())? {
+        Some(index) => listed[index].clone(),
+        None => normalize_page_id(task)?,
+    };
 
-    client.append_rich_text(page_id, &segments)?;
-    println!("{} Link appended!", "✓".green());
+    let result = client
+        .update_page(&task_id, None, None, &[done.done_spec()])
+        .await?;
+    println!("{} Done: {}", "✓".green(), extract_title(&result));
 
     Ok(())
 }
 
-pub fn handle_get_block_ids(client: &NotionClient, page_id: &str) -> Result<()> {
-    println!("{} {}", "Getting block IDs for:".blue(), page_id);
+pub async fn handle_users_list(client: &NotionClient, limit: usize) -> Result<()> {
+    status!("{}", "Listing workspace users".blue());
 
-    let blocks = client.get_blocks(page_id)?;
-    println!("{} {} blocks found\n", "✓".green(), blocks.len());
+    let users = client.list_users(limit).await?;
+    println!("{} {} users found\n", "✓".green(), users.len());
 
-    for block in &blocks {
-        let id = block.get("id").and_then(|i| i.as_str()).unwrap_or("no-id");
-        let block_type = block
+    for user in &users {
+        let id = user.get("id").and_then(|i| i.as_str()).unwrap_or("no-id");
+        let name = user
+            .get("name")
+            .and_then(|n| n.as_str())
+            .unwrap_or("(unnamed)");
+        let user_type = user
             .get("type")
             .and_then(|t| t.as_str())
             .unwrap_or("unknown");
-        println!("{}  [{}]", id, block_type);
+
+        println!("  {} [{}] {}", "•".cyan(), user_type, name);
+        if let Some(email) = user
+            .get("person")
+            .and_then(|p| p.get("email"))
+            .and_then(|e| e.as_str())
+        {
+            println!("    Email: {}", email);
+        }
+        println!("    ID: {}", id.dimmed());
     }
 
     Ok(())
 }
 
-pub fn handle_move(
-    client: &NotionClient,
-    page_id: &str,
-    new_parent: &str,
-    delete_original: bool,
-) -> Result<()> {
-    println!("{} {} → {}", "Moving page:".blue(), page_id, new_parent);
+pub async fn handle_whoami(client: &NotionClient) -> Result<()> {
+    let me = client.get_me().await?;
 
-    let result = client.move_page(page_id, new_parent, delete_original)?;
+    let name = me
+        .get("name")
+        .and_then(|n| n.as_str())
+        .unwrap_or("(unnamed)");
+    let id = me.get("id").and_then(|i| i.as_str()).unwrap_or("no-id");
+    let bot = me.get("bot");
 
-    let new_id = result
-        .get("id")
-        .and_then(|i| i.as_str())
-        .unwrap_or("unknown");
-    let url = result.get("url").and_then(|u| u.as_str());
+    println!("{} {}", "Integration:".green(), name);
+    println!("  ID: {}", id);
 
-    println!("{} Page moved successfully!", "✓".green());
-    println!("  New ID: {}", new_id);
-    if let Some(u) = url {
-        println!("  URL: {}", u);
+    if let Some(workspace) = bot
+        .and_then(|b| b.get("workspace_name"))
+        .and_then(|w| w.as_str())
+    {
+        println!("  Workspace: {}", workspace);
     }
-    if delete_original {
-        println!("  {} Original page archived", "→".blue());
-    } else {
+
+    if let Some(owner) = bot.and_then(|b| b.get("owner")) {
+        let owner_desc = match owner.get("type").and_then(|t| t.as_str()) {
+            Some("workspace") => "workspace".to_string(),
+            Some("user") => {
+                let user = owner.get("user");
+                let owner_name = user
+                    .and_then(|u| u.get("name"))
+                    .and_then(|n| n.as_str())
+                    .unwrap_or("unknown user");
+                match user
+                    .and_then(|u| u.get("person"))
+                    .and_then(|p| p.get("email"))
+                    .and_then(|e| e.as_str())
+                {
+                    Some(email) => format!("{} <{}>", owner_name, email),
+                    None => owner_name.to_string(),
+                }
+            }
+            _ => "unknown".to_string(),
+        };
+        println!("  Owner: {}", owner_desc);
+    }
+
+    Ok(())
+}
+
+/// Make a request with the configured API version and show which of the
+/// client's version-specific behaviours apply to it
+pub async fn handle_api_version_check(client: &NotionClient) -> Result<()> {
+    let version = client.api_version();
+    println!("{} {}", "API version:".blue(), version);
+    if version != DEFAULT_API_VERSION {
+        println!("  (default: {})", DEFAULT_API_VERSION);
+    }
+    if !KNOWN_API_VERSIONS.contains(&version) {
+        eprintln!(
+            "{} Not a version this CLI knows (known: {}); it is treated like the closest older one",
+            "⚠".yellow(),
+            KNOWN_API_VERSIONS.join(", ")
+        );
+    }
+
+    let me = client
+        .get_me()
+        .await
+        .with_context(|| format!("Request with API version {} failed", version))?;
+    println!(
+        "{} Accepted by the API (integration: {})",
+        "✓".green(),
+        me["name"].as_str().unwrap_or("(unnamed)")
+    );
+
+    if client.uses_data_sources() {
         println!(
-            "  {} Original page kept (use --delete to remove)",
-            "ℹ".yellow()
+            "  Queries: /data_sources/{{id}}/query (database IDs resolve to their data source)"
         );
+        println!("  New databases: schema on the initial data source, relations by data source");
+        println!("  New rows: parent data_source_id");
+    } else {
+        println!("  Queries: /databases/{{id}}/query");
+        println!("  New databases: schema as top-level properties, relations by database");
+        println!("  New rows: parent database_id");
     }
 
     Ok(())
 }
+
+/// Run the JSON-RPC daemon (see [`notion_cli_tool::server`])
+pub async fn handle_serve(client: &NotionClient, socket: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        // Spinners would interleave across concurrent requests
+        progress::set_enabled(false);
+        eprintln!(
+            "{} Listening on {} (Ctrl-C to stop)",
+            "→".blue(),
+            socket.display()
+        );
+        server::serve(client.clone(), socket).await?;
+        eprintln!("{} Server stopped", "✓".green());
+        Ok(())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (client, socket);
+        bail!("serve needs Unix domain sockets, which this platform doesn't have")
+    }
+}
@@ -0,0 +1,403 @@
+//! Dates as people type them and as they are shown.
+//!
+//! [`parse_date`] accepts ISO dates and date-times as well as relative ones
+//! (`tomorrow`, `next friday 9:30`, `in 3 days`) and returns the ISO 8601
+//! value Notion expects. Times without an offset are in the local time zone,
+//! or in the one the binary sets from `--tz` with [`set_time_zone`].
+//!
+//! [`format_date`] shows the ISO dates and timestamps Notion returns. Until
+//! the binary picks a [`DateDisplay`] (the `date_format` config key, `--utc`)
+//! they are only shortened, so library users get the values as stored.
+
+use anyhow::{bail, Context, Result};
+use chrono::format::StrftimeItems;
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime,
+    SecondsFormat, TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use std::fmt::{Display, Write};
+use std::sync::OnceLock;
+
+/// `date_format` when the config doesn't set one
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+static TIME_ZONE: OnceLock<Tz> = OnceLock::new();
+static DISPLAY: OnceLock<DateDisplay> = OnceLock::new();
+
+#[cfg(test)]
+thread_local! {
+    /// Display for the current test only (see [`with_date_display`])
+    static TEST_DISPLAY: std::cell::RefCell<Option<DateDisplay>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// How [`format_date`] shows dates and timestamps
+#[derive(Debug, Clone, PartialEq)]
+pub enum DateDisplay {
+    /// ISO 8601 in UTC (`2024-07-01T09:30:00Z`), for scripts
+    Utc,
+    /// A strftime `format` in the local (or `--tz`) time zone
+    Local { format: String },
+}
+
+/// Show dates with `display` for the rest of the process
+pub fn set_date_display(display: DateDisplay) {
+    let _ = DISPLAY.set(display);
+}
+
+/// Run `f` with dates shown with `display` on this thread, leaving the
+/// process-wide display to other tests
+#[cfg(test)]
+pub(crate) fn with_date_display<T>(display: DateDisplay, f: impl FnOnce() -> T) -> T {
+    TEST_DISPLAY.with(|d| d.replace(Some(display)));
+    let result = f();
+    TEST_DISPLAY.with(|d| d.replace(None));
+    result
+}
+
+/// The display set with [`set_date_display`], if any
+fn date_display() -> Option<DateDisplay> {
+    #[cfg(test)]
+    if let Some(display) = TEST_DISPLAY.with(|d| d.borrow().clone()) {
+        return Some(display);
+    }
+    DISPLAY.get().cloned()
+}
+
+/// Check a `date_format` value is a valid strftime format
+pub fn check_date_format(format: &str) -> Result<()> {
+    if StrftimeItems::new(format).parse().is_err() {
+        bail!(
+            "Invalid date_format '{}': use strftime fields like %Y-%m-%d %H:%M",
+            format
+        );
+    }
+    Ok(())
+}
+
+/// Show an ISO 8601 date or timestamp from the API as configured with
+/// [`set_date_display`]. Values that don't parse are returned as they are.
+pub fn format_date(value: &str) -> String {
+    match date_display().as_ref() {
+        None => shorten(value),
+        Some(DateDisplay::Utc) => match DateTime::parse_from_rfc3339(value) {
+            Ok(t) => t.to_utc().to_rfc3339_opts(SecondsFormat::Secs, true),
+            Err(_) => value.to_string(),
+        },
+        Some(DateDisplay::Local { format }) => match TIME_ZONE.get() {
+            Some(tz) => format_date_in(value, format, tz),
+            None => format_date_in(value, format, &Local),
+        },
+    }
+}
+
+/// Show `value` with a strftime `format`, converting date-times to `zone`.
+/// Dates without a time use the part of the format before its first time
+/// field.
+///
+/// ```
+/// use notion_cli_tool::dates::format_date_in;
+///
+/// let seoul: chrono_tz::Tz = "Asia/Seoul".parse().unwrap();
+/// let format = "%d.%m.%Y %H:%M";
+/// assert_eq!(format_date_in("2024-07-01T23:30:00.000Z", format, &seoul), "02.07.2024 08:30");
+/// assert_eq!(format_date_in("2024-07-01", format, &seoul), "01.07.2024");
+/// ```
+pub fn format_date_in<Z: TimeZone>(value: &str, format: &str, zone: &Z) -> String
+where
+    Z::Offset: Display,
+{
+    if let Ok(t) = DateTime::parse_from_rfc3339(value) {
+        return t.with_timezone(zone).format(format).to_string();
+    }
+    // A date can't fill fields like %z that may be left in the date part
+    let mut text = String::new();
+    match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        Ok(date) if write!(text, "{}", date.format(date_part(format))).is_ok() => text,
+        _ => value.to_string(),
+    }
+}
+
+/// The date part of a date-time format: up to its first time field
+fn date_part(format: &str) -> &str {
+    let mut end = format.len();
+    let mut fields = format.match_indices('%');
+    while let Some((start, _)) = fields.next() {
+        let spec = format[start + 1..].trim_start_matches(['-', '_', '0']);
+        if spec.starts_with(['H', 'k', 'I', 'l', 'M', 'S', 'p', 'P', 'R', 'T', 'X']) {
+            end = start;
+            break;
+        }
+        // `%%` is a literal percent sign
+        if spec.starts_with('%') {
+            fields.next();
+        }
+    }
+    let date = format[..end].trim_end_matches([' ', ',', 'T']);
+    date.strip_suffix(" at").unwrap_or(date)
+}
+
+/// `2024-07-01T09:30:00.000Z` → `2024-07-01 09:30`, as stored
+fn shorten(value: &str) -> String {
+    match value.split_once('T') {
+        Some((date, time)) => format!("{} {}", date, time.get(..5).unwrap_or(time)),
+        None => value.to_string(),
+    }
+}
+
+/// Use `tz` instead of the system time zone for the rest of the process
+pub fn set_time_zone(tz: Tz) {
+    let _ = TIME_ZONE.set(tz);
+}
+
+/// Today's date in the configured time zone
+pub fn today() -> NaiveDate {
+    match TIME_ZONE.get() {
+        Some(tz) => Utc::now().with_timezone(tz).date_naive(),
+        None => Local::now().date_naive(),
+    }
+}
+
+/// Parse a date value as of now, in the configured time zone.
+/// See [`parse_date_at`] for the accepted forms.
+pub fn parse_date(input: &str) -> Result<String> {
+    match TIME_ZONE.get() {
+        Some(tz) => parse_date_at(input, &Utc::now().with_timezone(tz)),
+        None => parse_date_at(input, &Local::now()),
+    }
+}
+
+/// Parse a date value relative to `now` into ISO 8601: `YYYY-MM-DD` for a
+/// day, or a date-time with its UTC offset when a time is given.
+///
+/// The day is anything [`parse_day`] takes, and may be followed by a time
+/// (`14:00`, `9:30:15`, `2pm`, `2:30pm`, optionally after `at`) with an
+/// optional offset (`Z`, `+09:00`). `now` is the current time. Times without
+/// an offset are in `now`'s time zone.
+///
+/// ```
+/// use chrono::{FixedOffset, TimeZone};
+/// use notion_cli_tool::dates::parse_date_at;
+///
+/// let seoul = FixedOffset::east_opt(9 * 3600).unwrap();
+/// let now = seoul.with_ymd_and_hms(2024, 7, 3, 10, 0, 0).unwrap();
+///
+/// assert_eq!(parse_date_at("tomorrow", &now).unwrap(), "2024-07-04");
+/// assert_eq!(parse_date_at("next friday 9:30", &now).unwrap(), "2024-07-05T09:30:00+09:00");
+/// assert_eq!(
+///     parse_date_at("2024-07-01 14:00-04:00", &now).unwrap(),
+///     "2024-07-01T14:00:00-04:00"
+/// );
+/// ```
+pub fn parse_date_at<Z: TimeZone>(input: &str, now: &DateTime<Z>) -> Result<String> {
+    let text = input.trim().to_lowercase();
+    if text == "now" {
+        return Ok(format_date_time(&now.fixed_offset()));
+    }
+
+    let today = now.date_naive();
+    let (day, time) = split_time(&text);
+    let Some((time, offset)) = time else {
+        return Ok(parse_day(input, today)?.to_string());
+    };
+    let day = if day.is_empty() {
+        today
+    } else {
+        parse_day(day, today)?
+    };
+
+    let local = NaiveDateTime::new(day, time);
+    let date_time = match offset {
+        Some(offset) => offset.from_local_datetime(&local).single(),
+        None => now
+            .timezone()
+            .from_local_datetime(&local)
+            .earliest()
+            .map(|t| t.fixed_offset()),
+    }
+    .with_context(|| format!("Invalid date '{}': that time doesn't exist", input))?;
+    Ok(format_date_time(&date_time))
+}
+
+fn format_date_time(date_time: &DateTime<FixedOffset>) -> String {
+    date_time.to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+/// Split `text` into its day and, when it ends in one, its time
+fn split_time(text: &str) -> (&str, Option<(NaiveTime, Option<FixedOffset>)>) {
+    // 2024-07-01T14:00 or 2024-07-01 14:00
+    if let (Some(day), Some(rest)) = (text.get(..10), text.get(11..)) {
+        if NaiveDate::parse_from_str(day, "%Y-%m-%d").is_ok()
+            && matches!(text.as_bytes()[10], b't' | b' ')
+        {
+            return (day, parse_time(rest.trim()));
+        }
+    }
+    let (day, time) = text.rsplit_once(' ').unwrap_or(("", text));
+    match parse_time(time) {
+        Some(time) => (
+            day.trim_end().trim_end_matches(" at").trim_end(),
+            Some(time),
+        ),
+        None => (text, None),
+    }
+}
+
+/// `14:00`, `14:00:30`, `2pm` or `2:30pm`, then an optional `z` or `±HH:MM`
+fn parse_time(text: &str) -> Option<(NaiveTime, Option<FixedOffset>)> {
+    let (time, offset) = if let Some(time) = text.strip_suffix('z') {
+        (time, Some(FixedOffset::east_opt(0)?))
+    } else {
+        match text.rfind(['+', '-']).filter(|&i| i > 0) {
+            Some(i) => (&text[..i], Some(parse_offset_suffix(&text[i..])?)),
+            None => (text, None),
+        }
+    };
+
+    let twelve_hour = time
+        .strip_suffix("am")
+        .map(|t| (t, 0))
+        .or_else(|| time.strip_suffix("pm").map(|t| (t, 12)));
+    let time = match twelve_hour {
+        Some((clock, shift)) => {
+            let (hour, minute) = clock.split_once(':').unwrap_or((clock, "0"));
+            let hour: u32 = hour.parse().ok().filter(|h| (1..=12).contains(h))?;
+            NaiveTime::from_hms_opt(hour % 12 + shift, minute.parse().ok()?, 0)?
+        }
+        None => ["%H:%M", "%H:%M:%S", "%H:%M:%S%.f"]
+            .iter()
+            .find_map(|format| NaiveTime::parse_from_str(time, format).ok())?,
+    };
+    Some((time, offset))
+}
+
+/// `+09:00`, `-0400` or `+09`
+fn parse_offset_suffix(text: &str) -> Option<FixedOffset> {
+    let sign = if text.starts_with('-') { -1 } else { 1 };
+    let digits = text[1..].replace(':', "");
+    if !matches!(digits.len(), 2 | 4) || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i32 = digits[..2].parse().ok()?;
+    let minutes: i32 = digits.get(2..).unwrap_or("0").parse().ok()?;
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Parse a day relative to `today`: `today`, `tomorrow`, `yesterday`,
+/// a weekday (`fri`, `next monday`: the next one after today), `next week`,
+/// `in 3 days` / `in 2 weeks`, `+3d` / `+2w`, or `YYYY-MM-DD`.
+pub fn parse_day(input: &str, today: NaiveDate) -> Result<NaiveDate> {
+    let text = input.trim().to_lowercase();
+    let text = text.as_str();
+
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return Ok(date);
+    }
+
+    let date = match text {
+        "today" => today,
+        "tomorrow" => today + Duration::days(1),
+        "yesterday" => today - Duration::days(1),
+        "next week" => today + Duration::weeks(1),
+        _ => {
+            let weekday = text.strip_prefix("next ").unwrap_or(text);
+            if let Ok(weekday) = weekday.parse::<Weekday>() {
+                let ahead = (weekday.num_days_from_monday() as i64
+                    - today.weekday().num_days_from_monday() as i64)
+                    .rem_euclid(7);
+                today + Duration::days(if ahead == 0 { 7 } else { ahead })
+            } else {
+                today
+                    + parse_offset(text).with_context(|| {
+                        format!(
+                            "Invalid date '{}': use YYYY-MM-DD, today, tomorrow, a weekday, \
+                         next week, 'in 3 days' or +3d, optionally followed by a time",
+                            input
+                        )
+                    })?
+            }
+        }
+    };
+    Ok(date)
+}
+
+/// `in 3 days`, `in 2 weeks`, `+3d`, `+2w`
+fn parse_offset(text: &str) -> Option<Duration> {
+    let (count, unit) = if let Some(rest) = text.strip_prefix("in ") {
+        rest.split_once(' ')?
+    } else {
+        let rest = text.strip_prefix('+')?;
+        rest.split_at(rest.find(|c: char| !c.is_ascii_digit())?)
+    };
+    let count: i64 = count.parse().ok()?;
+    match unit.trim() {
+        "d" | "day" | "days" => Some(Duration::days(count)),
+        "w" | "week" | "weeks" => Some(Duration::weeks(count)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_day() {
+        // A Wednesday
+        let today = NaiveDate::from_ymd_opt(2024, 7, 3).unwrap();
+        let date = |s: &str| parse_day(s, today).unwrap().to_string();
+
+        assert_eq!(date("today"), "2024-07-03");
+        assert_eq!(date("Tomorrow"), "2024-07-04");
+        assert_eq!(date("fri"), "2024-07-05");
+        assert_eq!(date("next monday"), "2024-07-08");
+        assert_eq!(date("wednesday"), "2024-07-10");
+        assert_eq!(date("in 3 days"), "2024-07-06");
+        assert_eq!(date("+2w"), "2024-07-17");
+        assert_eq!(date("2024-12-24"), "2024-12-24");
+        assert!(parse_day("someday", today).is_err());
+        assert!(parse_day("+3y", today).is_err());
+    }
+
+    #[test]
+    fn test_parse_date_times() {
+        let tz: Tz = "America/New_York".parse().unwrap();
+        let now = tz.with_ymd_and_hms(2024, 3, 8, 12, 0, 0).unwrap();
+        let date = |s: &str| parse_date_at(s, &now).unwrap();
+
+        assert_eq!(date("now"), "2024-03-08T12:00:00-05:00");
+        assert_eq!(date("17:45"), "2024-03-08T17:45:00-05:00");
+        // Daylight saving time starts on the 10th
+        assert_eq!(date("in 3 days at 2:30pm"), "2024-03-11T14:30:00-04:00");
+        assert_eq!(date("2024-07-01T14:00Z"), "2024-07-01T14:00:00+00:00");
+        assert_eq!(date("2024-07-01 12am"), "2024-07-01T00:00:00-04:00");
+        assert_eq!(date("tomorrow 09:15:30+0530"), "2024-03-09T09:15:30+05:30");
+        assert!(parse_date_at("2024-03-10 2:30", &now).is_err());
+        assert!(parse_date_at("tomorrow 25:00", &now).is_err());
+        assert!(parse_date_at("13pm", &now).is_err());
+    }
+
+    #[test]
+    fn test_format_date_in() {
+        let tz: Tz = "America/New_York".parse().unwrap();
+        let format = |value: &str, format: &str| format_date_in(value, format, &tz);
+
+        assert_eq!(
+            format("2024-07-01T09:30:00.000Z", DEFAULT_DATE_FORMAT),
+            "2024-07-01 05:30"
+        );
+        assert_eq!(
+            format("2024-07-01T09:30:00+09:00", "%b %-d, %Y at %-I:%M %p"),
+            "Jun 30, 2024 at 8:30 PM"
+        );
+        assert_eq!(
+            format("2024-07-01", "%b %-d, %Y at %-I:%M %p"),
+            "Jul 1, 2024"
+        );
+        assert_eq!(format("2024-07-01", "%Y-%m-%dT%H:%M"), "2024-07-01");
+        assert_eq!(format("soon", DEFAULT_DATE_FORMAT), "soon");
+        assert!(check_date_format("%Y-%m-%d %H:%M").is_ok());
+        assert!(check_date_format("%Y-%Q").is_err());
+    }
+}
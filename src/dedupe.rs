@@ -0,0 +1,174 @@
+//! Duplicate detection for database rows.
+//!
+//! [`find_duplicates`] groups fetched pages that share a key property value,
+//! and [`merge_properties`] fills the gaps of the row that is kept from its
+//! duplicates, for `db-dedupe`.
+
+use anyhow::{bail, Result};
+use serde_json::Value;
+
+use crate::client::COMPUTED_PROPERTIES;
+use crate::models::PropertyValue;
+
+/// Rows sharing one key value, oldest first
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateGroup<'a> {
+    /// The key as the oldest row has it
+    pub key: String,
+    pub rows: Vec<&'a Value>,
+}
+
+/// Group pages whose `key` property has the same value, ignoring case and
+/// surrounding whitespace. Only groups of two or more rows are returned,
+/// largest first; rows with an empty key are never duplicates.
+///
+/// ```
+/// use notion_cli_tool::dedupe::find_duplicates;
+/// use serde_json::json;
+///
+/// let row = |id: &str, created: &str, name: &str| json!({
+///     "id": id,
+///     "created_time": created,
+///     "properties": { "Name": { "type": "title", "title": [{ "plain_text": name }] } }
+/// });
+/// let rows = [
+///     row("b", "2024-02-01T00:00:00.000Z", "acme corp"),
+///     row("a", "2024-01-01T00:00:00.000Z", "Acme Corp"),
+///     row("c", "2024-03-01T00:00:00.000Z", "Globex"),
+/// ];
+///
+/// let groups = find_duplicates(&rows, "Name").unwrap();
+/// assert_eq!(groups.len(), 1);
+/// assert_eq!(groups[0].key, "Acme Corp");
+/// assert_eq!(groups[0].rows[0]["id"], "a");
+/// ```
+pub fn find_duplicates<'a>(rows: &'a [Value], key: &str) -> Result<Vec<DuplicateGroup<'a>>> {
+    if !rows.is_empty() && rows.iter().all(|r| r["properties"].get(key).is_none()) {
+        bail!("Property '{}' not found in the database rows", key);
+    }
+
+    let mut oldest_first: Vec<&Value> = rows.iter().collect();
+    oldest_first.sort_by_key(|r| r["created_time"].as_str().unwrap_or_default());
+
+    let mut groups: Vec<(String, DuplicateGroup)> = Vec::new();
+    for row in oldest_first {
+        let Some(value) = property(row, key).and_then(|p| p.display()) else {
+            continue;
+        };
+        let normalized = value.trim().to_lowercase();
+        if normalized.is_empty() {
+            continue;
+        }
+        match groups.iter_mut().find(|(n, _)| *n == normalized) {
+            Some((_, group)) => group.rows.push(row),
+            None => groups.push((
+                normalized,
+                DuplicateGroup {
+                    key: value.trim().to_string(),
+                    rows: vec![row],
+                },
+            )),
+        }
+    }
+
+    let mut duplicates: Vec<DuplicateGroup> = groups
+        .into_iter()
+        .map(|(_, group)| group)
+        .filter(|group| group.rows.len() > 1)
+        .collect();
+    duplicates.sort_by_key(|group| std::cmp::Reverse(group.rows.len()));
+    Ok(duplicates)
+}
+
+/// Property values to set on `keep` so it holds what its `duplicates` add:
+/// every writable property that is empty on `keep` takes the first non-empty
+/// value among them. An unchecked checkbox counts as empty. The result is an
+/// update payload's `properties`, empty when there is nothing to fill.
+///
+/// ```
+/// use notion_cli_tool::dedupe::merge_properties;
+/// use serde_json::json;
+///
+/// let keep = json!({ "properties": {
+///     "Email": { "type": "email", "email": null },
+///     "Phone": { "type": "phone_number", "phone_number": "555-0100" }
+/// }});
+/// let duplicate = json!({ "properties": {
+///     "Email": { "type": "email", "email": "hi@acme.test" },
+///     "Phone": { "type": "phone_number", "phone_number": "555-0199" }
+/// }});
+///
+/// let merged = merge_properties(&keep, &[&duplicate]);
+/// assert_eq!(merged, json!({ "Email": { "email": "hi@acme.test" } }));
+/// ```
+pub fn merge_properties(keep: &Value, duplicates: &[&Value]) -> Value {
+    let mut merged = serde_json::Map::new();
+    for (name, prop) in keep["properties"].as_object().into_iter().flatten() {
+        let Some(prop_type) = prop["type"].as_str() else {
+            continue;
+        };
+        if COMPUTED_PROPERTIES.contains(&prop_type) || has_value(keep, name) {
+            continue;
+        }
+        let found = duplicates
+            .iter()
+            .find(|row| row["properties"][name]["type"] == prop_type && has_value(row, name));
+        if let Some(row) = found {
+            merged.insert(
+                name.clone(),
+                serde_json::json!({ (prop_type): row["properties"][name][prop_type] }),
+            );
+        }
+    }
+    Value::Object(merged)
+}
+
+fn property(row: &Value, name: &str) -> Option<PropertyValue> {
+    row["properties"]
+        .get(name)
+        .and_then(|p| serde_json::from_value(p.clone()).ok())
+}
+
+fn has_value(row: &Value, name: &str) -> bool {
+    match property(row, name) {
+        Some(PropertyValue::Checkbox(checked)) => checked,
+        Some(value) => value.display().is_some(),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_empty_keys_and_merge_rules() {
+        let row = |id: &str, name: &str, done: bool, score: Value| {
+            json!({
+                "id": id,
+                "created_time": format!("2024-01-0{}T00:00:00.000Z", id),
+                "properties": {
+                    "Name": { "type": "rich_text", "rich_text": [{ "plain_text": name }] },
+                    "Done": { "type": "checkbox", "checkbox": done },
+                    "Score": { "type": "formula", "formula": { "type": "number", "number": score } }
+                }
+            })
+        };
+        let rows = [
+            row("1", " ", false, json!(null)),
+            row("2", "", false, json!(null)),
+            row("3", "Acme", false, json!(null)),
+            row("4", "ACME ", true, json!(5)),
+        ];
+
+        let groups = find_duplicates(&rows, "Name").unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].rows.len(), 2);
+
+        let merged = merge_properties(groups[0].rows[0], &groups[0].rows[1..]);
+        assert_eq!(merged, json!({ "Done": { "checkbox": true } }));
+
+        assert!(find_duplicates(&rows, "Missing").is_err());
+    }
+}
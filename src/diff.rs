@@ -0,0 +1,178 @@
+//! Block-level snapshots of a page and the differences between them.
+//!
+//! A [`Snapshot`] flattens a block tree into `(id, type, depth, text)` entries,
+//! small enough to store on disk between runs. [`diff`] matches blocks by ID,
+//! so edits show up as changes rather than a removal plus an addition.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::models::Block;
+
+/// One block in a snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotBlock {
+    pub id: String,
+    pub block_type: String,
+    pub depth: usize,
+    pub text: String,
+}
+
+/// The state of a page's content at one point in time
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub last_edited_time: String,
+    pub blocks: Vec<SnapshotBlock>,
+}
+
+impl Snapshot {
+    /// Flatten a block tree (depth-first, in page order)
+    pub fn new(last_edited_time: &str, blocks: &[Block]) -> Self {
+        let mut flat = Vec::new();
+        flatten(blocks, 0, &mut flat);
+        Self {
+            last_edited_time: last_edited_time.to_string(),
+            blocks: flat,
+        }
+    }
+}
+
+fn flatten(blocks: &[Block], depth: usize, out: &mut Vec<SnapshotBlock>) {
+    for block in blocks {
+        out.push(SnapshotBlock {
+            id: block.id.clone(),
+            block_type: block.block_type.clone(),
+            depth,
+            text: block.text(),
+        });
+        flatten(&block.children, depth + 1, out);
+    }
+}
+
+/// A difference between two snapshots
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added(SnapshotBlock),
+    Removed(SnapshotBlock),
+    Changed {
+        old: SnapshotBlock,
+        new: SnapshotBlock,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Added(b) => write!(f, "+ [{}] {}", b.block_type, b.text),
+            Change::Removed(b) => write!(f, "- [{}] {}", b.block_type, b.text),
+            Change::Changed { old, new } if old.block_type != new.block_type => write!(
+                f,
+                "~ [{} → {}] {} → {}",
+                old.block_type, new.block_type, old.text, new.text
+            ),
+            Change::Changed { old, new } => {
+                write!(f, "~ [{}] {} → {}", new.block_type, old.text, new.text)
+            }
+        }
+    }
+}
+
+/// Compare two snapshots. Removed blocks are listed at their old position,
+/// added and changed ones at their new position.
+pub fn diff(old: &Snapshot, new: &Snapshot) -> Vec<Change> {
+    let old_by_id: HashMap<&str, &SnapshotBlock> =
+        old.blocks.iter().map(|b| (b.id.as_str(), b)).collect();
+    let new_by_id: HashMap<&str, &SnapshotBlock> =
+        new.blocks.iter().map(|b| (b.id.as_str(), b)).collect();
+
+    let mut changes: Vec<(usize, Change)> = Vec::new();
+    for (i, block) in old.blocks.iter().enumerate() {
+        if !new_by_id.contains_key(block.id.as_str()) {
+            changes.push((i, Change::Removed(block.clone())));
+        }
+    }
+    for (i, block) in new.blocks.iter().enumerate() {
+        match old_by_id.get(block.id.as_str()) {
+            None => changes.push((i, Change::Added(block.clone()))),
+            Some(previous)
+                if previous.text != block.text || previous.block_type != block.block_type =>
+            {
+                changes.push((
+                    i,
+                    Change::Changed {
+                        old: (*previous).clone(),
+                        new: block.clone(),
+                    },
+                ))
+            }
+            Some(_) => {}
+        }
+    }
+
+    changes.sort_by_key(|(position, _)| *position);
+    changes.into_iter().map(|(_, change)| change).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(id: &str, text: &str) -> SnapshotBlock {
+        SnapshotBlock {
+            id: id.to_string(),
+            block_type: "paragraph".to_string(),
+            depth: 0,
+            text: text.to_string(),
+        }
+    }
+
+    fn snapshot(blocks: Vec<SnapshotBlock>) -> Snapshot {
+        Snapshot {
+            last_edited_time: String::new(),
+            blocks,
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = snapshot(vec![
+            block("a", "one"),
+            block("b", "two"),
+            block("c", "three"),
+        ]);
+        let new = snapshot(vec![
+            block("a", "one"),
+            block("c", "THREE"),
+            block("d", "four"),
+        ]);
+
+        let changes = diff(&old, &new);
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0], Change::Removed(block("b", "two")));
+        assert_eq!(changes[1].to_string(), "~ [paragraph] three → THREE");
+        assert_eq!(changes[2].to_string(), "+ [paragraph] four");
+
+        assert!(diff(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_flattens_children() {
+        let blocks: Vec<Block> = serde_json::from_value(serde_json::json!([{
+            "id": "t1",
+            "type": "toggle",
+            "toggle": { "rich_text": [{ "plain_text": "More" }] },
+            "children": [{
+                "id": "p1",
+                "type": "paragraph",
+                "paragraph": { "rich_text": [{ "plain_text": "hidden" }] }
+            }]
+        }]))
+        .unwrap();
+
+        let snapshot = Snapshot::new("2024-01-01T00:00:00.000Z", &blocks);
+        assert_eq!(snapshot.blocks.len(), 2);
+        assert_eq!(snapshot.blocks[1].depth, 1);
+        assert_eq!(snapshot.blocks[1].text, "hidden");
+    }
+}
@@ -0,0 +1,296 @@
+//! Round-trip editing of a page as Markdown.
+//!
+//! [`export`] writes a page's top-level blocks as a Markdown document, one
+//! chunk per block. Blocks that have no Markdown form become placeholder
+//! comments (`<!-- notion:<id> <type> -->`). After the document is edited,
+//! [`plan`] lines the chunks up with the original ones and works out the
+//! block operations that turn the page into the edited version: unchanged
+//! chunks are left alone, edited chunks of the same type are updated in place
+//! (keeping their children), and the rest are inserted or deleted.
+
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::client::NotionClient;
+use crate::markdown::{block_to_markdown, parse_blocks, MarkdownBlock};
+use crate::models::Block;
+
+const PLACEHOLDER_PREFIX: &str = "<!-- notion:";
+
+/// One top-level block of the original page, as exported
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pub id: String,
+    pub block_type: String,
+    /// The block's Markdown, or its placeholder comment
+    pub source: String,
+    /// Whether the block was exported as Markdown (placeholders can only be
+    /// kept or removed)
+    pub editable: bool,
+}
+
+/// A change to apply to the page
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditOp {
+    /// Replace the content of an existing block
+    Update {
+        id: String,
+        block: Value,
+    },
+    /// Insert new blocks after an existing block, or at the start when `None`
+    Insert {
+        after: Option<String>,
+        blocks: Vec<Value>,
+    },
+    Delete {
+        id: String,
+    },
+}
+
+/// Export top-level blocks as a Markdown document headed by `title`.
+/// Returns the document and the chunk each block was written as.
+pub fn export(title: &str, blocks: &[Block]) -> (String, Vec<Chunk>) {
+    let chunks: Vec<Chunk> = blocks
+        .iter()
+        .map(|block| match block_to_markdown(block) {
+            Some(source) => Chunk {
+                id: block.id.clone(),
+                block_type: block.block_type.clone(),
+                source,
+                editable: true,
+            },
+            None => Chunk {
+                id: block.id.clone(),
+                block_type: block.block_type.clone(),
+                source: format!(
+                    "{}{} {} -->",
+                    PLACEHOLDER_PREFIX, block.id, block.block_type
+                ),
+                editable: false,
+            },
+        })
+        .collect();
+
+    let header = format!(
+        "<!-- Editing \"{}\". Save and quit to apply. Nested content is kept with its \
+         parent block; notion: comments stand for blocks that can't be edited here \
+         (delete the line to delete the block). -->",
+        title.replace("-->", "")
+    );
+    let body: Vec<&str> = chunks.iter().map(|c| c.source.as_str()).collect();
+    let document = format!("{}\n\n{}\n", header, body.join("\n\n"));
+
+    (document, chunks)
+}
+
+/// An edited chunk: new Markdown, or a kept placeholder
+enum Edited {
+    Block { source: String, block: Value },
+    Placeholder { source: String },
+}
+
+impl Edited {
+    fn source(&self) -> &str {
+        match self {
+            Edited::Block { source, .. } | Edited::Placeholder { source } => source,
+        }
+    }
+}
+
+/// Work out the operations that turn `original` into the `edited` document
+pub fn plan(original: &[Chunk], edited: &str) -> Vec<EditOp> {
+    let edited: Vec<Edited> = parse_blocks(edited)
+        .into_iter()
+        .filter_map(|b| match b {
+            MarkdownBlock::Block { source, block } => Some(Edited::Block { source, block }),
+            // Other comments (like the header) are not content
+            MarkdownBlock::Comment(source) if source.starts_with(PLACEHOLDER_PREFIX) => {
+                Some(Edited::Placeholder { source })
+            }
+            MarkdownBlock::Comment(_) => None,
+        })
+        .collect();
+
+    let old: Vec<&str> = original.iter().map(|c| c.source.as_str()).collect();
+    let new: Vec<&str> = edited.iter().map(Edited::source).collect();
+    let matches = common_subsequence(&old, &new);
+
+    let mut ops = Vec::new();
+    let mut anchor: Option<String> = None;
+    let (mut i, mut j) = (0, 0);
+    for (mi, mj) in matches
+        .into_iter()
+        .chain(std::iter::once((old.len(), new.len())))
+    {
+        plan_gap(&original[i..mi], &edited[j..mj], &mut anchor, &mut ops);
+        if mi < old.len() {
+            anchor = Some(original[mi].id.clone());
+        }
+        (i, j) = (mi + 1, mj + 1);
+    }
+
+    ops
+}
+
+/// Turn a run of removed chunks and a run of new chunks (between two unchanged
+/// ones) into operations. New blocks update a removed block of the same type
+/// where one is left in order, and are inserted otherwise.
+fn plan_gap(
+    removed: &[Chunk],
+    added: &[Edited],
+    anchor: &mut Option<String>,
+    ops: &mut Vec<EditOp>,
+) {
+    let mut next_removed = 0;
+    let mut kept = vec![false; removed.len()];
+    let mut pending: Vec<Value> = Vec::new();
+
+    for item in added {
+        let Edited::Block { block, .. } = item else {
+            // A placeholder that doesn't line up with its block can't be restored
+            continue;
+        };
+        let block_type = block["type"].as_str().unwrap_or_default();
+        let reuse = removed[next_removed..]
+            .iter()
+            .position(|c| c.editable && c.block_type == block_type)
+            .map(|k| next_removed + k);
+
+        match reuse {
+            Some(k) => {
+                if !pending.is_empty() {
+                    ops.push(EditOp::Insert {
+                        after: anchor.clone(),
+                        blocks: std::mem::take(&mut pending),
+                    });
+                }
+                ops.push(EditOp::Update {
+                    id: removed[k].id.clone(),
+                    block: block.clone(),
+                });
+                kept[k] = true;
+                next_removed = k + 1;
+                *anchor = Some(removed[k].id.clone());
+            }
+            None => pending.push(block.clone()),
+        }
+    }
+    if !pending.is_empty() {
+        ops.push(EditOp::Insert {
+            after: anchor.clone(),
+            blocks: pending,
+        });
+    }
+
+    for (chunk, kept) in removed.iter().zip(kept) {
+        if !kept {
+            ops.push(EditOp::Delete {
+                id: chunk.id.clone(),
+            });
+        }
+    }
+}
+
+/// Index pairs of a longest common subsequence of `a` and `b`, in order
+fn common_subsequence(a: &[&str], b: &[&str]) -> Vec<(usize, usize)> {
+    let mut lengths = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = if a[i] == b[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+/// Apply planned operations to a page
+pub async fn apply(client: &NotionClient, page_id: &str, ops: &[EditOp]) -> Result<()> {
+    for op in ops {
+        match op {
+            EditOp::Update { id, block } => {
+                client.update_block(id, block).await?;
+            }
+            EditOp::Insert { after, blocks } => {
+                client
+                    .insert_children(page_id, after.as_deref(), blocks)
+                    .await?;
+            }
+            EditOp::Delete { id } => client.delete_block(id).await?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page() -> Vec<Block> {
+        serde_json::from_value(serde_json::json!([
+            { "id": "h", "type": "heading_1", "heading_1": { "rich_text": [{ "plain_text": "Title" }] } },
+            { "id": "p1", "type": "paragraph", "paragraph": { "rich_text": [{ "plain_text": "First" }] } },
+            { "id": "img", "type": "image", "image": { "type": "external", "external": { "url": "https://x/a.png" } } },
+            { "id": "p2", "type": "paragraph", "paragraph": { "rich_text": [{ "plain_text": "Second" }] } }
+        ]))
+        .unwrap()
+    }
+
+    fn kinds(ops: &[EditOp]) -> Vec<String> {
+        ops.iter()
+            .map(|op| match op {
+                EditOp::Update { id, .. } => format!("update {}", id),
+                EditOp::Insert { after, blocks } => format!(
+                    "insert {} after {}",
+                    blocks.len(),
+                    after.as_deref().unwrap_or("start")
+                ),
+                EditOp::Delete { id } => format!("delete {}", id),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_export_round_trip_is_a_no_op() {
+        let (document, chunks) = export("Notes", &page());
+        assert!(document.contains("<!-- notion:img image -->"));
+        assert!(plan(&chunks, &document).is_empty());
+    }
+
+    #[test]
+    fn test_plan_edits() {
+        let (document, chunks) = export("Notes", &page());
+        let edited = document
+            .replace("First", "First, edited")
+            .replace("<!-- notion:img image -->\n\n", "")
+            .replace("Second", "## New section\n\nSecond");
+
+        assert_eq!(
+            kinds(&plan(&chunks, &edited)),
+            ["update p1", "insert 1 after p1", "delete img"]
+        );
+    }
+
+    #[test]
+    fn test_plan_insert_at_start() {
+        let (document, chunks) = export("Notes", &page());
+        let edited = document.replace("# Title", "Intro\n\n# Title");
+        assert_eq!(kinds(&plan(&chunks, &edited)), ["insert 1 after start"]);
+    }
+}
@@ -0,0 +1,243 @@
+//! Errors returned by the Notion API.
+//!
+//! Failed responses carry a JSON body with a `code` and a `message`
+//! (`{"object":"error","status":404,"code":"object_not_found",...}`).
+//! [`NotionError`] groups those codes into categories, each with a hint for
+//! the user and an exit code for scripts. [`exit_code`] and [`to_json`] cover
+//! any error, including requests that never reached Notion.
+
+use std::fmt;
+
+use serde_json::{json, Value};
+
+/// Exit code for errors outside the categories below
+pub const EXIT_OTHER: i32 = 1;
+/// Exit code when Notion couldn't be reached (connection failed or timed out)
+pub const EXIT_NETWORK: i32 = 6;
+
+/// A failed Notion API response
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotionError {
+    pub kind: ErrorKind,
+    pub status: u16,
+    /// Notion's error code, e.g. `object_not_found` (empty if the body had none)
+    pub code: String,
+    pub message: String,
+    /// The `request_id` of the response, for reports to Notion support
+    pub request_id: Option<String>,
+}
+
+/// What went wrong, grouping Notion's error codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Missing or invalid API key (`unauthorized`)
+    Unauthorized,
+    /// The integration lacks a capability (`restricted_resource`)
+    RestrictedResource,
+    /// The object doesn't exist or isn't shared with the integration (`object_not_found`)
+    ObjectNotFound,
+    /// The request was malformed (`validation_error`, `invalid_json`, ...)
+    ValidationError,
+    /// The object was changed by another request at the same time (`conflict_error`)
+    Conflict,
+    /// Too many requests (`rate_limited`)
+    RateLimited,
+    /// Notion failed or is unavailable (`internal_server_error`, `service_unavailable`, ...)
+    ServerError,
+    /// Any other error code
+    Other,
+}
+
+impl NotionError {
+    /// Build an error from a response status and body. Falls back to the
+    /// status code when the body isn't a Notion error object.
+    pub fn from_response(status: u16, body: &str) -> Self {
+        let parsed: Value = serde_json::from_str(body).unwrap_or_default();
+        let code = parsed["code"].as_str().unwrap_or_default().to_string();
+        let message = match parsed["message"].as_str() {
+            Some(m) => m.to_string(),
+            None if body.trim().is_empty() => format!("HTTP {}", status),
+            None => format!("HTTP {}: {}", status, body.trim()),
+        };
+
+        let kind = match (code.as_str(), status) {
+            ("unauthorized", _) | ("", 401) => ErrorKind::Unauthorized,
+            ("restricted_resource", _) | ("", 403) => ErrorKind::RestrictedResource,
+            ("object_not_found", _) | ("", 404) => ErrorKind::ObjectNotFound,
+            (
+                "validation_error"
+                | "invalid_json"
+                | "invalid_request_url"
+                | "invalid_request"
+                | "missing_version",
+                _,
+            )
+            | ("", 400) => ErrorKind::ValidationError,
+            ("conflict_error", _) | ("", 409) => ErrorKind::Conflict,
+            ("rate_limited", _) | ("", 429) => ErrorKind::RateLimited,
+            (
+                "internal_server_error"
+                | "bad_gateway"
+                | "service_unavailable"
+                | "database_connection_unavailable"
+                | "gateway_timeout",
+                _,
+            )
+            | ("", 500..=599) => ErrorKind::ServerError,
+            _ => ErrorKind::Other,
+        };
+
+        NotionError {
+            kind,
+            status,
+            code,
+            message,
+            request_id: parsed["request_id"].as_str().map(String::from),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// What the user can do about it
+    pub fn hint(&self) -> Option<&'static str> {
+        match self.kind {
+            ErrorKind::Unauthorized => {
+                Some("Check your API key with `notion-cli config`, or set a new one with `notion-cli init`")
+            }
+            ErrorKind::RestrictedResource => Some(
+                "Enable the missing capability (read, update or insert content, comments, user info) in the integration's settings",
+            ),
+            ErrorKind::ObjectNotFound => Some(
+                "Check the ID, and share the page with your integration (••• → Connections → add the integration)",
+            ),
+            ErrorKind::Conflict => Some("The page was edited at the same time; try again"),
+            ErrorKind::RateLimited => {
+                Some("Try again later, or lower the request rate with --rate-limit")
+            }
+            ErrorKind::ServerError => {
+                Some("Notion is having trouble; try again later (status: https://status.notion.so)")
+            }
+            ErrorKind::ValidationError | ErrorKind::Other => None,
+        }
+    }
+
+    /// Process exit code for the error's category
+    pub fn exit_code(&self) -> i32 {
+        match self.kind {
+            ErrorKind::ObjectNotFound => 2,
+            ErrorKind::Unauthorized | ErrorKind::RestrictedResource => 3,
+            ErrorKind::ValidationError => 4,
+            ErrorKind::RateLimited => 5,
+            ErrorKind::Conflict => 7,
+            ErrorKind::ServerError => 8,
+            ErrorKind::Other => EXIT_OTHER,
+        }
+    }
+}
+
+impl fmt::Display for NotionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ErrorKind::Other if !self.code.is_empty() => {
+                write!(f, "{} ({})", self.message, self.code)
+            }
+            _ => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for NotionError {}
+
+/// Exit code for any error: the API error's category, [`EXIT_NETWORK`] when
+/// the request never got an answer, else [`EXIT_OTHER`]
+pub fn exit_code(e: &anyhow::Error) -> i32 {
+    if let Some(api) = e.downcast_ref::<NotionError>() {
+        return api.exit_code();
+    }
+    if is_network_error(e) {
+        return EXIT_NETWORK;
+    }
+    EXIT_OTHER
+}
+
+/// The error as a JSON object for scripts (`--error-format json`):
+/// `{"error": {"message", "exit_code", "hint", ...}}`, with the `status`,
+/// Notion `code` and `request_id` of API errors.
+///
+/// ```
+/// use notion_cli_tool::{error, NotionError};
+///
+/// let body = r#"{"object":"error","status":404,"code":"object_not_found","message":"Not found","request_id":"4b9a"}"#;
+/// let e = anyhow::Error::from(NotionError::from_response(404, body)).context("Failed to read page");
+/// let json = error::to_json(&e);
+/// assert_eq!(json["error"]["code"], "object_not_found");
+/// assert_eq!(json["error"]["request_id"], "4b9a");
+/// assert_eq!(json["error"]["exit_code"], 2);
+/// assert_eq!(json["error"]["message"], "Failed to read page: Not found");
+/// ```
+pub fn to_json(e: &anyhow::Error) -> Value {
+    let mut error = json!({
+        "message": format!("{:#}", e),
+        "exit_code": exit_code(e),
+    });
+    if let Some(api) = e.downcast_ref::<NotionError>() {
+        error["status"] = json!(api.status);
+        error["code"] = json!(api.code);
+        error["request_id"] = json!(api.request_id);
+        error["hint"] = json!(api.hint());
+    } else if is_network_error(e) {
+        error["code"] = json!("network_error");
+    }
+    json!({ "error": error })
+}
+
+/// Whether a request in `e` failed before Notion answered
+fn is_network_error(e: &anyhow::Error) -> bool {
+    e.chain().any(|cause| {
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|r| r.is_connect() || r.is_timeout() || r.is_request())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_response() {
+        let body = r#"{"object":"error","status":404,"code":"object_not_found","message":"Could not find page with ID: abc."}"#;
+        let err = NotionError::from_response(404, body);
+        assert_eq!(err.kind, ErrorKind::ObjectNotFound);
+        assert_eq!(err.message(), "Could not find page with ID: abc.");
+        assert_eq!(err.exit_code(), 2);
+        assert!(err.hint().unwrap().contains("share the page"));
+
+        let body = r#"{"object":"error","status":400,"code":"validation_error","message":"body failed validation"}"#;
+        assert_eq!(
+            NotionError::from_response(400, body).kind,
+            ErrorKind::ValidationError
+        );
+
+        let err = NotionError::from_response(502, "<html>Bad Gateway</html>");
+        assert_eq!(err.message(), "HTTP 502: <html>Bad Gateway</html>");
+        assert_eq!(err.exit_code(), 8);
+
+        let body = r#"{"code":"something_new","message":"Nope"}"#;
+        let err = NotionError::from_response(418, body);
+        assert_eq!(err.to_string(), "Nope (something_new)");
+        assert_eq!(err.exit_code(), 1);
+    }
+
+    #[test]
+    fn test_found_through_context() {
+        let err = anyhow::Error::from(NotionError::from_response(409, ""))
+            .context("Failed to update block")
+            .context("Failed to apply edits");
+        assert_eq!(err.downcast_ref::<NotionError>().unwrap().exit_code(), 7);
+        assert_eq!(exit_code(&err), 7);
+        assert_eq!(exit_code(&anyhow::anyhow!("Invalid property")), EXIT_OTHER);
+    }
+}
@@ -0,0 +1,688 @@
+//! Whole-page export as Markdown or HTML.
+//!
+//! [`walk`] goes through a block tree once, grouping consecutive list items
+//! into lists, and hands each block (with its children already written) to a
+//! [`Writer`]. [`Markdown`] and [`Html`] only decide what each block looks
+//! like. Unlike [`crate::edit::export`], the output is meant to be read, not
+//! applied back: nested content, tables and media are all included.
+
+use std::collections::HashMap;
+use syntect::html::{styled_line_to_highlighted_html, IncludeBackground};
+
+use crate::markdown::from_rich_text;
+use crate::models::{Block, BlockKind, MediaContent, RichText};
+use crate::render::highlight_lines;
+
+/// Stylesheet inlined into HTML exports
+const CSS: &str = r#"
+body { margin: 0; background: #fff; color: #37352f; }
+article { max-width: 720px; margin: 0 auto; padding: 48px 24px;
+  font: 16px/1.6 -apple-system, BlinkMacSystemFont, "Segoe UI", Helvetica, Arial, sans-serif; }
+h1, h2, h3, h4 { line-height: 1.3; margin: 1.6em 0 0.4em; }
+h1.page-title { font-size: 2.4em; margin-top: 0; }
+p { margin: 0.4em 0; }
+a { color: inherit; text-decoration: underline; text-decoration-color: #aaa; }
+code { font: 85% SFMono-Regular, Menlo, Consolas, monospace; background: #f1f1ef;
+  color: #eb5757; padding: 0.15em 0.3em; border-radius: 3px; }
+pre { background: #f7f6f3; padding: 16px; border-radius: 4px; overflow-x: auto; }
+pre code { background: none; color: #37352f; padding: 0; font-size: 85%; }
+blockquote { margin: 0.6em 0; padding-left: 14px; border-left: 3px solid #37352f; }
+hr { border: none; border-top: 1px solid #e9e9e7; margin: 1.2em 0; }
+table { border-collapse: collapse; margin: 0.6em 0; }
+th, td { border: 1px solid #e9e9e7; padding: 6px 10px; text-align: left; vertical-align: top; }
+th { background: #f7f6f3; }
+figure { margin: 0.8em 0; } figure img, figure video { max-width: 100%; }
+figcaption { color: #787774; font-size: 0.9em; }
+details { margin: 0.4em 0; } summary { cursor: pointer; }
+.children { margin-left: 1.5em; }
+"#;
+
+/// How one output format writes blocks
+pub trait Writer {
+    /// Output for `block`, given the output for its children (empty for
+    /// tables, whose rows are in `block.children`)
+    fn block(&mut self, block: &Block, children: String) -> String;
+    /// Output for a run of list items, given the output for each item
+    fn list(&mut self, ordered: bool, items: Vec<String>) -> String;
+    /// Join the output of sibling blocks
+    fn join(&mut self, parts: Vec<String>) -> String;
+}
+
+/// Write a block tree with `writer`
+pub fn walk(blocks: &[Block], writer: &mut impl Writer) -> String {
+    let mut parts = Vec::new();
+    let mut i = 0;
+    while i < blocks.len() {
+        let ordered = match blocks[i].kind {
+            BlockKind::BulletedListItem(_) => false,
+            BlockKind::NumberedListItem(_) => true,
+            _ => {
+                let block = &blocks[i];
+                let children = match block.kind {
+                    BlockKind::Table(_) => String::new(),
+                    _ => walk(&block.children, writer),
+                };
+                parts.push(writer.block(block, children));
+                i += 1;
+                continue;
+            }
+        };
+
+        let mut items = Vec::new();
+        while let Some(block) = blocks.get(i).filter(|b| is_list_item(b, ordered)) {
+            let children = walk(&block.children, writer);
+            items.push(writer.block(block, children));
+            i += 1;
+        }
+        parts.push(writer.list(ordered, items));
+    }
+    writer.join(parts)
+}
+
+fn is_list_item(block: &Block, ordered: bool) -> bool {
+    match block.kind {
+        BlockKind::BulletedListItem(_) => !ordered,
+        BlockKind::NumberedListItem(_) => ordered,
+        _ => false,
+    }
+}
+
+/// URLs of the images, videos, audio, PDFs and files in a block tree, each
+/// once, in document order
+pub fn asset_urls(blocks: &[Block]) -> Vec<String> {
+    fn collect(blocks: &[Block], urls: &mut Vec<String>) {
+        for block in blocks {
+            if let Some(url) = media(block).and_then(MediaContent::url) {
+                if !urls.iter().any(|u| u == url) {
+                    urls.push(url.to_string());
+                }
+            }
+            collect(&block.children, urls);
+        }
+    }
+    let mut urls = Vec::new();
+    collect(blocks, &mut urls);
+    urls
+}
+
+/// File name for the `n`th downloaded asset: its name in the URL path,
+/// prefixed with `n` so assets with the same name don't collide
+///
+/// ```
+/// use notion_cli_tool::export::asset_file_name;
+///
+/// let url = "https://prod-files.s3.amazonaws.com/abc/Screen%20Shot.png?X-Amz-Expires=3600";
+/// assert_eq!(asset_file_name(3, url), "3-Screen_Shot.png");
+/// ```
+pub fn asset_file_name(n: usize, url: &str) -> String {
+    let name = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.path_segments()?.next_back().map(String::from))
+        .map(|segment| percent_decode(&segment))
+        .unwrap_or_default();
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let name = name.trim_start_matches('.');
+    if name.is_empty() {
+        format!("{}-asset", n)
+    } else {
+        format!("{}-{}", n, name)
+    }
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn media(block: &Block) -> Option<&MediaContent> {
+    match &block.kind {
+        BlockKind::Image(m)
+        | BlockKind::Video(m)
+        | BlockKind::Pdf(m)
+        | BlockKind::Audio(m)
+        | BlockKind::File(m) => Some(m),
+        _ => None,
+    }
+}
+
+/// Link text for a file-like block: its caption, else its name, else the URL
+fn media_label(m: &MediaContent, url: &str) -> String {
+    let caption = crate::models::plain_text(&m.caption);
+    if !caption.is_empty() {
+        caption
+    } else {
+        m.name.clone().unwrap_or_else(|| url.to_string())
+    }
+}
+
+/// Web link for a page or database
+fn notion_url(id: &str) -> String {
+    format!("https://www.notion.so/{}", id.replace('-', ""))
+}
+
+/// Markdown output. Media links point at `assets` (URL to local path)
+/// where given, else at their original URLs.
+pub struct Markdown<'a> {
+    pub assets: &'a HashMap<String, String>,
+}
+
+impl Markdown<'_> {
+    fn link<'u>(&'u self, url: &'u str) -> &'u str {
+        self.assets.get(url).map_or(url, String::as_str)
+    }
+}
+
+impl Writer for Markdown<'_> {
+    fn block(&mut self, block: &Block, children: String) -> String {
+        let text = |rich_text: &[RichText]| from_rich_text(rich_text);
+        let own = match &block.kind {
+            BlockKind::Paragraph(t) => text(&t.rich_text),
+            BlockKind::Heading1(t) => format!("# {}", text(&t.rich_text)),
+            BlockKind::Heading2(t) => format!("## {}", text(&t.rich_text)),
+            BlockKind::Heading3(t) => format!("### {}", text(&t.rich_text)),
+            BlockKind::BulletedListItem(t) | BlockKind::NumberedListItem(t) => {
+                // The marker is added by `list`; nested items follow directly
+                let own = text(&t.rich_text);
+                return if children.is_empty() {
+                    own
+                } else {
+                    format!("{}\n{}", own, children)
+                };
+            }
+            BlockKind::Toggle(t) => {
+                return format!(
+                    "<details>\n<summary>{}</summary>\n\n{}\n\n</details>",
+                    text(&t.rich_text),
+                    children
+                )
+            }
+            BlockKind::Quote(t) => {
+                let content = self.join(vec![text(&t.rich_text), children]);
+                return content
+                    .lines()
+                    .map(|l| {
+                        if l.is_empty() {
+                            ">".to_string()
+                        } else {
+                            format!("> {}", l)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+            }
+            BlockKind::Code(c) => {
+                let code = crate::models::plain_text(&c.rich_text);
+                // A fence longer than any backtick run inside the code
+                let longest = code.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+                let fence = "`".repeat(longest.max(2) + 1);
+                let language = if c.language == "plain text" {
+                    ""
+                } else {
+                    &c.language
+                };
+                format!("{}{}\n{}\n{}", fence, language, code, fence)
+            }
+            BlockKind::Divider => "---".to_string(),
+            BlockKind::Image(m) => {
+                let url = m.url().unwrap_or_default();
+                let alt = crate::models::plain_text(&m.caption);
+                format!("![{}]({})", alt, self.link(url))
+            }
+            BlockKind::Video(m) | BlockKind::Pdf(m) | BlockKind::Audio(m) | BlockKind::File(m) => {
+                let url = m.url().unwrap_or_default();
+                format!("[{}]({})", media_label(m, url), self.link(url))
+            }
+            BlockKind::Embed(e) => {
+                let caption = crate::models::plain_text(&e.caption);
+                let label = if caption.is_empty() { &e.url } else { &caption };
+                format!("[{}]({})", label, e.url)
+            }
+            BlockKind::Table(_) => table_block_markdown(&block.children),
+            BlockKind::TableRow(r) => r
+                .cells
+                .iter()
+                .map(|c| text(c))
+                .collect::<Vec<_>>()
+                .join(" | "),
+            BlockKind::ChildPage(c) | BlockKind::ChildDatabase(c) => {
+                format!("[{}]({})", c.title, notion_url(&block.id))
+            }
+            BlockKind::TableOfContents | BlockKind::Breadcrumb => String::new(),
+            BlockKind::Unsupported(kind) => format!("<!-- unsupported block: {} -->", kind),
+        };
+        self.join(vec![own, children])
+    }
+
+    fn list(&mut self, ordered: bool, items: Vec<String>) -> String {
+        items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let marker = if ordered {
+                    format!("{}. ", i + 1)
+                } else {
+                    "- ".to_string()
+                };
+                let indent = " ".repeat(marker.len());
+                let mut lines = item.lines();
+                let mut out = format!("{}{}", marker, lines.next().unwrap_or_default());
+                for line in lines {
+                    out.push('\n');
+                    if !line.is_empty() {
+                        out.push_str(&indent);
+                        out.push_str(line);
+                    }
+                }
+                out
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn join(&mut self, parts: Vec<String>) -> String {
+        parts
+            .into_iter()
+            .filter(|p| !p.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// A table block as a GFM table; the first row is the header row, as GFM
+/// requires one
+fn table_block_markdown(rows: &[Block]) -> String {
+    let mut rows = rows.iter().filter_map(|row| match &row.kind {
+        BlockKind::TableRow(r) => Some(
+            r.cells
+                .iter()
+                .map(|c| from_rich_text(c))
+                .collect::<Vec<_>>(),
+        ),
+        _ => None,
+    });
+    match rows.next() {
+        Some(header) => markdown_table(&header, &rows.collect::<Vec<_>>()),
+        None => String::new(),
+    }
+}
+
+/// A GFM table. Pipes and line breaks in cells are escaped, and short rows
+/// are padded to the widest one.
+///
+/// ```
+/// use notion_cli_tool::export::markdown_table;
+///
+/// let table = markdown_table(&["Name".into()], &[vec!["a|b".into()]]);
+/// assert_eq!(table, "| Name |\n| --- |\n| a\\|b |");
+/// ```
+pub fn markdown_table(headers: &[String], rows: &[Vec<String>]) -> String {
+    let width = rows
+        .iter()
+        .map(Vec::len)
+        .chain([headers.len()])
+        .max()
+        .unwrap_or(0);
+    if width == 0 {
+        return String::new();
+    }
+
+    let line = |cells: &[String]| {
+        let mut cells: Vec<String> = cells
+            .iter()
+            .map(|c| c.replace('|', "\\|").replace('\n', "<br>"))
+            .collect();
+        cells.resize(width, String::new());
+        format!("| {} |", cells.join(" | "))
+    };
+    let mut lines = vec![line(headers), line(&vec!["---".to_string(); width])];
+    lines.extend(rows.iter().map(|r| line(r)));
+    lines.join("\n")
+}
+
+/// HTML output. Media points at `assets` (URL to local path) where given,
+/// else at their original URLs.
+pub struct Html<'a> {
+    pub assets: &'a HashMap<String, String>,
+}
+
+impl Html<'_> {
+    fn src(&self, url: &str) -> String {
+        escape_html(self.assets.get(url).map_or(url, String::as_str))
+    }
+}
+
+impl Writer for Html<'_> {
+    fn block(&mut self, block: &Block, children: String) -> String {
+        let nested = if children.is_empty() {
+            String::new()
+        } else {
+            format!("\n<div class=\"children\">\n{}\n</div>", children)
+        };
+        match &block.kind {
+            BlockKind::Paragraph(t) => format!("<p>{}</p>{}", html_rich_text(&t.rich_text), nested),
+            BlockKind::Heading1(t) => {
+                format!("<h2>{}</h2>{}", html_rich_text(&t.rich_text), nested)
+            }
+            BlockKind::Heading2(t) => {
+                format!("<h3>{}</h3>{}", html_rich_text(&t.rich_text), nested)
+            }
+            BlockKind::Heading3(t) => {
+                format!("<h4>{}</h4>{}", html_rich_text(&t.rich_text), nested)
+            }
+            BlockKind::BulletedListItem(t) | BlockKind::NumberedListItem(t) => {
+                // `list` wraps this in <li>; nested lists follow directly
+                format!("{}{}", html_rich_text(&t.rich_text), children)
+            }
+            BlockKind::Toggle(t) => format!(
+                "<details>\n<summary>{}</summary>\n{}\n</details>",
+                html_rich_text(&t.rich_text),
+                children
+            ),
+            BlockKind::Quote(t) => format!(
+                "<blockquote>{}{}</blockquote>",
+                html_rich_text(&t.rich_text),
+                children
+            ),
+            BlockKind::Code(c) => {
+                let code = crate::models::plain_text(&c.rich_text);
+                format!(
+                    "<pre><code class=\"language-{}\">{}</code></pre>",
+                    escape_html(&c.language.replace(' ', "-")),
+                    highlight_html(&code, &c.language)
+                )
+            }
+            BlockKind::Divider => "<hr>".to_string(),
+            BlockKind::Image(m) => {
+                let url = m.url().unwrap_or_default();
+                let caption = html_rich_text(&m.caption);
+                let alt = escape_html(&crate::models::plain_text(&m.caption));
+                let figcaption = if caption.is_empty() {
+                    String::new()
+                } else {
+                    format!("<figcaption>{}</figcaption>", caption)
+                };
+                format!(
+                    "<figure><img src=\"{}\" alt=\"{}\">{}</figure>",
+                    self.src(url),
+                    alt,
+                    figcaption
+                )
+            }
+            BlockKind::Video(m) | BlockKind::Audio(m) => {
+                let url = m.url().unwrap_or_default();
+                let tag = if matches!(block.kind, BlockKind::Video(_)) {
+                    "video"
+                } else {
+                    "audio"
+                };
+                format!(
+                    "<figure><{} controls src=\"{}\"></{}><figcaption><a href=\"{}\">{}</a></figcaption></figure>",
+                    tag,
+                    self.src(url),
+                    tag,
+                    self.src(url),
+                    escape_html(&media_label(m, url))
+                )
+            }
+            BlockKind::Pdf(m) | BlockKind::File(m) => {
+                let url = m.url().unwrap_or_default();
+                format!(
+                    "<p class=\"file\">📎 <a href=\"{}\">{}</a></p>",
+                    self.src(url),
+                    escape_html(&media_label(m, url))
+                )
+            }
+            BlockKind::Embed(e) => {
+                let caption = crate::models::plain_text(&e.caption);
+                let label = if caption.is_empty() { &e.url } else { &caption };
+                format!(
+                    "<p class=\"embed\"><a href=\"{}\">{}</a></p>",
+                    escape_html(&e.url),
+                    escape_html(label)
+                )
+            }
+            BlockKind::Table(t) => {
+                html_table(&block.children, t.has_column_header, t.has_row_header)
+            }
+            BlockKind::TableRow(r) => {
+                let cells: Vec<String> = r.cells.iter().map(|c| html_rich_text(c)).collect();
+                format!("<p>{}</p>", cells.join(" | "))
+            }
+            BlockKind::ChildPage(c) | BlockKind::ChildDatabase(c) => {
+                let icon = if matches!(block.kind, BlockKind::ChildPage(_)) {
+                    "📄"
+                } else {
+                    "🗃"
+                };
+                format!(
+                    "<p class=\"child-page\">{} <a href=\"{}\">{}</a></p>{}",
+                    icon,
+                    notion_url(&block.id),
+                    escape_html(&c.title),
+                    nested
+                )
+            }
+            BlockKind::TableOfContents | BlockKind::Breadcrumb => String::new(),
+            BlockKind::Unsupported(kind) => {
+                format!("<!-- unsupported block: {} -->", kind.replace("--", ""))
+            }
+        }
+    }
+
+    fn list(&mut self, ordered: bool, items: Vec<String>) -> String {
+        let tag = if ordered { "ol" } else { "ul" };
+        let items: Vec<String> = items
+            .into_iter()
+            .map(|item| format!("<li>{}</li>", item))
+            .collect();
+        format!("<{}>\n{}\n</{}>", tag, items.join("\n"), tag)
+    }
+
+    fn join(&mut self, parts: Vec<String>) -> String {
+        parts
+            .into_iter()
+            .filter(|p| !p.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn html_table(rows: &[Block], column_header: bool, row_header: bool) -> String {
+    let mut out = String::from("<table>\n");
+    let rows = rows.iter().filter_map(|row| match &row.kind {
+        BlockKind::TableRow(r) => Some(&r.cells),
+        _ => None,
+    });
+    for (i, cells) in rows.enumerate() {
+        out.push_str("<tr>");
+        for (j, cell) in cells.iter().enumerate() {
+            let tag = if (column_header && i == 0) || (row_header && j == 0) {
+                "th"
+            } else {
+                "td"
+            };
+            out.push_str(&format!("<{}>{}</{}>", tag, html_rich_text(cell), tag));
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>");
+    out
+}
+
+/// Inline HTML for rich text: formatting as tags, links as anchors
+///
+/// ```
+/// use notion_cli_tool::export::html_rich_text;
+/// use notion_cli_tool::models::RichText;
+///
+/// let rich_text: Vec<RichText> = serde_json::from_value(serde_json::json!([
+///     { "plain_text": "a < " },
+///     { "plain_text": "b", "annotations": { "bold": true }, "href": "https://x.dev" }
+/// ]))
+/// .unwrap();
+/// assert_eq!(html_rich_text(&rich_text), "a &lt; <a href=\"https://x.dev\"><strong>b</strong></a>");
+/// ```
+pub fn html_rich_text(rich_text: &[RichText]) -> String {
+    let mut out = String::new();
+    for item in rich_text {
+        let mut html = escape_html(&item.text()).replace('\n', "<br>");
+        let a = &item.annotations;
+        for (on, tag) in [
+            (a.code, "code"),
+            (a.bold, "strong"),
+            (a.italic, "em"),
+            (a.strikethrough, "s"),
+            (a.underline, "u"),
+        ] {
+            if on {
+                html = format!("<{}>{}</{}>", tag, html, tag);
+            }
+        }
+        if let Some(href) = &item.href {
+            html = format!("<a href=\"{}\">{}</a>", escape_html(href), html);
+        }
+        out.push_str(&html);
+    }
+    out
+}
+
+/// A code block's `code` as HTML with inline colors from syntect's light
+/// `InspiredGitHub` theme, or only escaped when there's no syntax for its
+/// `language`.
+///
+/// ```
+/// use notion_cli_tool::export::highlight_html;
+///
+/// let html = highlight_html("let x = \"<a>\";", "rust");
+/// assert!(html.contains("<span style=\"color:"));
+/// assert!(html.contains("&lt;a&gt;"));
+/// assert_eq!(highlight_html("if <b>", "plain text"), "if &lt;b&gt;");
+/// ```
+pub fn highlight_html(code: &str, language: &str) -> String {
+    highlight_lines(code, language, "InspiredGitHub", |ranges| {
+        styled_line_to_highlighted_html(ranges, IncludeBackground::No).ok()
+    })
+    .map(|lines| lines.concat())
+    .unwrap_or_else(|| escape_html(code))
+}
+
+/// Escape `&`, `<`, `>` and quotes
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// A Markdown document: the title as a heading, then the blocks
+pub fn to_markdown(title: &str, blocks: &[Block], assets: &HashMap<String, String>) -> String {
+    let body = walk(blocks, &mut Markdown { assets });
+    format!("# {}\n\n{}\n", title, body)
+}
+
+/// A standalone HTML document with inline CSS
+pub fn to_html(title: &str, blocks: &[Block], assets: &HashMap<String, String>) -> String {
+    let body = walk(blocks, &mut Html { assets });
+    let title = escape_html(title);
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<article>\n\
+         <h1 class=\"page-title\">{}</h1>\n{}\n</article>\n</body>\n</html>\n",
+        title, CSS, title, body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn blocks(value: serde_json::Value) -> Vec<Block> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn item(kind: &str, text: &str, children: serde_json::Value) -> serde_json::Value {
+        json!({
+            "id": text, "type": kind, "has_children": children.as_array().is_some_and(|c| !c.is_empty()),
+            kind: { "rich_text": [{ "plain_text": text }] },
+            "children": children
+        })
+    }
+
+    #[test]
+    fn test_lists_are_grouped_and_nested() {
+        let tree = blocks(json!([
+            item(
+                "bulleted_list_item",
+                "one",
+                json!([item("numbered_list_item", "sub", json!([]))])
+            ),
+            item("bulleted_list_item", "two", json!([])),
+            item("paragraph", "after", json!([])),
+        ]));
+        let assets = HashMap::new();
+
+        assert_eq!(
+            walk(&tree, &mut Markdown { assets: &assets }),
+            "- one\n  1. sub\n- two\n\nafter"
+        );
+        assert_eq!(
+            walk(&tree, &mut Html { assets: &assets }),
+            "<ul>\n<li>one<ol>\n<li>sub</li>\n</ol></li>\n<li>two</li>\n</ul>\n<p>after</p>"
+        );
+    }
+
+    #[test]
+    fn test_assets_use_local_paths() {
+        let tree = blocks(json!([{
+            "id": "i", "type": "image",
+            "image": { "caption": [], "file": { "url": "https://files.example/a.png?sig=1" } }
+        }]));
+        let urls = asset_urls(&tree);
+        assert_eq!(urls, ["https://files.example/a.png?sig=1"]);
+
+        let assets = HashMap::from([(urls[0].clone(), "page_files/1-a.png".to_string())]);
+        let html = to_html("T", &tree, &assets);
+        assert!(html.contains("<img src=\"page_files/1-a.png\""));
+        assert_eq!(
+            walk(&tree, &mut Markdown { assets: &assets }),
+            "![](page_files/1-a.png)"
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Commit history for `git-log`.
+//!
+//! Runs `git log` in a working tree and turns its commits into Notion
+//! blocks (a bulleted list or a table) for release-notes pages.
+
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+
+use crate::blocks;
+use crate::client::RichTextSegment;
+
+/// Field separator in the `git log` format; never appears in commit data
+const SEPARATOR: char = '\u{1f}';
+
+/// Table rows per table block, header included (the API's nesting limit)
+const MAX_TABLE_ROWS: usize = 100;
+
+/// One commit from `git log`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Commit {
+    pub hash: String,
+    pub author: String,
+    /// Author date, `YYYY-MM-DD`
+    pub date: String,
+    /// First line of the message
+    pub subject: String,
+}
+
+impl Commit {
+    /// The abbreviated hash shown in lists and tables
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(7)]
+    }
+}
+
+/// Commits reachable from `HEAD`, newest first. With `since` (a tag, branch
+/// or hash) only commits after it are listed; merges are skipped.
+pub fn log(repo: Option<&Path>, since: Option<&str>, limit: Option<usize>) -> Result<Vec<Commit>> {
+    let mut command = Command::new("git");
+    if let Some(repo) = repo {
+        command.arg("-C").arg(repo);
+    }
+    command.args([
+        "log",
+        "--no-merges",
+        "--date=short",
+        "--format=%H%x1f%an%x1f%ad%x1f%s",
+    ]);
+    if let Some(limit) = limit {
+        command.arg(format!("--max-count={}", limit));
+    }
+    match since {
+        Some(since) => command.arg(format!("{}..HEAD", since)),
+        None => command.arg("HEAD"),
+    };
+    command.arg("--");
+
+    let output = command.output().context("Failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git log failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(parse_log(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `git log` output in the format [`log`] requests
+pub fn parse_log(output: &str) -> Vec<Commit> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, SEPARATOR);
+            Some(Commit {
+                hash: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                subject: fields.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// One bulleted list item per commit: `` `abc1234` Subject — Author ``
+pub fn list_blocks(commits: &[Commit]) -> Vec<Value> {
+    commits
+        .iter()
+        .map(|commit| {
+            let hash = RichTextSegment {
+                code: true,
+                ..RichTextSegment::plain(commit.short_hash())
+            };
+            let author = RichTextSegment {
+                italic: true,
+                ..RichTextSegment::plain(&commit.author)
+            };
+            blocks::rich_block(
+                "bulleted_list_item",
+                &[
+                    hash,
+                    RichTextSegment::plain(&format!(" {} — ", commit.subject)),
+                    author,
+                ],
+            )
+        })
+        .collect()
+}
+
+/// Tables with Hash, Author, Date and Message columns, split so each stays
+/// within the API's row limit
+pub fn table_blocks(commits: &[Commit]) -> Vec<Value> {
+    let header = ["Hash", "Author", "Date", "Message"]
+        .map(String::from)
+        .to_vec();
+    commits
+        .chunks(MAX_TABLE_ROWS - 1)
+        .map(|chunk| {
+            let rows: Vec<Vec<String>> = std::iter::once(header.clone())
+                .chain(chunk.iter().map(|c| {
+                    vec![
+                        c.short_hash().to_string(),
+                        c.author.clone(),
+                        c.date.clone(),
+                        c.subject.clone(),
+                    ]
+                }))
+                .collect();
+            blocks::table(&rows, true)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_and_blocks() {
+        let output = "0123456789abcdef\u{1f}Ada\u{1f}2026-01-02\u{1f}Fix a | b\n\
+                      fedcba9876543210\u{1f}Linus\u{1f}2026-01-01\u{1f}Initial commit\n";
+        let commits = parse_log(output);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].short_hash(), "0123456");
+        assert_eq!(commits[0].subject, "Fix a | b");
+        assert_eq!(commits[1].author, "Linus");
+
+        let list = list_blocks(&commits);
+        let text = &list[0]["bulleted_list_item"]["rich_text"];
+        assert_eq!(text[0]["text"]["content"], "0123456");
+        assert_eq!(text[0]["annotations"]["code"], true);
+        assert_eq!(text[1]["text"]["content"], " Fix a | b — ");
+
+        let tables = table_blocks(&commits);
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0]["table"]["table_width"], 4);
+        assert_eq!(tables[0]["table"]["children"].as_array().unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,51 @@
+//! Library interface for notion-cli.
+//!
+//! Embed the same Notion client, block builders and renderers the
+//! `notion-cli` binary uses, without shelling out to it.
+//!
+//! ```no_run
+//! use notion_cli_tool::{blocks, NotionClient};
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let client = NotionClient::new("ntn_xxx".to_string(), 30)?;
+//!
+//! for page in client.search("meeting notes", 10).await? {
+//!     println!("{}", notion_cli_tool::render::extract_title(&page));
+//! }
+//!
+//! client
+//!     .append_children(
+//!         "2fb74f32-4ab9-80f5-83df-c93c885072e7",
+//!         &[blocks::heading(2, "Summary"), blocks::paragraph("All done.")],
+//!     )
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod batch;
+pub mod blocks;
+pub mod client;
+pub mod clip;
+pub mod dates;
+pub mod dedupe;
+pub mod diff;
+pub mod edit;
+pub mod error;
+pub mod export;
+pub mod git;
+pub mod markdown;
+pub mod models;
+pub mod oauth;
+pub mod progress;
+pub mod render;
+pub mod replay;
+pub mod server;
+pub mod stats;
+pub mod sync;
+pub mod todo;
+pub mod usage;
+pub mod utils;
+
+pub use client::{NotionClient, PropertySpec, RichTextSegment};
+pub use error::{ErrorKind, NotionError};
@@ -1,144 +1,1114 @@
 mod cli;
-mod client;
 mod commands;
-mod render;
-mod utils;
+mod tui;
 
-use anyhow::Result;
-use clap::Parser;
+use anyhow::{bail, Context, Result};
+use clap::{CommandFactory, Parser};
+use clap_complete::Shell;
 use colored::Colorize;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
 
-use cli::{Cli, Commands};
-use client::NotionClient;
+use cli::{
+    ApiVersionCommand, AuthCommand, CacheCommand, Cli, Commands, CommentCommand, ConfigCommand,
+    DatabaseTarget, DbPropCommand, InitArgs, PageTarget, TodoCommand, UsersCommand,
+};
 use commands::*;
-use utils::{get_api_key, get_config_path, load_config, save_config, Config};
+use notion_cli_tool::client::NetworkOptions;
+use notion_cli_tool::dates::{check_date_format, DateDisplay, DEFAULT_DATE_FORMAT};
+use notion_cli_tool::usage::ApiStats;
+use notion_cli_tool::utils::{
+    delete_keyring_token, get_api_key, get_config_api_key, get_config_path,
+    get_profile_api_version, load_config, load_config_for_update, load_id_cache,
+    load_keyring_token, normalize_page_id, save_config, save_keyring_token, Config, Profile,
+    Workspace, DEFAULT_CONCURRENCY, DEFAULT_RATE_LIMIT, DEFAULT_TIMEOUT_SECS, MAX_RETRIES,
+};
+use notion_cli_tool::{dates, error, git, oauth, progress, ErrorKind, NotionClient, NotionError};
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+/// Exit code for invalid command-line arguments, as for rejected requests
+const EXIT_USAGE: i32 = 4;
+/// Exit code when no API key is configured
+const EXIT_AUTH: i32 = 3;
+
+/// Whether errors are printed as JSON (`--error-format json` / `--output json`)
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::try_parse().unwrap_or_else(|e| {
+        if !e.use_stderr() {
+            // --help and --version
+            e.exit();
+        }
+        let _ = e.print();
+        std::process::exit(EXIT_USAGE);
+    });
+    let error_format = cli.output.as_deref().unwrap_or(&cli.error_format);
+    JSON_ERRORS.store(error_format == "json", Ordering::Relaxed);
+    progress::set_enabled(!cli.quiet && io::stdout().is_terminal());
+    let quiet = cli.quiet || cli.command.id_only();
+    set_quiet(quiet);
+    colored::control::set_override(use_color(&cli.color));
+    if let Some(tz) = cli.tz {
+        dates::set_time_zone(tz);
+    }
 
     // Handle commands that don't need API key first
     match &cli.command {
-        Commands::Init { api_key } => {
-            return handle_init(api_key.clone());
+        Commands::Init(args)
+        | Commands::Auth {
+            action: AuthCommand::Login(args),
+        } => {
+            let result = handle_init(args, cli.profile.as_deref(), cli.timeout).await;
+            if let Err(e) = result {
+                report_error(&e);
+            }
+            return Ok(());
+        }
+        Commands::Auth {
+            action: AuthCommand::Logout,
+        } => {
+            return handle_logout(cli.profile.as_deref());
+        }
+        Commands::Auth {
+            action: AuthCommand::Status,
+        } => {
+            if let Err(e) = handle_auth_status(&cli).await {
+                report_error(&e);
+            }
+            return Ok(());
+        }
+        Commands::Auth {
+            action: AuthCommand::List,
+        } => {
+            if let Err(e) = handle_auth_list(&cli).await {
+                report_error(&e);
+            }
+            return Ok(());
+        }
+        Commands::Config { action } => {
+            let profile = cli.profile.as_deref();
+            let result = match action {
+                None => handle_config_with_cli_key(cli.api_key.as_deref(), profile),
+                Some(ConfigCommand::Get { key }) => handle_config_get(profile, key),
+                Some(ConfigCommand::Set { key, value }) => {
+                    handle_config_set(profile, key, Some(value))
+                }
+                Some(ConfigCommand::Unset { key }) => handle_config_set(profile, key, None),
+            };
+            if let Err(e) = result {
+                report_error(&e);
+            }
+            return Ok(());
+        }
+        Commands::Cache {
+            action: CacheCommand::Clear,
+        } => {
+            if let Err(e) = handle_cache_clear() {
+                report_error(&e);
+            }
+            return Ok(());
         }
-        Commands::Config => {
-            return handle_config_with_cli_key(cli.api_key.as_deref());
+        Commands::Completions { shell } => {
+            handle_completions(*shell);
+            return Ok(());
+        }
+        Commands::CompletePages => {
+            handle_complete_pages();
+            return Ok(());
         }
         _ => {}
     }
 
-    // Get API key with priority: CLI arg > env var > config file
-    let api_key = match get_api_key(cli.api_key.as_deref()) {
-        Ok(key) => key,
-        Err(e) => {
-            eprintln!("{} {}", "✗".red(), e);
-            std::process::exit(1);
+    let config = load_config();
+    let date_format = config.date_format.as_deref().unwrap_or(DEFAULT_DATE_FORMAT);
+    if let Err(e) = check_date_format(date_format) {
+        report_error(&e);
+    }
+    dates::set_date_display(if cli.utc {
+        DateDisplay::Utc
+    } else {
+        DateDisplay::Local {
+            format: date_format.to_string(),
         }
+    });
+
+    let profile = match config.profile(cli.profile.as_deref()) {
+        Ok(p) => p,
+        Err(e) => report_error(&e),
+    };
+
+    // Get API key with priority: CLI arg > env var > profile > config file
+    let api_key = match get_api_key(cli.api_key.as_deref(), cli.profile.as_deref()) {
+        Ok(key) => key,
+        Err(e) => exit_with(&e, EXIT_AUTH),
     };
 
-    let client = match NotionClient::new(api_key, cli.timeout) {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("{} Failed to initialize client: {}", "✗".red(), e);
-            std::process::exit(1);
+    let timeout = cli
+        .timeout
+        .or(config.timeout)
+        .unwrap_or(DEFAULT_TIMEOUT_SECS);
+    let max_retries = cli
+        .max_retries
+        .or(config.max_retries)
+        .unwrap_or(MAX_RETRIES);
+    let rate_limit = cli
+        .rate_limit
+        .or(config.rate_limit)
+        .unwrap_or(DEFAULT_RATE_LIMIT);
+    let concurrency = cli
+        .concurrency
+        .map(usize::from)
+        .or(config.concurrency)
+        .unwrap_or(DEFAULT_CONCURRENCY);
+    let stats = cli.stats.then(ApiStats::new);
+    let client = match NotionClient::new_with_network(api_key, timeout, &network_options(&config)) {
+        Ok(c) => c
+            .with_api_version(
+                cli.api_version
+                    .clone()
+                    .unwrap_or_else(|| get_profile_api_version(profile)),
+            )
+            .with_max_retries(max_retries)
+            .with_rate_limit(rate_limit)
+            .with_concurrency(concurrency)
+            .with_page_cache(config.cache.unwrap_or(false) && !cli.no_cache)
+            .with_quiet(quiet),
+        Err(e) => report_error(&e.context("Failed to initialize client")),
+    };
+    let client = match &stats {
+        Some(stats) => client.with_stats(stats.clone()),
+        None => client,
+    };
+
+    let default_parent = config.default_parent(profile);
+    let todo_database = config.todo_database(profile);
+    let started = Instant::now();
+    let result = run(&client, cli.command, default_parent, todo_database).await;
+    if let Some(stats) = &stats {
+        eprintln!("{}", stats.report(started.elapsed()).dimmed());
+    }
+
+    if let Err(e) = result {
+        report_error(&e);
+    }
+
+    Ok(())
+}
+
+/// Proxy and CA bundle from the config file
+fn network_options(config: &Config) -> NetworkOptions {
+    NetworkOptions {
+        proxy: config.proxy.clone(),
+        ca_bundle: config.ca_bundle.clone(),
+    }
+}
+
+/// Resolve `--color`: `auto` colors only a terminal, and never when `NO_COLOR` is set
+fn use_color(choice: &str) -> bool {
+    match choice {
+        "always" => true,
+        "never" => false,
+        _ => {
+            std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()) && io::stdout().is_terminal()
         }
+    }
+}
+
+/// Print an error with its causes and exit. Notion API errors also get a hint
+/// and an exit code for their category (see the README); with
+/// `--error-format json` the error is printed as a JSON object instead.
+fn report_error(e: &anyhow::Error) -> ! {
+    exit_with(e, error::exit_code(e))
+}
+
+/// [`report_error`] with the given exit code
+fn exit_with(e: &anyhow::Error, code: i32) -> ! {
+    // The reader went away (e.g. `| head`): not an error
+    let broken_pipe = e.chain().any(|cause| {
+        cause
+            .downcast_ref::<io::Error>()
+            .is_some_and(|io| io.kind() == io::ErrorKind::BrokenPipe)
+    });
+    if broken_pipe {
+        std::process::exit(0);
+    }
+
+    if JSON_ERRORS.load(Ordering::Relaxed) {
+        let mut json = error::to_json(e);
+        json["error"]["exit_code"] = serde_json::json!(code);
+        eprintln!("{}", json);
+    } else {
+        eprintln!("{} {:#}", "✗".red(), e);
+        if let Some(hint) = e.downcast_ref::<NotionError>().and_then(NotionError::hint) {
+            eprintln!("  {} {}", "hint:".dimmed(), hint);
+        }
+    }
+    std::process::exit(code)
+}
+
+async fn run(
+    client: &NotionClient,
+    command: Commands,
+    default_parent: Option<&str>,
+    todo_database: Option<&str>,
+) -> Result<()> {
+    let parent_or_default = |parent: Option<String>| {
+        parent
+            .or_else(|| default_parent.map(String::from))
+            .context("--parent is required (or set default_parent in the profile)")
     };
 
-    let result = match cli.command {
-        Commands::Init { .. } | Commands::Config => unreachable!(),
-        Commands::Search { query, limit } => handle_search(&client, &query, limit),
-        Commands::Read { page_id } => handle_read(&client, &page_id),
+    match command {
+        Commands::Init(_)
+        | Commands::Auth { .. }
+        | Commands::Config { .. }
+        | Commands::Cache { .. }
+        | Commands::Completions { .. }
+        | Commands::CompletePages => unreachable!(),
+        Commands::Search {
+            query,
+            limit,
+            only,
+            sort,
+            direction,
+            format,
+            id_only,
+            pagination,
+        } => {
+            let object = only.as_deref().map(|o| o.trim_end_matches('s'));
+            match pagination.manual() {
+                Some(page_size) => {
+                    let page = client
+                        .search_page(
+                            &query,
+                            object,
+                            sort.as_deref(),
+                            &direction,
+                            pagination.start_cursor.as_deref(),
+                            page_size,
+                        )
+                        .await?;
+                    print_json(&page)
+                }
+                None => {
+                    handle_search(
+                        client,
+                        &query,
+                        object,
+                        sort.as_deref(),
+                        &direction,
+                        limit,
+                        format == "ndjson",
+                        id_only,
+                    )
+                    .await
+                }
+            }
+        }
+        Commands::Blocks {
+            target,
+            recursive,
+            types,
+            contains,
+            id_only,
+            pagination,
+        } => {
+            let page_id = resolve_page(client, &target).await?;
+            match pagination.manual() {
+                Some(page_size) => {
+                    let page = client
+                        .get_blocks_page(&page_id, pagination.start_cursor.as_deref(), page_size)
+                        .await?;
+                    print_json(&page)
+                }
+                None => {
+                    handle_blocks(
+                        client,
+                        &page_id,
+                        recursive,
+                        &types,
+                        contains.as_deref(),
+                        id_only,
+                    )
+                    .await
+                }
+            }
+        }
+        Commands::Read {
+            target,
+            depth,
+            resolve_mentions,
+            follow,
+            resolve,
+            copy,
+        } => {
+            handle_read(
+                client,
+                &resolve_page(client, &target).await?,
+                depth,
+                resolve_mentions,
+                follow,
+                resolve,
+                copy,
+            )
+            .await
+        }
+        Commands::Grep {
+            pattern,
+            page,
+            ignore_case,
+            limit,
+        } => handle_grep(client, &pattern, page.as_deref(), ignore_case, limit).await,
+        Commands::Info { target } => {
+            handle_info(client, &resolve_page(client, &target).await?).await
+        }
+        Commands::Open { page, print } => handle_open(client, &page, print).await,
         Commands::Create {
+            parent,
+            parent_db,
+            props,
+            title,
+            content,
+            file,
+            icon,
+            icon_url,
+            offline,
+            id_only,
+        } => {
+            let parent = match parent_db {
+                Some(database) => NewPageParent::Database(database),
+                None => NewPageParent::Page(parent_or_default(parent)?),
+            };
+            handle_create(
+                client,
+                &parent,
+                &props,
+                &title,
+                content.as_deref(),
+                file.as_deref(),
+                icon.or(icon_url).as_deref(),
+                offline,
+                id_only,
+            )
+            .await
+        }
+        Commands::DbCreate {
+            target,
+            title,
+            props,
+        } => {
+            handle_db_create(
+                client,
+                &resolve_database(client, &target).await?,
+                &title,
+                &props,
+            )
+            .await
+        }
+        Commands::DbExport {
+            target,
+            format,
+            columns,
+            filters,
+            output,
+        } => {
+            handle_db_export(
+                client,
+                &resolve_database(client, &target).await?,
+                &format,
+                &columns,
+                &filters,
+                output.as_deref(),
+            )
+            .await
+        }
+        Commands::DbImport { target, file } => {
+            handle_db_import(client, &resolve_database(client, &target).await?, &file).await
+        }
+        Commands::DbArchive {
+            target,
+            filters,
+            older_than,
+            timestamp,
+            yes,
+        } => {
+            handle_db_archive(
+                client,
+                &resolve_database(client, &target).await?,
+                &filters,
+                older_than,
+                &timestamp,
+                yes,
+            )
+            .await
+        }
+        Commands::DbDedupe {
+            target,
+            key,
+            archive,
+            merge,
+            yes,
+        } => {
+            handle_db_dedupe(
+                client,
+                &resolve_database(client, &target).await?,
+                &key,
+                archive,
+                merge,
+                yes,
+            )
+            .await
+        }
+        Commands::DbNew {
             parent,
             title,
+            schema,
+            props,
+        } => {
+            let parent = parent_or_default(parent)?;
+            handle_db_new(client, &parent, &title, schema.as_deref(), &props).await
+        }
+        Commands::DbSchema { target } => {
+            handle_db_schema(client, &resolve_database(client, &target).await?).await
+        }
+        Commands::Append {
+            target,
+            after,
             content,
-        } => handle_create(&client, &parent, &title, content.as_deref()),
-        Commands::Append { page_id, content } => handle_append(&client, &page_id, &content),
+            file,
+            clipboard,
+            markdown,
+            mention_user,
+            mention_page,
+            offline,
+        } => {
+            let page_id = resolve_page(client, &target).await?;
+            let content = if clipboard {
+                match read_clipboard()? {
+                    ClipboardContent::Text(text) => Some(text),
+                    ClipboardContent::Image(png) => {
+                        if offline {
+                            bail!("Clipboard images can't be queued offline");
+                        }
+                        return handle_append_clipboard_image(
+                            client,
+                            &page_id,
+                            png,
+                            after.as_deref(),
+                        )
+                        .await;
+                    }
+                }
+            } else {
+                content
+            };
+            handle_append(
+                client,
+                &page_id,
+                content.as_deref(),
+                file.as_deref(),
+                markdown,
+                &mention_user,
+                &mention_page,
+                after.as_deref(),
+                offline,
+            )
+            .await
+        }
+        Commands::GitLog {
+            target,
+            since,
+            repo,
+            limit,
+            format,
+            heading,
+            database,
+        } => {
+            let commits = git::log(repo.as_deref(), since.as_deref(), limit)?;
+            if database {
+                let target = DatabaseTarget {
+                    database_id: target.page_id,
+                    name: target.name,
+                };
+                handle_git_log_rows(client, &resolve_database(client, &target).await?, &commits)
+                    .await
+            } else {
+                handle_git_log(
+                    client,
+                    &resolve_page(client, &target).await?,
+                    &commits,
+                    &format,
+                    heading.as_deref(),
+                )
+                .await
+            }
+        }
+        Commands::Clip { target, url, after } => {
+            handle_clip(
+                client,
+                &resolve_page(client, &target).await?,
+                &url,
+                after.as_deref(),
+            )
+            .await
+        }
         Commands::AppendCode {
-            page_id,
+            target,
+            after,
             code,
+            file,
             language,
-        } => handle_append_code(&client, &page_id, &code, &language),
+            caption,
+        } => {
+            handle_append_code(
+                client,
+                &resolve_page(client, &target).await?,
+                code.as_deref(),
+                file.as_deref(),
+                language.as_deref(),
+                caption.as_deref(),
+                after.as_deref(),
+            )
+            .await
+        }
+        Commands::AppendJson {
+            target,
+            file,
+            after,
+        } => {
+            handle_append_json(
+                client,
+                &resolve_page(client, &target).await?,
+                &file,
+                after.as_deref(),
+            )
+            .await
+        }
         Commands::AppendBookmark {
-            page_id,
+            target,
+            after,
             url,
             caption,
-        } => handle_append_bookmark(&client, &page_id, &url, caption.as_deref()),
+        } => {
+            handle_append_bookmark(
+                client,
+                &resolve_page(client, &target).await?,
+                &url,
+                caption.as_deref(),
+                after.as_deref(),
+            )
+            .await
+        }
+        Commands::AppendImage {
+            target,
+            after,
+            url,
+            caption,
+        } => {
+            handle_append_image(
+                client,
+                &resolve_page(client, &target).await?,
+                &url,
+                caption.as_deref(),
+                after.as_deref(),
+            )
+            .await
+        }
+        Commands::AppendVideo {
+            target,
+            after,
+            url,
+            caption,
+        } => {
+            handle_append_video(
+                client,
+                &resolve_page(client, &target).await?,
+                &url,
+                caption.as_deref(),
+                after.as_deref(),
+            )
+            .await
+        }
+        Commands::AppendEmbed {
+            target,
+            after,
+            url,
+            caption,
+        } => {
+            handle_append_embed(
+                client,
+                &resolve_page(client, &target).await?,
+                &url,
+                caption.as_deref(),
+                after.as_deref(),
+            )
+            .await
+        }
+        Commands::Upload {
+            target,
+            after,
+            path,
+            caption,
+        } => {
+            handle_upload(
+                client,
+                &resolve_page(client, &target).await?,
+                &path,
+                caption.as_deref(),
+                after.as_deref(),
+            )
+            .await
+        }
         Commands::Update {
-            page_id,
+            target,
             title,
             icon,
-        } => handle_update(&client, &page_id, title.as_deref(), icon.as_deref()),
-        Commands::Delete { page_id } => handle_delete(&client, &page_id),
+            icon_url,
+            props,
+            offline,
+        } => {
+            handle_update(
+                client,
+                &resolve_page(client, &target).await?,
+                title.as_deref(),
+                icon.or(icon_url).as_deref(),
+                &props,
+                offline,
+            )
+            .await
+        }
+        Commands::Relate {
+            target,
+            property,
+            to,
+            remove,
+        } => {
+            handle_relate(
+                client,
+                &resolve_page(client, &target).await?,
+                &property,
+                &to,
+                remove,
+            )
+            .await
+        }
+        Commands::SyncQueue { list, drop_failed } => {
+            handle_sync_queue(client, list, drop_failed).await
+        }
+        Commands::Delete { target, yes } => {
+            handle_delete(client, &resolve_page(client, &target).await?, yes).await
+        }
         Commands::Query {
-            database_id,
-            filter,
-            sort,
-            direction,
+            target,
+            filters,
             limit,
-        } => handle_query(
-            &client,
-            &database_id,
-            filter.as_deref(),
-            sort.as_deref(),
-            &direction,
-            limit,
-        ),
-        Commands::DeleteBlock { block_id } => handle_delete_block(&client, &block_id),
+            output,
+            pagination,
+        } => {
+            handle_query(
+                client,
+                &resolve_database(client, &target).await?,
+                &filters,
+                limit,
+                &output,
+                &pagination,
+            )
+            .await
+        }
+        Commands::Clear {
+            target,
+            block_type,
+            after,
+            yes,
+        } => {
+            handle_clear(
+                client,
+                &resolve_page(client, &target).await?,
+                block_type.as_deref(),
+                after.as_deref(),
+                yes,
+            )
+            .await
+        }
+        Commands::UpdateBlock {
+            block_id,
+            text,
+            checked,
+            unchecked,
+            language,
+        } => {
+            let checked = (checked || unchecked).then_some(checked);
+            handle_update_block(
+                client,
+                &block_id,
+                text.as_deref(),
+                checked,
+                language.as_deref(),
+            )
+            .await
+        }
+        Commands::MoveBlock {
+            block_id,
+            to,
+            after,
+        } => handle_move_block(client, &block_id, &to, after.as_deref()).await,
+        Commands::DeleteBlock { block_id, yes } => {
+            handle_delete_block(client, &block_id, yes).await
+        }
         Commands::AppendHeading {
-            page_id,
+            target,
+            after,
             text,
             level,
-        } => handle_append_heading(&client, &page_id, &text, level),
-        Commands::AppendDivider { page_id } => handle_append_divider(&client, &page_id),
-        Commands::AppendList { page_id, items } => handle_append_list(&client, &page_id, &items),
+        } => {
+            handle_append_heading(
+                client,
+                &resolve_page(client, &target).await?,
+                &text,
+                level,
+                after.as_deref(),
+            )
+            .await
+        }
+        Commands::AppendDivider { target, after } => {
+            handle_append_divider(
+                client,
+                &resolve_page(client, &target).await?,
+                after.as_deref(),
+            )
+            .await
+        }
+        Commands::AppendToc { target, after } => {
+            handle_append_toc(
+                client,
+                &resolve_page(client, &target).await?,
+                after.as_deref(),
+            )
+            .await
+        }
+        Commands::AppendBreadcrumb { target, after } => {
+            handle_append_breadcrumb(
+                client,
+                &resolve_page(client, &target).await?,
+                after.as_deref(),
+            )
+            .await
+        }
+        Commands::AppendQuote {
+            target,
+            after,
+            text,
+        } => {
+            handle_append_quote(
+                client,
+                &resolve_page(client, &target).await?,
+                &text,
+                after.as_deref(),
+            )
+            .await
+        }
+        Commands::AppendToggle {
+            target,
+            after,
+            text,
+            child,
+        } => {
+            handle_append_toggle(
+                client,
+                &resolve_page(client, &target).await?,
+                &text,
+                &child,
+                after.as_deref(),
+            )
+            .await
+        }
+        Commands::AppendSynced {
+            target,
+            after,
+            original,
+            new: _,
+            content,
+            file,
+            markdown,
+        } => {
+            handle_append_synced(
+                client,
+                &resolve_page(client, &target).await?,
+                original.as_deref(),
+                content.as_deref(),
+                file.as_deref(),
+                markdown,
+                after.as_deref(),
+            )
+            .await
+        }
+        Commands::AppendTable {
+            target,
+            after,
+            csv,
+            rows,
+            header,
+        } => {
+            handle_append_table(
+                client,
+                &resolve_page(client, &target).await?,
+                csv.as_deref(),
+                rows.as_deref(),
+                header,
+                after.as_deref(),
+            )
+            .await
+        }
+        Commands::AppendList {
+            target,
+            after,
+            items,
+            file,
+            numbered,
+        } => {
+            handle_append_list(
+                client,
+                &resolve_page(client, &target).await?,
+                items.as_deref(),
+                file.as_deref(),
+                numbered,
+                after.as_deref(),
+            )
+            .await
+        }
         Commands::AppendLink {
-            page_id,
+            target,
+            after,
             prefix,
             link_text,
             url,
             suffix,
-        } => handle_append_link(
-            &client,
-            &page_id,
-            prefix.as_deref(),
-            &link_text,
-            &url,
-            suffix.as_deref(),
-        ),
-        Commands::GetBlockIds { page_id } => handle_get_block_ids(&client, &page_id),
+        } => {
+            handle_append_link(
+                client,
+                &resolve_page(client, &target).await?,
+                prefix.as_deref(),
+                &link_text,
+                &url,
+                suffix.as_deref(),
+                after.as_deref(),
+            )
+            .await
+        }
+        Commands::GetBlockIds { target } => {
+            handle_get_block_ids(client, &resolve_page(client, &target).await?).await
+        }
+        Commands::Tree {
+            page_id: Some(page_id),
+            name,
+            depth,
+            ..
+        } => {
+            let target = PageTarget { page_id, name };
+            handle_tree(client, &resolve_page(client, &target).await?, depth).await
+        }
+        Commands::Tree {
+            page_id: None,
+            root,
+            depth,
+            format,
+            ..
+        } => handle_page_tree(client, root.as_deref(), depth, &format).await,
         Commands::Move {
-            page_id,
+            target,
             parent,
             delete,
-        } => handle_move(&client, &page_id, &parent, delete),
-    };
-
-    if let Err(e) = result {
-        eprintln!("{} {}", "✗".red(), e);
-        std::process::exit(1);
+            yes,
+        } => {
+            handle_move(
+                client,
+                &resolve_page(client, &target).await?,
+                &parent,
+                delete,
+                yes,
+            )
+            .await
+        }
+        Commands::Export {
+            target,
+            format,
+            output,
+            download_assets,
+        } => {
+            handle_export(
+                client,
+                &resolve_page(client, &target).await?,
+                &format,
+                output.as_deref(),
+                download_assets,
+            )
+            .await
+        }
+        Commands::Edit { target } => {
+            handle_edit(client, &resolve_page(client, &target).await?).await
+        }
+        Commands::Watch {
+            target,
+            interval,
+            exec,
+        } => {
+            handle_watch(
+                client,
+                &resolve_page(client, &target).await?,
+                interval,
+                exec.as_deref(),
+            )
+            .await
+        }
+        Commands::Batch { file, keep_going } => handle_batch(client, &file, keep_going).await,
+        Commands::Duplicate {
+            target,
+            parent,
+            title,
+            id_only,
+        } => {
+            handle_duplicate(
+                client,
+                &resolve_page(client, &target).await?,
+                parent.as_deref(),
+                title.as_deref(),
+                id_only,
+            )
+            .await
+        }
+        Commands::Push {
+            dir,
+            root,
+            archive_removed,
+            dry_run,
+        } => handle_push(client, &dir, &root, archive_removed, dry_run).await,
+        Commands::Pull { target, dir, prune } => {
+            handle_pull(client, &resolve_page(client, &target).await?, &dir, prune).await
+        }
+        Commands::CreateFromTemplate {
+            template,
+            parent,
+            title,
+            vars,
+        } => {
+            handle_create_from_template(
+                client,
+                &template,
+                &parent_or_default(parent)?,
+                title.as_deref(),
+                &vars,
+            )
+            .await
+        }
+        Commands::DbProp { action } => match action {
+            DbPropCommand::Add {
+                target,
+                spec,
+                options,
+            } => {
+                handle_db_prop_add(
+                    client,
+                    &resolve_database(client, &target).await?,
+                    &spec,
+                    &options,
+                )
+                .await
+            }
+            DbPropCommand::Rename {
+                target,
+                property,
+                new_name,
+            } => {
+                handle_db_prop_rename(
+                    client,
+                    &resolve_database(client, &target).await?,
+                    &property,
+                    &new_name,
+                )
+                .await
+            }
+            DbPropCommand::Remove {
+                target,
+                property,
+                yes,
+            } => {
+                handle_db_prop_remove(
+                    client,
+                    &resolve_database(client, &target).await?,
+                    &property,
+                    yes,
+                )
+                .await
+            }
+            DbPropCommand::Options {
+                target,
+                property,
+                add,
+                remove,
+            } => {
+                handle_db_prop_options(
+                    client,
+                    &resolve_database(client, &target).await?,
+                    &property,
+                    &add,
+                    &remove,
+                )
+                .await
+            }
+        },
+        Commands::Comment { action } => match action {
+            CommentCommand::Add { target, text } => {
+                handle_comment_add(client, &resolve_page(client, &target).await?, &text).await
+            }
+            CommentCommand::List { target } => {
+                handle_comment_list(client, &resolve_page(client, &target).await?).await
+            }
+            CommentCommand::Reply {
+                discussion_id,
+                text,
+            } => handle_comment_reply(client, &discussion_id, &text).await,
+        },
+        Commands::Users { action } => match action {
+            UsersCommand::List { limit } => handle_users_list(client, limit).await,
+        },
+        Commands::Todo { database, action } => {
+            let database = database
+                .or_else(|| todo_database.map(String::from))
+                .context("--database is required (or set todo_database in the config)")?;
+            match action {
+                TodoCommand::Add { title, due, props } => {
+                    handle_todo_add(client, &database, &title, due.as_deref(), &props).await
+                }
+                TodoCommand::List { status, all, limit } => {
+                    handle_todo_list(client, &database, status.as_deref(), all, limit).await
+                }
+                TodoCommand::Done { task } => handle_todo_done(client, &database, &task).await,
+            }
+        }
+        Commands::Whoami => handle_whoami(client).await,
+        Commands::ApiVersion {
+            action: ApiVersionCommand::Check,
+        } => handle_api_version_check(client).await,
+        Commands::Serve { socket } => handle_serve(client, &socket).await,
+        Commands::Tui { query } => tui::run(client.clone(), query).await,
     }
-
-    Ok(())
 }
 
-fn handle_init(api_key: Option<String>) -> Result<()> {
-    let key = if let Some(k) = api_key {
-        k
-    } else {
-        // Prompt for API key
-        print!("{} Enter your Notion API key: ", "→".blue());
-        io::stdout().flush()?;
+async fn handle_init(args: &InitArgs, profile: Option<&str>, timeout: Option<u64>) -> Result<()> {
+    if args.oauth {
+        return handle_oauth_login(args, profile).await;
+    }
+    let interactive = io::stdin().is_terminal();
 
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        input.trim().to_string()
+    let key = match &args.api_key {
+        Some(k) => k.clone(),
+        None => prompt("Enter your Notion API key")?,
     };
 
     if key.is_empty() {
-        eprintln!("{} API key cannot be empty", "✗".red());
-        std::process::exit(1);
+        bail!("API key cannot be empty");
     }
 
     // Validate key format (should start with secret_ or ntn_)
@@ -149,11 +1119,60 @@ fn handle_init(api_key: Option<String>) -> Result<()> {
         );
     }
 
-    // Save to config
-    let config = Config {
-        api_key: Some(key),
-        timeout: None,
+    if !args.no_verify {
+        let client = NotionClient::new_with_network(
+            key.clone(),
+            timeout.unwrap_or(DEFAULT_TIMEOUT_SECS),
+            &network_options(&load_config()),
+        )?;
+        let me = client
+            .get_me()
+            .await
+            .context("API key check failed (use --no-verify to save it anyway)")?;
+        let name = me.get("name").and_then(|n| n.as_str()).unwrap_or("unnamed");
+        println!("{} Authenticated as {}", "✓".green(), name.bold());
+    }
+
+    // Optional settings, only asked for interactively
+    let mut default_parent = None;
+    let mut saved_timeout = None;
+    if interactive {
+        let parent = prompt("Default parent page ID or URL (Enter to skip)")?;
+        if !parent.is_empty() {
+            default_parent = Some(normalize_page_id(&parent)?);
+        }
+        let t = prompt(&format!(
+            "Request timeout in seconds (Enter for {})",
+            DEFAULT_TIMEOUT_SECS
+        ))?;
+        if !t.is_empty() {
+            saved_timeout = Some(
+                t.parse::<u64>()
+                    .with_context(|| format!("Invalid timeout '{}'", t))?,
+            );
+        }
+    }
+
+    // Save to config, keeping other settings and profiles
+    let mut config = load_config_for_update()?;
+    if saved_timeout.is_some() {
+        config.timeout = saved_timeout;
+    }
+    let (key_slot, keyring_slot, parent_slot) = match profile {
+        Some(name) => {
+            let p = config.profiles.entry(name.to_string()).or_default();
+            (&mut p.api_key, &mut p.keyring, &mut p.default_parent)
+        }
+        None => (
+            &mut config.api_key,
+            &mut config.keyring,
+            &mut config.default_parent,
+        ),
     };
+    if default_parent.is_some() {
+        *parent_slot = default_parent;
+    }
+    store_key(key_slot, keyring_slot, profile, key, args.keyring)?;
     save_config(&config)?;
 
     let path = get_config_path().unwrap();
@@ -163,9 +1182,289 @@ fn handle_init(api_key: Option<String>) -> Result<()> {
     Ok(())
 }
 
-fn handle_config_with_cli_key(cli_api_key: Option<&str>) -> Result<()> {
+/// Put `key` in config.toml, or in the OS keyring with config.toml only
+/// keeping a reference to the entry
+fn store_key(
+    key_slot: &mut Option<String>,
+    keyring_slot: &mut Option<String>,
+    profile: Option<&str>,
+    key: String,
+    keyring: bool,
+) -> Result<()> {
+    if keyring {
+        let account = profile.unwrap_or("default");
+        save_keyring_token(account, &key)?;
+        *key_slot = None;
+        *keyring_slot = Some(account.to_string());
+        println!("{} API key saved to the system keyring", "✓".green());
+    } else {
+        *key_slot = Some(key);
+        *keyring_slot = None;
+    }
+    Ok(())
+}
+
+/// `auth login --oauth`: authorize a public integration in the browser and
+/// save the access token it grants, with its workspace
+async fn handle_oauth_login(args: &InitArgs, profile: Option<&str>) -> Result<()> {
+    let client_id = match &args.client_id {
+        Some(id) => id.clone(),
+        None => prompt("OAuth client ID")?,
+    };
+    let client_secret = match &args.client_secret {
+        Some(secret) => secret.clone(),
+        None => prompt("OAuth client secret")?,
+    };
+    if client_id.is_empty() || client_secret.is_empty() {
+        bail!("OAuth login needs the integration's client ID and secret");
+    }
+
+    let port = args.port.unwrap_or(oauth::DEFAULT_CALLBACK_PORT);
+    let redirect_uri = oauth::redirect_uri(port);
+    let listener = std::net::TcpListener::bind(("127.0.0.1", port))
+        .with_context(|| format!("Failed to listen on port {} (use --port)", port))?;
+    let state = oauth::new_state();
+    let url = oauth::authorize_url(&client_id, &redirect_uri, &state);
+
+    println!("{} Opening the Notion consent page:", "→".blue());
+    println!("  {}", url);
+    if let Err(e) = open_in_browser(&url) {
+        eprintln!("{} {:#}; open the URL above yourself", "⚠".yellow(), e);
+    }
+    println!(
+        "{} Waiting for the redirect to {}",
+        "→".blue(),
+        redirect_uri
+    );
+
+    let code = tokio::task::spawn_blocking(move || oauth::receive_code(&listener, &state))
+        .await
+        .context("OAuth callback server stopped")??;
+    let http = network_options(&load_config())
+        .client_builder()?
+        .build()
+        .context("Failed to create HTTP client")?;
+    let token = oauth::exchange_code(&http, &client_id, &client_secret, &code, &redirect_uri)
+        .await
+        .context("Failed to exchange the authorization code")?;
+
+    let workspace = Workspace {
+        id: token.workspace_id,
+        name: token.workspace_name,
+        bot_id: token.bot_id,
+    };
+    println!(
+        "{} Authorized for workspace {}",
+        "✓".green(),
+        workspace.name.as_deref().unwrap_or(&workspace.id).bold()
+    );
+
+    let mut config = load_config_for_update()?;
+    let (key_slot, keyring_slot, workspace_slot) = match profile {
+        Some(name) => {
+            let p = config.profiles.entry(name.to_string()).or_default();
+            (&mut p.api_key, &mut p.keyring, &mut p.workspace)
+        }
+        None => (
+            &mut config.api_key,
+            &mut config.keyring,
+            &mut config.workspace,
+        ),
+    };
+    *workspace_slot = Some(workspace);
+    store_key(
+        key_slot,
+        keyring_slot,
+        profile,
+        token.access_token,
+        args.keyring,
+    )?;
+    save_config(&config)?;
+
+    let path = get_config_path().unwrap();
+    println!("{} Config saved to {}", "✓".green(), path.display());
+    Ok(())
+}
+
+fn prompt(label: &str) -> Result<String> {
+    print!("{} {}: ", "→".blue(), label);
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(input.trim().to_string())
+}
+
+fn handle_logout(profile: Option<&str>) -> Result<()> {
+    let mut config = load_config_for_update()?;
+    let (key_slot, keyring_slot, workspace_slot) = match profile {
+        Some(name) => {
+            let p = config
+                .profiles
+                .get_mut(name)
+                .with_context(|| format!("Profile '{}' not found", name))?;
+            (&mut p.api_key, &mut p.keyring, &mut p.workspace)
+        }
+        None => (
+            &mut config.api_key,
+            &mut config.keyring,
+            &mut config.workspace,
+        ),
+    };
+
+    if let Some(account) = keyring_slot.take() {
+        delete_keyring_token(&account)?;
+        println!(
+            "{} Removed API key '{}' from the system keyring",
+            "✓".green(),
+            account
+        );
+    }
+    key_slot.take();
+    workspace_slot.take();
+    save_config(&config)?;
+
+    println!("{} Logged out", "✓".green());
+    Ok(())
+}
+
+/// What a token is allowed to do, found with read-only requests
+struct TokenHealth {
+    me: serde_json::Value,
+    read_content: bool,
+    /// `None` without user information; `Some(true)` when emails are included
+    user_emails: Option<bool>,
+}
+
+/// Check a token against `/users/me`, then probe the read capabilities.
+/// Only a `restricted_resource` answer counts as a missing capability.
+async fn check_token(
+    key: &str,
+    profile: Option<&Profile>,
+    cli: &Cli,
+    config: &Config,
+) -> Result<TokenHealth> {
+    let client = NotionClient::new_with_network(
+        key.to_string(),
+        cli.timeout
+            .or(config.timeout)
+            .unwrap_or(DEFAULT_TIMEOUT_SECS),
+        &network_options(config),
+    )?
+    .with_api_version(
+        cli.api_version
+            .clone()
+            .unwrap_or_else(|| get_profile_api_version(profile)),
+    );
+    let granted = |result: Result<Vec<serde_json::Value>>| match result {
+        Ok(items) => Ok(Some(items)),
+        Err(e)
+            if e.downcast_ref::<NotionError>()
+                .is_some_and(|api| api.kind == ErrorKind::RestrictedResource) =>
+        {
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    };
+
+    let me = client.get_me().await?;
+    let read_content = granted(client.search("", 1).await)?.is_some();
+    let user_emails = granted(client.list_users(10).await)?
+        .map(|users| users.iter().any(|u| u["person"]["email"].is_string()));
+
+    Ok(TokenHealth {
+        me,
+        read_content,
+        user_emails,
+    })
+}
+
+fn print_token_health(label: &str, key: &str, health: &TokenHealth) {
+    let me = &health.me;
+    let workspace = me["bot"]["workspace_name"]
+        .as_str()
+        .or(me["name"].as_str())
+        .unwrap_or("(unnamed)");
+    println!("{} {}: {}", "✓".green(), label.bold(), workspace);
+    println!("    Bot: {}", me["id"].as_str().unwrap_or_default());
+    println!("    Key: {}", mask_api_key(key));
+
+    let mut capabilities = Vec::new();
+    if health.read_content {
+        capabilities.push("read content");
+    }
+    match health.user_emails {
+        Some(true) => capabilities.push("read users with email"),
+        Some(false) => capabilities.push("read users"),
+        None => {}
+    }
+    if capabilities.is_empty() {
+        capabilities.push("none readable");
+    }
+    println!("    Capabilities: {}", capabilities.join(", "));
+}
+
+/// `auth status`: check the key commands would use, failing if it's rejected
+async fn handle_auth_status(cli: &Cli) -> Result<()> {
+    let config = load_config();
+    let profile = config.profile(cli.profile.as_deref())?;
+    let key = get_api_key(cli.api_key.as_deref(), cli.profile.as_deref())?;
+
+    let health = check_token(&key, profile, cli, &config)
+        .await
+        .context("API key is invalid, expired or revoked")?;
+    print_token_health(cli.profile.as_deref().unwrap_or("default"), &key, &health);
+    Ok(())
+}
+
+/// `auth list`: check the saved key of the top-level config and every
+/// profile, failing if any is rejected
+async fn handle_auth_list(cli: &Cli) -> Result<()> {
+    let config = load_config();
+    let mut names: Vec<Option<&str>> = Vec::new();
+    if config.api_key.is_some() || config.keyring.is_some() {
+        names.push(None);
+    }
+    names.extend(config.profiles.keys().map(|n| Some(n.as_str())));
+    if names.is_empty() {
+        bail!("No API keys saved (run `notion-cli auth login`)");
+    }
+
+    let mut failed = 0;
+    for name in &names {
+        let label = name.unwrap_or("default");
+        let checked = match get_config_api_key(&config, *name) {
+            Ok(Some(key)) => check_token(&key, config.profile(*name)?, cli, &config)
+                .await
+                .map(|health| (key, health)),
+            Ok(None) => Err(anyhow::anyhow!("no API key saved")),
+            Err(e) => Err(e),
+        };
+        match checked {
+            Ok((key, health)) => print_token_health(label, &key, &health),
+            Err(e) => {
+                failed += 1;
+                let reason = match e.downcast_ref::<NotionError>() {
+                    Some(api) if api.kind == ErrorKind::Unauthorized => {
+                        "invalid, expired or revoked".to_string()
+                    }
+                    _ => format!("{:#}", e),
+                };
+                println!("{} {}: {}", "✗".red(), label.bold(), reason.red());
+            }
+        }
+    }
+
+    if failed > 0 {
+        bail!("{} of {} API keys failed the check", failed, names.len());
+    }
+    Ok(())
+}
+
+fn handle_config_with_cli_key(cli_api_key: Option<&str>, profile_name: Option<&str>) -> Result<()> {
     let config = load_config();
     let path = get_config_path();
+    let profile = config.profile(profile_name)?;
 
     println!("{}", "Notion CLI Configuration".blue().bold());
     println!();
@@ -173,6 +1472,9 @@ fn handle_config_with_cli_key(cli_api_key: Option<&str>) -> Result<()> {
     if let Some(p) = &path {
         println!("Config file: {}", p.display());
     }
+    if let Some(name) = profile_name {
+        println!("Profile: {}", name.cyan());
+    }
     println!();
 
     // API key status - show source based on priority order (matching get_api_key)
@@ -185,9 +1487,16 @@ fn handle_config_with_cli_key(cli_api_key: Option<&str>) -> Result<()> {
     } else if let Ok(key) = std::env::var("NOTION_API_KEY") {
         let masked = mask_api_key(&key);
         println!("{} (from environment)", masked.green());
+    } else if let Some(key) = profile.and_then(|p| p.api_key.as_ref()) {
+        let masked = mask_api_key(key);
+        println!("{} (from profile)", masked.green());
+    } else if let Some(account) = profile.and_then(|p| p.keyring.as_ref()) {
+        print_keyring_key(account);
     } else if let Some(key) = &config.api_key {
         let masked = mask_api_key(key);
         println!("{} (from config)", masked.green());
+    } else if let Some(account) = &config.keyring {
+        print_keyring_key(account);
     } else {
         // Check .env as fallback
         if dotenvy::dotenv().is_ok() {
@@ -201,22 +1510,105 @@ fn handle_config_with_cli_key(cli_api_key: Option<&str>) -> Result<()> {
             println!("{}", "not set".red());
         }
     }
+    let workspace = match profile {
+        Some(p) => p.workspace.as_ref(),
+        None => config.workspace.as_ref(),
+    };
+    if let Some(w) = workspace {
+        println!(
+            "OAuth workspace: {} (bot {})",
+            w.name.as_deref().unwrap_or(&w.id),
+            w.bot_id
+        );
+    }
 
     println!();
     println!("{}", "Priority order:".dimmed());
     println!("  1. --api-key option");
     println!("  2. NOTION_API_KEY environment variable");
-    println!("  3. ~/.config/notion-cli/config.toml");
+    println!("  3. ~/.config/notion-cli/config.toml (selected profile first)");
     println!("  4. .env file (backward compatibility)");
 
     // Timeout
     if let Some(t) = config.timeout {
         println!("\nTimeout: {}s", t);
     }
+    if let Some(n) = config.max_retries {
+        println!("Max retries: {}", n);
+    }
+    if let Some(r) = config.rate_limit {
+        println!("Rate limit: {} requests/s", r);
+    }
+    if let Some(n) = config.concurrency {
+        println!("Concurrency: {}", n);
+    }
+    if config.cache == Some(true) {
+        println!("Page cache: on");
+    }
+    if let Some(proxy) = &config.proxy {
+        println!("Proxy: {}", proxy);
+    }
+    if let Some(path) = &config.ca_bundle {
+        println!("CA bundle: {}", path.display());
+    }
 
+    println!("\nAPI version: {}", get_profile_api_version(profile));
+    if let Some(parent) = config.default_parent(profile) {
+        println!("Default parent: {}", parent);
+    }
+
+    if !config.profiles.is_empty() {
+        println!("\n{}", "Profiles:".blue());
+        for name in config.profiles.keys() {
+            let marker = if Some(name.as_str()) == profile_name {
+                "*"
+            } else {
+                " "
+            };
+            println!("  {} {}", marker, name);
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_config_get(profile: Option<&str>, key: &str) -> Result<()> {
+    let config = load_config();
+    match config.get_value(profile, key)? {
+        Some(value) if key == "api_key" => println!("{}", mask_api_key(&value)),
+        Some(value) => println!("{}", value),
+        None => bail!("{} is not set", key),
+    }
+    Ok(())
+}
+
+fn handle_config_set(profile: Option<&str>, key: &str, value: Option<&str>) -> Result<()> {
+    let mut config = load_config_for_update()?;
+    if value.is_none() {
+        // Unsetting shouldn't create an empty profile
+        config.profile(profile)?;
+    }
+    config.set_value(profile, key, value)?;
+    save_config(&config)?;
+
+    let target = match profile {
+        Some(name) => format!(" (profile {})", name),
+        None => String::new(),
+    };
+    match value {
+        Some(_) => println!("{} Set {}{}", "✓".green(), key, target),
+        None => println!("{} Unset {}{}", "✓".green(), key, target),
+    }
     Ok(())
 }
 
+fn print_keyring_key(account: &str) {
+    match load_keyring_token(account) {
+        Ok(key) => println!("{} (from keyring: {})", mask_api_key(&key).green(), account),
+        Err(e) => println!("{} ({})", "unavailable".red(), e),
+    }
+}
+
 fn mask_api_key(key: &str) -> String {
     let chars: Vec<char> = key.chars().collect();
     if chars.len() > 12 {
@@ -227,3 +1619,60 @@ fn mask_api_key(key: &str) -> String {
         "***".to_string()
     }
 }
+
+fn handle_completions(shell: Shell) {
+    let mut cmd = Cli::command();
+    let bin_name = cmd.get_name().to_string();
+    clap_complete::generate(shell, &mut cmd, &bin_name, &mut io::stdout());
+
+    // Subcommands taking a page or database ID get suggestions from the ID cache
+    let id_commands: Vec<&str> = cmd
+        .get_subcommands()
+        .flat_map(|sub| std::iter::once(sub).chain(sub.get_subcommands()))
+        .filter(|sub| {
+            sub.get_arguments()
+                .any(|a| a.get_id() == "page_id" || a.get_id() == "database_id")
+        })
+        .map(|sub| sub.get_name())
+        .collect();
+
+    match shell {
+        Shell::Bash => {
+            println!(
+                r#"
+_{func}_ids() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    _{func} "$@"
+    if [[ ${{#COMPREPLY[@]}} -eq 0 && "$cur" != -* ]]; then
+        COMPREPLY=($(compgen -W "$({bin} __complete-pages 2>/dev/null | cut -f1)" -- "$cur"))
+    fi
+}}
+complete -F _{func}_ids -o nosort -o bashdefault -o default {bin}"#,
+                bin = bin_name,
+                func = bin_name.replace('-', "__")
+            );
+        }
+        Shell::Fish => {
+            println!(
+                "complete -c {bin} -n \"__fish_seen_subcommand_from {cmds}\" -f -a \"({bin} __complete-pages)\"",
+                bin = bin_name,
+                cmds = id_commands.join(" ")
+            );
+        }
+        _ => {}
+    }
+}
+
+fn handle_complete_pages() {
+    let cache = load_id_cache();
+    let mut entries: Vec<_> = cache
+        .pages
+        .values()
+        .chain(cache.databases.values())
+        .collect();
+    entries.sort_by_key(|e| std::cmp::Reverse(e.last_used));
+
+    for entry in entries {
+        println!("{}\t{}", entry.id, entry.title);
+    }
+}
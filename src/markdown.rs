@@ -0,0 +1,666 @@
+//! Markdown conversion.
+//!
+//! Inline formatting (`**bold**`, `*italic*`, `` `code` ``, `~~strike~~` and
+//! `[links](url)`) is parsed into [`RichTextSegment`]s, as are
+//! `@[user:email]` and `@[page:id]` mentions. Unclosed markers are kept as
+//! literal text.
+//!
+//! Block-level Markdown (headings, lists, quotes, fenced code, dividers) is
+//! parsed by [`parse_blocks`]; [`block_to_markdown`] goes the other way.
+
+use serde_json::Value;
+
+use crate::blocks::{self, MAX_TEXT_LENGTH};
+use crate::client::RichTextSegment;
+use crate::models::{Block, BlockKind, RichText};
+
+/// Parse inline Markdown formatting into rich text segments.
+///
+/// ```
+/// use notion_cli_tool::markdown::parse_inline;
+/// use notion_cli_tool::RichTextSegment;
+///
+/// let segments = parse_inline("Run **cargo test** before [merging](https://example.com)");
+/// assert_eq!(segments[1].text, "cargo test");
+/// assert!(segments[1].bold);
+/// assert_eq!(segments[3].link.as_deref(), Some("https://example.com"));
+///
+/// let segments = parse_inline("Ping @[user:ada@example.com]");
+/// assert_eq!(segments[1], RichTextSegment::mention_user("ada@example.com"));
+/// ```
+pub fn parse_inline(text: &str) -> Vec<RichTextSegment> {
+    let mut segments = Vec::new();
+    parse_into(text, &RichTextSegment::default(), &mut segments);
+    segments
+}
+
+/// Paragraph blocks for Markdown text, with inline formatting applied and
+/// long content split to fit the rich text limit.
+pub fn paragraphs(content: &str) -> Vec<Value> {
+    blocks::split_text(content.trim_end(), MAX_TEXT_LENGTH)
+        .into_iter()
+        .map(|chunk| blocks::rich_paragraph(&parse_inline(chunk.trim_end_matches('\n'))))
+        .collect()
+}
+
+/// A block parsed from Markdown, with the source lines it came from
+#[derive(Debug, Clone)]
+pub enum MarkdownBlock {
+    Block {
+        source: String,
+        block: Value,
+    },
+    /// A single-line `<!-- ... -->` HTML comment
+    Comment(String),
+}
+
+/// Parse block-level Markdown into Notion blocks. Blank lines separate
+/// paragraphs; consecutive `>` lines form one quote.
+///
+/// ```
+/// use notion_cli_tool::markdown::{parse_blocks, MarkdownBlock};
+///
+/// let blocks = parse_blocks("# Title\n\nSome *text*\n\n- one\n- two");
+/// assert_eq!(blocks.len(), 4);
+/// if let MarkdownBlock::Block { block, .. } = &blocks[0] {
+///     assert_eq!(block["type"], "heading_1");
+/// }
+/// ```
+pub fn parse_blocks(markdown: &str) -> Vec<MarkdownBlock> {
+    let mut out = Vec::new();
+    let mut lines = markdown.lines().peekable();
+    let mut paragraph: Vec<&str> = Vec::new();
+
+    let flush = |paragraph: &mut Vec<&str>, out: &mut Vec<MarkdownBlock>| {
+        if !paragraph.is_empty() {
+            let source = paragraph.join("\n");
+            let block = blocks::rich_paragraph(&parse_inline(&source));
+            out.push(MarkdownBlock::Block { source, block });
+            paragraph.clear();
+        }
+    };
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim_end();
+
+        if trimmed.trim().is_empty() {
+            flush(&mut paragraph, &mut out);
+            continue;
+        }
+
+        if let Some(language) = trimmed.strip_prefix("```") {
+            flush(&mut paragraph, &mut out);
+            let mut source = vec![trimmed];
+            let mut code = Vec::new();
+            for line in lines.by_ref() {
+                source.push(line);
+                if line.trim_end() == "```" {
+                    break;
+                }
+                code.push(line);
+            }
+            let language = match language.trim() {
+                "" => "plain text",
+                language => blocks::code_language(language).unwrap_or(language),
+            };
+            out.push(MarkdownBlock::Block {
+                source: source.join("\n"),
+                block: blocks::code(&code.join("\n"), language),
+            });
+            continue;
+        }
+
+        if trimmed.starts_with("<!--") && trimmed.ends_with("-->") {
+            flush(&mut paragraph, &mut out);
+            out.push(MarkdownBlock::Comment(trimmed.to_string()));
+            continue;
+        }
+
+        if trimmed.starts_with('>') {
+            flush(&mut paragraph, &mut out);
+            let mut source = vec![trimmed];
+            while let Some(next) = lines.next_if(|l| l.starts_with('>')) {
+                source.push(next.trim_end());
+            }
+            let text: Vec<&str> = source
+                .iter()
+                .map(|l| {
+                    let l = &l[1..];
+                    l.strip_prefix(' ').unwrap_or(l)
+                })
+                .collect();
+            out.push(MarkdownBlock::Block {
+                source: source.join("\n"),
+                block: blocks::rich_block("quote", &parse_inline(&text.join("\n"))),
+            });
+            continue;
+        }
+
+        let single = if matches!(trimmed, "---" | "***" | "___") {
+            Some(blocks::divider())
+        } else if let Some((level, text)) = heading_line(trimmed) {
+            let block_type = match level {
+                1 => "heading_1",
+                2 => "heading_2",
+                _ => "heading_3",
+            };
+            Some(blocks::rich_block(block_type, &parse_inline(text)))
+        } else if let Some(text) = ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|marker| trimmed.strip_prefix(marker))
+        {
+            Some(blocks::rich_block(
+                "bulleted_list_item",
+                &parse_inline(text),
+            ))
+        } else {
+            numbered_line(trimmed)
+                .map(|text| blocks::rich_block("numbered_list_item", &parse_inline(text)))
+        };
+
+        match single {
+            Some(block) => {
+                flush(&mut paragraph, &mut out);
+                out.push(MarkdownBlock::Block {
+                    source: trimmed.to_string(),
+                    block,
+                });
+            }
+            None => paragraph.push(trimmed),
+        }
+    }
+    flush(&mut paragraph, &mut out);
+
+    out
+}
+
+/// `# text` to `### text` (deeper levels count as 3)
+fn heading_line(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    line[level..].strip_prefix(' ').map(|text| (level, text))
+}
+
+/// `1. text`
+fn numbered_line(line: &str) -> Option<&str> {
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits == 0 {
+        return None;
+    }
+    line[digits..].strip_prefix(". ")
+}
+
+/// Markdown for a block's own content, or `None` when the block can't be
+/// written as Markdown that [`parse_blocks`] reads back as the same block
+/// (media, tables, toggles, empty paragraphs, ...). Children are not included.
+pub fn block_to_markdown(block: &Block) -> Option<String> {
+    let single_line = |text: String| Some(text).filter(|t| !t.is_empty() && !t.contains('\n'));
+
+    match &block.kind {
+        BlockKind::Paragraph(t) => {
+            let text = from_rich_text(&t.rich_text);
+            if text.trim().is_empty() || text.lines().any(|l| l.trim().is_empty()) {
+                return None;
+            }
+            let lines: Vec<String> = text.lines().map(escape_line_start).collect();
+            Some(lines.join("\n"))
+        }
+        BlockKind::Heading1(t) => {
+            single_line(from_rich_text(&t.rich_text)).map(|t| format!("# {}", t))
+        }
+        BlockKind::Heading2(t) => {
+            single_line(from_rich_text(&t.rich_text)).map(|t| format!("## {}", t))
+        }
+        BlockKind::Heading3(t) => {
+            single_line(from_rich_text(&t.rich_text)).map(|t| format!("### {}", t))
+        }
+        BlockKind::BulletedListItem(t) => {
+            single_line(from_rich_text(&t.rich_text)).map(|t| format!("- {}", t))
+        }
+        BlockKind::NumberedListItem(t) => {
+            single_line(from_rich_text(&t.rich_text)).map(|t| format!("1. {}", t))
+        }
+        BlockKind::Quote(t) => {
+            let text = from_rich_text(&t.rich_text);
+            let lines: Vec<String> = text
+                .lines()
+                .map(|l| {
+                    if l.is_empty() {
+                        ">".to_string()
+                    } else {
+                        format!("> {}", l)
+                    }
+                })
+                .collect();
+            Some(lines.join("\n")).filter(|t| !t.is_empty())
+        }
+        BlockKind::Code(c) => {
+            let code = crate::models::plain_text(&c.rich_text);
+            if code.lines().any(|l| l.trim_end() == "```") {
+                return None;
+            }
+            Some(format!("```{}\n{}\n```", c.language, code))
+        }
+        BlockKind::Divider => Some("---".to_string()),
+        _ => None,
+    }
+}
+
+/// Inline Markdown for rich text, the inverse of [`parse_inline`].
+///
+/// ```
+/// use notion_cli_tool::markdown::from_rich_text;
+/// use notion_cli_tool::models::RichText;
+///
+/// let rich_text: Vec<RichText> = serde_json::from_value(serde_json::json!([
+///     { "plain_text": "a " },
+///     { "plain_text": "bold", "annotations": { "bold": true } },
+///     { "plain_text": " " },
+///     { "plain_text": "link", "href": "https://x.dev" }
+/// ]))
+/// .unwrap();
+/// assert_eq!(from_rich_text(&rich_text), "a **bold** [link](https://x.dev)");
+/// ```
+pub fn from_rich_text(rich_text: &[RichText]) -> String {
+    let mut out = String::new();
+    for item in rich_text {
+        let text = &item.plain_text;
+        let a = &item.annotations;
+
+        // Markers must hug the text, so surrounding whitespace goes outside
+        let core = text.trim();
+        if core.is_empty() {
+            out.push_str(text);
+            continue;
+        }
+        let leading = &text[..text.len() - text.trim_start().len()];
+        let trailing = &text[text.trim_end().len()..];
+
+        let mut md = if a.code && !core.contains('`') {
+            format!("`{}`", core)
+        } else {
+            escape_inline(core, out.chars().last())
+        };
+        if a.strikethrough {
+            md = format!("~~{}~~", md);
+        }
+        if a.italic {
+            md = format!("*{}*", md);
+        }
+        if a.bold {
+            md = format!("**{}**", md);
+        }
+        if let Some(href) = item
+            .href
+            .as_deref()
+            .filter(|h| !h.contains(char::is_whitespace))
+        {
+            md = format!("[{}]({})", md, href);
+        }
+
+        out.push_str(leading);
+        out.push_str(&md);
+        out.push_str(trailing);
+    }
+    out
+}
+
+/// Escape `text` so [`parse_inline`] keeps it literal.
+///
+/// ```
+/// use notion_cli_tool::markdown::{escape, parse_inline};
+///
+/// let segments = parse_inline(&escape("**not bold** @[user:x]"));
+/// assert_eq!(segments.len(), 1);
+/// assert_eq!(segments[0].text, "**not bold** @[user:x]");
+/// ```
+pub fn escape(text: &str) -> String {
+    escape_inline(text, None)
+}
+
+/// Escape characters [`parse_inline`] would treat as formatting
+fn escape_inline(text: &str, prev: Option<char>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut prev = prev;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let special = match c {
+            '\\' | '*' | '`' | '[' => true,
+            '~' => chars.peek() == Some(&'~'),
+            '_' => !prev.is_some_and(char::is_alphanumeric),
+            _ => false,
+        };
+        if special {
+            out.push('\\');
+        }
+        out.push(c);
+        prev = Some(c);
+    }
+    out
+}
+
+/// Escape a paragraph line that would otherwise parse as another block type
+fn escape_line_start(line: &str) -> String {
+    let block_like = heading_line(line).is_some()
+        || numbered_line(line).is_some()
+        || ["- ", "+ ", ">", "```", "<!--"]
+            .iter()
+            .any(|p| line.starts_with(p))
+        || matches!(line.trim_end(), "---" | "___");
+
+    if !block_like {
+        return line.to_string();
+    }
+    match numbered_line(line) {
+        Some(_) => line.replacen(". ", "\\. ", 1),
+        None => format!("\\{}", line),
+    }
+}
+
+fn parse_into(text: &str, style: &RichTextSegment, out: &mut Vec<RichTextSegment>) {
+    let mut plain = String::new();
+    let mut rest = text;
+
+    let flush = |plain: &mut String, out: &mut Vec<RichTextSegment>| {
+        if !plain.is_empty() {
+            out.push(styled(style, std::mem::take(plain)));
+        }
+    };
+
+    while let Some(c) = rest.chars().next() {
+        let prev = plain.chars().last();
+
+        // Backslash escapes the next character
+        if c == '\\' {
+            let mut chars = rest[1..].chars();
+            if let Some(next) = chars.next() {
+                plain.push(next);
+                rest = chars.as_str();
+                continue;
+            }
+        }
+
+        if c == '`' {
+            if let Some(end) = rest[1..].find('`') {
+                flush(&mut plain, out);
+                let mut seg = styled(style, rest[1..1 + end].to_string());
+                seg.code = true;
+                out.push(seg);
+                rest = &rest[end + 2..];
+                continue;
+            }
+        }
+
+        if c == '@' {
+            if let Some((mention, consumed)) = split_mention(rest) {
+                flush(&mut plain, out);
+                out.push(RichTextSegment {
+                    bold: style.bold,
+                    italic: style.italic,
+                    strikethrough: style.strikethrough,
+                    code: style.code,
+                    ..mention
+                });
+                rest = &rest[consumed..];
+                continue;
+            }
+        }
+
+        if c == '[' {
+            if let Some((label, url, consumed)) = split_link(rest) {
+                flush(&mut plain, out);
+                let mut link_style = style.clone();
+                link_style.link = Some(url.to_string());
+                parse_into(label, &link_style, out);
+                rest = &rest[consumed..];
+                continue;
+            }
+        }
+
+        let delimited = [
+            ("**", Span::Bold),
+            ("__", Span::Bold),
+            ("~~", Span::Strikethrough),
+            ("*", Span::Italic),
+            ("_", Span::Italic),
+        ]
+        .into_iter()
+        .filter(|(marker, _)| rest.starts_with(marker))
+        // `_` only opens emphasis at a word start, so snake_case stays literal
+        .filter(|(marker, _)| !marker.starts_with('_') || !prev.is_some_and(char::is_alphanumeric))
+        .find_map(|(marker, span)| {
+            find_closing(&rest[marker.len()..], marker).map(|end| (marker, span, end))
+        });
+
+        if let Some((marker, span, end)) = delimited {
+            flush(&mut plain, out);
+            let mut inner_style = style.clone();
+            match span {
+                Span::Bold => inner_style.bold = true,
+                Span::Italic => inner_style.italic = true,
+                Span::Strikethrough => inner_style.strikethrough = true,
+            }
+            let inner = &rest[marker.len()..marker.len() + end];
+            parse_into(inner, &inner_style, out);
+            rest = &rest[2 * marker.len() + end..];
+            continue;
+        }
+
+        plain.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    flush(&mut plain, out);
+}
+
+enum Span {
+    Bold,
+    Italic,
+    Strikethrough,
+}
+
+/// A `@[user:...]` or `@[page:...]` mention at the start of `text`, with
+/// the bytes it spans
+fn split_mention(text: &str) -> Option<(RichTextSegment, usize)> {
+    let inner = text.strip_prefix("@[")?;
+    let end = inner.find(']')?;
+    let (kind, target) = inner[..end].split_once(':')?;
+    let target = target.trim();
+    if target.is_empty() {
+        return None;
+    }
+    let segment = match kind {
+        "user" => RichTextSegment::mention_user(target),
+        "page" => RichTextSegment::mention_page(target),
+        _ => return None,
+    };
+    Some((segment, end + 3))
+}
+
+fn styled(style: &RichTextSegment, text: String) -> RichTextSegment {
+    RichTextSegment {
+        text,
+        ..style.clone()
+    }
+}
+
+/// Offset of the closing `marker` in `text`, requiring non-empty content that
+/// doesn't start or end with whitespace. A single `*`/`_` never matches half of
+/// a doubled marker.
+fn find_closing(text: &str, marker: &str) -> Option<usize> {
+    if text.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let mut search_from = 0;
+    while let Some(pos) = text[search_from..].find(marker) {
+        let end = search_from + pos;
+        let after = &text[end + marker.len()..];
+        let doubled = marker.len() == 1 && after.starts_with(marker);
+        if end > 0 && !text[..end].ends_with(char::is_whitespace) && !doubled {
+            return Some(end);
+        }
+        search_from = end + marker.len() + usize::from(doubled);
+    }
+
+    None
+}
+
+/// Split `[label](url)` at the start of `text` into label, URL and the number
+/// of bytes consumed.
+fn split_link(text: &str) -> Option<(&str, &str, usize)> {
+    let label_end = text.find("](")?;
+    let url_start = label_end + 2;
+    let url_len = text[url_start..].find(')')?;
+    let label = &text[1..label_end];
+    let url = &text[url_start..url_start + url_len];
+    if label.is_empty() || url.is_empty() || url.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((label, url, url_start + url_len + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn describe(segments: &[RichTextSegment]) -> Vec<(String, &'static str)> {
+        segments
+            .iter()
+            .map(|s| {
+                let kind = match (s.bold, s.italic, s.strikethrough, s.code) {
+                    (true, true, _, _) => "bold+italic",
+                    (true, _, _, _) => "bold",
+                    (_, true, _, _) => "italic",
+                    (_, _, true, _) => "strike",
+                    (_, _, _, true) => "code",
+                    _ => "plain",
+                };
+                (s.text.clone(), kind)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_inline_annotations() {
+        let segments = parse_inline("a **b** *c* `d` ~~e~~");
+        assert_eq!(
+            describe(&segments),
+            vec![
+                ("a ".to_string(), "plain"),
+                ("b".to_string(), "bold"),
+                (" ".to_string(), "plain"),
+                ("c".to_string(), "italic"),
+                (" ".to_string(), "plain"),
+                ("d".to_string(), "code"),
+                (" ".to_string(), "plain"),
+                ("e".to_string(), "strike"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_inline_nested() {
+        let segments = parse_inline("**bold _and italic_**");
+        assert_eq!(
+            describe(&segments),
+            vec![
+                ("bold ".to_string(), "bold"),
+                ("and italic".to_string(), "bold+italic"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_inline_link_with_formatting() {
+        let segments = parse_inline("see [the **docs**](https://x.dev)");
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[2].text, "docs");
+        assert!(segments[2].bold);
+        assert_eq!(segments[2].link.as_deref(), Some("https://x.dev"));
+    }
+
+    #[test]
+    fn test_parse_inline_literals() {
+        for text in ["snake_case_name", "2 * 3 * 4", "**unclosed", "a \\*b\\*"] {
+            let segments = parse_inline(text);
+            assert_eq!(segments.len(), 1, "{}", text);
+            assert!(!segments[0].bold && !segments[0].italic, "{}", text);
+        }
+        assert_eq!(parse_inline("a \\*b\\*")[0].text, "a *b*");
+    }
+
+    #[test]
+    fn test_parse_blocks() {
+        let md = "# Title\n\nfirst line\nsecond line\n\n> quoted\n> more\n\n```rust\nfn main() {}\n\n```\n---\n1. step\n<!-- note -->";
+        let blocks = parse_blocks(md);
+        let types: Vec<String> = blocks
+            .iter()
+            .map(|b| match b {
+                MarkdownBlock::Block { block, .. } => block["type"].as_str().unwrap().to_string(),
+                MarkdownBlock::Comment(_) => "comment".to_string(),
+            })
+            .collect();
+        assert_eq!(
+            types,
+            [
+                "heading_1",
+                "paragraph",
+                "quote",
+                "code",
+                "divider",
+                "numbered_list_item",
+                "comment"
+            ]
+        );
+
+        let MarkdownBlock::Block { source, block } = &blocks[3] else {
+            panic!("expected a code block");
+        };
+        assert_eq!(source, "```rust\nfn main() {}\n\n```");
+        assert_eq!(block["code"]["language"], "rust");
+        assert_eq!(
+            block["code"]["rich_text"][0]["text"]["content"],
+            "fn main() {}\n"
+        );
+    }
+
+    #[test]
+    fn test_block_markdown_round_trip() {
+        let blocks: Vec<Block> = serde_json::from_value(serde_json::json!([
+            { "type": "paragraph", "paragraph": { "rich_text": [{ "plain_text": "# not a heading_1 *really*" }] } },
+            { "type": "paragraph", "paragraph": { "rich_text": [{ "plain_text": "2. not a list" }] } },
+            { "type": "heading_2", "heading_2": { "rich_text": [{ "plain_text": "Plan", "annotations": { "bold": true } }] } },
+            { "type": "quote", "quote": { "rich_text": [{ "plain_text": "a\nb" }] } }
+        ]))
+        .unwrap();
+
+        let markdown: Vec<String> = blocks.iter().filter_map(block_to_markdown).collect();
+        assert_eq!(
+            markdown,
+            [
+                "\\# not a heading_1 \\*really\\*",
+                "2\\. not a list",
+                "## **Plan**",
+                "> a\n> b"
+            ]
+        );
+
+        for (md, original) in markdown.iter().zip(&blocks) {
+            let parsed = parse_blocks(md);
+            let MarkdownBlock::Block { block, .. } = &parsed[0] else {
+                panic!("expected a block");
+            };
+            let content: String = block[&original.block_type]["rich_text"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|t| t["text"]["content"].as_str().unwrap())
+                .collect();
+            assert_eq!(block["type"], original.block_type.as_str());
+            assert_eq!(content, original.text());
+        }
+    }
+}
@@ -0,0 +1,1038 @@
+//! Typed views of Notion API objects.
+//!
+//! Notion encodes variants as `{"type": "<kind>", "<kind>": {...}}`. Each model
+//! deserializes through a raw intermediate and maps the type tag onto an enum,
+//! so unknown kinds become `Unsupported(<kind>)` instead of failing.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::dates::format_date;
+
+/// Text annotations (formatting) on a rich text item
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Annotations {
+    pub bold: bool,
+    pub italic: bool,
+    pub strikethrough: bool,
+    pub underline: bool,
+    pub code: bool,
+    pub color: String,
+}
+
+/// A single rich text item
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RichText {
+    pub plain_text: String,
+    pub href: Option<String>,
+    pub annotations: Annotations,
+    /// Set for `mention` items
+    pub mention: Option<Mention>,
+    /// Set for inline `equation` items
+    pub equation: Option<Equation>,
+}
+
+impl RichText {
+    /// Text to show for the item: `@Name` for user mentions, the formatted
+    /// date for date mentions, the expression for equations, else `plain_text`
+    pub fn text(&self) -> String {
+        if let Some(equation) = &self.equation {
+            return equation.expression.clone();
+        }
+        let Some(mention) = &self.mention else {
+            return self.plain_text.clone();
+        };
+        match (mention.kind.as_str(), &mention.date, &mention.user) {
+            ("date", Some(date), _) => date.display(),
+            ("user", _, Some(user)) if !user.name.is_empty() => format!("@{}", user.name),
+            ("user", _, _) if self.plain_text.is_empty() => "@Unknown user".to_string(),
+            ("page" | "database", _, _) if self.plain_text.is_empty() => "Untitled".to_string(),
+            _ => self.plain_text.clone(),
+        }
+    }
+}
+
+/// A mention of a user, page, database, date or link inside rich text
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Mention {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub user: Option<MentionedUser>,
+    pub page: Option<MentionedObject>,
+    pub database: Option<MentionedObject>,
+    pub date: Option<DateValue>,
+}
+
+impl Mention {
+    /// ID of the mentioned page or database
+    pub fn object_id(&self) -> Option<&str> {
+        self.page
+            .as_ref()
+            .or(self.database.as_ref())
+            .map(|o| o.id.as_str())
+    }
+}
+
+/// A user in a mention, people or created_by/last_edited_by property
+/// (`name` is missing without the user information capability)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MentionedUser {
+    pub id: String,
+    pub name: String,
+}
+
+/// A mentioned page or database
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MentionedObject {
+    pub id: String,
+}
+
+/// An inline equation (KaTeX expression)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Equation {
+    pub expression: String,
+}
+
+/// Concatenate the text of a rich text array (see [`RichText::text`])
+pub fn plain_text(rich_text: &[RichText]) -> String {
+    rich_text.iter().map(RichText::text).collect()
+}
+
+/// Content of text-like blocks (paragraph, headings, list items, toggle, ...)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TextContent {
+    pub rich_text: Vec<RichText>,
+}
+
+/// Content of a code block
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CodeContent {
+    pub rich_text: Vec<RichText>,
+    pub language: String,
+}
+
+/// Content of a table block (rows are its `table_row` children)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TableContent {
+    pub table_width: usize,
+    pub has_column_header: bool,
+    pub has_row_header: bool,
+}
+
+/// Content of a table row: one rich text array per cell
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TableRowContent {
+    pub cells: Vec<Vec<RichText>>,
+}
+
+/// URL holder for hosted (`file`) and `external` file objects
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct FileUrl {
+    pub url: String,
+}
+
+/// Content of image, video, pdf, audio and file blocks
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MediaContent {
+    pub caption: Vec<RichText>,
+    pub name: Option<String>,
+    pub external: Option<FileUrl>,
+    pub file: Option<FileUrl>,
+}
+
+impl MediaContent {
+    /// URL of the media, whether externally linked or hosted by Notion
+    pub fn url(&self) -> Option<&str> {
+        self.external
+            .as_ref()
+            .or(self.file.as_ref())
+            .map(|f| f.url.as_str())
+    }
+}
+
+/// Content of embed blocks
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct EmbedContent {
+    pub url: String,
+    pub caption: Vec<RichText>,
+}
+
+/// Content of child_page and child_database blocks
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ChildContent {
+    pub title: String,
+}
+
+/// The type-specific part of a block
+#[derive(Debug, Clone)]
+pub enum BlockKind {
+    Paragraph(TextContent),
+    Heading1(TextContent),
+    Heading2(TextContent),
+    Heading3(TextContent),
+    BulletedListItem(TextContent),
+    NumberedListItem(TextContent),
+    Toggle(TextContent),
+    Quote(TextContent),
+    Code(CodeContent),
+    Image(MediaContent),
+    Video(MediaContent),
+    Pdf(MediaContent),
+    Audio(MediaContent),
+    File(MediaContent),
+    Embed(EmbedContent),
+    Table(TableContent),
+    TableRow(TableRowContent),
+    Divider,
+    TableOfContents,
+    Breadcrumb,
+    /// A sub-page; its content is not part of the parent's block tree
+    ChildPage(ChildContent),
+    /// An inline database
+    ChildDatabase(ChildContent),
+    /// A block type this crate does not model yet (holds the type name)
+    Unsupported(String),
+}
+
+impl BlockKind {
+    fn parse(block_type: &str, content: Option<Value>) -> Self {
+        let content = content.unwrap_or(Value::Null);
+        let text = || serde_json::from_value::<TextContent>(content.clone()).ok();
+        let media = || serde_json::from_value::<MediaContent>(content.clone()).ok();
+
+        let kind = match block_type {
+            "paragraph" => text().map(BlockKind::Paragraph),
+            "heading_1" => text().map(BlockKind::Heading1),
+            "heading_2" => text().map(BlockKind::Heading2),
+            "heading_3" => text().map(BlockKind::Heading3),
+            "bulleted_list_item" => text().map(BlockKind::BulletedListItem),
+            "numbered_list_item" => text().map(BlockKind::NumberedListItem),
+            "toggle" => text().map(BlockKind::Toggle),
+            "quote" => text().map(BlockKind::Quote),
+            "code" => serde_json::from_value(content.clone())
+                .ok()
+                .map(BlockKind::Code),
+            "image" => media().map(BlockKind::Image),
+            "video" => media().map(BlockKind::Video),
+            "pdf" => media().map(BlockKind::Pdf),
+            "audio" => media().map(BlockKind::Audio),
+            "file" => media().map(BlockKind::File),
+            "embed" => serde_json::from_value(content.clone())
+                .ok()
+                .map(BlockKind::Embed),
+            "table" => serde_json::from_value(content.clone())
+                .ok()
+                .map(BlockKind::Table),
+            "table_row" => serde_json::from_value(content.clone())
+                .ok()
+                .map(BlockKind::TableRow),
+            "divider" => Some(BlockKind::Divider),
+            "table_of_contents" => Some(BlockKind::TableOfContents),
+            "breadcrumb" => Some(BlockKind::Breadcrumb),
+            "child_page" => serde_json::from_value(content.clone())
+                .ok()
+                .map(BlockKind::ChildPage),
+            "child_database" => serde_json::from_value(content.clone())
+                .ok()
+                .map(BlockKind::ChildDatabase),
+            _ => None,
+        };
+
+        kind.unwrap_or_else(|| BlockKind::Unsupported(block_type.to_string()))
+    }
+}
+
+/// A block, with nested `children` when fetched recursively
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "RawBlock")]
+pub struct Block {
+    pub id: String,
+    pub block_type: String,
+    pub has_children: bool,
+    pub kind: BlockKind,
+    pub children: Vec<Block>,
+}
+
+#[derive(Deserialize)]
+struct RawBlock {
+    #[serde(default)]
+    id: String,
+    #[serde(rename = "type", default)]
+    block_type: String,
+    #[serde(default)]
+    has_children: bool,
+    #[serde(default)]
+    children: Vec<Block>,
+    #[serde(flatten)]
+    rest: serde_json::Map<String, Value>,
+}
+
+impl From<RawBlock> for Block {
+    fn from(mut raw: RawBlock) -> Self {
+        let content = raw.rest.remove(&raw.block_type);
+        Self {
+            kind: BlockKind::parse(&raw.block_type, content),
+            id: raw.id,
+            block_type: raw.block_type,
+            has_children: raw.has_children,
+            children: raw.children,
+        }
+    }
+}
+
+impl Block {
+    /// Plain text content of the block: its rich text, a media caption or URL,
+    /// or the cells of a table row. Empty for blocks without text.
+    pub fn text(&self) -> String {
+        match &self.kind {
+            BlockKind::Paragraph(t)
+            | BlockKind::Heading1(t)
+            | BlockKind::Heading2(t)
+            | BlockKind::Heading3(t)
+            | BlockKind::BulletedListItem(t)
+            | BlockKind::NumberedListItem(t)
+            | BlockKind::Toggle(t)
+            | BlockKind::Quote(t) => plain_text(&t.rich_text),
+            BlockKind::Code(c) => plain_text(&c.rich_text),
+            BlockKind::Image(m)
+            | BlockKind::Video(m)
+            | BlockKind::Pdf(m)
+            | BlockKind::Audio(m)
+            | BlockKind::File(m) => {
+                let caption = plain_text(&m.caption);
+                if caption.is_empty() {
+                    m.url().unwrap_or_default().to_string()
+                } else {
+                    caption
+                }
+            }
+            BlockKind::Embed(e) => {
+                let caption = plain_text(&e.caption);
+                if caption.is_empty() {
+                    e.url.clone()
+                } else {
+                    caption
+                }
+            }
+            BlockKind::TableRow(r) => r
+                .cells
+                .iter()
+                .map(|c| plain_text(c))
+                .collect::<Vec<_>>()
+                .join(" | "),
+            BlockKind::ChildPage(c) | BlockKind::ChildDatabase(c) => c.title.clone(),
+            BlockKind::Table(_)
+            | BlockKind::Divider
+            | BlockKind::TableOfContents
+            | BlockKind::Breadcrumb
+            | BlockKind::Unsupported(_) => String::new(),
+        }
+    }
+
+    /// The block's own rich text items (not its children's), for rewriting
+    /// them before rendering
+    pub fn rich_text_mut(&mut self) -> Vec<&mut RichText> {
+        match &mut self.kind {
+            BlockKind::Paragraph(t)
+            | BlockKind::Heading1(t)
+            | BlockKind::Heading2(t)
+            | BlockKind::Heading3(t)
+            | BlockKind::BulletedListItem(t)
+            | BlockKind::NumberedListItem(t)
+            | BlockKind::Toggle(t)
+            | BlockKind::Quote(t) => t.rich_text.iter_mut().collect(),
+            BlockKind::Code(c) => c.rich_text.iter_mut().collect(),
+            BlockKind::Image(m)
+            | BlockKind::Video(m)
+            | BlockKind::Pdf(m)
+            | BlockKind::Audio(m)
+            | BlockKind::File(m) => m.caption.iter_mut().collect(),
+            BlockKind::Embed(e) => e.caption.iter_mut().collect(),
+            BlockKind::TableRow(r) => r.cells.iter_mut().flatten().collect(),
+            BlockKind::Table(_)
+            | BlockKind::Divider
+            | BlockKind::TableOfContents
+            | BlockKind::Breadcrumb
+            | BlockKind::ChildPage(_)
+            | BlockKind::ChildDatabase(_)
+            | BlockKind::Unsupported(_) => Vec::new(),
+        }
+    }
+}
+
+/// A select / multi-select / status option
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SelectOption {
+    pub name: String,
+    pub color: String,
+}
+
+/// A page linked through a relation property
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RelatedPage {
+    pub id: String,
+    /// Not part of the API response; filled in by `query --resolve-relations`
+    pub title: Option<String>,
+}
+
+/// A file attached to a files property
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct FileRef {
+    pub name: String,
+}
+
+/// An auto-incremented ID such as `TASK-42`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct UniqueId {
+    pub prefix: Option<String>,
+    pub number: Option<i64>,
+}
+
+/// A rollup's result and the function that computed it (`sum`, `count`,
+/// `show_original`, ...)
+#[derive(Debug, Clone, Default)]
+pub struct Rollup {
+    pub function: String,
+    /// A single number or date, or the rolled-up values
+    pub values: Vec<PropertyValue>,
+}
+
+/// A formula's result, typed as the formula returns it
+#[derive(Debug, Clone)]
+pub enum FormulaValue {
+    String(String),
+    Number(f64),
+    Boolean(bool),
+    Date(DateValue),
+}
+
+impl FormulaValue {
+    fn parse(content: &Value) -> Option<Self> {
+        let result_type = content.get("type")?.as_str()?;
+        let result = content.get(result_type)?;
+        match result_type {
+            "string" => result.as_str().map(|s| FormulaValue::String(s.to_string())),
+            "number" => result.as_f64().map(FormulaValue::Number),
+            "boolean" => result.as_bool().map(FormulaValue::Boolean),
+            "date" => serde_json::from_value(result.clone())
+                .ok()
+                .map(FormulaValue::Date),
+            _ => None,
+        }
+    }
+
+    pub fn display(&self) -> String {
+        match self {
+            FormulaValue::String(s) => s.clone(),
+            FormulaValue::Number(n) => number_text(*n),
+            FormulaValue::Boolean(b) => if *b { "✓" } else { "✗" }.to_string(),
+            FormulaValue::Date(d) => d.display(),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            FormulaValue::String(s) => s.clone().into(),
+            FormulaValue::Number(n) => (*n).into(),
+            FormulaValue::Boolean(b) => (*b).into(),
+            FormulaValue::Date(d) => serde_json::json!({ "start": d.start, "end": d.end }),
+        }
+    }
+}
+
+/// A date or date range
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct DateValue {
+    pub start: String,
+    pub end: Option<String>,
+    pub time_zone: Option<String>,
+}
+
+impl DateValue {
+    /// The start, or a `start → end` range, as [`format_date`] shows dates
+    pub fn display(&self) -> String {
+        match self.end.as_deref() {
+            Some(end) => format!("{} → {}", format_date(&self.start), format_date(end)),
+            None => format_date(&self.start),
+        }
+    }
+}
+
+/// A page property value
+#[derive(Debug, Clone)]
+pub enum PropertyValue {
+    Title(Vec<RichText>),
+    RichText(Vec<RichText>),
+    Select(Option<SelectOption>),
+    MultiSelect(Vec<SelectOption>),
+    Number(Option<f64>),
+    Checkbox(bool),
+    Date(Option<DateValue>),
+    Url(Option<String>),
+    Email(Option<String>),
+    PhoneNumber(Option<String>),
+    Status(Option<SelectOption>),
+    People(Vec<MentionedUser>),
+    Relation(Vec<RelatedPage>),
+    Rollup(Rollup),
+    Formula(Option<FormulaValue>),
+    Files(Vec<FileRef>),
+    CreatedTime(String),
+    LastEditedTime(String),
+    CreatedBy(MentionedUser),
+    LastEditedBy(MentionedUser),
+    UniqueId(UniqueId),
+    /// A property type this crate does not model yet (holds the type name)
+    Unsupported(String),
+}
+
+impl PropertyValue {
+    /// Human-readable value, or `None` when the property is empty or unsupported
+    pub fn display(&self) -> Option<String> {
+        let non_empty = |s: String| if s.is_empty() { None } else { Some(s) };
+
+        match self {
+            PropertyValue::Title(rt) | PropertyValue::RichText(rt) => non_empty(plain_text(rt)),
+            PropertyValue::Select(opt) => opt.as_ref().map(|o| o.name.clone()),
+            PropertyValue::MultiSelect(opts) => non_empty(
+                opts.iter()
+                    .map(|o| o.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+            PropertyValue::Number(n) => n.map(number_text),
+            PropertyValue::Checkbox(c) => Some(if *c { "✓" } else { "✗" }.to_string()),
+            PropertyValue::Date(d) => d.as_ref().map(DateValue::display),
+            PropertyValue::Url(u) | PropertyValue::Email(u) | PropertyValue::PhoneNumber(u) => {
+                u.clone()
+            }
+            PropertyValue::Status(opt) => opt.as_ref().map(|o| o.name.clone()),
+            PropertyValue::People(users) => {
+                non_empty(users.iter().map(user_name).collect::<Vec<_>>().join(", "))
+            }
+            PropertyValue::Relation(pages) => non_empty(
+                pages
+                    .iter()
+                    .map(|p| p.title.as_deref().unwrap_or(&p.id))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+            PropertyValue::Rollup(rollup) => non_empty(
+                rollup
+                    .values
+                    .iter()
+                    .filter_map(PropertyValue::display)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+            PropertyValue::Formula(f) => f
+                .as_ref()
+                .map(FormulaValue::display)
+                .filter(|s| !s.is_empty()),
+            PropertyValue::Files(files) => non_empty(
+                files
+                    .iter()
+                    .map(|f| f.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+            PropertyValue::CreatedTime(t) | PropertyValue::LastEditedTime(t) => {
+                non_empty(format_date(t))
+            }
+            PropertyValue::CreatedBy(user) | PropertyValue::LastEditedBy(user) => {
+                non_empty(user_name(user).to_string())
+            }
+            PropertyValue::UniqueId(id) => id.number.map(|n| match id.prefix.as_deref() {
+                Some(prefix) if !prefix.is_empty() => format!("{}-{}", prefix, n),
+                _ => n.to_string(),
+            }),
+            PropertyValue::Unsupported(_) => None,
+        }
+    }
+}
+
+impl PropertyValue {
+    /// The value as plain JSON: a string, number, boolean or array of them,
+    /// `{"start", "end"}` for dates, and `null` when unset or unsupported
+    pub fn to_json(&self) -> Value {
+        let option = |s: &Option<String>| s.clone().map_or(Value::Null, Value::String);
+        let names = |names: Vec<&str>| Value::from(names);
+
+        match self {
+            PropertyValue::Title(rt) | PropertyValue::RichText(rt) => plain_text(rt).into(),
+            PropertyValue::Select(opt) | PropertyValue::Status(opt) => {
+                option(&opt.as_ref().map(|o| o.name.clone()))
+            }
+            PropertyValue::MultiSelect(opts) => {
+                names(opts.iter().map(|o| o.name.as_str()).collect())
+            }
+            PropertyValue::Number(n) => n.map_or(Value::Null, Value::from),
+            PropertyValue::Checkbox(c) => Value::Bool(*c),
+            PropertyValue::Date(d) => d.as_ref().map_or(
+                Value::Null,
+                |d| serde_json::json!({ "start": d.start, "end": d.end }),
+            ),
+            PropertyValue::Url(u) | PropertyValue::Email(u) | PropertyValue::PhoneNumber(u) => {
+                option(u)
+            }
+            PropertyValue::People(users) => names(users.iter().map(user_name).collect()),
+            PropertyValue::Relation(pages) => names(pages.iter().map(|p| p.id.as_str()).collect()),
+            PropertyValue::Rollup(rollup) => {
+                rollup.values.iter().map(PropertyValue::to_json).collect()
+            }
+            PropertyValue::Formula(f) => f.as_ref().map_or(Value::Null, FormulaValue::to_json),
+            PropertyValue::Files(files) => names(files.iter().map(|f| f.name.as_str()).collect()),
+            PropertyValue::CreatedTime(t) | PropertyValue::LastEditedTime(t) => t.clone().into(),
+            PropertyValue::CreatedBy(user) | PropertyValue::LastEditedBy(user) => {
+                user_name(user).into()
+            }
+            PropertyValue::UniqueId(_) => option(&self.display()),
+            PropertyValue::Unsupported(_) => Value::Null,
+        }
+    }
+}
+
+/// A user's name, or their ID when the name isn't available
+fn user_name(user: &MentionedUser) -> &str {
+    if user.name.is_empty() {
+        &user.id
+    } else {
+        &user.name
+    }
+}
+
+/// A number without float noise: `3`, `0.3`, `33.3333333333`
+fn number_text(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        return format!("{}", n as i64);
+    }
+    let s = format!("{:.10}", n);
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+impl<'de> Deserialize<'de> for PropertyValue {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut raw = serde_json::Map::<String, Value>::deserialize(deserializer)?;
+        let prop_type = raw
+            .get("type")
+            .and_then(|t| t.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let content = raw.remove(&prop_type).unwrap_or(Value::Null);
+
+        fn parse<T: serde::de::DeserializeOwned + Default>(v: Value) -> T {
+            serde_json::from_value(v).unwrap_or_default()
+        }
+
+        Ok(match prop_type.as_str() {
+            "title" => PropertyValue::Title(parse(content)),
+            "rich_text" => PropertyValue::RichText(parse(content)),
+            "select" => PropertyValue::Select(parse(content)),
+            "multi_select" => PropertyValue::MultiSelect(parse(content)),
+            "number" => PropertyValue::Number(content.as_f64()),
+            "checkbox" => PropertyValue::Checkbox(content.as_bool().unwrap_or(false)),
+            "date" => PropertyValue::Date(parse(content)),
+            "url" => PropertyValue::Url(content.as_str().map(String::from)),
+            "email" => PropertyValue::Email(content.as_str().map(String::from)),
+            "phone_number" => PropertyValue::PhoneNumber(content.as_str().map(String::from)),
+            "status" => PropertyValue::Status(parse(content)),
+            "people" => PropertyValue::People(parse(content)),
+            "relation" => PropertyValue::Relation(parse(content)),
+            "rollup" => PropertyValue::Rollup(Rollup {
+                function: content["function"].as_str().unwrap_or_default().to_string(),
+                values: match content.get("array") {
+                    Some(items) => parse(items.clone()),
+                    // A number or date rollup has the same shape as a property value
+                    None => serde_json::from_value(content).into_iter().collect(),
+                },
+            }),
+            "formula" => PropertyValue::Formula(FormulaValue::parse(&content)),
+            "files" => PropertyValue::Files(parse(content)),
+            "created_time" => PropertyValue::CreatedTime(parse(content)),
+            "last_edited_time" => PropertyValue::LastEditedTime(parse(content)),
+            "created_by" => PropertyValue::CreatedBy(parse(content)),
+            "last_edited_by" => PropertyValue::LastEditedBy(parse(content)),
+            "unique_id" => PropertyValue::UniqueId(parse(content)),
+            _ => PropertyValue::Unsupported(prop_type),
+        })
+    }
+}
+
+/// A page or database object as returned by search, query and retrieve
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Page {
+    pub object: String,
+    pub id: String,
+    pub url: Option<String>,
+    pub archived: bool,
+    pub properties: BTreeMap<String, PropertyValue>,
+    /// Database objects carry their title at the top level
+    pub title: Vec<RichText>,
+}
+
+impl Page {
+    /// Title from the page's title-typed property, or the database title
+    pub fn title(&self) -> Option<String> {
+        self.properties
+            .values()
+            .find_map(|p| match p {
+                PropertyValue::Title(rt) => Some(plain_text(rt)),
+                _ => None,
+            })
+            .filter(|t| !t.is_empty())
+            .or_else(|| Some(plain_text(&self.title)))
+            .filter(|t| !t.is_empty())
+    }
+}
+
+/// A page or database in a page hierarchy, with the sub-pages and databases
+/// under it (see [`NotionClient::get_page_tree`](crate::NotionClient::get_page_tree))
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PageNode {
+    /// `page` or `database`
+    pub object: String,
+    pub id: String,
+    pub title: String,
+    pub children: Vec<PageNode>,
+}
+
+impl PageNode {
+    /// The node for a `child_page` or `child_database` block
+    pub fn from_block(block: &Value) -> Option<Self> {
+        let (object, kind) = match block["type"].as_str()? {
+            "child_page" => ("page", "child_page"),
+            "child_database" => ("database", "child_database"),
+            _ => return None,
+        };
+        Some(Self {
+            object: object.to_string(),
+            id: block["id"].as_str()?.to_string(),
+            title: block[kind]["title"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            children: Vec::new(),
+        })
+    }
+
+    /// The nodes for the `child_page` and `child_database` blocks anywhere in
+    /// a block tree (as fetched with nested `children`), in order
+    pub fn from_blocks(blocks: &[Value]) -> Vec<Self> {
+        let mut nodes = Vec::new();
+        for block in blocks {
+            match Self::from_block(block) {
+                Some(node) => nodes.push(node),
+                None => nodes.extend(Self::from_blocks(
+                    block["children"]
+                        .as_array()
+                        .map(Vec::as_slice)
+                        .unwrap_or_default(),
+                )),
+            }
+        }
+        nodes
+    }
+
+    /// Pages and databases below this node, at any depth
+    ///
+    /// ```
+    /// use notion_cli_tool::models::PageNode;
+    ///
+    /// let node = |object: &str, children| PageNode {
+    ///     object: object.to_string(),
+    ///     children,
+    ///     ..Default::default()
+    /// };
+    /// let tree = node("page", vec![node("page", vec![node("database", vec![])]), node("page", vec![])]);
+    /// assert_eq!(tree.descendants(), (2, 1));
+    /// ```
+    pub fn descendants(&self) -> (usize, usize) {
+        self.children
+            .iter()
+            .fold((0, 0), |(pages, databases), child| {
+                let (p, d) = child.descendants();
+                let (own_page, own_database) = if child.object == "database" {
+                    (0, 1)
+                } else {
+                    (1, 0)
+                };
+                (pages + p + own_page, databases + d + own_database)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dates::{with_date_display, DateDisplay};
+    use serde_json::json;
+
+    #[test]
+    fn test_page_title_from_custom_title_property() {
+        let page: Page = serde_json::from_value(json!({
+            "object": "page",
+            "id": "abc",
+            "properties": {
+                "Task": { "id": "title", "type": "title", "title": [{ "plain_text": "Ship it" }] },
+                "Done": { "id": "x", "type": "checkbox", "checkbox": true }
+            }
+        }))
+        .unwrap();
+        assert_eq!(page.title(), Some("Ship it".to_string()));
+    }
+
+    #[test]
+    fn test_property_values_as_json() {
+        let props: BTreeMap<String, PropertyValue> = serde_json::from_value(json!({
+            "Tags": { "type": "multi_select", "multi_select": [{ "name": "a" }, { "name": "b" }] },
+            "Points": { "type": "number", "number": null },
+            "Due": { "type": "date", "date": { "start": "2024-07-01" } },
+            "ID": { "type": "unique_id", "unique_id": { "prefix": "T", "number": 7 } }
+        }))
+        .unwrap();
+        assert_eq!(props["Tags"].to_json(), json!(["a", "b"]));
+        assert_eq!(props["Points"].to_json(), Value::Null);
+        assert_eq!(
+            props["Due"].to_json(),
+            json!({ "start": "2024-07-01", "end": null })
+        );
+        assert_eq!(props["ID"].to_json(), json!("T-7"));
+    }
+
+    #[test]
+    fn test_database_title() {
+        let db: Page = serde_json::from_value(json!({
+            "object": "database",
+            "title": [{ "plain_text": "Tasks" }],
+            "properties": { "Name": { "type": "title", "title": {} } }
+        }))
+        .unwrap();
+        assert_eq!(db.title(), Some("Tasks".to_string()));
+    }
+
+    #[test]
+    fn test_property_display() {
+        let prop: PropertyValue = serde_json::from_value(json!({
+            "type": "multi_select",
+            "multi_select": [{ "name": "a" }, { "name": "b" }]
+        }))
+        .unwrap();
+        assert_eq!(prop.display(), Some("a, b".to_string()));
+
+        let empty: PropertyValue =
+            serde_json::from_value(json!({ "type": "select", "select": null })).unwrap();
+        assert_eq!(empty.display(), None);
+    }
+
+    #[test]
+    fn test_computed_and_reference_property_display() {
+        let display = |value: Value| {
+            serde_json::from_value::<PropertyValue>(value)
+                .unwrap()
+                .display()
+        };
+
+        let people = json!({
+            "type": "people",
+            "people": [{ "id": "u1", "name": "Ada" }, { "id": "u2" }]
+        });
+        assert_eq!(display(people), Some("Ada, u2".to_string()));
+
+        let relation =
+            json!({ "type": "relation", "relation": [{ "id": "p1" }], "has_more": false });
+        assert_eq!(display(relation), Some("p1".to_string()));
+
+        let rollup = json!({
+            "type": "rollup",
+            "rollup": {
+                "type": "array",
+                "array": [
+                    { "type": "title", "title": [{ "plain_text": "Alpha" }] },
+                    { "type": "number", "number": 2 }
+                ]
+            }
+        });
+        assert_eq!(display(rollup), Some("Alpha, 2".to_string()));
+
+        let sum = json!({ "type": "rollup", "rollup": { "type": "number", "number": 7.5 } });
+        assert_eq!(display(sum), Some("7.5".to_string()));
+
+        let formula =
+            json!({ "type": "formula", "formula": { "type": "boolean", "boolean": true } });
+        assert_eq!(display(formula), Some("✓".to_string()));
+
+        let unique_id =
+            json!({ "type": "unique_id", "unique_id": { "prefix": "TASK", "number": 42 } });
+        assert_eq!(display(unique_id), Some("TASK-42".to_string()));
+
+        let created = json!({ "type": "created_time", "created_time": "2024-07-01T09:30:00.000Z" });
+        assert_eq!(display(created), Some("2024-07-01 09:30".to_string()));
+
+        let status = json!({ "type": "status", "status": { "name": "Done" } });
+        assert_eq!(display(status), Some("Done".to_string()));
+
+        let files = json!({ "type": "files", "files": [{ "name": "spec.pdf", "type": "file" }] });
+        assert_eq!(display(files), Some("spec.pdf".to_string()));
+    }
+
+    #[test]
+    fn test_date_property_uses_date_display() {
+        let due: PropertyValue = serde_json::from_value(json!({
+            "type": "date",
+            "date": { "start": "2024-07-01T09:30:00.000+09:00", "end": "2024-07-03" }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            with_date_display(DateDisplay::Utc, || due.display()),
+            Some("2024-07-01T00:30:00Z → 2024-07-03".to_string())
+        );
+        let day = DateDisplay::Local {
+            format: "%d.%m.%Y".to_string(),
+        };
+        assert_eq!(
+            with_date_display(day, || due.display())
+                .unwrap()
+                .split_once(" → ")
+                .unwrap()
+                .1,
+            "03.07.2024"
+        );
+    }
+
+    #[test]
+    fn test_formula_and_rollup_values() {
+        let parse = |value: Value| serde_json::from_value::<PropertyValue>(value).unwrap();
+
+        let ratio = parse(json!({
+            "type": "formula",
+            "formula": { "type": "number", "number": 0.30000000000000004 }
+        }));
+        assert_eq!(ratio.display(), Some("0.3".to_string()));
+        assert_eq!(ratio.to_json(), json!(0.30000000000000004));
+
+        let due = parse(json!({
+            "type": "formula",
+            "formula": { "type": "date", "date": { "start": "2024-07-01" } }
+        }));
+        assert_eq!(due.display(), Some("2024-07-01".to_string()));
+
+        let label =
+            parse(json!({ "type": "formula", "formula": { "type": "string", "string": "" } }));
+        assert_eq!(label.display(), None);
+        assert_eq!(label.to_json(), json!(""));
+
+        let PropertyValue::Rollup(rollup) = parse(json!({
+            "type": "rollup",
+            "rollup": { "type": "number", "number": 3, "function": "count" }
+        })) else {
+            panic!("expected a rollup");
+        };
+        assert_eq!(rollup.function, "count");
+        assert_eq!(
+            PropertyValue::Rollup(rollup).display(),
+            Some("3".to_string())
+        );
+    }
+
+    #[test]
+    fn test_unknown_block_type_is_unsupported() {
+        let block: Block = serde_json::from_value(json!({
+            "id": "b1",
+            "type": "fancy_widget",
+            "fancy_widget": {}
+        }))
+        .unwrap();
+        assert!(matches!(block.kind, BlockKind::Unsupported(ref t) if t == "fancy_widget"));
+    }
+
+    #[test]
+    fn test_child_page_block() {
+        let block: Block = serde_json::from_value(json!({
+            "id": "b2",
+            "type": "child_page",
+            "has_children": true,
+            "child_page": { "title": "Specs" }
+        }))
+        .unwrap();
+        assert!(matches!(block.kind, BlockKind::ChildPage(ref c) if c.title == "Specs"));
+        assert_eq!(block.text(), "Specs");
+    }
+
+    #[test]
+    fn test_rich_text_mentions_and_equations() {
+        let rich_text: Vec<RichText> = serde_json::from_value(json!([
+            { "type": "text", "plain_text": "Ask " },
+            {
+                "type": "mention",
+                "plain_text": "@Alice",
+                "mention": { "type": "user", "user": { "id": "u1", "name": "Alice" } }
+            },
+            { "type": "text", "plain_text": " by " },
+            {
+                "type": "mention",
+                "plain_text": "2024-07-01T09:30:00.000+09:00 → 2024-07-02",
+                "mention": {
+                    "type": "date",
+                    "date": { "start": "2024-07-01T09:30:00.000+09:00", "end": "2024-07-02" }
+                }
+            },
+            { "type": "text", "plain_text": ": " },
+            { "type": "equation", "plain_text": "", "equation": { "expression": "E=mc^2" } },
+            {
+                "type": "mention",
+                "plain_text": "",
+                "mention": { "type": "page", "page": { "id": "p1" } }
+            }
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            plain_text(&rich_text),
+            "Ask @Alice by 2024-07-01 09:30 → 2024-07-02: E=mc^2Untitled"
+        );
+        assert_eq!(
+            rich_text[6].mention.as_ref().unwrap().object_id(),
+            Some("p1")
+        );
+    }
+
+    #[test]
+    fn test_nested_children() {
+        let block: Block = serde_json::from_value(json!({
+            "id": "t1",
+            "type": "toggle",
+            "has_children": true,
+            "toggle": { "rich_text": [{ "plain_text": "More" }] },
+            "children": [{
+                "id": "p1",
+                "type": "paragraph",
+                "paragraph": { "rich_text": [{ "plain_text": "hidden" }] }
+            }]
+        }))
+        .unwrap();
+        assert!(matches!(block.kind, BlockKind::Toggle(_)));
+        assert_eq!(block.children.len(), 1);
+    }
+}
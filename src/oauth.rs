@@ -0,0 +1,240 @@
+//! OAuth authorization-code flow for public integrations.
+//!
+//! `auth login --oauth` sends the user to Notion's consent page with a
+//! `localhost` redirect URI, answers the redirect with [`receive_code`] and
+//! trades the code for an access token with [`exchange_code`]. The redirect
+//! URI must be registered on the integration exactly as built by
+//! [`redirect_uri`].
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use crate::error::NotionError;
+use crate::utils::NOTION_API_BASE;
+
+/// Port of the local callback server unless `--port` is given
+pub const DEFAULT_CALLBACK_PORT: u16 = 8765;
+
+/// What the token endpoint returns
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub bot_id: String,
+    pub workspace_id: String,
+    #[serde(default)]
+    pub workspace_name: Option<String>,
+}
+
+/// `http://localhost:<port>/callback`
+pub fn redirect_uri(port: u16) -> String {
+    format!("http://localhost:{}/callback", port)
+}
+
+/// An unguessable `state` value to tie the callback to this login
+pub fn new_state() -> String {
+    // RandomState is seeded from the OS for every instance
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(std::process::id());
+    format!("{:016x}", hasher.finish())
+}
+
+/// Consent page URL for `client_id`
+///
+/// ```
+/// use notion_cli_tool::oauth::authorize_url;
+///
+/// let url = authorize_url("abc", "http://localhost:8765/callback", "xyz");
+/// assert!(url.contains("client_id=abc"));
+/// assert!(url.contains("redirect_uri=http%3A%2F%2Flocalhost%3A8765%2Fcallback"));
+/// ```
+pub fn authorize_url(client_id: &str, redirect_uri: &str, state: &str) -> String {
+    let mut url = reqwest::Url::parse(&format!("{}/oauth/authorize", NOTION_API_BASE))
+        .expect("valid API base URL");
+    url.query_pairs_mut()
+        .append_pair("client_id", client_id)
+        .append_pair("response_type", "code")
+        .append_pair("owner", "user")
+        .append_pair("redirect_uri", redirect_uri)
+        .append_pair("state", state);
+    url.into()
+}
+
+/// Serve redirects on `listener` until one arrives at `/callback` for this
+/// login, and return its authorization code. Other paths (like the browser's
+/// favicon request) get a 404, callbacks with another `state` a 400, and
+/// connections that send nothing are dropped.
+pub fn receive_code(listener: &TcpListener, state: &str) -> Result<String> {
+    loop {
+        let (mut stream, _) = listener
+            .accept()
+            .context("Failed to accept the OAuth callback")?;
+
+        // Browsers open spare connections that may never send a request
+        let mut request_line = String::new();
+        let read = stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .and_then(|_| BufReader::new(&stream).read_line(&mut request_line));
+        if read.is_err() {
+            continue;
+        }
+        let target = request_line.split_whitespace().nth(1).unwrap_or_default();
+
+        let result = match parse_callback(target, state) {
+            Callback::Done(result) => result,
+            Callback::OtherLogin => {
+                respond(
+                    &mut stream,
+                    "400 Bad Request",
+                    "This callback belongs to another login.",
+                );
+                continue;
+            }
+            Callback::OtherPath => {
+                respond(&mut stream, "404 Not Found", "");
+                continue;
+            }
+        };
+
+        match &result {
+            Ok(_) => respond(
+                &mut stream,
+                "200 OK",
+                "notion-cli is now logged in. You can close this tab.",
+            ),
+            Err(e) => respond(
+                &mut stream,
+                "400 Bad Request",
+                &format!("Login failed: {}", e),
+            ),
+        }
+        return result;
+    }
+}
+
+/// Answer a request with a plain-text `message`
+fn respond(stream: &mut TcpStream, status: &str, message: &str) {
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        message.len(),
+        message
+    );
+}
+
+/// A request to the callback server
+#[derive(Debug)]
+enum Callback {
+    /// The authorization code, or why it wasn't granted
+    Done(Result<String>),
+    /// A `/callback` whose `state` isn't this login's
+    OtherLogin,
+    /// Any other path, like `/favicon.ico`
+    OtherPath,
+}
+
+/// What a `/callback?code=...&state=...` request target carries
+fn parse_callback(target: &str, state: &str) -> Callback {
+    let Some(url) = reqwest::Url::parse(&format!("http://localhost{}", target))
+        .ok()
+        .filter(|url| url.path() == "/callback")
+    else {
+        return Callback::OtherPath;
+    };
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.into_owned())
+    };
+
+    if param("state").as_deref() != Some(state) {
+        Callback::OtherLogin
+    } else if let Some(error) = param("error") {
+        Callback::Done(Err(anyhow::anyhow!(
+            "Authorization was not granted ({})",
+            error
+        )))
+    } else {
+        Callback::Done(param("code").context("Callback has no authorization code"))
+    }
+}
+
+/// Trade an authorization code for an access token
+pub async fn exchange_code(
+    http: &reqwest::Client,
+    client_id: &str,
+    client_secret: &str,
+    code: &str,
+    redirect_uri: &str,
+) -> Result<TokenResponse> {
+    let response = http
+        .post(format!("{}/oauth/token", NOTION_API_BASE))
+        .basic_auth(client_id, Some(client_secret))
+        .json(&serde_json::json!({
+            "grant_type": "authorization_code",
+            "code": code,
+            "redirect_uri": redirect_uri,
+        }))
+        .send()
+        .await
+        .context("Failed to reach the Notion token endpoint")?;
+
+    let status = response.status();
+    let body = response.text().await.context("Failed to read response")?;
+    if !status.is_success() {
+        return Err(NotionError::from_response(status.as_u16(), &body).into());
+    }
+    serde_json::from_str(&body).context("Failed to parse token response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_callback() {
+        let Callback::Done(code) = parse_callback("/callback?code=c0de&state=s1", "s1") else {
+            panic!("expected a code");
+        };
+        assert_eq!(code.unwrap(), "c0de");
+
+        assert!(matches!(
+            parse_callback("/favicon.ico", "s1"),
+            Callback::OtherPath
+        ));
+        assert!(matches!(
+            parse_callback("/callback?code=c0de&state=other", "s1"),
+            Callback::OtherLogin
+        ));
+        let Callback::Done(denied) = parse_callback("/callback?error=access_denied&state=s1", "s1")
+        else {
+            panic!("expected a result");
+        };
+        assert!(denied.unwrap_err().to_string().contains("access_denied"));
+    }
+
+    #[test]
+    fn test_receive_code_outlasts_stray_connections() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let browser = std::thread::spawn(move || {
+            // A preconnect that closes without a request, then a stale callback
+            drop(TcpStream::connect(addr).unwrap());
+            let mut stale = TcpStream::connect(addr).unwrap();
+            write!(stale, "GET /callback?code=old&state=other HTTP/1.1\r\n\r\n").unwrap();
+            let mut answer = String::new();
+            let _ = std::io::Read::read_to_string(&mut stale, &mut answer);
+            let mut real = TcpStream::connect(addr).unwrap();
+            write!(real, "GET /callback?code=c0de&state=s1 HTTP/1.1\r\n\r\n").unwrap();
+            answer
+        });
+
+        assert_eq!(receive_code(&listener, "s1").unwrap(), "c0de");
+        assert!(browser.join().unwrap().starts_with("HTTP/1.1 400"));
+    }
+}
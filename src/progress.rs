@@ -0,0 +1,48 @@
+//! Progress spinners and bars on stderr for long-running operations.
+//!
+//! Reporting is off by default so library users get no terminal output; the
+//! binary turns it on unless `--quiet` is given or stdout isn't a terminal.
+//! When off, the returned bars are hidden and all updates are no-ops. Bars
+//! clear themselves when dropped, including on early returns.
+
+use indicatif::{ProgressBar, ProgressFinish, ProgressStyle};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Turn progress reporting on or off for the whole process
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// A spinner counting items of unknown total (`inc` as they arrive)
+pub fn spinner(message: &str) -> ProgressBar {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return ProgressBar::hidden();
+    }
+    let bar = ProgressBar::new_spinner()
+        .with_style(
+            ProgressStyle::with_template("{spinner} {msg}: {human_pos}")
+                .expect("valid progress template"),
+        )
+        .with_message(message.to_string())
+        .with_finish(ProgressFinish::AndClear);
+    bar.enable_steady_tick(Duration::from_millis(100));
+    bar
+}
+
+/// A bar for `len` steps, with an ETA
+pub fn bar(len: usize, message: &str) -> ProgressBar {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return ProgressBar::hidden();
+    }
+    ProgressBar::new(len as u64)
+        .with_style(
+            ProgressStyle::with_template("{msg} [{bar:30}] {pos}/{len} (ETA {eta})")
+                .expect("valid progress template")
+                .progress_chars("=> "),
+        )
+        .with_message(message.to_string())
+        .with_finish(ProgressFinish::AndClear)
+}
@@ -1,138 +1,988 @@
-use colored::Colorize;
+use colored::{Color, ColoredString, Colorize};
+use console::{measure_text_width, pad_str, truncate_str, Alignment};
+use std::sync::OnceLock;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Style, ThemeSet};
+use syntect::parsing::SyntaxSet;
+use syntect::util::{as_24_bit_terminal_escaped, LinesWithEndings};
+
+use crate::models::{
+    plain_text, Block, BlockKind, ChildContent, MediaContent, Page, PageNode, PropertyValue,
+    RichText, TableContent, TextContent,
+};
 
 pub fn extract_title(item: &serde_json::Value) -> String {
-    if let Some(props) = item.get("properties") {
-        if let Some(title_prop) = props.get("title").or(props.get("Name")) {
-            if let Some(title_arr) = title_prop.get("title").and_then(|t| t.as_array()) {
-                if let Some(first) = title_arr.first() {
-                    if let Some(text) = first.get("plain_text").and_then(|t| t.as_str()) {
-                        return text.to_string();
-                    }
-                }
+    serde_json::from_value::<Page>(item.clone())
+        .ok()
+        .and_then(|page| page.title())
+        .unwrap_or_else(|| "(Untitled)".to_string())
+}
+
+pub fn extract_property_value(prop: &serde_json::Value) -> Option<String> {
+    serde_json::from_value::<PropertyValue>(prop.clone())
+        .ok()
+        .and_then(|p| p.display())
+}
+
+/// Describe the configuration of a database property definition
+/// (select choices, number format, formula expression, relation target, ...)
+pub fn schema_details(prop: &serde_json::Value) -> String {
+    let prop_type = prop.get("type").and_then(|t| t.as_str()).unwrap_or("");
+    let config = prop.get(prop_type);
+    let field = |key: &str| {
+        config
+            .and_then(|c| c.get(key))
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string()
+    };
+
+    match prop_type {
+        "select" | "multi_select" | "status" => config
+            .and_then(|c| c.get("options"))
+            .and_then(|o| o.as_array())
+            .map(|opts| {
+                opts.iter()
+                    .filter_map(|o| o.get("name").and_then(|n| n.as_str()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .unwrap_or_default(),
+        "number" => field("format"),
+        "formula" => field("expression"),
+        "relation" => {
+            let target = field("data_source_id");
+            if target.is_empty() {
+                format!("→ {}", field("database_id"))
+            } else {
+                format!("→ {}", target)
             }
         }
+        "rollup" => format!(
+            "{}.{} ({})",
+            field("relation_property_name"),
+            field("rollup_property_name"),
+            field("function")
+        ),
+        "unique_id" => field("prefix"),
+        _ => String::new(),
     }
+}
 
-    if let Some(title_arr) = item.get("title").and_then(|t| t.as_array()) {
-        if let Some(first) = title_arr.first() {
-            if let Some(text) = first.get("plain_text").and_then(|t| t.as_str()) {
-                return text.to_string();
-            }
-        }
+/// Print a database schema as an aligned Name / Type / Details table,
+/// with the title property first
+pub fn print_schema(properties: &serde_json::Value) {
+    let Some(props) = properties.as_object() else {
+        return;
+    };
+
+    let mut rows: Vec<(String, String, String)> = props
+        .iter()
+        .map(|(name, prop)| {
+            let prop_type = prop
+                .get("type")
+                .and_then(|t| t.as_str())
+                .unwrap_or("unknown")
+                .to_string();
+            (name.clone(), prop_type, schema_details(prop))
+        })
+        .collect();
+    rows.sort_by_key(|(name, prop_type, _)| (prop_type != "title", name.to_lowercase()));
+
+    let name_width = rows
+        .iter()
+        .map(|(n, _, _)| n.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    let type_width = rows
+        .iter()
+        .map(|(_, t, _)| t.len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+
+    println!(
+        "  {:<nw$}  {:<tw$}  {}",
+        "Name".bold(),
+        "Type".bold(),
+        "Details".bold(),
+        nw = name_width,
+        tw = type_width
+    );
+    for (name, prop_type, details) in &rows {
+        let pad = name_width - name.chars().count();
+        println!(
+            "  {}{}  {:<tw$}  {}",
+            name,
+            " ".repeat(pad),
+            prop_type.cyan(),
+            details.dimmed(),
+            tw = type_width
+        );
     }
+}
+
+/// A page's property values other than the title, one `Name  value` line
+/// each with the names aligned, in name order. Rollups are followed by their
+/// function (`(sum)`); empty values show as `-`.
+pub fn format_properties(properties: &serde_json::Value) -> Vec<String> {
+    let values: Vec<(&String, PropertyValue)> = properties
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(_, prop)| prop["type"] != "title")
+        .filter_map(|(name, prop)| Some((name, serde_json::from_value(prop.clone()).ok()?)))
+        .collect();
+    let width = values
+        .iter()
+        .map(|(name, _)| measure_text_width(name))
+        .max()
+        .unwrap_or(0);
 
-    "(Untitled)".to_string()
+    values
+        .iter()
+        .map(|(name, value)| {
+            let text = value
+                .display()
+                .map_or_else(|| "-".dimmed().to_string(), |v| v.replace('\n', " "));
+            let function = match value {
+                PropertyValue::Rollup(rollup) if !rollup.function.is_empty() => {
+                    format!(" ({})", rollup.function.replace('_', " "))
+                        .dimmed()
+                        .to_string()
+                }
+                _ => String::new(),
+            };
+            format!(
+                "{}  {}{}",
+                pad_str(name, width, Alignment::Left, None),
+                text,
+                function
+            )
+        })
+        .collect()
 }
 
-pub fn extract_property_value(prop: &serde_json::Value) -> Option<String> {
-    if let Some(rich_text) = prop.get("rich_text").and_then(|r| r.as_array()) {
-        let text: String = rich_text
-            .iter()
-            .filter_map(|rt| rt.get("plain_text").and_then(|t| t.as_str()))
-            .collect();
-        if !text.is_empty() {
-            return Some(text);
+/// Columns are never truncated below this width to fit the terminal
+const MIN_COLUMN_WIDTH: usize = 8;
+
+/// Lay out rows as an aligned table: a header line, a rule, then one line per
+/// row. With `max_width`, the widest columns shrink (down to
+/// [`MIN_COLUMN_WIDTH`]) until a line fits, and cut-off cells end in `…`.
+/// Widths are measured in terminal columns, so wide characters line up.
+pub fn format_table(
+    headers: &[String],
+    rows: &[Vec<String>],
+    max_width: Option<usize>,
+) -> Vec<String> {
+    const GAP: &str = "  ";
+    let single_line = |s: &str| s.replace(['\n', '\r'], " ");
+
+    let mut widths: Vec<usize> = headers.iter().map(|h| measure_text_width(h)).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(measure_text_width(&single_line(cell)));
         }
     }
 
-    if let Some(select) = prop.get("select") {
-        if let Some(name) = select.get("name").and_then(|n| n.as_str()) {
-            return Some(name.to_string());
+    if let Some(max_width) = max_width {
+        let gaps = GAP.len() * widths.len().saturating_sub(1);
+        let available = max_width.saturating_sub(gaps);
+        while widths.iter().sum::<usize>() > available {
+            let Some(widest) = (0..widths.len()).max_by_key(|&i| widths[i]) else {
+                break;
+            };
+            if widths[widest] <= MIN_COLUMN_WIDTH {
+                break;
+            }
+            widths[widest] -= 1;
         }
     }
 
-    if let Some(multi_select) = prop.get("multi_select").and_then(|m| m.as_array()) {
-        let values: Vec<&str> = multi_select
+    let line = |cells: &[String]| {
+        cells
             .iter()
-            .filter_map(|s| s.get("name").and_then(|n| n.as_str()))
-            .collect();
-        if !values.is_empty() {
-            return Some(values.join(", "));
+            .zip(&widths)
+            .map(|(cell, &width)| {
+                let cell = single_line(cell);
+                let cell = truncate_str(&cell, width, "…");
+                pad_str(&cell, width, Alignment::Left, None).into_owned()
+            })
+            .collect::<Vec<_>>()
+            .join(GAP)
+            .trim_end()
+            .to_string()
+    };
+
+    let rule: Vec<String> = widths.iter().map(|&w| "─".repeat(w)).collect();
+    [line(headers), rule.join(GAP)]
+        .into_iter()
+        .chain(rows.iter().map(|row| line(row)))
+        .collect()
+}
+
+/// Print a list of blocks, descending into any nested `children` with indentation
+pub fn print_blocks(blocks: &[Block], indent: usize) {
+    let mut number = 0;
+    for block in blocks {
+        // Consecutive numbered items count up, as in Notion
+        number = match block.kind {
+            BlockKind::NumberedListItem(_) => number + 1,
+            _ => 0,
+        };
+        print_numbered_block(block, indent, number);
+
+        // Table rows are rendered by the table itself
+        if !matches!(block.kind, BlockKind::Table(_)) {
+            print_blocks(&block.children, indent + 1);
+        }
+    }
+}
+
+/// Plain text of a block tree, one block per line with nested blocks
+/// indented and list items marked, as `read --copy` puts it on the clipboard
+pub fn blocks_to_text(blocks: &[Block]) -> String {
+    let mut lines = Vec::new();
+    push_text_lines(blocks, 0, &mut lines);
+    lines.join("\n")
+}
+
+fn push_text_lines(blocks: &[Block], depth: usize, lines: &mut Vec<String>) {
+    let indent = "  ".repeat(depth);
+    let mut number = 0;
+    for block in blocks {
+        number = match block.kind {
+            BlockKind::NumberedListItem(_) => number + 1,
+            _ => 0,
+        };
+        let marker = match block.kind {
+            BlockKind::BulletedListItem(_) => "- ".to_string(),
+            BlockKind::NumberedListItem(_) => format!("{}. ", number),
+            _ => String::new(),
+        };
+        lines.push(format!("{}{}{}", indent, marker, block.text()));
+        // Table rows sit at the table's own level
+        let depth = match block.kind {
+            BlockKind::Table(_) => depth,
+            _ => depth + 1,
+        };
+        push_text_lines(&block.children, depth, lines);
+    }
+}
+
+/// Characters of block text shown in a [`format_block_tree`] line
+const TREE_PREVIEW_WIDTH: usize = 50;
+
+/// Draw a block tree with branch lines, one block per line: its type, the
+/// start of its text and its ID. Blocks whose children weren't fetched (past
+/// `--depth`, or sub-pages) are marked with `+`.
+pub fn format_block_tree(blocks: &[Block]) -> Vec<String> {
+    let mut lines = Vec::new();
+    push_tree_lines(blocks, "", &mut lines);
+    lines
+}
+
+fn push_tree_lines(blocks: &[Block], prefix: &str, lines: &mut Vec<String>) {
+    for (i, block) in blocks.iter().enumerate() {
+        let last = i + 1 == blocks.len();
+        let mut line = format!(
+            "{}{}{}",
+            prefix,
+            if last { "└─ " } else { "├─ " },
+            block.block_type.cyan()
+        );
+        let text = block.text();
+        let preview = text.lines().next().unwrap_or_default().trim();
+        if !preview.is_empty() {
+            let preview = truncate_str(preview, TREE_PREVIEW_WIDTH, "…");
+            line.push_str(&format!(" \"{}\"", preview));
+        }
+        if block.has_children && block.children.is_empty() {
+            line.push_str(" +");
         }
+        line.push_str(&format!("  {}", block.id.dimmed()));
+        lines.push(line);
+
+        let prefix = format!("{}{}", prefix, if last { "   " } else { "│  " });
+        push_tree_lines(&block.children, &prefix, lines);
     }
+}
+
+/// Draw page trees (see [`PageNode`]): each top-level node on its own line,
+/// nodes below it on branch lines. A node shows its title, how many pages
+/// and databases are under it, and its ID.
+pub fn format_page_tree(nodes: &[PageNode]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for node in nodes {
+        lines.push(page_tree_line(node, ""));
+        push_page_tree_lines(&node.children, "", &mut lines);
+    }
+    lines
+}
+
+fn push_page_tree_lines(nodes: &[PageNode], prefix: &str, lines: &mut Vec<String>) {
+    for (i, node) in nodes.iter().enumerate() {
+        let last = i + 1 == nodes.len();
+        let branch = format!("{}{}", prefix, if last { "└─ " } else { "├─ " });
+        lines.push(page_tree_line(node, &branch));
+
+        let prefix = format!("{}{}", prefix, if last { "   " } else { "│  " });
+        push_page_tree_lines(&node.children, &prefix, lines);
+    }
+}
+
+fn page_tree_line(node: &PageNode, branch: &str) -> String {
+    let icon = if node.object == "database" {
+        "🗃"
+    } else {
+        "📄"
+    };
+    let title = if node.title.is_empty() {
+        "(Untitled)"
+    } else {
+        &node.title
+    };
+    let mut line = format!("{}{} {}", branch, icon, title);
+    let (pages, databases) = node.descendants();
+    let counts: Vec<String> = [
+        (pages, "page", "pages"),
+        (databases, "database", "databases"),
+    ]
+    .into_iter()
+    .filter(|(n, _, _)| *n > 0)
+    .map(|(n, one, many)| format!("{} {}", n, if n == 1 { one } else { many }))
+    .collect();
+    if !counts.is_empty() {
+        line.push_str(&format!(" {}", format!("({})", counts.join(", ")).dimmed()));
+    }
+    line.push_str(&format!("  {}", node.id.dimmed()));
+    line
+}
+
+/// List the headings of the page under each table of contents block, as
+/// nested bulleted items, so [`print_blocks`] shows them. Sub-pages fetched
+/// into the tree (see `read --follow`) get tables of contents of their own.
+pub fn expand_tables_of_contents(blocks: &mut [Block]) {
+    let mut headings = Vec::new();
+    collect_headings(blocks, &mut headings);
+    let toc = nest_headings(&headings);
+    fill_tables_of_contents(blocks, &toc);
+}
 
-    if let Some(number) = prop.get("number") {
-        if let Some(n) = number.as_f64() {
-            return Some(n.to_string());
+fn collect_headings(blocks: &[Block], out: &mut Vec<(u8, Vec<RichText>)>) {
+    for block in blocks {
+        match &block.kind {
+            BlockKind::Heading1(t) => out.push((1, t.rich_text.clone())),
+            BlockKind::Heading2(t) => out.push((2, t.rich_text.clone())),
+            BlockKind::Heading3(t) => out.push((3, t.rich_text.clone())),
+            BlockKind::ChildPage(_) => continue,
+            _ => {}
         }
+        collect_headings(&block.children, out);
     }
+}
 
-    if let Some(checkbox) = prop.get("checkbox").and_then(|c| c.as_bool()) {
-        return Some(if checkbox { "✓" } else { "✗" }.to_string());
+/// Turn headings into list items, each lower-level heading nested under the
+/// heading above it
+fn nest_headings(headings: &[(u8, Vec<RichText>)]) -> Vec<Block> {
+    let mut items = Vec::new();
+    let mut i = 0;
+    while i < headings.len() {
+        let (level, rich_text) = &headings[i];
+        let end = headings[i + 1..]
+            .iter()
+            .position(|(next, _)| next <= level)
+            .map_or(headings.len(), |n| i + 1 + n);
+        items.push(Block {
+            id: String::new(),
+            block_type: "bulleted_list_item".to_string(),
+            has_children: end > i + 1,
+            kind: BlockKind::BulletedListItem(TextContent {
+                rich_text: rich_text.clone(),
+            }),
+            children: nest_headings(&headings[i + 1..end]),
+        });
+        i = end;
     }
+    items
+}
 
-    if let Some(date) = prop.get("date") {
-        if let Some(start) = date.get("start").and_then(|s| s.as_str()) {
-            return Some(start.to_string());
+fn fill_tables_of_contents(blocks: &mut [Block], toc: &[Block]) {
+    for block in blocks {
+        match block.kind {
+            BlockKind::TableOfContents => block.children = toc.to_vec(),
+            BlockKind::ChildPage(_) => expand_tables_of_contents(&mut block.children),
+            _ => fill_tables_of_contents(&mut block.children, toc),
         }
     }
+}
 
-    if let Some(url) = prop.get("url").and_then(|u| u.as_str()) {
-        return Some(url.to_string());
+fn render_table(table: &TableContent, rows: &[Block], pad: &str) -> String {
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .filter_map(|row| match &row.kind {
+            BlockKind::TableRow(r) => Some(r.cells.iter().map(|c| plain_text(c)).collect()),
+            _ => None,
+        })
+        .collect();
+
+    if cells.is_empty() {
+        return format!("{}{}", pad, "(empty table)".dimmed());
     }
 
-    None
+    let columns = cells
+        .iter()
+        .map(|r| r.len())
+        .max()
+        .unwrap_or(0)
+        .max(table.table_width);
+    let widths: Vec<usize> = (0..columns)
+        .map(|i| {
+            cells
+                .iter()
+                .filter_map(|r| r.get(i))
+                .map(|c| c.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let border = |fill: &str| {
+        let parts: Vec<String> = widths.iter().map(|w| fill.repeat(w + 2)).collect();
+        format!("{}+{}+", pad, parts.join("+"))
+    };
+
+    let mut lines = vec![border("-")];
+    for (i, row) in cells.iter().enumerate() {
+        let parts: Vec<String> = widths
+            .iter()
+            .enumerate()
+            .map(|(col, w)| {
+                let cell = row.get(col).map(String::as_str).unwrap_or("");
+                format!(" {}{} ", cell, " ".repeat(w - cell.chars().count()))
+            })
+            .collect();
+        lines.push(format!("{}|{}|", pad, parts.join("|")));
+
+        if i == 0 && table.has_column_header && cells.len() > 1 {
+            lines.push(border("="));
+        }
+    }
+    lines.push(border("-"));
+
+    lines.join("\n")
 }
 
-pub fn print_block(block: &serde_json::Value) {
-    let block_type = block
-        .get("type")
-        .and_then(|t| t.as_str())
-        .unwrap_or("unknown");
+pub fn print_block(block: &Block, indent: usize) {
+    print_numbered_block(block, indent, 1);
+}
 
-    match block_type {
-        "paragraph" => {
-            if let Some(text) = extract_rich_text(block, "paragraph") {
-                println!("{}", text);
+/// [`print_block`], numbering a numbered list item `number`
+fn print_numbered_block(block: &Block, indent: usize, number: usize) {
+    let pad = "  ".repeat(indent);
+
+    match &block.kind {
+        BlockKind::Paragraph(c) => {
+            if let Some(text) = extract_rich_text(&c.rich_text) {
+                println!("{}", indent_lines(&text, &pad));
+            }
+        }
+        BlockKind::Heading1(c) => {
+            if let Some(text) = extract_rich_text(&c.rich_text) {
+                println!("\n{}{}", pad, format!("# {}", text).bold());
+            }
+        }
+        BlockKind::Heading2(c) => {
+            if let Some(text) = extract_rich_text(&c.rich_text) {
+                println!("\n{}{}", pad, format!("## {}", text).bold());
             }
         }
-        "heading_1" => {
-            if let Some(text) = extract_rich_text(block, "heading_1") {
-                println!("\n{}", format!("# {}", text).bold());
+        BlockKind::Heading3(c) => {
+            if let Some(text) = extract_rich_text(&c.rich_text) {
+                println!("\n{}{}", pad, format!("### {}", text).bold());
             }
         }
-        "heading_2" => {
-            if let Some(text) = extract_rich_text(block, "heading_2") {
-                println!("\n{}", format!("## {}", text).bold());
+        BlockKind::BulletedListItem(c) => {
+            if let Some(text) = extract_rich_text(&c.rich_text) {
+                println!("{}  • {}", pad, text);
             }
         }
-        "heading_3" => {
-            if let Some(text) = extract_rich_text(block, "heading_3") {
-                println!("\n{}", format!("### {}", text).bold());
+        BlockKind::NumberedListItem(c) => {
+            if let Some(text) = extract_rich_text(&c.rich_text) {
+                println!("{}  {}. {}", pad, number, text);
             }
         }
-        "bulleted_list_item" => {
-            if let Some(text) = extract_rich_text(block, "bulleted_list_item") {
-                println!("  • {}", text);
+        BlockKind::Toggle(c) => {
+            if let Some(text) = extract_rich_text(&c.rich_text) {
+                println!("{}▸ {}", pad, text);
             }
         }
-        "numbered_list_item" => {
-            if let Some(text) = extract_rich_text(block, "numbered_list_item") {
-                println!("  1. {}", text);
+        BlockKind::Quote(c) => {
+            if let Some(text) = extract_rich_text(&c.rich_text) {
+                println!("{}", indent_lines(&text, &format!("{}> ", pad)));
             }
         }
-        "code" => {
-            if let Some(text) = extract_rich_text(block, "code") {
-                println!("```\n{}\n```", text.dimmed());
+        BlockKind::Code(c) => {
+            let code = plain_text(&c.rich_text);
+            if !code.is_empty() {
+                let color = colored::control::SHOULD_COLORIZE.should_colorize();
+                println!("{}", format_code(&code, &c.language, &pad, color));
             }
         }
-        "divider" => {
-            println!("{}", "---".dimmed());
+        BlockKind::Image(m) => print_media(m, "🖼", "image", &pad),
+        BlockKind::Video(m) => print_media(m, "🎬", "video", &pad),
+        BlockKind::Pdf(m) => print_media(m, "📄", "pdf", &pad),
+        BlockKind::Audio(m) => print_media(m, "🔊", "audio", &pad),
+        BlockKind::File(m) => print_media(m, "📎", "file", &pad),
+        BlockKind::Embed(e) => {
+            let label = extract_rich_text(&e.caption).unwrap_or_else(|| "[embed]".to_string());
+            println!("{}🔗 {} ({})", pad, label, e.url.dimmed());
+        }
+        BlockKind::Table(table) => {
+            println!("{}", render_table(table, &block.children, &pad));
+        }
+        BlockKind::Divider => {
+            println!("{}{}", pad, "---".dimmed());
+        }
+        BlockKind::TableOfContents => {
+            println!("{}{}", pad, "[Table of contents]".dimmed());
+        }
+        BlockKind::Breadcrumb => {
+            println!("{}{}", pad, "[Breadcrumb]".dimmed());
+        }
+        BlockKind::ChildPage(c) => {
+            // A followed sub-page (see `read --follow`) starts a new section
+            let line = format!("📄 {} ({})", child_title(c), block.id.dimmed());
+            if block.children.is_empty() {
+                println!("{}{}", pad, line);
+            } else {
+                println!("\n{}{}", pad, line.bold());
+            }
+        }
+        BlockKind::ChildDatabase(c) => {
+            println!("{}🗃 {} ({})", pad, child_title(c), block.id.dimmed());
+        }
+        BlockKind::Unsupported(kind) => {
+            println!("{}{}", pad, format!("[{}]", kind).dimmed());
         }
-        _ => {}
+        BlockKind::TableRow(_) => {}
     }
 }
 
-pub fn extract_rich_text(block: &serde_json::Value, block_type: &str) -> Option<String> {
-    let rich_text = block.get(block_type)?.get("rich_text")?.as_array()?;
-    let text: String = rich_text
-        .iter()
-        .filter_map(|rt| rt.get("plain_text").and_then(|t| t.as_str()))
-        .collect();
+/// Print a media block as `icon label (url)`, labelled by caption, file name or kind
+fn print_media(media: &MediaContent, icon: &str, kind: &str, pad: &str) {
+    let label = extract_rich_text(&media.caption)
+        .or_else(|| media.name.clone())
+        .unwrap_or_else(|| format!("[{}]", kind));
+
+    match media.url() {
+        Some(url) => println!("{}{} {} ({})", pad, icon, label, url.dimmed()),
+        None => println!("{}{} {}", pad, icon, label),
+    }
+}
+
+fn child_title(child: &ChildContent) -> &str {
+    if child.title.is_empty() {
+        "Untitled"
+    } else {
+        &child.title
+    }
+}
+
+/// A code block as a fence tagged with its language. With `color`, the code
+/// is syntax highlighted when its language is known and dimmed otherwise.
+fn format_code(code: &str, language: &str, pad: &str, color: bool) -> String {
+    let tag = if language == "plain text" {
+        ""
+    } else {
+        language
+    };
+    let body = if color {
+        highlight(code, language)
+            .map(|code| indent_lines(&code, pad))
+            .unwrap_or_else(|| indent_lines(code, pad).dimmed().to_string())
+    } else {
+        indent_lines(code, pad)
+    };
+    format!("{pad}```{}\n{}\n{pad}```", tag, body)
+}
+
+/// `code` with 24-bit terminal colors for a Notion code block `language`,
+/// or `None` when there's no syntax for it
+fn highlight(code: &str, language: &str) -> Option<String> {
+    let lines = highlight_lines(code, language, "base16-ocean.dark", |ranges| {
+        let escaped = as_24_bit_terminal_escaped(ranges, false);
+        // Reset at the end of each line so indentation isn't colored
+        Some(format!("{}\x1b[0m", escaped.trim_end_matches(['\r', '\n'])))
+    })?;
+    Some(lines.join("\n"))
+}
+
+/// Highlight `code` with syntect's syntax for a Notion code block `language`
+/// and one of its default themes, turning each line's styled ranges (line
+/// ending included) into text with `render`. `None` when there's no syntax
+/// for the language. Used for `read` and HTML exports alike.
+pub(crate) fn highlight_lines(
+    code: &str,
+    language: &str,
+    theme: &str,
+    mut render: impl FnMut(&[(Style, &str)]) -> Option<String>,
+) -> Option<Vec<String>> {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    static THEMES: OnceLock<ThemeSet> = OnceLock::new();
 
-    if text.is_empty() {
+    let token = match language {
+        "plain text" => return None,
+        "c++" => "cpp",
+        "c#" => "cs",
+        "shell" | "bash" => "sh",
+        other => other,
+    };
+    let syntaxes = SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines);
+    let syntax = syntaxes.find_syntax_by_token(token)?;
+    let theme = THEMES
+        .get_or_init(ThemeSet::load_defaults)
+        .themes
+        .get(theme)?;
+
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    LinesWithEndings::from(code)
+        .map(|line| render(&highlighter.highlight_line(line, syntaxes).ok()?))
+        .collect()
+}
+
+/// Prefix every line of `text` with `pad`
+fn indent_lines(text: &str, pad: &str) -> String {
+    if pad.is_empty() {
+        return text.to_string();
+    }
+    text.lines()
+        .map(|line| format!("{}{}", pad, line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Rich text for the terminal, with its annotations styled (see
+/// [`styled_rich_text`]), or `None` when empty
+pub fn extract_rich_text(rich_text: &[RichText]) -> Option<String> {
+    if plain_text(rich_text).is_empty() {
         None
     } else {
-        Some(text)
+        Some(styled_rich_text(rich_text))
+    }
+}
+
+/// Render rich text with its annotations as terminal styles: bold, italic,
+/// strikethrough, underline, code (cyan) and Notion colors. Links become
+/// terminal hyperlinks when color is on, else `text (url)`. Mentions show
+/// the user, page or date they name, and equations their expression.
+pub fn styled_rich_text(rich_text: &[RichText]) -> String {
+    let colorize = colored::control::SHOULD_COLORIZE.should_colorize();
+    rich_text
+        .iter()
+        .map(|item| {
+            let styled = style_text(item).to_string();
+            match item.href.as_deref() {
+                Some(href) if colorize => {
+                    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", href, styled)
+                }
+                // Mentions link to what they name, which the text already says
+                Some(_) if item.mention.is_some() => styled,
+                Some(href) if href != item.plain_text => format!("{} ({})", styled, href),
+                _ => styled,
+            }
+        })
+        .collect()
+}
+
+fn style_text(item: &RichText) -> ColoredString {
+    let a = &item.annotations;
+    let mut text = item.text().normal();
+    if item.equation.is_some() {
+        text = text.italic();
+    }
+    if a.bold {
+        text = text.bold();
+    }
+    if a.italic {
+        text = text.italic();
+    }
+    if a.strikethrough {
+        text = text.strikethrough();
+    }
+    if a.underline || item.href.is_some() {
+        text = text.underline();
+    }
+    if a.code {
+        text = text.cyan();
+    }
+    match a.color.strip_suffix("_background") {
+        Some(color) => match notion_color(color) {
+            Some(c) => text.on_color(c),
+            None => text,
+        },
+        None => match notion_color(&a.color) {
+            Some(c) => text.color(c),
+            None => text,
+        },
+    }
+}
+
+/// Closest terminal color for a Notion text color (`default` has none)
+fn notion_color(name: &str) -> Option<Color> {
+    match name {
+        "gray" => Some(Color::BrightBlack),
+        "brown" | "yellow" => Some(Color::Yellow),
+        "orange" => Some(Color::BrightYellow),
+        "green" => Some(Color::Green),
+        "blue" => Some(Color::Blue),
+        "purple" => Some(Color::Magenta),
+        "pink" => Some(Color::BrightMagenta),
+        "red" => Some(Color::Red),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_code_tags_language() {
+        let code = "fn main() {\n    run();\n}";
+        assert_eq!(
+            format_code(code, "rust", "  ", false),
+            "  ```rust\n  fn main() {\n      run();\n  }\n  ```"
+        );
+        assert_eq!(format_code("ls", "plain text", "", false), "```\nls\n```");
+
+        let highlighted = format_code(code, "rust", "", true);
+        assert!(
+            highlighted.starts_with("```rust\n\x1b[38;2;"),
+            "{:?}",
+            highlighted
+        );
+        assert_eq!(highlighted.lines().count(), 5);
+        assert!(highlight(code, "plain text").is_none());
+        assert!(highlight(code, "not-a-language").is_none());
+    }
+
+    #[test]
+    fn test_format_table_aligns_and_truncates() {
+        let headers = vec!["Name".to_string(), "Status".to_string()];
+        let rows = vec![
+            vec!["Write the quarterly report".to_string(), "Done".to_string()],
+            vec!["이슈 정리".to_string(), "In progress".to_string()],
+        ];
+
+        let lines = format_table(&headers, &rows, None);
+        assert_eq!(lines[0], "Name                        Status");
+        assert_eq!(lines[2], "Write the quarterly report  Done");
+        assert_eq!(lines[3], "이슈 정리                   In progress");
+
+        let lines = format_table(&headers, &rows, Some(24));
+        assert_eq!(lines[2], "Write the …  Done");
+        assert!(lines.iter().all(|l| measure_text_width(l) <= 24));
+    }
+
+    fn row(cells: &[&str]) -> Block {
+        serde_json::from_value(serde_json::json!({
+            "type": "table_row",
+            "table_row": {
+                "cells": cells.iter().map(|c| vec![serde_json::json!({ "plain_text": c })]).collect::<Vec<_>>()
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_styled_rich_text_links() {
+        colored::control::set_override(false);
+        let rich_text: Vec<RichText> = serde_json::from_value(serde_json::json!([
+            { "plain_text": "See ", "annotations": { "bold": true } },
+            { "plain_text": "the docs", "href": "https://example.com" },
+            { "plain_text": " or " },
+            { "plain_text": "https://example.org", "href": "https://example.org" }
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            styled_rich_text(&rich_text),
+            "See the docs (https://example.com) or https://example.org"
+        );
+        assert_eq!(extract_rich_text(&[]), None);
+    }
+
+    #[test]
+    fn test_expand_tables_of_contents() {
+        let heading = |level: u8, text: &str| {
+            let block_type = format!("heading_{}", level);
+            serde_json::json!({
+                "type": block_type,
+                (block_type): { "rich_text": [{ "plain_text": text }] }
+            })
+        };
+        let mut blocks: Vec<Block> = serde_json::from_value(serde_json::json!([
+            { "type": "table_of_contents", "table_of_contents": {} },
+            heading(1, "Intro"),
+            heading(2, "Goals"),
+            heading(3, "Stretch"),
+            heading(2, "Scope"),
+            heading(1, "Plan")
+        ]))
+        .unwrap();
+
+        expand_tables_of_contents(&mut blocks);
+        let toc = &blocks[0].children;
+        let texts = |items: &[Block]| items.iter().map(Block::text).collect::<Vec<_>>();
+        assert_eq!(texts(toc), ["Intro", "Plan"]);
+        assert_eq!(texts(&toc[0].children), ["Goals", "Scope"]);
+        assert_eq!(texts(&toc[0].children[0].children), ["Stretch"]);
+    }
+
+    #[test]
+    fn test_blocks_to_text() {
+        let item = |block_type: &str, text: &str| {
+            serde_json::json!({
+                "type": block_type,
+                (block_type): { "rich_text": [{ "plain_text": text }] }
+            })
+        };
+        let mut list = item("bulleted_list_item", "Fruit");
+        list["children"] = serde_json::json!([
+            item("numbered_list_item", "Apple"),
+            item("numbered_list_item", "Pear")
+        ]);
+        let blocks: Vec<Block> =
+            serde_json::from_value(serde_json::json!([item("paragraph", "Groceries"), list]))
+                .unwrap();
+
+        assert_eq!(
+            blocks_to_text(&blocks),
+            "Groceries\n- Fruit\n  1. Apple\n  2. Pear"
+        );
+    }
+
+    #[test]
+    fn test_format_properties() {
+        colored::control::set_override(false);
+        let properties = serde_json::json!({
+            "Name": { "type": "title", "title": [{ "plain_text": "Q3 report" }] },
+            "Total": {
+                "type": "rollup",
+                "rollup": { "type": "number", "number": 1234.5, "function": "sum" }
+            },
+            "Ratio": { "type": "formula", "formula": { "type": "number", "number": 0.30000000000000004 } },
+            "Shipped": { "type": "formula", "formula": { "type": "boolean", "boolean": false } },
+            "Due": { "type": "formula", "formula": { "type": "date", "date": { "start": "2024-07-01" } } },
+            "Latest": {
+                "type": "rollup",
+                "rollup": { "type": "date", "date": null, "function": "latest_date" }
+            }
+        });
+
+        assert_eq!(
+            format_properties(&properties),
+            [
+                "Due      2024-07-01",
+                "Latest   - (latest date)",
+                "Ratio    0.3",
+                "Shipped  ✗",
+                "Total    1234.5 (sum)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_block_tree() {
+        colored::control::set_override(false);
+        let block = |id: &str, block_type: &str, text: &str| {
+            serde_json::json!({
+                "id": id,
+                "type": block_type,
+                (block_type): { "rich_text": [{ "plain_text": text }] }
+            })
+        };
+        let mut toggle = block("b2", "toggle", "Details");
+        toggle["has_children"] = serde_json::json!(true);
+        toggle["children"] = serde_json::json!([
+            block("b3", "paragraph", "First line\nsecond line"),
+            block("b4", "paragraph", "")
+        ]);
+        let mut list = block("b5", "bulleted_list_item", "Not fetched");
+        list["has_children"] = serde_json::json!(true);
+        let blocks: Vec<Block> = serde_json::from_value(serde_json::json!([
+            block("b1", "heading_2", "Intro"),
+            toggle,
+            list
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            format_block_tree(&blocks),
+            [
+                "├─ heading_2 \"Intro\"  b1",
+                "├─ toggle \"Details\"  b2",
+                "│  ├─ paragraph \"First line\"  b3",
+                "│  └─ paragraph  b4",
+                "└─ bulleted_list_item \"Not fetched\" +  b5",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_page_tree() {
+        colored::control::set_override(false);
+        let node = |object: &str, id: &str, title: &str, children| PageNode {
+            object: object.to_string(),
+            id: id.to_string(),
+            title: title.to_string(),
+            children,
+        };
+        let tree = node(
+            "page",
+            "p1",
+            "Wiki",
+            vec![
+                node(
+                    "page",
+                    "p2",
+                    "Projects",
+                    vec![node("database", "d1", "Tasks", vec![])],
+                ),
+                node("page", "p3", "", vec![]),
+            ],
+        );
+
+        assert_eq!(
+            format_page_tree(&[tree]),
+            [
+                "📄 Wiki (2 pages, 1 database)  p1",
+                "├─ 📄 Projects (1 database)  p2",
+                "│  └─ 🗃 Tasks  d1",
+                "└─ 📄 (Untitled)  p3",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_table_alignment() {
+        colored::control::set_override(false);
+        let table = TableContent {
+            table_width: 2,
+            has_column_header: true,
+            has_row_header: false,
+        };
+        let rows = vec![row(&["Name", "Qty"]), row(&["Apple", "3"])];
+
+        let rendered = render_table(&table, &rows, "");
+        let expected = "\
++-------+-----+
+| Name  | Qty |
++=======+=====+
+| Apple | 3   |
++-------+-----+";
+        assert_eq!(rendered, expected);
     }
 }
@@ -0,0 +1,134 @@
+//! Recorded API responses ("cassettes") for running without a live workspace.
+//!
+//! With `NOTION_CLI_RECORD=<dir>`, every response the client receives is also
+//! written to `<dir>`; with `NOTION_CLI_REPLAY=<dir>`, responses are read back
+//! from there and nothing is sent. Each request maps to one JSON file named
+//! after its method and path, plus a hash of its query string and body, so a
+//! recording can be edited by hand or written from scratch for tests:
+//!
+//! ```text
+//! fixtures/get-pages-2fb74f324ab980f583dfc93c885072e7.json
+//! {"status": 200, "body": {"object": "page", "id": "...", ...}}
+//! ```
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Whether responses are being written or read back
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Record,
+    Replay,
+}
+
+/// A directory of recorded responses
+#[derive(Debug, Clone)]
+pub struct Cassette {
+    pub dir: PathBuf,
+    pub mode: Mode,
+}
+
+/// One recorded response
+#[derive(Debug, Serialize, Deserialize)]
+struct Recording {
+    status: u16,
+    body: serde_json::Value,
+}
+
+impl Cassette {
+    pub fn new(dir: impl Into<PathBuf>, mode: Mode) -> Self {
+        Self {
+            dir: dir.into(),
+            mode,
+        }
+    }
+
+    /// Replay from `NOTION_CLI_REPLAY`, or record to `NOTION_CLI_RECORD`
+    pub fn from_env() -> Option<Self> {
+        let var = |name| env::var_os(name).filter(|v| !v.is_empty());
+        var("NOTION_CLI_REPLAY")
+            .map(|dir| Self::new(dir, Mode::Replay))
+            .or_else(|| var("NOTION_CLI_RECORD").map(|dir| Self::new(dir, Mode::Record)))
+    }
+
+    /// File holding the response to `method` on `url` (relative to the API
+    /// base, query string included) with `body`
+    pub fn path_for(&self, method: &str, url: &str, body: &[u8]) -> PathBuf {
+        self.dir.join(fixture_name(method, url, body))
+    }
+
+    /// The recorded response at `path`
+    pub fn load(&self, path: &Path) -> Result<reqwest::Response> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("No recorded response at {}", path.display()))?;
+        let recording: Recording = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid recording {}", path.display()))?;
+        let body = match recording.body {
+            serde_json::Value::String(text) => text,
+            json => json.to_string(),
+        };
+        let response = http::Response::builder()
+            .status(recording.status)
+            .header("Content-Type", "application/json")
+            .body(body)
+            .context("Invalid recorded status")?;
+        Ok(response.into())
+    }
+
+    /// Save `response` at `path`, returning an equivalent response to use in
+    /// its place (the body can only be read once)
+    pub async fn record(
+        &self,
+        path: &Path,
+        response: reqwest::Response,
+    ) -> Result<reqwest::Response> {
+        let status = response.status().as_u16();
+        let text = response.text().await.context("Failed to read response")?;
+        let body = serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text));
+
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let content = serde_json::to_string_pretty(&Recording { status, body })
+            .context("Failed to serialize recording")?;
+        fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+
+        self.load(path)
+    }
+}
+
+/// `get-pages-abc.json`, or `post-search-1a2b3c4d.json` when the request
+/// has a query string or body
+///
+/// ```
+/// use notion_cli_tool::replay::fixture_name;
+///
+/// assert_eq!(fixture_name("GET", "/pages/abc", b""), "get-pages-abc.json");
+/// assert_ne!(
+///     fixture_name("POST", "/search", br#"{"query":"a"}"#),
+///     fixture_name("POST", "/search", br#"{"query":"b"}"#)
+/// );
+/// ```
+pub fn fixture_name(method: &str, url: &str, body: &[u8]) -> String {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let mut name = method.to_lowercase();
+    for segment in path.split('/').filter(|s| !s.is_empty()) {
+        name.push('-');
+        name.push_str(segment);
+    }
+    if !query.is_empty() || !body.is_empty() {
+        let hash = fnv1a(query.as_bytes().iter().chain(body));
+        name.push_str(&format!("-{:08x}", hash));
+    }
+    name.push_str(".json");
+    name
+}
+
+/// 32-bit FNV-1a: small and stable across Rust versions, unlike `DefaultHasher`
+fn fnv1a<'a>(bytes: impl Iterator<Item = &'a u8>) -> u32 {
+    bytes.fold(0x811c9dc5, |hash, &b| {
+        (hash ^ b as u32).wrapping_mul(0x01000193)
+    })
+}
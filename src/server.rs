@@ -0,0 +1,331 @@
+//! JSON-RPC daemon for `serve`.
+//!
+//! One long-lived [`NotionClient`] answers requests on a Unix domain socket,
+//! so scripts and editors making many small calls share its connection pool,
+//! rate limiter and caches instead of paying process startup and a TLS
+//! handshake per call. Each line is a JSON-RPC 2.0 request and gets one line
+//! back; methods are named after the CLI subcommands:
+//!
+//! ```text
+//! {"jsonrpc":"2.0","id":1,"method":"search","params":{"query":"roadmap","limit":5}}
+//! {"jsonrpc":"2.0","id":2,"method":"append","params":{"page":"<page_id>","content":"Hi"}}
+//! ```
+//!
+//! Read methods are `search`, `read`, `blocks`, `query`, `db-schema` and
+//! `whoami`. Writes take the same fields as batch operations (see
+//! [`crate::batch`]) and return the API response.
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::batch::Operation;
+use crate::client::NotionClient;
+use crate::error::NotionError;
+
+/// Methods that change the workspace, run as [`Operation`]s
+const WRITE_METHODS: &[&str] = &[
+    "create",
+    "db-create",
+    "append",
+    "append-code",
+    "append-heading",
+    "update",
+    "delete",
+    "delete-block",
+    "comment",
+];
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// A failed API call; `data` carries the CLI's exit code for the error
+const API_ERROR: i64 = -32000;
+
+#[derive(Deserialize)]
+struct Request {
+    /// Absent for notifications, which get no response
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SearchParams {
+    #[serde(default)]
+    query: String,
+    /// `page` or `database`
+    object: Option<String>,
+    #[serde(default = "default_search_limit")]
+    limit: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReadParams {
+    page: String,
+    depth: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BlocksParams {
+    block: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct QueryParams {
+    database: String,
+    /// A raw Notion filter object
+    filter: Option<Value>,
+    #[serde(default = "default_query_limit")]
+    limit: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DatabaseParams {
+    database: String,
+}
+
+fn default_search_limit() -> usize {
+    20
+}
+
+fn default_query_limit() -> usize {
+    100
+}
+
+/// A JSON-RPC error object
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+}
+
+impl From<anyhow::Error> for RpcError {
+    fn from(e: anyhow::Error) -> Self {
+        let data = e
+            .downcast_ref::<NotionError>()
+            .map(|api| json!({ "exit_code": api.exit_code(), "hint": api.hint() }));
+        Self {
+            code: API_ERROR,
+            message: format!("{:#}", e),
+            data,
+        }
+    }
+}
+
+/// Answer one request line, or `None` for a notification
+///
+/// ```
+/// # async fn run() -> anyhow::Result<()> {
+/// use notion_cli_tool::{server, NotionClient};
+///
+/// let client = NotionClient::new("ntn_xxx".to_string(), 30)?;
+/// let response = server::handle_line(&client, r#"{"jsonrpc":"2.0","id":1,"method":"nope"}"#)
+///     .await
+///     .unwrap();
+/// assert_eq!(response["error"]["code"], -32601);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn handle_line(client: &NotionClient, line: &str) -> Option<Value> {
+    let request: Request = match serde_json::from_str::<Value>(line) {
+        Err(e) => {
+            return Some(error_response(
+                Value::Null,
+                RpcError::new(PARSE_ERROR, e.to_string()),
+            ))
+        }
+        Ok(value) => match serde_json::from_value(value) {
+            Ok(request) => request,
+            Err(e) => {
+                let error = RpcError::new(INVALID_REQUEST, e.to_string());
+                return Some(error_response(Value::Null, error));
+            }
+        },
+    };
+
+    let result = dispatch(client, &request.method, request.params).await;
+    let id = request.id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => error_response(id, error),
+    })
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    let mut body = json!({ "code": error.code, "message": error.message });
+    if let Some(data) = error.data {
+        body["data"] = data;
+    }
+    json!({ "jsonrpc": "2.0", "id": id, "error": body })
+}
+
+/// Deserialize `params`, treating a missing value as `{}`
+fn params<T: serde::de::DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+async fn dispatch(client: &NotionClient, method: &str, raw: Value) -> Result<Value, RpcError> {
+    match method {
+        "search" => {
+            let p: SearchParams = params(raw)?;
+            let results = client
+                .search_filtered(&p.query, p.object.as_deref(), None, "desc", p.limit)
+                .await?;
+            Ok(Value::Array(results))
+        }
+        "read" => {
+            let p: ReadParams = params(raw)?;
+            let (page, blocks) = client.get_page_content(&p.page, p.depth).await?;
+            Ok(json!({ "page": page, "blocks": blocks }))
+        }
+        "blocks" => {
+            let p: BlocksParams = params(raw)?;
+            Ok(Value::Array(client.get_blocks(&p.block).await?))
+        }
+        "query" => {
+            let p: QueryParams = params(raw)?;
+            let results = client
+                .query_database(&p.database, p.filter.as_ref(), &[], p.limit)
+                .await?;
+            Ok(Value::Array(results))
+        }
+        "db-schema" => {
+            let p: DatabaseParams = params(raw)?;
+            let (database, properties) = client.get_database_schema(&p.database).await?;
+            Ok(json!({ "database": database, "properties": properties }))
+        }
+        "whoami" => Ok(client.get_me().await?),
+        write if WRITE_METHODS.contains(&write) => {
+            let mut fields = match raw {
+                Value::Null => json!({}),
+                Value::Object(fields) => Value::Object(fields),
+                _ => return Err(RpcError::new(INVALID_PARAMS, "params must be an object")),
+            };
+            fields["op"] = json!(write);
+            let op: Operation = params(fields)?;
+            Ok(op.execute(client).await?)
+        }
+        other => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Unknown method '{}'", other),
+        )),
+    }
+}
+
+/// Serve requests on a Unix socket at `path` until interrupted. A stale
+/// socket file is replaced; one with a live server behind it is an error.
+#[cfg(unix)]
+pub async fn serve(client: NotionClient, path: &std::path::Path) -> Result<()> {
+    use anyhow::{bail, Context};
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{UnixListener, UnixStream};
+
+    if path.exists() {
+        if UnixStream::connect(path).await.is_ok() {
+            bail!("Another server is already listening on {}", path.display());
+        }
+        std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove stale socket {}", path.display()))?;
+    }
+    // Anyone who can connect acts with the API key, so the socket is bound
+    // in a directory only we can enter and moved into place once it's 0600
+    let parent = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(std::path::Path::new("."));
+    let private = parent.join(format!(".notion-cli-serve-{}", std::process::id()));
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private)
+        .with_context(|| format!("Failed to create {}", private.display()))?;
+    let bound = private.join("socket");
+    let listener = UnixListener::bind(&bound)
+        .and_then(|listener| {
+            std::fs::set_permissions(&bound, std::fs::Permissions::from_mode(0o600))?;
+            std::fs::rename(&bound, path)?;
+            Ok(listener)
+        })
+        .with_context(|| format!("Failed to listen on {}", path.display()));
+    let _ = std::fs::remove_file(&bound);
+    let _ = std::fs::remove_dir(&private);
+    let listener = listener?;
+
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => accepted.context("Failed to accept a connection")?.0,
+            _ = tokio::signal::ctrl_c() => break,
+        };
+        let client = client.clone();
+        tokio::spawn(async move {
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                if let Some(response) = handle_line(&client, &line).await {
+                    let mut out = response.to_string();
+                    out.push('\n');
+                    if writer.write_all(out.as_bytes()).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    let _ = std::fs::remove_file(path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_handle_line_errors() {
+        let client = NotionClient::new("ntn_test".to_string(), 5).unwrap();
+
+        let parse = handle_line(&client, "{not json").await.unwrap();
+        assert_eq!(parse["error"]["code"], PARSE_ERROR);
+        assert_eq!(parse["id"], Value::Null);
+
+        let params = handle_line(
+            &client,
+            r#"{"jsonrpc":"2.0","id":"a","method":"read","params":{"pgae":"x"}}"#,
+        )
+        .await
+        .unwrap();
+        assert_eq!(params["id"], "a");
+        assert_eq!(params["error"]["code"], INVALID_PARAMS);
+
+        let write = handle_line(&client, r#"{"id":2,"method":"append","params":[1]}"#)
+            .await
+            .unwrap();
+        assert_eq!(write["error"]["code"], INVALID_PARAMS);
+
+        let notification = handle_line(&client, r#"{"method":"nope"}"#).await;
+        assert!(notification.is_none());
+    }
+}
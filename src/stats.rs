@@ -0,0 +1,157 @@
+//! Client-side aggregation of database query results.
+//!
+//! [`group_stats`] groups fetched pages by a property value and totals numeric
+//! properties per group, for `query --count-by` and `--sum`.
+
+use anyhow::{bail, Result};
+use serde_json::Value;
+
+use crate::models::PropertyValue;
+
+/// Label for pages whose group-by property is empty
+pub const EMPTY_GROUP: &str = "(empty)";
+
+/// Rows sharing one group-by value
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupStats {
+    pub key: String,
+    pub count: usize,
+    /// Per summed property: the total and how many rows had a value
+    pub sums: Vec<(f64, usize)>,
+}
+
+impl GroupStats {
+    /// Average of the `i`th summed property over the rows that had a value
+    pub fn average(&self, i: usize) -> Option<f64> {
+        let (total, n) = self.sums[i];
+        (n > 0).then(|| total / n as f64)
+    }
+}
+
+/// Group pages by the value of `group_by` (one group for everything when
+/// `None`) and total the `sum` properties in each. A page counts once per
+/// option of a multi-select. Groups come largest first.
+///
+/// ```
+/// use notion_cli_tool::stats::group_stats;
+/// use serde_json::json;
+///
+/// let page = |status: &str, points: f64| json!({ "properties": {
+///     "Status": { "type": "select", "select": { "name": status } },
+///     "Points": { "type": "number", "number": points }
+/// }});
+/// let results = [page("Done", 3.0), page("Todo", 1.0), page("Done", 2.0)];
+///
+/// let groups = group_stats(&results, Some("Status"), &["Points".to_string()]).unwrap();
+/// assert_eq!((groups[0].key.as_str(), groups[0].count), ("Done", 2));
+/// assert_eq!(groups[0].sums[0], (5.0, 2));
+/// assert_eq!(groups[0].average(0), Some(2.5));
+/// ```
+pub fn group_stats(
+    results: &[Value],
+    group_by: Option<&str>,
+    sum: &[String],
+) -> Result<Vec<GroupStats>> {
+    let mut groups: Vec<GroupStats> = Vec::new();
+
+    for item in results {
+        let property = |name: &str| {
+            item["properties"]
+                .get(name)
+                .and_then(|p| serde_json::from_value::<PropertyValue>(p.clone()).ok())
+        };
+
+        let mut values = Vec::with_capacity(sum.len());
+        for name in sum {
+            let value = property(name).and_then(|p| p.display());
+            values.push(match value {
+                Some(v) => match v.parse::<f64>() {
+                    Ok(n) => Some(n),
+                    Err(_) => bail!("Property '{}' is not numeric (found '{}')", name, v),
+                },
+                None => None,
+            });
+        }
+
+        let keys = match group_by.map(property) {
+            None => vec![String::new()],
+            Some(Some(PropertyValue::MultiSelect(options))) if !options.is_empty() => {
+                options.into_iter().map(|o| o.name).collect()
+            }
+            Some(value) => vec![value
+                .and_then(|v| v.display())
+                .unwrap_or_else(|| EMPTY_GROUP.to_string())],
+        };
+
+        for key in keys {
+            let index = match groups.iter().position(|g| g.key == key) {
+                Some(i) => i,
+                None => {
+                    groups.push(GroupStats {
+                        key,
+                        count: 0,
+                        sums: vec![(0.0, 0); sum.len()],
+                    });
+                    groups.len() - 1
+                }
+            };
+            let group = &mut groups[index];
+            group.count += 1;
+            for (slot, value) in group.sums.iter_mut().zip(&values) {
+                if let Some(n) = value {
+                    slot.0 += n;
+                    slot.1 += 1;
+                }
+            }
+        }
+    }
+
+    groups.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+    Ok(groups)
+}
+
+/// Format a total or average without trailing zeros (`3`, `2.5`, `0.33`)
+///
+/// ```
+/// use notion_cli_tool::stats::format_number;
+///
+/// assert_eq!(format_number(3.0), "3");
+/// assert_eq!(format_number(1.0 / 3.0), "0.33");
+/// ```
+pub fn format_number(n: f64) -> String {
+    let s = format!("{:.2}", n);
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_group_stats_multi_select_and_empty() {
+        let results = [
+            json!({ "properties": {
+                "Tags": { "type": "multi_select", "multi_select": [{ "name": "a" }, { "name": "b" }] },
+                "Points": { "type": "number", "number": 2 }
+            }}),
+            json!({ "properties": {
+                "Tags": { "type": "multi_select", "multi_select": [] },
+                "Points": { "type": "number", "number": null }
+            }}),
+        ];
+
+        let groups = group_stats(&results, Some("Tags"), &["Points".to_string()]).unwrap();
+        let keys: Vec<_> = groups.iter().map(|g| g.key.as_str()).collect();
+        assert_eq!(keys, ["(empty)", "a", "b"]);
+        assert_eq!(groups[0].average(0), None);
+        assert_eq!(groups[1].sums[0], (2.0, 1));
+
+        let all = group_stats(&results, None, &[]).unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].count, 2);
+
+        let not_numeric = group_stats(&results, None, &["Tags".to_string()]);
+        assert!(not_numeric.is_err());
+    }
+}
@@ -0,0 +1,456 @@
+//! Mirroring a directory of Markdown files as a Notion page tree, and back.
+//!
+//! `push` maps each subdirectory to a parent page and each `.md` file to a
+//! page under it. A `Foo.md` next to a `Foo/` directory is that directory's
+//! page, which is how `pull` writes pages with sub-pages. [`scan`] reads the directory, and [`plan`] compares it with
+//! the [`PushState`] kept in the directory from the last push (page IDs and
+//! content hashes by path) to decide what to create, update or archive.
+//!
+//! `pull` writes pages as `.md` files starting with [`FrontMatter`] (the page
+//! ID and its `last_edited_time`), which tells it which files are up to date.
+//! Pushing a pulled directory ignores the front matter.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the state file `push` keeps in the pushed directory
+pub const STATE_FILE: &str = ".notion-push.json";
+
+/// A page to mirror: a directory (a parent page, empty unless it has a
+/// `.md` file of the same name beside it) or a `.md` file
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalPage {
+    /// Path relative to the pushed directory, with `/` separators
+    pub path: String,
+    pub title: String,
+    /// The file's Markdown without its title heading, `None` for directories
+    /// without a file
+    pub markdown: Option<String>,
+    /// [`content_hash`] of the whole file, `None` for directories without one
+    pub hash: Option<String>,
+}
+
+/// The pages of a Markdown directory, each parent before its children, in
+/// name order. Hidden entries and other files are skipped, as are
+/// directories without Markdown files below them.
+pub fn scan(dir: &Path) -> Result<Vec<LocalPage>> {
+    let mut pages = Vec::new();
+    scan_into(dir, "", &mut pages)?;
+    Ok(pages)
+}
+
+/// Returns whether any Markdown file was found under `dir`
+fn scan_into(dir: &Path, prefix: &str, pages: &mut Vec<LocalPage>) -> Result<bool> {
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .collect::<Result<_, _>>()
+        .with_context(|| format!("Failed to read {}", dir.display()))?;
+    entries.sort_by_key(|e| e.file_name());
+
+    let mut found = false;
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') {
+            continue;
+        }
+        let path = format!("{}{}", prefix, name);
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            // `Foo.md` beside `Foo/` holds the directory page's content
+            let own_file = dir.join(format!("{}.md", name));
+            let page = if own_file.is_file() {
+                read_page(&own_file, path.clone(), &name)?
+            } else {
+                LocalPage {
+                    path: path.clone(),
+                    title: name,
+                    markdown: None,
+                    hash: None,
+                }
+            };
+            let has_file = page.markdown.is_some();
+            let start = pages.len();
+            pages.push(page);
+            if scan_into(&entry.path(), &format!("{}/", path), pages)? || has_file {
+                found = true;
+            } else {
+                pages.truncate(start);
+            }
+        } else if let Some(stem) = name.strip_suffix(".md").filter(|_| entry.path().is_file()) {
+            if dir.join(stem).is_dir() {
+                continue;
+            }
+            pages.push(read_page(&entry.path(), path, stem)?);
+            found = true;
+        }
+    }
+    Ok(found)
+}
+
+/// The page for the Markdown file at `file`, titled by its heading or `stem`
+fn read_page(file: &Path, path: String, stem: &str) -> Result<LocalPage> {
+    let content =
+        fs::read_to_string(file).with_context(|| format!("Failed to read {}", file.display()))?;
+    let (title, markdown) = split_title(stem, strip_front_matter(&content));
+    Ok(LocalPage {
+        path,
+        title,
+        markdown: Some(markdown.to_string()),
+        hash: Some(content_hash(&content)),
+    })
+}
+
+/// A page title and body from Markdown: a leading `# ` heading is the title,
+/// otherwise the file name is.
+///
+/// ```
+/// use notion_cli_tool::sync::split_title;
+///
+/// assert_eq!(split_title("setup", "# Getting started\n\nInstall it."), ("Getting started".to_string(), "Install it."));
+/// assert_eq!(split_title("setup", "Install it."), ("setup".to_string(), "Install it."));
+/// ```
+pub fn split_title<'a>(file_stem: &str, markdown: &'a str) -> (String, &'a str) {
+    let body = markdown.trim_start();
+    let (first, rest) = body.split_once('\n').unwrap_or((body, ""));
+    match first.trim_end().strip_prefix("# ") {
+        Some(title) if !title.trim().is_empty() => (title.trim().to_string(), rest.trim_start()),
+        _ => (file_stem.to_string(), markdown),
+    }
+}
+
+/// A stable 64-bit FNV-1a hash of `content`, as hex
+///
+/// ```
+/// use notion_cli_tool::sync::content_hash;
+///
+/// assert_eq!(content_hash(""), "cbf29ce484222325");
+/// assert_ne!(content_hash("a"), content_hash("b"));
+/// ```
+pub fn content_hash(content: &str) -> String {
+    let hash = content
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{:016x}", hash)
+}
+
+/// The directory part of a page path, empty at the top
+pub fn parent_path(path: &str) -> &str {
+    path.rsplit_once('/')
+        .map(|(parent, _)| parent)
+        .unwrap_or("")
+}
+
+/// The page a pulled file was written from
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrontMatter {
+    pub id: String,
+    pub last_edited_time: String,
+}
+
+impl FrontMatter {
+    /// The front matter at the start of a file and the Markdown after it, or
+    /// `None` when the file has no `id` in front matter.
+    ///
+    /// ```
+    /// use notion_cli_tool::sync::FrontMatter;
+    ///
+    /// let file = "---\nid: abc\nlast_edited_time: 2024-07-01T09:30:00.000Z\n---\n\n# Notes\n";
+    /// let (front, body) = FrontMatter::parse(file).unwrap();
+    /// assert_eq!(front.id, "abc");
+    /// assert_eq!(body, "# Notes\n");
+    /// assert_eq!(front.to_markdown(), &file[..file.len() - body.len()]);
+    ///
+    /// assert!(FrontMatter::parse("# Notes").is_none());
+    /// ```
+    pub fn parse(markdown: &str) -> Option<(Self, &str)> {
+        let rest = markdown.strip_prefix("---\n")?;
+        let (header, body) = match rest.split_once("\n---\n") {
+            Some(split) => split,
+            None => (rest.strip_suffix("\n---")?, ""),
+        };
+        let mut front = Self::default();
+        for line in header.lines() {
+            match line.split_once(':').map(|(k, v)| (k.trim(), v.trim())) {
+                Some(("id", value)) => front.id = value.to_string(),
+                Some(("last_edited_time", value)) => front.last_edited_time = value.to_string(),
+                _ => {}
+            }
+        }
+        (!front.id.is_empty()).then(|| (front, body.trim_start_matches('\n')))
+    }
+
+    pub fn to_markdown(&self) -> String {
+        format!(
+            "---\nid: {}\nlast_edited_time: {}\n---\n\n",
+            self.id, self.last_edited_time
+        )
+    }
+}
+
+/// Markdown without the front matter `pull` writes, if any
+pub fn strip_front_matter(markdown: &str) -> &str {
+    FrontMatter::parse(markdown).map_or(markdown, |(_, body)| body)
+}
+
+/// A file name (without extension) for a page title: characters that aren't
+/// allowed in file names on some system become `-`.
+///
+/// ```
+/// use notion_cli_tool::sync::file_stem;
+///
+/// assert_eq!(file_stem("Q3 / Q4: Plans?"), "Q3 - Q4- Plans-");
+/// assert_eq!(file_stem("  "), "Untitled");
+/// ```
+pub fn file_stem(title: &str) -> String {
+    let stem: String = title
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => '-',
+            c => c,
+        })
+        .collect();
+    let stem = stem.trim_start_matches('.');
+    if stem.is_empty() {
+        "Untitled".to_string()
+    } else {
+        stem.to_string()
+    }
+}
+
+/// The `.md` files under `dir` (hidden entries aside) that have
+/// [`FrontMatter`], with the page IDs they were pulled from
+pub fn pulled_files(dir: &Path) -> Result<Vec<(PathBuf, String)>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in
+            fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path
+                .file_name()
+                .is_some_and(|n| n.to_string_lossy().starts_with('.'))
+            {
+                continue;
+            }
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension().is_some_and(|e| e == "md") {
+                let content = fs::read_to_string(&path).unwrap_or_default();
+                if let Some((front, _)) = FrontMatter::parse(&content) {
+                    files.push((path, front.id));
+                }
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// What the last push created, by page path
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PushState {
+    /// The page the directory is pushed under
+    pub root: String,
+    pub pages: BTreeMap<String, PushedPage>,
+}
+
+/// A pushed page, with the hash of the file content it was last given
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PushedPage {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+}
+
+impl PushState {
+    pub fn new(root: &str) -> Self {
+        Self {
+            root: root.to_string(),
+            pages: BTreeMap::new(),
+        }
+    }
+
+    /// The state saved at `path`, `None` when there is no file yet
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Invalid push state in {}", path.display()))
+            .map(Some)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self).context("Failed to serialize state")?;
+        fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// The page a path goes under: its directory's page, or the root
+    pub fn parent_id(&self, path: &str) -> Option<&str> {
+        match parent_path(path) {
+            "" => Some(&self.root),
+            parent => self.pages.get(parent).map(|p| p.id.as_str()),
+        }
+    }
+}
+
+/// What `push` does with one page
+#[derive(Debug, Clone, PartialEq)]
+pub enum PushAction<'a> {
+    Create(&'a LocalPage),
+    /// The file changed since it was pushed to the page with this ID
+    Update(&'a LocalPage, String),
+    Unchanged(&'a LocalPage),
+    /// Pushed before, but no longer in the directory
+    Removed {
+        path: String,
+        id: String,
+    },
+}
+
+/// Compare a scanned directory with the last push: new pages are created,
+/// files whose hash changed are updated. Removed pages come last, deepest
+/// first, so a page is archived before the directory page holding it.
+///
+/// ```
+/// use notion_cli_tool::sync::{plan, LocalPage, PushAction, PushState, PushedPage};
+///
+/// let page = |path: &str, hash: &str| LocalPage {
+///     path: path.to_string(),
+///     title: path.to_string(),
+///     markdown: Some(String::new()),
+///     hash: Some(hash.to_string()),
+/// };
+/// let pages = [page("a.md", "1"), page("b.md", "2")];
+///
+/// let mut state = PushState::new("root");
+/// let pushed = |id: &str, hash: &str| PushedPage { id: id.to_string(), hash: Some(hash.to_string()) };
+/// state.pages.insert("a.md".to_string(), pushed("p1", "1"));
+/// state.pages.insert("old.md".to_string(), pushed("p2", "9"));
+///
+/// assert_eq!(
+///     plan(&state, &pages),
+///     [
+///         PushAction::Unchanged(&pages[0]),
+///         PushAction::Create(&pages[1]),
+///         PushAction::Removed { path: "old.md".to_string(), id: "p2".to_string() },
+///     ]
+/// );
+/// ```
+pub fn plan<'a>(state: &PushState, pages: &'a [LocalPage]) -> Vec<PushAction<'a>> {
+    let mut actions: Vec<PushAction> = pages
+        .iter()
+        .map(|page| match state.pages.get(&page.path) {
+            None => PushAction::Create(page),
+            Some(pushed) if pushed.hash != page.hash => PushAction::Update(page, pushed.id.clone()),
+            Some(_) => PushAction::Unchanged(page),
+        })
+        .collect();
+
+    let mut removed: Vec<(&String, &PushedPage)> = state
+        .pages
+        .iter()
+        .filter(|(path, _)| !pages.iter().any(|p| &p.path == *path))
+        .collect();
+    removed.sort_by_key(|(path, _)| std::cmp::Reverse(path.matches('/').count()));
+    actions.extend(
+        removed
+            .into_iter()
+            .map(|(path, pushed)| PushAction::Removed {
+                path: path.clone(),
+                id: pushed.id.clone(),
+            }),
+    );
+    actions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_maps_directories_and_markdown_files() {
+        let dir = std::env::temp_dir().join(format!("notion-cli-scan-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("guides/empty")).unwrap();
+        fs::create_dir_all(dir.join(".git")).unwrap();
+        // Pulled files keep their front matter out of the page
+        fs::write(
+            dir.join("index.md"),
+            "---\nid: p1\nlast_edited_time: 2024-07-01T09:30:00.000Z\n---\n\n# Welcome\n\nStart here.",
+        )
+        .unwrap();
+        fs::write(dir.join("guides/setup.md"), "Install it.").unwrap();
+        fs::write(dir.join("guides/notes.txt"), "not pushed").unwrap();
+        fs::write(dir.join(".git/HEAD.md"), "hidden").unwrap();
+
+        let pages = scan(&dir).unwrap();
+        let summary: Vec<(&str, &str, Option<&str>)> = pages
+            .iter()
+            .map(|p| (p.path.as_str(), p.title.as_str(), p.markdown.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("guides", "guides", None),
+                ("guides/setup.md", "setup", Some("Install it.")),
+                ("index.md", "Welcome", Some("Start here.")),
+            ]
+        );
+        assert_eq!(pages[1].hash, Some(content_hash("Install it.")));
+        assert_eq!(
+            pulled_files(&dir).unwrap(),
+            [(dir.join("index.md"), "p1".to_string())]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_plan_updates_changed_files_and_removes_deepest_first() {
+        let pages = [LocalPage {
+            path: "a.md".to_string(),
+            title: "a".to_string(),
+            markdown: Some("new".to_string()),
+            hash: Some(content_hash("new")),
+        }];
+        let mut state = PushState::new("root");
+        for (path, id, hash) in [
+            ("a.md", "p1", Some(content_hash("old"))),
+            ("docs", "p2", None),
+            ("docs/b.md", "p3", Some(content_hash("b"))),
+        ] {
+            state.pages.insert(
+                path.to_string(),
+                PushedPage {
+                    id: id.to_string(),
+                    hash,
+                },
+            );
+        }
+
+        let actions = plan(&state, &pages);
+        assert_eq!(actions[0], PushAction::Update(&pages[0], "p1".to_string()));
+        let removed: Vec<&str> = actions[1..]
+            .iter()
+            .filter_map(|a| match a {
+                PushAction::Removed { path, .. } => Some(path.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(removed, ["docs/b.md", "docs"]);
+        assert_eq!(state.parent_id("docs/b.md"), Some("p2"));
+        assert_eq!(state.parent_id("a.md"), Some("root"));
+    }
+}
@@ -0,0 +1,224 @@
+//! Helpers for the `todo` commands, which treat a database as a task list.
+//!
+//! [`TaskFields::detect`] finds the due date and completion properties in a
+//! database schema, so any reasonably shaped task database works without
+//! configuration.
+
+use anyhow::{bail, Result};
+use serde_json::{json, Value};
+
+use crate::client::PropertySpec;
+
+/// How a task database records that a task is finished
+#[derive(Debug, Clone, PartialEq)]
+pub enum DoneField {
+    /// A status property; `done` is the option to set
+    Status { name: String, done: String },
+    /// A select property (e.g. "Status"); `done` is the option to set
+    Select { name: String, done: String },
+    /// A checkbox property (e.g. "Done")
+    Checkbox { name: String },
+}
+
+impl DoneField {
+    /// Property assignment that marks a task as finished
+    pub fn done_spec(&self) -> PropertySpec {
+        let (name, prop_type, value) = match self {
+            DoneField::Status { name, done } => (name, "status", done.as_str()),
+            DoneField::Select { name, done } => (name, "select", done.as_str()),
+            DoneField::Checkbox { name } => (name, "checkbox", "true"),
+        };
+        PropertySpec {
+            name: name.clone(),
+            prop_type: prop_type.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    /// Query filter for tasks that are not finished
+    pub fn open_filter(&self) -> Value {
+        match self {
+            DoneField::Status { name, done } => {
+                json!({ "property": name, "status": { "does_not_equal": done } })
+            }
+            DoneField::Select { name, done } => {
+                json!({ "property": name, "select": { "does_not_equal": done } })
+            }
+            DoneField::Checkbox { name } => {
+                json!({ "property": name, "checkbox": { "equals": false } })
+            }
+        }
+    }
+
+    /// Query filter for tasks with the given status, when statuses are named
+    pub fn status_filter(&self, status: &str) -> Option<Value> {
+        match self {
+            DoneField::Status { name, .. } => {
+                Some(json!({ "property": name, "status": { "equals": status } }))
+            }
+            DoneField::Select { name, .. } => {
+                Some(json!({ "property": name, "select": { "equals": status } }))
+            }
+            DoneField::Checkbox { .. } => None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            DoneField::Status { name, .. }
+            | DoneField::Select { name, .. }
+            | DoneField::Checkbox { name } => name,
+        }
+    }
+}
+
+/// The properties of a task database the `todo` commands use
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TaskFields {
+    /// Date property holding the due date
+    pub due: Option<String>,
+    pub done: Option<DoneField>,
+}
+
+impl TaskFields {
+    /// Find the due date and completion properties in a database schema.
+    ///
+    /// The due date is a date property named like "Due" or "Deadline", else
+    /// the first date property. Completion is a status property (done means
+    /// the first option of its "Complete" group), else a select named
+    /// "Status", else a checkbox (preferring one named like "Done").
+    pub fn detect(properties: &Value) -> Self {
+        let Some(props) = properties.as_object() else {
+            return Self::default();
+        };
+        let of_type = |wanted: &str| -> Vec<(&String, &Value)> {
+            props
+                .iter()
+                .filter(|(_, p)| p["type"].as_str() == Some(wanted))
+                .collect()
+        };
+        let named_like = |name: &str, words: &[&str]| {
+            let name = name.to_lowercase();
+            words.iter().any(|w| name.contains(w))
+        };
+
+        let dates = of_type("date");
+        let due = dates
+            .iter()
+            .find(|(name, _)| named_like(name, &["due", "deadline"]))
+            .or(dates.first())
+            .map(|(name, _)| name.to_string());
+
+        let done = if let Some((name, prop)) = of_type("status").first() {
+            Some(DoneField::Status {
+                name: name.to_string(),
+                done: complete_status(prop).unwrap_or_else(|| "Done".to_string()),
+            })
+        } else if let Some((name, prop)) = of_type("select")
+            .into_iter()
+            .find(|(name, _)| named_like(name, &["status"]))
+        {
+            Some(DoneField::Select {
+                name: name.to_string(),
+                done: option_named(&prop["select"], &["done", "complete", "completed"])
+                    .unwrap_or_else(|| "Done".to_string()),
+            })
+        } else {
+            let checkboxes = of_type("checkbox");
+            checkboxes
+                .iter()
+                .find(|(name, _)| named_like(name, &["done", "complete"]))
+                .or(checkboxes.first())
+                .map(|(name, _)| DoneField::Checkbox {
+                    name: name.to_string(),
+                })
+        };
+
+        Self { due, done }
+    }
+}
+
+/// First option in a status property's "Complete" group
+fn complete_status(prop: &Value) -> Option<String> {
+    let status = &prop["status"];
+    let group = status["groups"]
+        .as_array()?
+        .iter()
+        .find(|g| g["name"].as_str() == Some("Complete"))?;
+    let first_id = group["option_ids"].as_array()?.first()?.as_str()?;
+    status["options"]
+        .as_array()?
+        .iter()
+        .find(|o| o["id"].as_str() == Some(first_id))?["name"]
+        .as_str()
+        .map(String::from)
+}
+
+/// A select option whose name is one of `names` (case-insensitive)
+fn option_named(config: &Value, names: &[&str]) -> Option<String> {
+    config["options"]
+        .as_array()?
+        .iter()
+        .filter_map(|o| o["name"].as_str())
+        .find(|n| names.contains(&n.to_lowercase().as_str()))
+        .map(String::from)
+}
+
+/// Validate a `todo done` argument given as a list position (1-based)
+pub fn parse_index(arg: &str, listed: usize) -> Result<Option<usize>> {
+    let Ok(index) = arg.parse::<usize>() else {
+        return Ok(None);
+    };
+    if index == 0 || index > listed {
+        bail!(
+            "No task #{} in the last `todo list` ({} tasks listed)",
+            index,
+            listed
+        );
+    }
+    Ok(Some(index - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_fields() {
+        let schema = json!({
+            "Name": { "type": "title", "title": {} },
+            "Created": { "type": "date", "date": {} },
+            "Due date": { "type": "date", "date": {} },
+            "Status": {
+                "type": "status",
+                "status": {
+                    "options": [
+                        { "id": "1", "name": "Not started" },
+                        { "id": "2", "name": "Shipped" }
+                    ],
+                    "groups": [
+                        { "name": "To-do", "option_ids": ["1"] },
+                        { "name": "Complete", "option_ids": ["2"] }
+                    ]
+                }
+            }
+        });
+        let fields = TaskFields::detect(&schema);
+        assert_eq!(fields.due.as_deref(), Some("Due date"));
+        assert_eq!(
+            fields.done,
+            Some(DoneField::Status {
+                name: "Status".to_string(),
+                done: "Shipped".to_string()
+            })
+        );
+
+        let schema = json!({
+            "Task": { "type": "title", "title": {} },
+            "Done": { "type": "checkbox", "checkbox": {} }
+        });
+        let fields = TaskFields::detect(&schema);
+        assert_eq!(fields.due, None);
+        assert_eq!(fields.done.unwrap().done_spec().value, "true");
+    }
+}
@@ -0,0 +1,576 @@
+//! Terminal UI for `tui`: search, browse the page tree and preview pages.
+//!
+//! Every API call runs on a background task and reports back over a channel,
+//! so the interface stays responsive while pages load.
+
+use anyhow::{Context, Result};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{self, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use notion_cli_tool::models::{Block, BlockKind};
+use notion_cli_tool::render::extract_title;
+use notion_cli_tool::{progress, NotionClient};
+
+use crate::commands::open_in_browser;
+
+/// Rows listed when expanding a database
+const DATABASE_ROWS: usize = 50;
+/// Nesting levels fetched for a preview
+const PREVIEW_DEPTH: usize = 2;
+
+const HELP: &str =
+    "/ search  ↑↓ move  → expand  ← collapse  o open  a append  x archive  r reload  q quit";
+
+/// A page or database in the tree
+struct Node {
+    id: String,
+    title: String,
+    database: bool,
+    url: Option<String>,
+    depth: usize,
+    expanded: bool,
+}
+
+impl Node {
+    fn from_json(item: &serde_json::Value, depth: usize) -> Self {
+        let object = item["object"].as_str().unwrap_or_default();
+        Node {
+            id: item["id"].as_str().unwrap_or_default().to_string(),
+            title: extract_title(item),
+            database: object == "database" || object == "data_source",
+            url: item["url"].as_str().map(String::from),
+            depth,
+            expanded: false,
+        }
+    }
+}
+
+enum Preview {
+    Loading,
+    Ready(Vec<String>),
+    Failed(String),
+}
+
+/// What a key press goes to
+enum Mode {
+    Browse,
+    Search,
+    Append(String),
+    ConfirmArchive,
+}
+
+/// Results of background requests
+enum Msg {
+    Searched(Result<Vec<serde_json::Value>>),
+    Expanded(String, Result<Vec<serde_json::Value>>),
+    Previewed(String, Result<Vec<String>>),
+    Appended(String, Result<()>),
+    Archived(String, Result<()>),
+}
+
+struct App {
+    client: NotionClient,
+    tx: mpsc::UnboundedSender<Msg>,
+    mode: Mode,
+    query: String,
+    nodes: Vec<Node>,
+    list: ListState,
+    previews: HashMap<String, Preview>,
+    scroll: u16,
+    status: String,
+    quit: bool,
+}
+
+/// Run the interface until the user quits, starting with a search for `query`
+pub async fn run(client: NotionClient, query: Option<String>) -> Result<()> {
+    // Spinners would draw over the interface
+    progress::set_enabled(false);
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut app = App {
+        client,
+        tx,
+        mode: Mode::Browse,
+        query: query.unwrap_or_default(),
+        nodes: Vec::new(),
+        list: ListState::default(),
+        previews: HashMap::new(),
+        scroll: 0,
+        status: HELP.to_string(),
+        quit: false,
+    };
+    app.search();
+
+    let mut terminal = ratatui::init();
+    let result = app.event_loop(&mut terminal, &mut rx);
+    ratatui::restore();
+    result
+}
+
+impl App {
+    fn event_loop(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        rx: &mut mpsc::UnboundedReceiver<Msg>,
+    ) -> Result<()> {
+        while !self.quit {
+            while let Ok(msg) = rx.try_recv() {
+                self.receive(msg);
+            }
+            terminal
+                .draw(|frame| self.draw(frame))
+                .context("Failed to draw the interface")?;
+
+            if event::poll(Duration::from_millis(100))? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.key(key);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn selected(&self) -> Option<&Node> {
+        self.list.selected().and_then(|i| self.nodes.get(i))
+    }
+
+    /// Run `request` on a background task and send its result with `wrap`
+    fn spawn<T, F>(&self, request: F, wrap: impl FnOnce(Result<T>) -> Msg + Send + 'static)
+    where
+        T: Send + 'static,
+        F: std::future::Future<Output = Result<T>> + Send + 'static,
+    {
+        let tx = self.tx.clone();
+        tokio::spawn(async move {
+            let _ = tx.send(wrap(request.await));
+        });
+    }
+
+    fn search(&mut self) {
+        let client = self.client.clone();
+        let query = self.query.clone();
+        self.status = format!("Searching for \"{}\"...", query);
+        self.spawn(
+            async move {
+                client
+                    .search_filtered(&query, None, Some("last_edited_time"), "desc", 50)
+                    .await
+            },
+            Msg::Searched,
+        );
+    }
+
+    /// Load the selected node's preview unless it is already loaded
+    fn preview(&mut self, reload: bool) {
+        let Some(node) = self.selected() else {
+            return;
+        };
+        if !reload && self.previews.contains_key(&node.id) {
+            return;
+        }
+        let (id, database) = (node.id.clone(), node.database);
+        self.previews.insert(id.clone(), Preview::Loading);
+        self.scroll = 0;
+
+        let client = self.client.clone();
+        let key = id.clone();
+        self.spawn(
+            async move {
+                if database {
+                    let (_, properties) = client.get_database_schema(&id).await?;
+                    Ok(schema_lines(&properties))
+                } else {
+                    let blocks = client
+                        .get_blocks_recursive(&id, Some(PREVIEW_DEPTH))
+                        .await?;
+                    let blocks: Vec<Block> = serde_json::from_value(blocks.into())?;
+                    let mut lines = Vec::new();
+                    preview_lines(&blocks, 0, &mut lines);
+                    Ok(lines)
+                }
+            },
+            move |result| Msg::Previewed(key, result),
+        );
+    }
+
+    /// Show the selected node's sub-pages (or a database's rows)
+    fn expand(&mut self) {
+        let Some(node) = self.selected() else {
+            return;
+        };
+        if node.expanded {
+            return;
+        }
+        let (id, database) = (node.id.clone(), node.database);
+        self.status = format!("Loading \"{}\"...", node.title);
+
+        let client = self.client.clone();
+        let key = id.clone();
+        self.spawn(
+            async move {
+                if database {
+                    client.query_database(&id, None, &[], DATABASE_ROWS).await
+                } else {
+                    let blocks = client.get_blocks(&id).await?;
+                    Ok(blocks.into_iter().filter_map(child_object).collect())
+                }
+            },
+            move |result| Msg::Expanded(key, result),
+        );
+    }
+
+    fn collapse(&mut self) {
+        let Some(i) = self.list.selected() else {
+            return;
+        };
+        let depth = self.nodes[i].depth;
+        if self.nodes[i].expanded {
+            self.nodes[i].expanded = false;
+            let end = self.subtree_end(i);
+            self.nodes.drain(i + 1..end);
+        } else if depth > 0 {
+            // Jump to the parent
+            if let Some(parent) = self.nodes[..i].iter().rposition(|n| n.depth < depth) {
+                self.select(parent);
+            }
+        }
+    }
+
+    /// Index after the last descendant of node `i`
+    fn subtree_end(&self, i: usize) -> usize {
+        let depth = self.nodes[i].depth;
+        self.nodes[i + 1..]
+            .iter()
+            .position(|n| n.depth <= depth)
+            .map_or(self.nodes.len(), |p| i + 1 + p)
+    }
+
+    fn select(&mut self, i: usize) {
+        if self.nodes.is_empty() {
+            self.list.select(None);
+            return;
+        }
+        self.list.select(Some(i.min(self.nodes.len() - 1)));
+        self.scroll = 0;
+        self.preview(false);
+    }
+
+    fn receive(&mut self, msg: Msg) {
+        match msg {
+            Msg::Searched(Ok(results)) => {
+                self.status = format!("{} results. {}", results.len(), HELP);
+                self.nodes = results.iter().map(|r| Node::from_json(r, 0)).collect();
+                self.select(0);
+            }
+            Msg::Expanded(id, Ok(children)) => {
+                let Some(i) = self.nodes.iter().position(|n| n.id == id && !n.expanded) else {
+                    return;
+                };
+                let depth = self.nodes[i].depth + 1;
+                self.status = format!("{} items. {}", children.len(), HELP);
+                self.nodes[i].expanded = true;
+                let children = children.iter().map(|c| Node::from_json(c, depth));
+                self.nodes.splice(i + 1..i + 1, children);
+            }
+            Msg::Previewed(id, result) => {
+                let preview = match result {
+                    Ok(lines) => Preview::Ready(lines),
+                    Err(e) => Preview::Failed(format!("{:#}", e)),
+                };
+                self.previews.insert(id, preview);
+            }
+            Msg::Appended(id, Ok(())) => {
+                self.status = "✓ Appended".to_string();
+                if self.selected().is_some_and(|n| n.id == id) {
+                    self.preview(true);
+                } else {
+                    self.previews.remove(&id);
+                }
+            }
+            Msg::Archived(id, Ok(())) => {
+                self.status = "✓ Archived".to_string();
+                if let Some(i) = self.nodes.iter().position(|n| n.id == id) {
+                    let end = self.subtree_end(i);
+                    self.nodes.drain(i..end);
+                    self.select(i.saturating_sub(usize::from(i == self.nodes.len())));
+                }
+            }
+            Msg::Searched(Err(e))
+            | Msg::Expanded(_, Err(e))
+            | Msg::Appended(_, Err(e))
+            | Msg::Archived(_, Err(e)) => {
+                self.status = format!("✗ {:#}", e);
+            }
+        }
+    }
+
+    fn key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return;
+        }
+
+        match &mut self.mode {
+            Mode::Search => match key.code {
+                KeyCode::Enter => {
+                    self.mode = Mode::Browse;
+                    self.search();
+                }
+                KeyCode::Esc => self.mode = Mode::Browse,
+                KeyCode::Backspace => {
+                    self.query.pop();
+                }
+                KeyCode::Char(c) => self.query.push(c),
+                _ => {}
+            },
+            Mode::Append(text) => match key.code {
+                KeyCode::Enter => {
+                    let text = std::mem::take(text);
+                    self.mode = Mode::Browse;
+                    self.append(text);
+                }
+                KeyCode::Esc => self.mode = Mode::Browse,
+                KeyCode::Backspace => {
+                    text.pop();
+                }
+                KeyCode::Char(c) => text.push(c),
+                _ => {}
+            },
+            Mode::ConfirmArchive => {
+                self.mode = Mode::Browse;
+                if key.code == KeyCode::Char('y') {
+                    self.archive();
+                } else {
+                    self.status = HELP.to_string();
+                }
+            }
+            Mode::Browse => self.browse_key(key.code),
+        }
+    }
+
+    fn browse_key(&mut self, code: KeyCode) {
+        let i = self.list.selected().unwrap_or(0);
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('/') => self.mode = Mode::Search,
+            KeyCode::Down | KeyCode::Char('j') => self.select(i + 1),
+            KeyCode::Up | KeyCode::Char('k') => self.select(i.saturating_sub(1)),
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter => self.expand(),
+            KeyCode::Left | KeyCode::Char('h') => self.collapse(),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::Char('r') => self.preview(true),
+            KeyCode::Char('o') => {
+                if let Some(node) = self.selected() {
+                    let url = node.url.clone().unwrap_or_else(|| {
+                        format!("https://www.notion.so/{}", node.id.replace('-', ""))
+                    });
+                    self.status = match open_in_browser(&url) {
+                        Ok(()) => format!("Opened {}", url),
+                        Err(e) => format!("✗ {:#}", e),
+                    };
+                }
+            }
+            KeyCode::Char('a') if self.selected().is_some_and(|n| !n.database) => {
+                self.mode = Mode::Append(String::new());
+            }
+            KeyCode::Char('x') => {
+                if let Some(node) = self.selected() {
+                    self.status = format!("Archive \"{}\"? (y/N)", node.title);
+                    self.mode = Mode::ConfirmArchive;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn append(&mut self, text: String) {
+        let Some(node) = self.selected() else {
+            return;
+        };
+        if text.trim().is_empty() {
+            return;
+        }
+        let client = self.client.clone();
+        let id = node.id.clone();
+        let key = id.clone();
+        self.status = "Appending...".to_string();
+        self.spawn(
+            async move { client.append_blocks(&id, &text, None).await.map(|_| ()) },
+            move |result| Msg::Appended(key, result),
+        );
+    }
+
+    fn archive(&mut self) {
+        let Some(node) = self.selected() else {
+            return;
+        };
+        let client = self.client.clone();
+        let id = node.id.clone();
+        let key = id.clone();
+        self.status = format!("Archiving \"{}\"...", node.title);
+        self.spawn(
+            async move { client.delete_page(&id).await.map(|_| ()) },
+            move |result| Msg::Archived(key, result),
+        );
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [search, main, status] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [tree, preview] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(main);
+
+        let searching = matches!(self.mode, Mode::Search);
+        let search_style = if searching {
+            Style::new().yellow()
+        } else {
+            Style::new()
+        };
+        frame.render_widget(
+            Paragraph::new(self.query.as_str()).block(
+                widgets::Block::bordered()
+                    .title(" Search ")
+                    .border_style(search_style),
+            ),
+            search,
+        );
+
+        let items: Vec<ListItem> = self
+            .nodes
+            .iter()
+            .map(|n| {
+                let marker = if n.expanded { "▾" } else { "▸" };
+                let icon = if n.database { "🗃" } else { "📄" };
+                ListItem::new(format!(
+                    "{}{} {} {}",
+                    "  ".repeat(n.depth),
+                    marker,
+                    icon,
+                    n.title
+                ))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(widgets::Block::bordered().title(" Pages "))
+            .highlight_style(Style::new().reversed());
+        frame.render_stateful_widget(list, tree, &mut self.list);
+
+        let (title, lines) = match self.selected() {
+            None => (String::new(), vec![]),
+            Some(node) => {
+                let lines = match self.previews.get(&node.id) {
+                    None | Some(Preview::Loading) => vec![Line::from("Loading...".dim())],
+                    Some(Preview::Failed(e)) => vec![Line::from(format!("✗ {}", e).red())],
+                    Some(Preview::Ready(lines)) if lines.is_empty() => {
+                        vec![Line::from("(empty)".dim())]
+                    }
+                    Some(Preview::Ready(lines)) => {
+                        lines.iter().map(|l| Line::from(l.as_str())).collect()
+                    }
+                };
+                (format!(" {} ", node.title), lines)
+            }
+        };
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(widgets::Block::bordered().title(title))
+                .wrap(Wrap { trim: false })
+                .scroll((self.scroll, 0)),
+            preview,
+        );
+
+        let line = match &self.mode {
+            Mode::Append(text) => Line::from(format!("Append: {}", text)).yellow(),
+            Mode::Search => Line::from("Type a search, Enter to run, Esc to cancel").yellow(),
+            _ => Line::from(self.status.as_str()).dim(),
+        };
+        frame.render_widget(Paragraph::new(line), status);
+    }
+}
+
+/// A `child_page` / `child_database` block as a search-result-like object
+fn child_object(block: serde_json::Value) -> Option<serde_json::Value> {
+    let (object, kind) = match block["type"].as_str()? {
+        "child_page" => ("page", "child_page"),
+        "child_database" => ("database", "child_database"),
+        _ => return None,
+    };
+    let title = block[kind]["title"].as_str().unwrap_or_default();
+    Some(serde_json::json!({
+        "object": object,
+        "id": block["id"],
+        "title": [{ "plain_text": title }]
+    }))
+}
+
+/// `Name: type` per property of a database schema
+fn schema_lines(properties: &serde_json::Value) -> Vec<String> {
+    let mut lines: Vec<String> = properties
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(name, p)| format!("{}: {}", name, p["type"].as_str().unwrap_or("?")))
+        .collect();
+    lines.sort();
+    lines
+}
+
+/// Plain-text lines for a block tree, indented by nesting
+fn preview_lines(blocks: &[Block], indent: usize, out: &mut Vec<String>) {
+    let pad = "  ".repeat(indent);
+    let mut number = 0;
+    for block in blocks {
+        number = match block.kind {
+            BlockKind::NumberedListItem(_) => number + 1,
+            _ => 0,
+        };
+        let text = block.text();
+        let line = match &block.kind {
+            BlockKind::Heading1(_) => format!("# {}", text),
+            BlockKind::Heading2(_) => format!("## {}", text),
+            BlockKind::Heading3(_) => format!("### {}", text),
+            BlockKind::BulletedListItem(_) => format!("• {}", text),
+            BlockKind::NumberedListItem(_) => format!("{}. {}", number, text),
+            BlockKind::Toggle(_) => format!("▸ {}", text),
+            BlockKind::Quote(_) => format!("│ {}", text),
+            BlockKind::Code(_) => format!("```\n{}\n```", text),
+            BlockKind::Divider => "───".to_string(),
+            BlockKind::ChildPage(_) => format!("📄 {}", text),
+            BlockKind::ChildDatabase(_) => format!("🗃 {}", text),
+            BlockKind::Image(_) | BlockKind::Video(_) | BlockKind::Embed(_) => {
+                format!("[{}] {}", block.block_type, text)
+            }
+            BlockKind::Pdf(_) | BlockKind::Audio(_) | BlockKind::File(_) => {
+                format!("[{}] {}", block.block_type, text)
+            }
+            BlockKind::Table(_) | BlockKind::TableOfContents | BlockKind::Breadcrumb => {
+                String::new()
+            }
+            BlockKind::Unsupported(kind) => format!("[{}]", kind),
+            BlockKind::Paragraph(_) | BlockKind::TableRow(_) => text,
+        };
+        out.extend(line.lines().map(|l| format!("{}{}", pad, l)));
+        // Table rows are the table's children; keep them at its level
+        let nested = if matches!(block.kind, BlockKind::Table(_)) {
+            indent
+        } else {
+            indent + 1
+        };
+        preview_lines(&block.children, nested, out);
+    }
+}
@@ -0,0 +1,180 @@
+//! API call statistics for `--stats`.
+//!
+//! The client reports every call it sends (see
+//! [`NotionClient::with_stats`](crate::NotionClient::with_stats)) to an
+//! [`ApiStats`], which totals calls, retries, bytes and time per endpoint.
+//! Endpoints are keyed by method and path, with IDs replaced by `{id}` so
+//! that calls on different pages add up.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// One API call as the client saw it, retries included
+#[derive(Debug, Clone, Default)]
+pub struct Call {
+    pub endpoint: String,
+    pub retries: u32,
+    /// Request and response bodies, over all attempts
+    pub bytes: u64,
+}
+
+/// Totals for one endpoint
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EndpointStats {
+    pub calls: u64,
+    pub retries: u64,
+    pub bytes: u64,
+    pub time: Duration,
+}
+
+/// Shared call counters; clones add to the same totals
+#[derive(Debug, Clone, Default)]
+pub struct ApiStats {
+    endpoints: Arc<Mutex<BTreeMap<String, EndpointStats>>>,
+}
+
+impl ApiStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a finished call that took `time`, waits for retries included
+    pub fn record(&self, call: &Call, time: Duration) {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let stats = endpoints.entry(call.endpoint.clone()).or_default();
+        stats.calls += 1;
+        stats.retries += u64::from(call.retries);
+        stats.bytes += call.bytes;
+        stats.time += time;
+    }
+
+    /// Totals per endpoint, sorted by endpoint
+    pub fn endpoints(&self) -> Vec<(String, EndpointStats)> {
+        self.endpoints
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(endpoint, stats)| (endpoint.clone(), *stats))
+            .collect()
+    }
+
+    /// A summary line for the whole command (`wall` is its run time)
+    /// followed by an aligned line per endpoint, busiest first.
+    ///
+    /// ```
+    /// use notion_cli_tool::usage::{ApiStats, Call};
+    /// use std::time::Duration;
+    ///
+    /// let stats = ApiStats::new();
+    /// let call = |endpoint: &str, retries| Call { endpoint: endpoint.to_string(), retries, bytes: 2048 };
+    /// stats.record(&call("GET /blocks/{id}/children", 0), Duration::from_millis(300));
+    /// stats.record(&call("GET /blocks/{id}/children", 1), Duration::from_millis(900));
+    /// stats.record(&call("PATCH /pages/{id}", 0), Duration::from_millis(200));
+    ///
+    /// let report = stats.report(Duration::from_millis(1500));
+    /// let lines: Vec<&str> = report.lines().collect();
+    /// assert_eq!(lines[0], "API calls: 3 (1 retry, 6.0 KB) in 1.5s");
+    /// assert_eq!(lines[1], "  GET /blocks/{id}/children  2 calls  1 retry    4.0 KB  1.2s");
+    /// assert_eq!(lines[2], "  PATCH /pages/{id}           1 call  0 retries  2.0 KB  200ms");
+    /// ```
+    pub fn report(&self, wall: Duration) -> String {
+        let mut endpoints = self.endpoints();
+        endpoints.sort_by_key(|(_, s)| std::cmp::Reverse(s.calls));
+        let total = endpoints
+            .iter()
+            .fold(EndpointStats::default(), |mut total, (_, s)| {
+                total.calls += s.calls;
+                total.retries += s.retries;
+                total.bytes += s.bytes;
+                total
+            });
+
+        let mut out = format!(
+            "API calls: {} ({}, {}) in {}",
+            total.calls,
+            plural(total.retries, "retry", "retries"),
+            format_bytes(total.bytes),
+            format_duration(wall)
+        );
+        let rows: Vec<[String; 5]> = endpoints
+            .iter()
+            .map(|(endpoint, s)| {
+                [
+                    endpoint.clone(),
+                    plural(s.calls, "call", "calls"),
+                    plural(s.retries, "retry", "retries"),
+                    format_bytes(s.bytes),
+                    format_duration(s.time),
+                ]
+            })
+            .collect();
+        let width = |i: usize| rows.iter().map(|r| r[i].chars().count()).max().unwrap_or(0);
+        let widths = [width(0), width(1), width(2), width(3)];
+        for row in &rows {
+            let _ = write!(
+                out,
+                "\n  {:<w0$}  {:>w1$}  {:<w2$}  {:>w3$}  {}",
+                row[0],
+                row[1],
+                row[2],
+                row[3],
+                row[4],
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2],
+                w3 = widths[3]
+            );
+        }
+        out
+    }
+}
+
+/// `GET /blocks/{id}/children` for a request to
+/// `/v1/blocks/2fb74f32-.../children?page_size=100`: the API version prefix
+/// and query string are dropped and IDs replaced.
+///
+/// ```
+/// use notion_cli_tool::usage::endpoint;
+///
+/// assert_eq!(
+///     endpoint("GET", "/v1/blocks/2fb74f324ab980f583dfc93c885072e7/children"),
+///     "GET /blocks/{id}/children"
+/// );
+/// assert_eq!(endpoint("POST", "/v1/search"), "POST /search");
+/// ```
+pub fn endpoint(method: &str, path: &str) -> String {
+    let path = path.split('?').next().unwrap_or_default();
+    let segments: Vec<&str> = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .skip_while(|s| *s == "v1")
+        .map(|s| if is_id(s) { "{id}" } else { s })
+        .collect();
+    format!("{} /{}", method, segments.join("/"))
+}
+
+fn is_id(segment: &str) -> bool {
+    let hex: Vec<char> = segment.chars().filter(|c| *c != '-').collect();
+    hex.len() == 32 && hex.iter().all(char::is_ascii_hexdigit)
+}
+
+fn plural(n: u64, one: &str, many: &str) -> String {
+    format!("{} {}", n, if n == 1 { one } else { many })
+}
+
+fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1_048_575 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    match duration.as_millis() {
+        ms @ 0..=999 => format!("{}ms", ms),
+        _ => format!("{:.1}s", duration.as_secs_f64()),
+    }
+}
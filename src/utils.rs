@@ -1,19 +1,269 @@
 use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::batch::Operation;
+use crate::diff::Snapshot;
 
 pub const NOTION_API_BASE: &str = "https://api.notion.com/v1";
 pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
 pub const MAX_RETRIES: u32 = 3;
 pub const DEFAULT_RETRY_DELAY_SECS: u64 = 1;
+/// Average requests per second, matching Notion's documented limit
+pub const DEFAULT_RATE_LIMIT: f64 = 3.0;
+/// Sibling blocks whose children are fetched at the same time
+pub const DEFAULT_CONCURRENCY: usize = 4;
+/// Service name for API keys stored in the OS keyring
+pub const KEYRING_SERVICE: &str = "notion-cli";
+/// Files larger than this are sent with the multi-part upload mode
+pub const SINGLE_PART_UPLOAD_LIMIT: usize = 20 * 1024 * 1024;
+/// Chunk size for multi-part uploads
+pub const UPLOAD_PART_SIZE: usize = 10 * 1024 * 1024;
+
+/// `Notion-Version` sent unless overridden
+pub const DEFAULT_API_VERSION: &str = "2025-09-03";
+
+/// First API version where databases are queried through data sources
+pub const DATA_SOURCES_API_VERSION: &str = "2025-09-03";
+
+/// API versions the client is known to work with, oldest first
+pub const KNOWN_API_VERSIONS: &[&str] = &["2022-06-28", "2025-09-03"];
+
+/// Keys accepted by `config get/set/unset`
+pub const CONFIG_KEYS: &[&str] = &[
+    "api_key",
+    "keyring",
+    "timeout",
+    "max_retries",
+    "rate_limit",
+    "concurrency",
+    "cache",
+    "default_parent",
+    "todo_database",
+    "api_version",
+    "proxy",
+    "ca_bundle",
+    "date_format",
+];
 
 /// Config file structure
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Config {
     pub api_key: Option<String>,
+    /// Keyring account holding the API key (instead of `api_key`)
+    pub keyring: Option<String>,
     pub timeout: Option<u64>,
+    /// Retries for rate limits and transient errors (default for `--max-retries`)
+    pub max_retries: Option<u32>,
+    /// Requests per second (default for `--rate-limit`)
+    pub rate_limit: Option<f64>,
+    /// Parallel block fetches (default for `--concurrency`)
+    pub concurrency: Option<usize>,
+    /// Keep page content on disk for `read` and `grep` (off unless `true`)
+    pub cache: Option<bool>,
+    /// Parent page used by `create` and `db-new` when `--parent` is omitted
+    pub default_parent: Option<String>,
+    /// Database used by the `todo` commands
+    pub todo_database: Option<String>,
+    /// Workspace the OAuth token was granted for (`auth login --oauth`)
+    pub workspace: Option<Workspace>,
+    /// Proxy URL, used unless `HTTPS_PROXY` / `ALL_PROXY` is set
+    pub proxy: Option<String>,
+    /// PEM file of extra CA certificates to trust
+    pub ca_bundle: Option<PathBuf>,
+    /// strftime format for dates and times in output (default: `%Y-%m-%d %H:%M`)
+    pub date_format: Option<String>,
+    /// Named workspace profiles (`[profiles.<name>]`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// Per-workspace settings selected with `--profile` / `NOTION_PROFILE`.
+/// Unset fields fall back to the top-level config.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Profile {
+    pub api_key: Option<String>,
+    /// Keyring account holding the API key (instead of `api_key`)
+    pub keyring: Option<String>,
+    pub api_version: Option<String>,
+    /// Overrides the top-level `default_parent`
+    pub default_parent: Option<String>,
+    /// Overrides the top-level `todo_database`
+    pub todo_database: Option<String>,
+    /// Workspace the OAuth token was granted for (`auth login --oauth`)
+    pub workspace: Option<Workspace>,
+}
+
+/// Where an OAuth access token is valid, as returned by the token exchange
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Workspace {
+    pub id: String,
+    pub name: Option<String>,
+    pub bot_id: String,
+}
+
+impl Config {
+    /// Default parent page: the profile's, else the top-level one
+    pub fn default_parent<'a>(&'a self, profile: Option<&'a Profile>) -> Option<&'a str> {
+        profile
+            .and_then(|p| p.default_parent.as_deref())
+            .or(self.default_parent.as_deref())
+    }
+
+    /// Task database for `todo`: the profile's, else the top-level one
+    pub fn todo_database<'a>(&'a self, profile: Option<&'a Profile>) -> Option<&'a str> {
+        profile
+            .and_then(|p| p.todo_database.as_deref())
+            .or(self.todo_database.as_deref())
+    }
+
+    /// Read a single key, from the profile when one is given
+    pub fn get_value(&self, profile: Option<&str>, key: &str) -> Result<Option<String>> {
+        let value = match (self.profile(profile)?, key) {
+            (_, "timeout") => self.timeout.map(|t| t.to_string()),
+            (_, "max_retries") => self.max_retries.map(|n| n.to_string()),
+            (_, "rate_limit") => self.rate_limit.map(|r| r.to_string()),
+            (_, "concurrency") => self.concurrency.map(|n| n.to_string()),
+            (_, "cache") => self.cache.map(|c| c.to_string()),
+            (_, "proxy") => self.proxy.clone(),
+            (_, "ca_bundle") => self.ca_bundle.as_ref().map(|p| p.display().to_string()),
+            (_, "date_format") => self.date_format.clone(),
+            (Some(p), "api_key") => p.api_key.clone(),
+            (Some(p), "keyring") => p.keyring.clone(),
+            (Some(p), "default_parent") => p.default_parent.clone(),
+            (Some(p), "todo_database") => p.todo_database.clone(),
+            (Some(p), "api_version") => p.api_version.clone(),
+            (None, "api_key") => self.api_key.clone(),
+            (None, "keyring") => self.keyring.clone(),
+            (None, "default_parent") => self.default_parent.clone(),
+            (None, "todo_database") => self.todo_database.clone(),
+            (None, "api_version") => None,
+            (_, other) => bail!("Unknown config key '{}'", other),
+        };
+        Ok(value)
+    }
+
+    /// Set (`Some`) or remove (`None`) a single key, on the profile when one
+    /// is given. Setting a key on a missing profile creates it.
+    pub fn set_value(
+        &mut self,
+        profile: Option<&str>,
+        key: &str,
+        value: Option<&str>,
+    ) -> Result<()> {
+        if key == "timeout" {
+            self.timeout = value
+                .map(|v| {
+                    v.parse()
+                        .with_context(|| format!("Invalid timeout '{}'", v))
+                })
+                .transpose()?;
+            return Ok(());
+        }
+        if key == "max_retries" {
+            self.max_retries = value
+                .map(|v| {
+                    v.parse()
+                        .with_context(|| format!("Invalid max_retries '{}'", v))
+                })
+                .transpose()?;
+            return Ok(());
+        }
+        if key == "rate_limit" {
+            self.rate_limit = value
+                .map(|v| match v.parse::<f64>() {
+                    Ok(rate) if rate >= 0.0 => Ok(rate),
+                    _ => bail!("Invalid rate_limit '{}'", v),
+                })
+                .transpose()?;
+            return Ok(());
+        }
+
+        if key == "proxy" {
+            self.proxy = value.map(String::from);
+            return Ok(());
+        }
+        if key == "ca_bundle" {
+            self.ca_bundle = value.map(PathBuf::from);
+            return Ok(());
+        }
+        if key == "date_format" {
+            if let Some(format) = value {
+                crate::dates::check_date_format(format)?;
+            }
+            self.date_format = value.map(String::from);
+            return Ok(());
+        }
+        if key == "cache" {
+            self.cache = value
+                .map(|v| {
+                    v.parse()
+                        .with_context(|| format!("Invalid cache '{}' (use true or false)", v))
+                })
+                .transpose()?;
+            return Ok(());
+        }
+        if key == "concurrency" {
+            self.concurrency = value
+                .map(|v| match v.parse::<usize>() {
+                    Ok(n) if n >= 1 => Ok(n),
+                    _ => bail!("Invalid concurrency '{}'", v),
+                })
+                .transpose()?;
+            return Ok(());
+        }
+
+        let value = match (key, value) {
+            ("default_parent" | "todo_database", Some(v)) => Some(normalize_page_id(v)?),
+            (_, v) => v.map(String::from),
+        };
+
+        let slot = match profile {
+            Some(name) => {
+                let p = self.profiles.entry(name.to_string()).or_default();
+                match key {
+                    "api_key" => &mut p.api_key,
+                    "keyring" => &mut p.keyring,
+                    "default_parent" => &mut p.default_parent,
+                    "todo_database" => &mut p.todo_database,
+                    "api_version" => &mut p.api_version,
+                    other => bail!("Unknown config key '{}'", other),
+                }
+            }
+            None => match key {
+                "api_key" => &mut self.api_key,
+                "keyring" => &mut self.keyring,
+                "default_parent" => &mut self.default_parent,
+                "todo_database" => &mut self.todo_database,
+                "api_version" => bail!("api_version is a profile setting (use --profile)"),
+                other => bail!("Unknown config key '{}'", other),
+            },
+        };
+        *slot = value;
+
+        Ok(())
+    }
+
+    /// Look up a profile by name (`None` selects no profile)
+    pub fn profile(&self, name: Option<&str>) -> Result<Option<&Profile>> {
+        match name {
+            None => Ok(None),
+            Some(name) => match self.profiles.get(name) {
+                Some(profile) => Ok(Some(profile)),
+                None => bail!(
+                    "Profile '{}' not found in {}",
+                    name,
+                    get_config_path()
+                        .map(|p| p.display().to_string())
+                        .unwrap_or_else(|| "config.toml".to_string())
+                ),
+            },
+        }
+    }
 }
 
 /// Get config file path: ~/.config/notion-cli/config.toml
@@ -29,6 +279,33 @@ pub fn load_config() -> Config {
         .unwrap_or_default()
 }
 
+/// Load config to change and save it. Unlike [`load_config`], a config file
+/// that can't be read or parsed is an error, since saving over it would
+/// drop the API key and profiles it holds.
+pub fn load_config_for_update() -> Result<Config> {
+    match get_config_path() {
+        Some(path) => read_config_file(&path),
+        None => Ok(Config::default()),
+    }
+}
+
+/// The config in `path`, or the default when there's no file yet
+fn read_config_file(path: &Path) -> Result<Config> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Config::default()),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read {}", path.display()));
+        }
+    };
+    toml::from_str(&content).with_context(|| {
+        format!(
+            "{} is invalid; fix it before changing settings",
+            path.display()
+        )
+    })
+}
+
 /// Save config to file
 pub fn save_config(config: &Config) -> Result<()> {
     let path = get_config_path().context("Could not determine config directory")?;
@@ -39,18 +316,316 @@ pub fn save_config(config: &Config) -> Result<()> {
     }
 
     let content = toml::to_string_pretty(config).context("Failed to serialize config")?;
-    fs::write(&path, content).context("Failed to write config file")?;
+
+    // The config may hold an API key, so keep it private to the user: a new
+    // file is created 0600, and an existing one is tightened before writing
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path).context("Failed to write config file")?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))
+            .context("Failed to set config file permissions")?;
+    }
+    file.write_all(content.as_bytes())
+        .context("Failed to write config file")?;
+
+    Ok(())
+}
+
+/// A page or database remembered by title
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedId {
+    pub id: String,
+    pub title: String,
+    /// Unix timestamp of the last lookup that used this entry
+    #[serde(default)]
+    pub last_used: u64,
+}
+
+/// Name → ID mappings for `--name` lookups, keyed by the lowercased name
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct IdCache {
+    #[serde(default)]
+    pub pages: BTreeMap<String, CachedId>,
+    #[serde(default)]
+    pub databases: BTreeMap<String, CachedId>,
+}
+
+impl IdCache {
+    fn entries(&self, object: &str) -> &BTreeMap<String, CachedId> {
+        if object == "database" {
+            &self.databases
+        } else {
+            &self.pages
+        }
+    }
+
+    fn entries_mut(&mut self, object: &str) -> &mut BTreeMap<String, CachedId> {
+        if object == "database" {
+            &mut self.databases
+        } else {
+            &mut self.pages
+        }
+    }
+
+    /// Look up a cached ID by the name it was resolved from (case-insensitive)
+    pub fn get(&self, object: &str, name: &str) -> Option<&CachedId> {
+        self.entries(object).get(&name.to_lowercase())
+    }
+
+    /// Remember that `name` resolved to `id` (whose full title is `title`),
+    /// marking the entry as just used
+    pub fn insert(&mut self, object: &str, name: &str, title: &str, id: &str) {
+        let last_used = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        self.entries_mut(object).insert(
+            name.to_lowercase(),
+            CachedId {
+                id: id.to_string(),
+                title: title.to_string(),
+                last_used,
+            },
+        );
+    }
+}
+
+/// Get ID cache path: ~/.cache/notion-cli/ids.json
+pub fn get_id_cache_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|p| p.join("notion-cli").join("ids.json"))
+}
+
+/// Load the ID cache, or an empty one if missing or unreadable
+pub fn load_id_cache() -> IdCache {
+    get_id_cache_path()
+        .and_then(|path| fs::read_to_string(&path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Save the ID cache
+pub fn save_id_cache(cache: &IdCache) -> Result<()> {
+    let path = get_id_cache_path().context("Could not determine cache directory")?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create cache directory")?;
+    }
+
+    let content = serde_json::to_string_pretty(cache).context("Failed to serialize ID cache")?;
+    fs::write(&path, content).context("Failed to write ID cache")?;
+
+    Ok(())
+}
+
+/// Path of the stored `watch` snapshot for a page
+pub fn get_snapshot_path(page_id: &str) -> Option<PathBuf> {
+    dirs::cache_dir().map(|p| {
+        p.join("notion-cli")
+            .join("snapshots")
+            .join(format!("{}.json", page_id))
+    })
+}
+
+/// Load the last snapshot taken of a page, if any
+pub fn load_snapshot(page_id: &str) -> Option<Snapshot> {
+    get_snapshot_path(page_id)
+        .and_then(|path| fs::read_to_string(&path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+}
+
+/// Save a page snapshot for the next `watch` run
+pub fn save_snapshot(page_id: &str, snapshot: &Snapshot) -> Result<()> {
+    let path = get_snapshot_path(page_id).context("Could not determine cache directory")?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create cache directory")?;
+    }
+
+    let content = serde_json::to_string(snapshot).context("Failed to serialize snapshot")?;
+    fs::write(&path, content).context("Failed to write snapshot")?;
+
+    Ok(())
+}
+
+/// A page and its block tree as last fetched, valid while the page's
+/// `last_edited_time` is unchanged
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CachedPage {
+    pub last_edited_time: String,
+    /// Nesting depth the blocks were fetched with (`None`: all levels)
+    pub depth: Option<usize>,
+    pub page: serde_json::Value,
+    pub blocks: Vec<serde_json::Value>,
+}
+
+/// Directory of cached page content: ~/.cache/notion-cli/pages
+pub fn get_page_cache_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|p| p.join("notion-cli").join("pages"))
+}
+
+/// Load the cached content of a page, if any
+pub fn load_cached_page(page_id: &str) -> Option<CachedPage> {
+    get_page_cache_dir()
+        .map(|dir| dir.join(format!("{}.json", page_id)))
+        .and_then(|path| fs::read_to_string(&path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+}
+
+/// Cache a page's content for later reads
+pub fn save_cached_page(page_id: &str, cached: &CachedPage) -> Result<()> {
+    let dir = get_page_cache_dir().context("Could not determine cache directory")?;
+    fs::create_dir_all(&dir).context("Failed to create cache directory")?;
+
+    let content = serde_json::to_string(cached).context("Failed to serialize cached page")?;
+    fs::write(dir.join(format!("{}.json", page_id)), content)
+        .context("Failed to write cached page")?;
 
     Ok(())
 }
 
+/// Delete all cached page content. Returns the number of pages removed.
+pub fn clear_page_cache() -> Result<usize> {
+    let Some(dir) = get_page_cache_dir().filter(|d| d.exists()) else {
+        return Ok(0);
+    };
+    let count = fs::read_dir(&dir)
+        .context("Failed to read cache directory")?
+        .filter(|entry| {
+            entry
+                .as_ref()
+                .is_ok_and(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+        })
+        .count();
+    fs::remove_dir_all(&dir).context("Failed to clear cache")?;
+    Ok(count)
+}
+
+/// Path of the offline queue: one pending batch operation per line
+pub fn get_queue_path() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|p| p.join("notion-cli").join("queue.jsonl"))
+}
+
+/// Load the queued operations, one JSON line each (empty if there is no queue)
+pub fn load_queue() -> Vec<String> {
+    get_queue_path()
+        .and_then(|path| fs::read_to_string(&path).ok())
+        .map(|content| {
+            content
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Add an operation to the end of the offline queue. Returns how many
+/// operations are now pending.
+pub fn push_queue(op: &Operation) -> Result<usize> {
+    let mut lines = load_queue();
+    lines.push(serde_json::to_string(op).context("Failed to serialize operation")?);
+    save_queue(&lines)?;
+    Ok(lines.len())
+}
+
+/// Replace the offline queue (removing the file when nothing is left)
+pub fn save_queue(lines: &[String]) -> Result<()> {
+    let path = get_queue_path().context("Could not determine data directory")?;
+
+    if lines.is_empty() {
+        if path.exists() {
+            fs::remove_file(&path).context("Failed to remove queue")?;
+        }
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create data directory")?;
+    }
+    let mut content = lines.join("\n");
+    content.push('\n');
+    fs::write(&path, content).context("Failed to write queue")?;
+
+    Ok(())
+}
+
+pub fn get_todo_list_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|p| p.join("notion-cli").join("todo.json"))
+}
+
+/// Load the task IDs shown by the last `todo list`, in display order
+pub fn load_todo_list() -> Vec<String> {
+    get_todo_list_path()
+        .and_then(|path| fs::read_to_string(&path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Save the task IDs shown by `todo list`, so `todo done <n>` can refer to them
+pub fn save_todo_list(ids: &[String]) -> Result<()> {
+    let path = get_todo_list_path().context("Could not determine cache directory")?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create cache directory")?;
+    }
+
+    let content = serde_json::to_string(ids).context("Failed to serialize task list")?;
+    fs::write(&path, content).context("Failed to write task list")?;
+
+    Ok(())
+}
+
+/// Store an API key in the OS keyring (Keychain, Secret Service or Credential Manager)
+pub fn save_keyring_token(account: &str, token: &str) -> Result<()> {
+    keyring::Entry::new(KEYRING_SERVICE, account)
+        .and_then(|entry| entry.set_password(token))
+        .context("Failed to save API key to the system keyring")
+}
+
+/// Load an API key from the OS keyring
+pub fn load_keyring_token(account: &str) -> Result<String> {
+    keyring::Entry::new(KEYRING_SERVICE, account)
+        .and_then(|entry| entry.get_password())
+        .with_context(|| {
+            format!(
+                "Failed to read API key '{}' from the system keyring",
+                account
+            )
+        })
+}
+
+/// Remove an API key from the OS keyring (missing entries are not an error)
+pub fn delete_keyring_token(account: &str) -> Result<()> {
+    match keyring::Entry::new(KEYRING_SERVICE, account).and_then(|entry| entry.delete_credential())
+    {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e).context("Failed to remove API key from the system keyring"),
+    }
+}
+
 pub fn get_api_version() -> String {
-    env::var("NOTION_API_VERSION").unwrap_or_else(|_| "2025-09-03".to_string())
+    env::var("NOTION_API_VERSION").unwrap_or_else(|_| DEFAULT_API_VERSION.to_string())
 }
 
-/// Get API key with priority: CLI arg > env var > config file > .env (backward compat)
-/// Pass cli_api_key as None if not provided via CLI
-pub fn get_api_key(cli_api_key: Option<&str>) -> Result<String> {
+/// API version for a profile: env var > profile's `api_version` > default
+pub fn get_profile_api_version(profile: Option<&Profile>) -> String {
+    match profile.and_then(|p| p.api_version.clone()) {
+        Some(version) if env::var("NOTION_API_VERSION").is_err() => version,
+        _ => get_api_version(),
+    }
+}
+
+/// Get API key with priority: CLI arg > env var > profile > config file > .env (backward compat)
+/// Pass cli_api_key / profile as None if not provided via CLI
+pub fn get_api_key(cli_api_key: Option<&str>, profile: Option<&str>) -> Result<String> {
     // 1. CLI argument (highest priority)
     if let Some(key) = cli_api_key {
         return Ok(key.to_string());
@@ -61,9 +636,8 @@ pub fn get_api_key(cli_api_key: Option<&str>) -> Result<String> {
         return Ok(key);
     }
 
-    // 3. Config file (~/.config/notion-cli/config.toml)
-    let config = load_config();
-    if let Some(key) = config.api_key {
+    // 3. Config file (~/.config/notion-cli/config.toml), selected profile first.
+    if let Some(key) = get_config_api_key(&load_config(), profile)? {
         return Ok(key);
     }
 
@@ -90,9 +664,117 @@ pub fn get_api_key(cli_api_key: Option<&str>) -> Result<String> {
     )
 }
 
-/// Normalize page ID: remove dashes, validate format
+/// API key saved in `config` for a profile (falling back to the top-level
+/// key), ignoring the environment. A `keyring` reference is looked up in the
+/// OS keyring.
+pub fn get_config_api_key(config: &Config, profile: Option<&str>) -> Result<Option<String>> {
+    if let Some(p) = config.profile(profile)? {
+        if let Some(key) = &p.api_key {
+            return Ok(Some(key.clone()));
+        }
+        if let Some(account) = &p.keyring {
+            return load_keyring_token(account).map(Some);
+        }
+    }
+    if let Some(key) = &config.api_key {
+        return Ok(Some(key.clone()));
+    }
+    match &config.keyring {
+        Some(account) => load_keyring_token(account).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Guess a MIME type from a file extension (falls back to application/octet-stream)
+pub fn guess_content_type(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+
+    match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "heic" => "image/heic",
+        "pdf" => "application/pdf",
+        "mp4" => "video/mp4",
+        "mov" => "video/quicktime",
+        "webm" => "video/webm",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "m4a" => "audio/mp4",
+        "txt" | "log" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        "zip" => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Extract the 32-hex ID from a Notion share URL.
+///
+/// Handles `https://www.notion.so/Workspace/Page-Title-<id>`, database view
+/// URLs (`/<db_id>?v=<view_id>`, the view ID is ignored) and peek URLs
+/// (`?p=<page_id>`, which point at the opened page). Returns `None` for input
+/// that is not a URL.
+pub fn extract_id_from_url(input: &str) -> Option<String> {
+    let input = input.trim();
+    let is_url = input.contains("://")
+        || input.starts_with("notion.so/")
+        || input.starts_with("www.notion.so/");
+    if !is_url {
+        return None;
+    }
+
+    let without_fragment = input.split('#').next().unwrap_or(input);
+    let (path, query) = match without_fragment.split_once('?') {
+        Some((p, q)) => (p, Some(q)),
+        None => (without_fragment, None),
+    };
+
+    // Peek mode: the page shown is in the `p` parameter
+    if let Some(query) = query {
+        for pair in query.split('&') {
+            if let Some(("p", value)) = pair.split_once('=') {
+                if let Some(id) = trailing_hex_id(value) {
+                    return Some(id);
+                }
+            }
+        }
+    }
+
+    let last_segment = path.trim_end_matches('/').rsplit('/').next()?;
+    trailing_hex_id(last_segment)
+}
+
+/// The 32 hex characters at the end of a slug like `Page-Title-<id>` (dashes allowed)
+fn trailing_hex_id(segment: &str) -> Option<String> {
+    let hex: String = segment
+        .chars()
+        .rev()
+        .filter(|c| *c != '-')
+        .take_while(|c| c.is_ascii_hexdigit())
+        .take(32)
+        .collect();
+
+    if hex.len() == 32 {
+        Some(hex.chars().rev().collect())
+    } else {
+        None
+    }
+}
+
+/// Normalize page ID: accept share URLs, remove dashes, validate format
 pub fn normalize_page_id(id: &str) -> Result<String> {
-    let clean: String = id.chars().filter(|c| c.is_ascii_hexdigit()).collect();
+    let clean: String = match extract_id_from_url(id) {
+        Some(from_url) => from_url,
+        None => id.chars().filter(|c| c.is_ascii_hexdigit()).collect(),
+    };
 
     if clean.len() != 32 {
         bail!(
@@ -116,6 +798,25 @@ pub fn normalize_page_id(id: &str) -> Result<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_read_config_file_rejects_invalid_toml() {
+        let dir = std::env::temp_dir().join(format!("notion-cli-config-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+
+        assert!(read_config_file(&path).unwrap().api_key.is_none());
+        fs::write(&path, "api_key = \"ntn_x\"\ntimeout = ").unwrap();
+        let err = read_config_file(&path).unwrap_err();
+        assert!(err.to_string().contains("is invalid"), "{}", err);
+        fs::write(&path, "api_key = \"ntn_x\"\n").unwrap();
+        assert_eq!(
+            read_config_file(&path).unwrap().api_key.as_deref(),
+            Some("ntn_x")
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_normalize_page_id_with_dashes() {
         let result = normalize_page_id("2fb74f32-4ab9-80f5-83df-c93c885072e7").unwrap();
@@ -128,22 +829,157 @@ mod tests {
         assert_eq!(result, "2fb74f32-4ab9-80f5-83df-c93c885072e7");
     }
 
+    #[test]
+    fn test_normalize_page_id_from_url() {
+        let result = normalize_page_id(
+            "https://www.notion.so/Workspace/Page-Title-2fb74f324ab980f583dfc93c885072e7",
+        )
+        .unwrap();
+        assert_eq!(result, "2fb74f32-4ab9-80f5-83df-c93c885072e7");
+    }
+
+    #[test]
+    fn test_normalize_page_id_from_database_view_url() {
+        let result = normalize_page_id(
+            "https://www.notion.so/acme/2fb74f324ab980f583dfc93c885072e7?v=0123456789abcdef0123456789abcdef",
+        )
+        .unwrap();
+        assert_eq!(result, "2fb74f32-4ab9-80f5-83df-c93c885072e7");
+    }
+
+    #[test]
+    fn test_normalize_page_id_from_peek_url() {
+        let result = normalize_page_id(
+            "https://www.notion.so/acme/0123456789abcdef0123456789abcdef?v=aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa&p=2fb74f324ab980f583dfc93c885072e7&pm=s",
+        )
+        .unwrap();
+        assert_eq!(result, "2fb74f32-4ab9-80f5-83df-c93c885072e7");
+    }
+
+    #[test]
+    fn test_normalize_page_id_invalid_url() {
+        assert!(normalize_page_id("https://www.notion.so/Workspace/No-Id-Here").is_err());
+    }
+
     #[test]
     fn test_normalize_page_id_invalid() {
         let result = normalize_page_id("invalid");
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_guess_content_type() {
+        assert_eq!(guess_content_type(Path::new("shot.PNG")), "image/png");
+        assert_eq!(guess_content_type(Path::new("doc.pdf")), "application/pdf");
+        assert_eq!(
+            guess_content_type(Path::new("archive.tar")),
+            "application/octet-stream"
+        );
+    }
+
     #[test]
     fn test_config_serialization() {
         let config = Config {
             api_key: Some("ntn_test123".to_string()),
             timeout: Some(60),
+            ..Default::default()
         };
 
         let serialized = toml::to_string_pretty(&config).unwrap();
         assert!(serialized.contains("api_key = \"ntn_test123\""));
         assert!(serialized.contains("timeout = 60"));
+        assert!(!serialized.contains("profiles"));
+    }
+
+    #[test]
+    fn test_config_keyring_reference() {
+        let toml_str = r#"
+keyring = "default"
+
+[profiles.work]
+keyring = "work"
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.api_key, None);
+        assert_eq!(config.keyring.as_deref(), Some("default"));
+        let work = config.profile(Some("work")).unwrap().unwrap();
+        assert_eq!(work.keyring.as_deref(), Some("work"));
+    }
+
+    #[test]
+    fn test_config_set_get_unset() {
+        let mut config = Config::default();
+        config.set_value(None, "timeout", Some("45")).unwrap();
+        assert_eq!(config.timeout, Some(45));
+        assert!(config.set_value(None, "timeout", Some("soon")).is_err());
+        config.set_value(None, "max_retries", Some("5")).unwrap();
+        assert_eq!(
+            config.get_value(None, "max_retries").unwrap().as_deref(),
+            Some("5")
+        );
+        assert!(config.set_value(None, "max_retries", Some("-1")).is_err());
+        config.set_value(None, "rate_limit", Some("2.5")).unwrap();
+        assert_eq!(config.rate_limit, Some(2.5));
+        assert!(config.set_value(None, "rate_limit", Some("-1")).is_err());
+
+        config.set_value(None, "concurrency", Some("8")).unwrap();
+        assert_eq!(config.concurrency, Some(8));
+        assert!(config.set_value(None, "concurrency", Some("0")).is_err());
+
+        config.set_value(None, "cache", Some("true")).unwrap();
+        assert_eq!(
+            config.get_value(None, "cache").unwrap(),
+            Some("true".to_string())
+        );
+        assert!(config.set_value(None, "cache", Some("yes")).is_err());
+
+        config
+            .set_value(
+                Some("work"),
+                "default_parent",
+                Some("2fb74f324ab980f583dfc93c885072e7"),
+            )
+            .unwrap();
+        assert_eq!(
+            config.get_value(Some("work"), "default_parent").unwrap(),
+            Some("2fb74f32-4ab9-80f5-83df-c93c885072e7".to_string())
+        );
+        assert_eq!(config.get_value(None, "default_parent").unwrap(), None);
+
+        config
+            .set_value(Some("work"), "default_parent", None)
+            .unwrap();
+        assert_eq!(
+            config.get_value(Some("work"), "default_parent").unwrap(),
+            None
+        );
+        assert!(config
+            .set_value(None, "api_version", Some("2022-06-28"))
+            .is_err());
+        assert!(config.get_value(None, "colour").is_err());
+    }
+
+    #[test]
+    fn test_config_profiles() {
+        let toml_str = r#"
+api_key = "ntn_default"
+
+[profiles.work]
+api_key = "ntn_work"
+default_parent = "2fb74f324ab980f583dfc93c885072e7"
+
+[profiles.personal]
+api_version = "2022-06-28"
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        let work = config.profile(Some("work")).unwrap().unwrap();
+        assert_eq!(work.api_key.as_deref(), Some("ntn_work"));
+        assert!(work.default_parent.is_some());
+        let personal = config.profile(Some("personal")).unwrap().unwrap();
+        assert_eq!(personal.api_key, None);
+        assert_eq!(personal.api_version.as_deref(), Some("2022-06-28"));
+        assert!(config.profile(None).unwrap().is_none());
+        assert!(config.profile(Some("missing")).is_err());
     }
 
     #[test]
@@ -174,4 +1010,12 @@ timeout = 45
         assert!(path.to_string_lossy().contains("notion-cli"));
         assert!(path.to_string_lossy().ends_with("config.toml"));
     }
+
+    #[test]
+    fn test_id_cache_lookup_is_case_insensitive() {
+        let mut cache = IdCache::default();
+        cache.insert("page", "Meeting Notes", "Meeting Notes 2024", "abc");
+        assert_eq!(cache.get("page", "meeting notes").unwrap().id, "abc");
+        assert!(cache.get("database", "Meeting Notes").is_none());
+    }
 }
@@ -0,0 +1,1127 @@
+//! Commands run against recorded API responses (see `notion_cli_tool::replay`).
+
+use notion_cli_tool::replay::{fixture_name, Cassette, Mode};
+use notion_cli_tool::NotionClient;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const PAGE_ID: &str = "2fb74f32-4ab9-80f5-83df-c93c885072e7";
+const TOGGLE_ID: &str = "9a3c1d2e-5f60-4a7b-8c9d-0e1f2a3b4c5d";
+const DATABASE_ID: &str = "8a1c0e4b-2d6f-4e0a-9b3c-5d7e9f1a2b3c";
+const DATA_SOURCE_ID: &str = "3e5f7a9b-1c2d-4e6f-8a0b-2c4d6e8f0a1b";
+
+/// A fresh fixtures directory for one test
+fn fixtures(test: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("notion-cli-replay-{}-{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Record `response` as the answer to `method url` with a JSON `body`
+fn record(dir: &Path, method: &str, url: &str, body: Option<&Value>, response: Value) {
+    record_status(dir, method, url, body, 200, response);
+}
+
+fn record_status(
+    dir: &Path,
+    method: &str,
+    url: &str,
+    body: Option<&Value>,
+    status: u16,
+    response: Value,
+) {
+    let body = body
+        .map(|b| serde_json::to_vec(b).unwrap())
+        .unwrap_or_default();
+    let recording = json!({ "status": status, "body": response });
+    fs::write(
+        dir.join(fixture_name(method, url, &body)),
+        recording.to_string(),
+    )
+    .unwrap();
+}
+
+fn paragraph(id: &str, text: &str, has_children: bool) -> Value {
+    json!({
+        "object": "block",
+        "id": id,
+        "type": "paragraph",
+        "has_children": has_children,
+        "paragraph": { "rich_text": [{ "type": "text", "plain_text": text }] }
+    })
+}
+
+fn list(results: Vec<Value>) -> Value {
+    json!({ "object": "list", "results": results, "has_more": false, "next_cursor": null })
+}
+
+/// A page with a paragraph that has one nested paragraph
+fn record_page(dir: &Path) {
+    record(
+        dir,
+        "GET",
+        &format!("/pages/{}", PAGE_ID),
+        None,
+        json!({
+            "object": "page",
+            "id": PAGE_ID,
+            "last_edited_time": "2024-07-01T09:30:00.000Z",
+            "properties": {
+                "Name": { "type": "title", "title": [{ "plain_text": "Release notes" }] }
+            }
+        }),
+    );
+    record(
+        dir,
+        "GET",
+        &format!("/blocks/{}/children?page_size=100", PAGE_ID),
+        None,
+        list(vec![paragraph(TOGGLE_ID, "Highlights", true)]),
+    );
+    record(
+        dir,
+        "GET",
+        &format!("/blocks/{}/children?page_size=100", TOGGLE_ID),
+        None,
+        list(vec![paragraph("b2", "Faster reads", false)]),
+    );
+}
+
+/// The blocks `push` sends for `markdown`
+fn markdown_blocks(markdown: &str) -> Vec<Value> {
+    notion_cli_tool::markdown::parse_blocks(markdown)
+        .into_iter()
+        .filter_map(|b| match b {
+            notion_cli_tool::markdown::MarkdownBlock::Block { block, .. } => Some(block),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Run the binary against `dir`, isolated from the user's config and cache
+fn notion_cli(dir: &Path, args: &[&str]) -> (bool, String, String) {
+    let home = dir.join("home");
+    let output = Command::new(env!("CARGO_BIN_EXE_notion-cli"))
+        .args(args)
+        .args(["--color", "never", "--quiet"])
+        .env("NOTION_CLI_REPLAY", dir)
+        .env("NOTION_API_KEY", "ntn_test")
+        .env("HOME", &home)
+        .env("XDG_CONFIG_HOME", home.join(".config"))
+        .env("XDG_CACHE_HOME", home.join(".cache"))
+        .env_remove("NOTION_PROFILE")
+        .output()
+        .unwrap();
+    (
+        output.status.success(),
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    )
+}
+
+#[tokio::test]
+async fn test_client_replays_nested_blocks() {
+    let dir = fixtures("client");
+    record_page(&dir);
+
+    let client = NotionClient::new("ntn_test".to_string(), 5)
+        .unwrap()
+        .with_cassette(Some(Cassette::new(&dir, Mode::Replay)));
+    let blocks = client.get_blocks_recursive(PAGE_ID, None).await.unwrap();
+
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0]["children"][0]["id"], "b2");
+}
+
+#[test]
+fn test_read_command() {
+    let dir = fixtures("read");
+    record_page(&dir);
+
+    let (ok, stdout, stderr) = notion_cli(&dir, &["read", PAGE_ID]);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("Release notes"));
+    assert!(stdout.contains("Highlights"));
+    assert!(stdout.contains("Faster reads"));
+}
+
+#[test]
+fn test_blocks_filters() {
+    let dir = fixtures("blocks");
+    record_page(&dir);
+
+    let (ok, stdout, stderr) = notion_cli(&dir, &["blocks", PAGE_ID]);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("paragraph \"Highlights\" +"), "{}", stdout);
+    assert!(!stdout.contains("Faster reads"));
+
+    let (ok, stdout, stderr) = notion_cli(
+        &dir,
+        &[
+            "blocks",
+            PAGE_ID,
+            "--recursive",
+            "--contains",
+            "FASTER",
+            "--id-only",
+        ],
+    );
+    assert!(ok, "{}", stderr);
+    assert_eq!(stdout, "b2\n");
+
+    let (ok, stdout, stderr) = notion_cli(
+        &dir,
+        &[
+            "blocks",
+            PAGE_ID,
+            "--recursive",
+            "--type",
+            "code",
+            "--id-only",
+        ],
+    );
+    assert!(ok, "{}", stderr);
+    assert_eq!(stdout, "");
+}
+
+#[test]
+fn test_export_html() {
+    let dir = fixtures("export");
+    record_page(&dir);
+
+    let (ok, stdout, stderr) = notion_cli(&dir, &["export", PAGE_ID, "--format", "html"]);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.starts_with("<!DOCTYPE html>"));
+    assert!(stdout.contains("<title>Release notes</title>"));
+    assert!(
+        stdout.contains("<p>Highlights</p>\n<div class=\"children\">\n<p>Faster reads</p>\n</div>")
+    );
+
+    let (ok, stdout, stderr) = notion_cli(&dir, &["export", PAGE_ID]);
+    assert!(ok, "{}", stderr);
+    assert_eq!(stdout, "# Release notes\n\nHighlights\n\nFaster reads\n");
+}
+
+/// Two rows in the data source's query results
+fn record_query(dir: &Path) {
+    let row = |name: &str, points: f64| {
+        json!({
+            "object": "page",
+            "id": PAGE_ID,
+            "properties": {
+                "Name": { "type": "title", "title": [{ "plain_text": name }] },
+                "Points": { "type": "number", "number": points }
+            }
+        })
+    };
+    record(
+        dir,
+        "POST",
+        &format!("/data_sources/{}/query", DATA_SOURCE_ID),
+        Some(&json!({ "page_size": 100 })),
+        list(vec![row("Alpha", 3.0), row("Beta", 1.5)]),
+    );
+}
+
+#[test]
+fn test_query_csv_command() {
+    let dir = fixtures("query");
+    record(
+        &dir,
+        "GET",
+        &format!("/databases/{}", DATABASE_ID),
+        None,
+        json!({
+            "object": "database",
+            "id": DATABASE_ID,
+            "data_sources": [{ "id": DATA_SOURCE_ID, "name": "Tasks" }]
+        }),
+    );
+    record_query(&dir);
+
+    let (ok, stdout, stderr) = notion_cli(&dir, &["query", DATABASE_ID, "--format", "csv"]);
+    assert!(ok, "{}", stderr);
+    assert_eq!(stdout, "Name,Points\nAlpha,3\nBeta,1.5\n");
+
+    let (ok, stdout, stderr) = notion_cli(&dir, &["query", DATABASE_ID, "--id-only", "--stats"]);
+    assert!(ok, "{}", stderr);
+    assert_eq!(stdout, format!("{}\n{}\n", PAGE_ID, PAGE_ID));
+    assert!(stderr.contains("API calls: 2 (0 retries"), "{}", stderr);
+    assert!(
+        stderr.contains("POST /data_sources/{id}/query  1 call"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_db_export() {
+    let dir = fixtures("db-export");
+    record_status(
+        &dir,
+        "GET",
+        &format!("/databases/{}", DATA_SOURCE_ID),
+        None,
+        404,
+        json!({ "object": "error", "status": 404, "code": "object_not_found", "message": "Not found" }),
+    );
+    record_query(&dir);
+
+    let (ok, stdout, stderr) = notion_cli(&dir, &["db-export", DATA_SOURCE_ID]);
+    assert!(ok, "{}", stderr);
+    assert_eq!(
+        stdout,
+        "| Name | Points |\n| --- | --- |\n| Alpha | 3 |\n| Beta | 1.5 |\n"
+    );
+
+    let (ok, stdout, stderr) = notion_cli(
+        &dir,
+        &[
+            "db-export",
+            DATA_SOURCE_ID,
+            "--format",
+            "json",
+            "--columns",
+            "Points",
+        ],
+    );
+    assert!(ok, "{}", stderr);
+    let rows: Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(rows[1]["id"], PAGE_ID);
+    assert_eq!(rows[1]["properties"], json!({ "Points": 1.5 }));
+}
+
+#[test]
+fn test_query_data_source_id() {
+    let dir = fixtures("data-source");
+    record_status(
+        &dir,
+        "GET",
+        &format!("/databases/{}", DATA_SOURCE_ID),
+        None,
+        404,
+        json!({ "object": "error", "status": 404, "code": "object_not_found", "message": "Not found" }),
+    );
+    record_query(&dir);
+
+    let (ok, stdout, stderr) = notion_cli(&dir, &["query", DATA_SOURCE_ID, "--format", "csv"]);
+    assert!(ok, "{}", stderr);
+    assert_eq!(stdout, "Name,Points\nAlpha,3\nBeta,1.5\n");
+}
+
+#[test]
+fn test_relate_adds_to_existing_links() {
+    let dir = fixtures("relate");
+    let linked = "11111111-2222-4333-8444-555555555555";
+    let added = "66666666-7777-4888-9999-000000000000";
+    record(
+        &dir,
+        "GET",
+        &format!("/pages/{}", PAGE_ID),
+        None,
+        json!({
+            "object": "page",
+            "id": PAGE_ID,
+            "properties": {
+                "Project": { "id": "rel1", "type": "relation", "relation": [{ "id": linked }], "has_more": false }
+            }
+        }),
+    );
+    record(
+        &dir,
+        "PATCH",
+        &format!("/pages/{}", PAGE_ID),
+        Some(
+            &json!({ "properties": { "Project": { "relation": [{ "id": linked }, { "id": added }] } } }),
+        ),
+        json!({ "object": "page", "id": PAGE_ID }),
+    );
+
+    let (ok, stdout, stderr) = notion_cli(
+        &dir,
+        &[
+            "relate",
+            PAGE_ID,
+            "--prop",
+            "project",
+            "--to",
+            &added.replace('-', ""),
+        ],
+    );
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("Linked 1 page(s) through 'Project' (2 linked now)"));
+
+    let (ok, stdout, stderr) = notion_cli(
+        &dir,
+        &["relate", PAGE_ID, "--prop", "Project", "--to", linked],
+    );
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("No change"), "{}", stdout);
+}
+
+#[test]
+fn test_db_prop_rename_updates_data_source() {
+    let dir = fixtures("db-prop");
+    record_status(
+        &dir,
+        "GET",
+        &format!("/databases/{}", DATA_SOURCE_ID),
+        None,
+        404,
+        json!({ "object": "error", "status": 404, "code": "object_not_found", "message": "Not found" }),
+    );
+    record(
+        &dir,
+        "GET",
+        &format!("/data_sources/{}", DATA_SOURCE_ID),
+        None,
+        json!({
+            "object": "data_source",
+            "id": DATA_SOURCE_ID,
+            "properties": {
+                "Name": { "id": "title", "type": "title", "title": {} },
+                "Points": { "id": "p1", "type": "number", "number": { "format": "number" } }
+            }
+        }),
+    );
+    record(
+        &dir,
+        "PATCH",
+        &format!("/data_sources/{}", DATA_SOURCE_ID),
+        Some(&json!({ "properties": { "Points": { "name": "Estimate" } } })),
+        json!({ "object": "data_source", "id": DATA_SOURCE_ID }),
+    );
+
+    let (ok, stdout, stderr) = notion_cli(
+        &dir,
+        &["db-prop", "rename", DATA_SOURCE_ID, "points", "Estimate"],
+    );
+    assert!(ok, "{}", stderr);
+    assert!(
+        stdout.contains("Renamed 'Points' to 'Estimate'"),
+        "{}",
+        stdout
+    );
+
+    let (ok, _, stderr) = notion_cli(
+        &dir,
+        &["db-prop", "rename", DATA_SOURCE_ID, "Points", "name"],
+    );
+    assert!(!ok);
+    assert!(
+        stderr.contains("Property 'Name' already exists"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_update_people_by_email() {
+    let dir = fixtures("people");
+    let ada = "11111111-2222-4333-8444-555555555555";
+    record(
+        &dir,
+        "GET",
+        "/users?page_size=100",
+        None,
+        list(vec![
+            json!({ "object": "user", "id": ada, "name": "Ada", "type": "person", "person": { "email": "ada@example.com" } }),
+            json!({ "object": "user", "id": "b0t", "name": "Importer", "type": "bot", "bot": {} }),
+        ]),
+    );
+    record(
+        &dir,
+        "PATCH",
+        &format!("/pages/{}", PAGE_ID),
+        Some(
+            &json!({ "properties": { "Owner": { "people": [{ "object": "user", "id": ada }] } } }),
+        ),
+        json!({ "object": "page", "id": PAGE_ID, "url": "https://www.notion.so/page" }),
+    );
+
+    let (ok, _, stderr) = notion_cli(
+        &dir,
+        &["update", PAGE_ID, "--prop", "Owner:people=Ada@Example.com"],
+    );
+    assert!(ok, "{}", stderr);
+
+    let (ok, _, stderr) = notion_cli(
+        &dir,
+        &[
+            "update",
+            PAGE_ID,
+            "--prop",
+            "Owner:people=grace@example.com",
+        ],
+    );
+    assert!(!ok);
+    assert!(
+        stderr.contains("No user with the email or name 'grace@example.com'"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_page_tree() {
+    let dir = fixtures("page_tree");
+    let sub_page = "4b6d8f0a-2c4e-4f6a-8b0c-2e4f6a8b0c2e";
+    record(
+        &dir,
+        "GET",
+        &format!("/pages/{}", PAGE_ID),
+        None,
+        json!({
+            "object": "page",
+            "id": PAGE_ID,
+            "properties": { "Name": { "type": "title", "title": [{ "plain_text": "Wiki" }] } }
+        }),
+    );
+    // The sub-page sits in a toggle; the database is a leaf
+    record(
+        &dir,
+        "GET",
+        &format!("/blocks/{}/children?page_size=100", PAGE_ID),
+        None,
+        list(vec![paragraph(TOGGLE_ID, "Sections", true)]),
+    );
+    record(
+        &dir,
+        "GET",
+        &format!("/blocks/{}/children?page_size=100", TOGGLE_ID),
+        None,
+        list(vec![
+            json!({ "object": "block", "id": sub_page, "type": "child_page", "has_children": true, "child_page": { "title": "Projects" } }),
+            json!({ "object": "block", "id": DATABASE_ID, "type": "child_database", "has_children": false, "child_database": { "title": "Tasks" } }),
+        ]),
+    );
+    record(
+        &dir,
+        "GET",
+        &format!("/blocks/{}/children?page_size=100", sub_page),
+        None,
+        list(vec![paragraph("b1", "No sub-pages here", false)]),
+    );
+
+    let (ok, stdout, stderr) = notion_cli(&dir, &["tree", "--root", PAGE_ID]);
+    assert!(ok, "{}", stderr);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            format!("📄 Wiki (1 page, 1 database)  {}", PAGE_ID),
+            format!("├─ 📄 Projects  {}", sub_page),
+            format!("└─ 🗃 Tasks  {}", DATABASE_ID),
+        ]
+    );
+
+    let (ok, stdout, stderr) = notion_cli(
+        &dir,
+        &[
+            "tree", "--root", PAGE_ID, "--depth", "1", "--format", "json",
+        ],
+    );
+    assert!(ok, "{}", stderr);
+    let tree: Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(tree[0]["title"], "Wiki");
+    assert_eq!(tree[0]["children"][0]["id"], sub_page);
+    assert_eq!(tree[0]["children"][1]["object"], "database");
+}
+
+#[test]
+fn test_push_markdown_directory() {
+    let dir = fixtures("push");
+    let docs = dir.join("docs");
+    fs::create_dir_all(docs.join("guide")).unwrap();
+    fs::write(docs.join("guide/setup.md"), "# Setup\n\nInstall it.\n").unwrap();
+    let guide_id = "5c7e9a1b-3d5f-4a7c-9e1b-3d5f7a9c1e3b";
+    let setup_id = "6d8f0b2c-4e6a-4b8d-8f2c-4e6a8b0d2f4c";
+
+    let create = |parent: &str, title: &str, id: &str| {
+        record(
+            &dir,
+            "POST",
+            "/pages",
+            Some(&json!({
+                "parent": { "page_id": parent },
+                "properties": { "title": { "title": [{ "type": "text", "text": { "content": title } }] } }
+            })),
+            json!({ "object": "page", "id": id }),
+        );
+    };
+    create(PAGE_ID, "guide", guide_id);
+    create(guide_id, "Setup", setup_id);
+    record(
+        &dir,
+        "PATCH",
+        &format!("/blocks/{}/children", setup_id),
+        Some(&json!({ "children": markdown_blocks("Install it.\n") })),
+        list(vec![]),
+    );
+
+    let docs_arg = docs.to_str().unwrap();
+    let (ok, stdout, stderr) = notion_cli(&dir, &["push", docs_arg, "--root", PAGE_ID]);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("+ guide/setup.md"), "{}", stdout);
+    assert!(
+        stdout.contains("2 created, 0 updated, 0 unchanged"),
+        "{}",
+        stdout
+    );
+
+    // Nothing changed: no requests at all
+    let (ok, stdout, stderr) = notion_cli(&dir, &["push", docs_arg, "--root", PAGE_ID]);
+    assert!(ok, "{}", stderr);
+    assert!(
+        stdout.contains("0 created, 0 updated, 2 unchanged"),
+        "{}",
+        stdout
+    );
+
+    fs::write(
+        docs.join("guide/setup.md"),
+        "# Setup\n\nInstall it twice.\n",
+    )
+    .unwrap();
+    let (ok, stdout, stderr) =
+        notion_cli(&dir, &["push", docs_arg, "--root", PAGE_ID, "--dry-run"]);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("~ guide/setup.md"), "{}", stdout);
+
+    // The new content goes in ahead of the kept sub-page, then the old
+    // paragraph is deleted
+    let old_paragraph = "8f0b2c4e-6a8b-4d0f-9b4e-6a8c0e2b4d6f";
+    let sub_page = "9a1c3e5b-7d9f-4a1c-8e5b-7d9f1b3d5f7a";
+    record(
+        &dir,
+        "PATCH",
+        &format!("/pages/{}", setup_id),
+        Some(&json!({
+            "properties": { "title": { "title": [{ "type": "text", "text": { "content": "Setup" } }] } }
+        })),
+        json!({ "object": "page", "id": setup_id }),
+    );
+    record(
+        &dir,
+        "GET",
+        &format!("/blocks/{}/children?page_size=100", setup_id),
+        None,
+        list(vec![
+            paragraph(old_paragraph, "Install it.", false),
+            json!({ "object": "block", "id": sub_page, "type": "child_page", "has_children": false, "child_page": { "title": "Notes" } }),
+        ]),
+    );
+    record(
+        &dir,
+        "PATCH",
+        &format!("/blocks/{}/children", setup_id),
+        Some(
+            &json!({ "children": markdown_blocks("Install it twice.\n"), "after": old_paragraph }),
+        ),
+        list(vec![json!({ "object": "block", "id": TOGGLE_ID })]),
+    );
+    record(
+        &dir,
+        "DELETE",
+        &format!("/blocks/{}", old_paragraph),
+        None,
+        json!({ "object": "block", "id": old_paragraph }),
+    );
+    let (ok, stdout, stderr) = notion_cli(&dir, &["push", docs_arg, "--root", PAGE_ID]);
+    assert!(ok, "{}", stderr);
+    assert!(
+        stdout.contains("0 created, 1 updated, 1 unchanged"),
+        "{}",
+        stdout
+    );
+
+    let (ok, _, stderr) = notion_cli(&dir, &["push", docs_arg, "--root", TOGGLE_ID]);
+    assert!(!ok);
+    assert!(stderr.contains("was pushed to page"), "{}", stderr);
+}
+
+#[test]
+fn test_read_numbers_list_items() {
+    let dir = fixtures("read_numbered");
+    record(
+        &dir,
+        "GET",
+        &format!("/pages/{}", PAGE_ID),
+        None,
+        json!({ "object": "page", "id": PAGE_ID, "properties": {} }),
+    );
+    let item = |id: &str, text: &str| {
+        json!({
+            "object": "block",
+            "id": id,
+            "type": "numbered_list_item",
+            "has_children": false,
+            "numbered_list_item": { "rich_text": [{ "type": "text", "plain_text": text }] }
+        })
+    };
+    record(
+        &dir,
+        "GET",
+        &format!("/blocks/{}/children?page_size=100", PAGE_ID),
+        None,
+        list(vec![
+            item("n1", "Install"),
+            item("n2", "Configure"),
+            paragraph("p1", "Then:", false),
+            item("n3", "Run"),
+        ]),
+    );
+
+    let (ok, stdout, stderr) = notion_cli(&dir, &["read", PAGE_ID]);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("1. Install"), "{}", stdout);
+    assert!(stdout.contains("2. Configure"), "{}", stdout);
+    assert!(stdout.contains("1. Run"), "{}", stdout);
+}
+
+#[test]
+fn test_pull_page_tree() {
+    let dir = fixtures("pull");
+    let root = "7e9a1c3d-5f7b-4c9e-8a3c-5e7a9c1e3a5d";
+    record_page(&dir);
+    record(
+        &dir,
+        "GET",
+        &format!("/pages/{}", root),
+        None,
+        json!({ "object": "page", "id": root, "last_edited_time": "2024-07-02T10:00:00.000Z" }),
+    );
+    record(
+        &dir,
+        "GET",
+        &format!("/blocks/{}/children?page_size=100", root),
+        None,
+        list(vec![json!({
+            "object": "block",
+            "id": PAGE_ID,
+            "type": "child_page",
+            "has_children": true,
+            "child_page": { "title": "Release notes" }
+        })]),
+    );
+
+    let out = dir.join("out");
+    let out_arg = out.to_str().unwrap();
+    let (ok, stdout, stderr) = notion_cli(&dir, &["pull", root, out_arg]);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("+ Release notes.md"), "{}", stdout);
+    let file = fs::read_to_string(out.join("Release notes.md")).unwrap();
+    assert!(
+        file.starts_with(&format!(
+            "---\nid: {}\nlast_edited_time: 2024-07-01T09:30:00.000Z\n---\n\n# Release notes\n",
+            PAGE_ID
+        )),
+        "{}",
+        file
+    );
+    assert!(file.contains("Faster reads"), "{}", file);
+
+    // A file from an earlier pull whose page is gone
+    fs::write(out.join("Old.md"), "---\nid: gone\n---\n\n# Old\n").unwrap();
+    let (ok, stdout, stderr) = notion_cli(&dir, &["pull", root, out_arg]);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("0 written, 1 unchanged"), "{}", stdout);
+    assert!(stdout.contains("? Old.md"), "{}", stdout);
+
+    let (ok, stdout, stderr) = notion_cli(&dir, &["pull", root, out_arg, "--prune"]);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("1 deleted"), "{}", stdout);
+    assert!(!out.join("Old.md").exists());
+}
+
+#[test]
+fn test_pull_then_push_keeps_the_tree() {
+    let dir = fixtures("round_trip");
+    let root = "7e9a1c3d-5f7b-4c9e-8a3c-5e7a9c1e3a5d";
+    let guide = "5c7e9a1b-3d5f-4a7c-9e1b-3d5f7a9c1e3b";
+    let setup = "6d8f0b2c-4e6a-4b8d-8f2c-4e6a8b0d2f4c";
+    let child_page = |id: &str, title: &str| json!({ "object": "block", "id": id, "type": "child_page", "has_children": true, "child_page": { "title": title } });
+    let page = |id: &str, title: &str, children: Vec<Value>| {
+        record(
+            &dir,
+            "GET",
+            &format!("/pages/{}", id),
+            None,
+            json!({
+                "object": "page",
+                "id": id,
+                "last_edited_time": "2024-07-01T09:30:00.000Z",
+                "properties": { "title": { "type": "title", "title": [{ "plain_text": title }] } }
+            }),
+        );
+        record(
+            &dir,
+            "GET",
+            &format!("/blocks/{}/children?page_size=100", id),
+            None,
+            list(children),
+        );
+    };
+    page(root, "Docs", vec![child_page(guide, "Guide")]);
+    page(
+        guide,
+        "Guide",
+        vec![
+            paragraph("b1", "Start here.", false),
+            child_page(setup, "Setup"),
+        ],
+    );
+    page(setup, "Setup", vec![paragraph("b2", "Install it.", false)]);
+
+    let out = dir.join("out");
+    let out_arg = out.to_str().unwrap();
+    let (ok, stdout, stderr) = notion_cli(&dir, &["pull", root, out_arg]);
+    assert!(ok, "{}", stderr);
+    assert!(out.join("Guide.md").is_file(), "{}", stdout);
+    assert!(out.join("Guide/Setup.md").is_file(), "{}", stdout);
+
+    // Guide.md is the content of the Guide/ page, and Setup goes under it
+    let new_guide = "8f0b2c4e-6a8b-4d0f-9b4e-6a8c0e2b4d6f";
+    let new_setup = "9a1c3e5b-7d9f-4a1c-8e5b-7d9f1b3d5f7a";
+    for (file, parent, title, id) in [
+        ("Guide.md", PAGE_ID, "Guide", new_guide),
+        ("Guide/Setup.md", new_guide, "Setup", new_setup),
+    ] {
+        record(
+            &dir,
+            "POST",
+            "/pages",
+            Some(&json!({
+                "parent": { "page_id": parent },
+                "properties": { "title": { "title": [{ "type": "text", "text": { "content": title } }] } }
+            })),
+            json!({ "object": "page", "id": id }),
+        );
+        let content = fs::read_to_string(out.join(file)).unwrap();
+        let body = notion_cli_tool::sync::strip_front_matter(&content);
+        let (_, markdown) = notion_cli_tool::sync::split_title(title, body);
+        record(
+            &dir,
+            "PATCH",
+            &format!("/blocks/{}/children", id),
+            Some(&json!({ "children": markdown_blocks(markdown) })),
+            list(vec![]),
+        );
+    }
+
+    let (ok, stdout, stderr) = notion_cli(&dir, &["push", out_arg, "--root", PAGE_ID]);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("+ Guide\n"), "{}", stdout);
+    assert!(stdout.contains("+ Guide/Setup.md"), "{}", stdout);
+    assert!(
+        stdout.contains("2 created, 0 updated, 0 unchanged"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn test_append_code_from_file() {
+    let dir = fixtures("append_code");
+    let source = dir.join("main.rs");
+    fs::write(&source, "fn main() {}\n").unwrap();
+    let block = json!({
+        "object": "block",
+        "type": "code",
+        "code": {
+            "rich_text": [{ "type": "text", "text": { "content": "fn main() {}\n" } }],
+            "language": "rust",
+            "caption": [{ "type": "text", "text": { "content": "main.rs" } }]
+        }
+    });
+    record(
+        &dir,
+        "PATCH",
+        &format!("/blocks/{}/children", PAGE_ID),
+        Some(&json!({ "children": [block] })),
+        list(vec![json!({ "object": "block", "id": TOGGLE_ID })]),
+    );
+
+    let (ok, stdout, stderr) = notion_cli(
+        &dir,
+        &[
+            "append-code",
+            PAGE_ID,
+            "--file",
+            source.to_str().unwrap(),
+            "--caption",
+        ],
+    );
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("Code block appended!"), "{}", stdout);
+
+    let (ok, _, stderr) = notion_cli(&dir, &["append-code", PAGE_ID, "x", "--caption"]);
+    assert!(!ok);
+    assert!(stderr.contains("--caption needs a value"), "{}", stderr);
+}
+
+#[test]
+fn test_append_mentions() {
+    let dir = fixtures("mentions");
+    let ada = "11111111-2222-4333-8444-555555555555";
+    record(
+        &dir,
+        "GET",
+        "/users?page_size=100",
+        None,
+        list(vec![
+            json!({ "object": "user", "id": ada, "name": "Ada", "type": "person", "person": { "email": "ada@example.com" } }),
+        ]),
+    );
+    let paragraph = json!({
+        "object": "block",
+        "type": "paragraph",
+        "paragraph": { "rich_text": [
+            { "type": "text", "text": { "content": "Review *this* " }, "annotations": {} },
+            { "type": "mention", "mention": { "type": "user", "user": { "id": ada } }, "annotations": {} },
+            { "type": "text", "text": { "content": " " }, "annotations": {} },
+            { "type": "mention", "mention": { "type": "page", "page": { "id": TOGGLE_ID } }, "annotations": {} }
+        ] }
+    });
+    record(
+        &dir,
+        "PATCH",
+        &format!("/blocks/{}/children", PAGE_ID),
+        Some(&json!({ "children": [paragraph] })),
+        list(vec![json!({ "object": "block", "id": TOGGLE_ID })]),
+    );
+
+    let (ok, stdout, stderr) = notion_cli(
+        &dir,
+        &[
+            "append",
+            PAGE_ID,
+            "Review *this*",
+            "--mention-user",
+            "ada@example.com",
+            "--mention-page",
+            &TOGGLE_ID.replace('-', ""),
+        ],
+    );
+    assert!(ok, "{}", stderr);
+    assert!(
+        stdout.contains("Content appended! (1 blocks)"),
+        "{}",
+        stdout
+    );
+
+    let (ok, _, stderr) = notion_cli(
+        &dir,
+        &["append", PAGE_ID, "-m", "Ping @[user:grace@example.com]"],
+    );
+    assert!(!ok);
+    assert!(
+        stderr.contains("Can't mention 'grace@example.com'"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_missing_recording_fails() {
+    let dir = fixtures("missing");
+
+    let (ok, _, stderr) = notion_cli(&dir, &["read", PAGE_ID]);
+    assert!(!ok);
+    assert!(stderr.contains("No recorded response"), "{}", stderr);
+}
+
+#[test]
+fn test_append_list_rejects_blank_items() {
+    let dir = fixtures("blank_list");
+
+    for items in ["", " , "] {
+        let (ok, _, stderr) = notion_cli(&dir, &["append-list", PAGE_ID, items]);
+        assert!(!ok);
+        assert!(stderr.contains("No list items given"), "{}", stderr);
+    }
+}
+
+#[test]
+fn test_json_error_output() {
+    let dir = fixtures("json-error");
+    record_status(
+        &dir,
+        "GET",
+        &format!("/pages/{}", PAGE_ID),
+        None,
+        404,
+        json!({
+            "object": "error",
+            "status": 404,
+            "code": "object_not_found",
+            "message": "Could not find page",
+            "request_id": "4b9a6c2e-0d1f-4e3a-8b5c-7d9e1f3a5b7c"
+        }),
+    );
+
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_notion-cli"))
+            .args(args)
+            .env("NOTION_CLI_REPLAY", &dir)
+            .env("NOTION_API_KEY", "ntn_test")
+            .env("HOME", dir.join("home"))
+            .output()
+            .unwrap()
+    };
+
+    let output = run(&["info", PAGE_ID, "--error-format", "json"]);
+    assert_eq!(output.status.code(), Some(2));
+    let error: Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(error["error"]["code"], "object_not_found");
+    assert_eq!(
+        error["error"]["request_id"],
+        "4b9a6c2e-0d1f-4e3a-8b5c-7d9e1f3a5b7c"
+    );
+    assert_eq!(error["error"]["exit_code"], 2);
+
+    let output = run(&["--output", "json", "info", PAGE_ID]);
+    assert_eq!(output.status.code(), Some(2));
+    let error: Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(error["error"]["code"], "object_not_found");
+
+    // Invalid arguments count as a rejected request
+    assert_eq!(run(&["info"]).status.code(), Some(4));
+}
+
+#[test]
+fn test_api_version_check() {
+    let dir = fixtures("api-version");
+    record(
+        &dir,
+        "GET",
+        "/users/me",
+        None,
+        json!({ "object": "user", "id": "u1", "type": "bot", "name": "CLI", "bot": {} }),
+    );
+
+    let (ok, stdout, stderr) = notion_cli(
+        &dir,
+        &["api-version", "check", "--api-version", "2022-06-28"],
+    );
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("API version: 2022-06-28"));
+    assert!(stdout.contains("/databases/{id}/query"));
+
+    let (ok, _, stderr) = notion_cli(&dir, &["api-version", "check", "--api-version", "latest"]);
+    assert!(!ok);
+    assert!(stderr.contains("expected a date"), "{}", stderr);
+}
+
+#[test]
+fn test_auth_status() {
+    let dir = fixtures("auth");
+    record(
+        &dir,
+        "GET",
+        "/users/me",
+        None,
+        json!({ "object": "user", "id": "bot-1", "type": "bot", "name": "CLI",
+                "bot": { "workspace_name": "Acme" } }),
+    );
+    record(
+        &dir,
+        "POST",
+        "/search",
+        Some(&json!({ "query": "", "page_size": 1 })),
+        list(vec![]),
+    );
+    record_status(
+        &dir,
+        "GET",
+        "/users?page_size=10",
+        None,
+        403,
+        json!({ "object": "error", "status": 403, "code": "restricted_resource", "message": "No access" }),
+    );
+
+    let (ok, stdout, stderr) = notion_cli(&dir, &["auth", "status"]);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("default: Acme"));
+    assert!(stdout.contains("Bot: bot-1"));
+    assert!(stdout.contains("Capabilities: read content\n"));
+
+    let revoked = fixtures("auth-revoked");
+    record_status(
+        &revoked,
+        "GET",
+        "/users/me",
+        None,
+        401,
+        json!({ "object": "error", "status": 401, "code": "unauthorized", "message": "API token is invalid." }),
+    );
+    let (ok, _, stderr) = notion_cli(&revoked, &["auth", "status"]);
+    assert!(!ok);
+    assert!(stderr.contains("invalid, expired or revoked"), "{}", stderr);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_serve_answers_json_rpc() {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    let dir = fixtures("serve");
+    record_page(&dir);
+    let socket = dir.join("notion.sock");
+    let home = dir.join("home");
+    let mut server = Command::new(env!("CARGO_BIN_EXE_notion-cli"))
+        .args(["serve", "--socket"])
+        .arg(&socket)
+        .env("NOTION_CLI_REPLAY", &dir)
+        .env("NOTION_API_KEY", "ntn_test")
+        .env("HOME", &home)
+        .env("XDG_CONFIG_HOME", home.join(".config"))
+        .env("XDG_CACHE_HOME", home.join(".cache"))
+        .env_remove("NOTION_PROFILE")
+        .spawn()
+        .unwrap();
+
+    let mut stream = (0..100)
+        .find_map(|_| {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            UnixStream::connect(&socket).ok()
+        })
+        .expect("server did not start");
+    let mut lines = BufReader::new(stream.try_clone().unwrap()).lines();
+    let mut call = |request: Value| -> Value {
+        writeln!(stream, "{}", request).unwrap();
+        serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap()
+    };
+
+    let read =
+        call(json!({ "jsonrpc": "2.0", "id": 1, "method": "read", "params": { "page": PAGE_ID } }));
+    assert_eq!(read["id"], 1);
+    assert_eq!(read["result"]["blocks"][0]["children"][0]["id"], "b2");
+
+    let missing =
+        call(json!({ "jsonrpc": "2.0", "id": 2, "method": "blocks", "params": { "block": "b2" } }));
+    assert_eq!(missing["error"]["code"], -32000);
+
+    // Only the user can connect, and the bind directory is gone
+    use std::os::unix::fs::PermissionsExt;
+    let mode = fs::metadata(&socket).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    let leftovers: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            e.file_name()
+                .to_string_lossy()
+                .starts_with(".notion-cli-serve")
+        })
+        .collect();
+    assert!(leftovers.is_empty());
+
+    server.kill().unwrap();
+    server.wait().unwrap();
+}